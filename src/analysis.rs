@@ -0,0 +1,223 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::models::{AO3Work, Rating};
+
+/// How many of the works have each additional (freeform) tag, most used first
+///
+/// Tags used as often are sorted by name.
+/// ```rust
+/// # fn example(html: &str) -> Result<(), Box<dyn std::error::Error>> {
+/// use ao3rs::analysis::tag_frequency;
+///
+/// let works = ao3rs::offline::parse_search_html(html)?;
+/// for (tag, count) in tag_frequency(&works).iter().take(10) {
+///     println!("{count:>5} {tag}");
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn tag_frequency(works: &[AO3Work]) -> Vec<(String, usize)> {
+    count_each(works.iter().flat_map(|work| distinct(&work.tags)))
+}
+
+/// How many of the works have each archive warning, most used first
+pub fn warning_distribution(works: &[AO3Work]) -> Vec<(String, usize)> {
+    count_each(works.iter().flat_map(|work| distinct(&work.warnings)))
+}
+
+/// How many of the works have each rating, from [None](Rating::None) to [Explicit](Rating::Explicit)
+///
+/// Every rating is listed, even when no work has it.
+pub fn rating_distribution(works: &[AO3Work]) -> Vec<(Rating, usize)> {
+    [
+        Rating::None,
+        Rating::NotRated,
+        Rating::General,
+        Rating::TeenAndUp,
+        Rating::Mature,
+        Rating::Explicit,
+    ]
+    .into_iter()
+    .map(|rating| {
+        let count = works.iter().filter(|w| w.get_rating() == rating).count();
+        (rating, count)
+    })
+    .collect()
+}
+
+/// Works whose word count is in `start..end`
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Bucket {
+    pub start: usize,
+    pub end: usize,
+    pub count: usize,
+}
+
+/// Count the works by word count, in buckets `bucket_size` words wide
+///
+/// Only the buckets that have works are listed, shortest first, so a single
+/// huge work doesn't make millions of empty ones.
+pub fn word_count_histogram(works: &[AO3Work], bucket_size: usize) -> Vec<Bucket> {
+    let bucket_size = bucket_size.max(1);
+    let mut counts = BTreeMap::new();
+    for work in works {
+        *counts.entry(work.word_count / bucket_size).or_insert(0) += 1;
+    }
+    counts
+        .into_iter()
+        .map(|(i, count)| Bucket {
+            start: i * bucket_size,
+            end: (i + 1) * bucket_size,
+            count,
+        })
+        .collect()
+}
+
+/// How often tags are used on the same work, for the most used tags
+///
+/// The diagonal is how many works have the tag at all.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CoOccurrence {
+    /// The tags of the rows and columns, most used first
+    pub tags: Vec<String>,
+
+    /// `counts[i][j]` works have both `tags[i]` and `tags[j]`
+    pub counts: Vec<Vec<usize>>,
+}
+
+impl CoOccurrence {
+    /// How many works have both tags, `None` when either isn't among the [tags](CoOccurrence::tags)
+    pub fn get(&self, a: &str, b: &str) -> Option<usize> {
+        let i = self.tags.iter().position(|t| t == a)?;
+        let j = self.tags.iter().position(|t| t == b)?;
+        Some(self.counts[i][j])
+    }
+}
+
+/// The co-occurrence matrix of the `top` most used additional tags of the works
+///
+/// Every tag would make a matrix too big to be of use, a few hundred tags are plenty.
+pub fn tag_co_occurrence(works: &[AO3Work], top: usize) -> CoOccurrence {
+    let tags: Vec<String> = tag_frequency(works)
+        .into_iter()
+        .take(top)
+        .map(|(tag, _)| tag)
+        .collect();
+    let index: HashMap<&str, usize> = tags
+        .iter()
+        .enumerate()
+        .map(|(i, tag)| (tag.as_str(), i))
+        .collect();
+    let mut counts = vec![vec![0; tags.len()]; tags.len()];
+    for work in works {
+        let present: Vec<usize> = distinct(&work.tags)
+            .filter_map(|tag| index.get(tag.as_str()).copied())
+            .collect();
+        for &i in &present {
+            for &j in &present {
+                counts[i][j] += 1;
+            }
+        }
+    }
+    CoOccurrence { tags, counts }
+}
+
+/// The tags of a work once each, AO3 doesn't stop a tag from being listed twice
+fn distinct(tags: &[String]) -> impl Iterator<Item = &String> {
+    tags.iter()
+        .enumerate()
+        .filter(|(i, tag)| !tags[..*i].contains(tag))
+        .map(|(_, tag)| tag)
+}
+
+fn count_each<'a>(names: impl Iterator<Item = &'a String>) -> Vec<(String, usize)> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for name in names {
+        *counts.entry(name).or_default() += 1;
+    }
+    let mut counts: Vec<(String, usize)> = counts
+        .into_iter()
+        .map(|(name, count)| (name.to_string(), count))
+        .collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::WorkBuilder;
+
+    #[test]
+    fn test_analysis() {
+        let works = vec![
+            WorkBuilder::new("1")
+                .set_tags(&["Fluff", "Hurt/Comfort", "Fluff"])
+                .set_word_count(500)
+                .set_rating(Rating::General)
+                .build(),
+            WorkBuilder::new("2")
+                .set_tags(&["Angst", "Hurt/Comfort"])
+                .set_word_count(12_000)
+                .set_rating(Rating::Mature)
+                .build(),
+            WorkBuilder::new("3")
+                .set_tags(&["Fluff"])
+                .set_word_count(999)
+                .set_rating(Rating::General)
+                .build(),
+        ];
+        assert_eq!(
+            tag_frequency(&works),
+            vec![
+                ("Fluff".to_string(), 2),
+                ("Hurt/Comfort".to_string(), 2),
+                ("Angst".to_string(), 1)
+            ]
+        );
+
+        let matrix = tag_co_occurrence(&works, 2);
+        assert_eq!(matrix.tags, vec!["Fluff", "Hurt/Comfort"]);
+        assert_eq!(matrix.get("Fluff", "Fluff"), Some(2));
+        assert_eq!(matrix.get("Fluff", "Hurt/Comfort"), Some(1));
+        assert_eq!(matrix.get("Angst", "Fluff"), None);
+
+        let ratings = rating_distribution(&works);
+        assert_eq!(ratings[2], (Rating::General, 2));
+        assert_eq!(ratings[4], (Rating::Mature, 1));
+        assert_eq!(ratings.iter().map(|(_, count)| count).sum::<usize>(), 3);
+
+        let histogram = word_count_histogram(&works, 1000);
+        assert_eq!(histogram.len(), 2);
+        assert_eq!(
+            histogram[0],
+            Bucket {
+                start: 0,
+                end: 1000,
+                count: 2
+            }
+        );
+        assert_eq!(
+            histogram[1],
+            Bucket {
+                start: 12000,
+                end: 13000,
+                count: 1
+            }
+        );
+        assert!(word_count_histogram(&[], 1000).is_empty());
+        let mut huge = AO3Work::default();
+        huge.word_count = 5_000_000;
+        assert_eq!(word_count_histogram(&[huge], 1).len(), 1);
+    }
+
+    #[test]
+    fn test_search_warnings() {
+        let works = crate::parse::parse_search(include_str!("parse_test/search.html")).unwrap();
+        let warnings = warning_distribution(&works);
+        assert!(!warnings.is_empty());
+        assert!(warnings.windows(2).all(|w| w[0].1 >= w[1].1));
+    }
+}
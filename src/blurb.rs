@@ -0,0 +1,381 @@
+use crate::convert::decode_entities;
+use crate::error::{Error, ParsingError};
+use crate::form::BASE_AO3_URL;
+use crate::models::{AO3Work, Rating};
+use crate::parse::{
+    chapter_count_of, parse_chapter_count, parse_word_count, search_all_by_attrib,
+    search_by_attrib, search_by_class, word_count_of,
+};
+
+/// A page with a list of work blurbs (search results, tag listings, bookmarks, ...),
+/// parsed into a DOM but not into works yet
+///
+/// Getting every field of every blurb is what [parse_search_html](crate::offline::parse_search_html)
+/// does, when only a few of them are needed the [Blurb]s can be read one field at a time.
+/// ```rust
+/// # fn example(html: &str) -> Result<(), Box<dyn std::error::Error>> {
+/// use ao3rs::blurb::BlurbPage;
+///
+/// let page = BlurbPage::parse(html)?;
+/// for blurb in page.blurbs() {
+///     println!("{:?}: {:?}", blurb.id(), blurb.title());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct BlurbPage<'a> {
+    dom: tl::VDom<'a>,
+}
+
+impl std::fmt::Debug for BlurbPage<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BlurbPage").finish_non_exhaustive()
+    }
+}
+
+impl<'a> BlurbPage<'a> {
+    pub fn parse(html: &'a str) -> Result<Self, Error> {
+        // only selectors and tree walks are used, so there's no need to index classes and ids
+        Ok(BlurbPage {
+            dom: tl::parse(html, tl::ParserOptions::default())?,
+        })
+    }
+
+    /// The blurbs in the order they are on the page
+    pub fn blurbs(&self) -> impl Iterator<Item = Blurb<'_>> {
+        let parser = self.dom.parser();
+        self.dom
+            .query_selector("[role=article]")
+            .into_iter()
+            .flatten()
+            .filter_map(move |nh| nh.get(parser))
+            .map(move |node| Blurb { parser, node })
+    }
+}
+
+/// A single work blurb, every field is only parsed when it's asked for
+#[derive(Clone, Copy)]
+pub struct Blurb<'p> {
+    parser: &'p tl::Parser<'p>,
+    node: &'p tl::Node<'p>,
+}
+
+impl std::fmt::Debug for Blurb<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Blurb").field("id", &self.id()).finish()
+    }
+}
+
+impl<'p> Blurb<'p> {
+    pub(crate) fn new(parser: &'p tl::Parser<'p>, node: &'p tl::Node<'p>) -> Self {
+        Blurb { parser, node }
+    }
+
+    pub fn id(&self) -> Option<&'p str> {
+        let id = self.node.as_tag()?.attributes().id()?.try_as_utf8_str()?;
+        Some(id.strip_prefix("work_").unwrap_or(id))
+    }
+
+    pub fn title(&self) -> Option<String> {
+        let link = format!("/works/{}", self.id()?);
+        let title = search_by_attrib(self.parser, self.node, "href", &link).ok()?;
+        Some(title.inner_text(self.parser).to_string())
+    }
+
+    pub fn authors(&self) -> Vec<String> {
+        search_all_by_attrib(self.parser, self.node, "rel", "author")
+            .map(|n| n.inner_text(self.parser).to_string())
+            .collect()
+    }
+
+    pub fn fandoms(&self) -> Vec<String> {
+        self.fandoms_heading()
+            .map(|heading| self.fandoms_in(heading))
+            .unwrap_or_default()
+    }
+
+    /// Day the work was last updated
+    pub fn date(&self) -> Option<chrono::NaiveDate> {
+        let date = search_by_class(self.parser, self.node, "datetime")?;
+        chrono::NaiveDate::parse_from_str(date.inner_text(self.parser).trim(), "%d %b %Y").ok()
+    }
+
+    /// Additional (freeform) tags
+    pub fn tags(&self) -> Vec<String> {
+        search_all_by_attrib(self.parser, self.node, "class", "freeforms")
+            .map(|n| decode_entities(&n.inner_text(self.parser)).into_owned())
+            .collect()
+    }
+
+    pub fn relationships(&self) -> Vec<String> {
+        search_all_by_attrib(self.parser, self.node, "class", "relationships")
+            .map(|n| decode_entities(&n.inner_text(self.parser)).into_owned())
+            .collect()
+    }
+
+    pub fn characters(&self) -> Vec<String> {
+        search_all_by_attrib(self.parser, self.node, "class", "characters")
+            .map(|n| decode_entities(&n.inner_text(self.parser)).into_owned())
+            .collect()
+    }
+
+    /// Archive warnings
+    pub fn warnings(&self) -> Vec<String> {
+        search_all_by_attrib(self.parser, self.node, "class", "warnings")
+            .map(|n| decode_entities(&n.inner_text(self.parser)).into_owned())
+            .collect()
+    }
+
+    /// Rating of the work, from the symbol at the top of the blurb
+    pub fn rating(&self) -> Rating {
+        search_by_class(self.parser, self.node, "rating")
+            .and_then(|n| n.as_tag()?.attributes().get("title")??.try_as_utf8_str())
+            .and_then(Rating::from_name)
+            .unwrap_or_default()
+    }
+
+    /// Summary as html
+    pub fn summary(&self) -> String {
+        search_by_class(self.parser, self.node, "summary")
+            .map(|n| n.inner_html(self.parser).trim().to_string())
+            .unwrap_or_default()
+    }
+
+    pub fn word_count(&self) -> usize {
+        parse_word_count(self.parser, self.node).unwrap_or_default()
+    }
+
+    /// Number of chapters posted so far
+    pub fn chapter_count(&self) -> usize {
+        parse_chapter_count(self.parser, self.node).map_or(0, |(count, _)| count)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        parse_chapter_count(self.parser, self.node).is_some_and(|(_, complete)| complete)
+    }
+
+    /// Parse every field of the blurb
+    ///
+    /// Goes through the blurb once, where asking for each field would go through it every time.
+    pub fn to_work(&self) -> Result<AO3Work, Error> {
+        let id = self
+            .id()
+            .ok_or(ParsingError::CouldNotFind("the id of the work."))?;
+        let mut builder = WorkBuilder {
+            link: format!("/works/{id}"),
+            ..Default::default()
+        };
+        if let Some(children) = self.node.children() {
+            for child in children.top().iter().filter_map(|nh| nh.get(self.parser)) {
+                builder.visit(self.parser, child, false);
+            }
+        }
+        builder.finish(id)
+    }
+
+    fn fandoms_heading(&self) -> Option<&'p tl::Node<'p>> {
+        search_by_attrib(self.parser, self.node, "class", "fandoms heading").ok()
+    }
+
+    fn fandoms_in(&self, heading: &'p tl::Node<'p>) -> Vec<String> {
+        search_all_by_attrib(self.parser, heading, "class", "tag")
+            .map(|n| n.inner_text(self.parser).to_string())
+            .collect()
+    }
+}
+
+/// Classes of the stats of a blurb, in the order [WorkBuilder] keeps them
+const STAT_CLASSES: [&str; 4] = ["kudos", "hits", "bookmarks", "comments"];
+
+/// Collects the fields of a blurb while its nodes are visited in document order
+#[derive(Default)]
+struct WorkBuilder {
+    /// Href of the link to the work, it holds the title
+    link: String,
+    title: Option<String>,
+    authors: Vec<String>,
+    has_fandoms: bool,
+    fandoms: Vec<String>,
+    date: Option<chrono::NaiveDate>,
+    relationships: Vec<String>,
+    characters: Vec<String>,
+    tags: Vec<String>,
+    warnings: Vec<String>,
+    rating: Option<Rating>,
+    summary: Option<String>,
+    word_count: Option<usize>,
+    chapter_count: Option<(usize, bool)>,
+    /// Kudos, hits, bookmarks and comments, in that order
+    stats: [Option<usize>; 4],
+}
+
+impl WorkBuilder {
+    fn visit(&mut self, parser: &tl::Parser, node: &tl::Node, in_fandoms: bool) {
+        let Some(tag) = node.as_tag() else {
+            return;
+        };
+        let attributes = tag.attributes();
+        let class = attributes.class().and_then(|c| c.try_as_utf8_str());
+        let is_member = |name: &str| class.is_some_and(|c| c.split_whitespace().any(|c| c == name));
+        // fields are taken from the first node that has them, like a search from the top would
+        if self.title.is_none()
+            && attributes
+                .get("href")
+                .flatten()
+                .is_some_and(|h| h == self.link.as_str())
+        {
+            self.title = Some(node.inner_text(parser).to_string());
+        }
+        if attributes
+            .get("rel")
+            .flatten()
+            .is_some_and(|r| r == "author")
+        {
+            self.authors.push(node.inner_text(parser).to_string());
+        }
+        if in_fandoms && class == Some("tag") {
+            self.fandoms.push(node.inner_text(parser).to_string());
+        }
+        if class == Some("freeforms") {
+            self.tags
+                .push(decode_entities(&node.inner_text(parser)).into_owned());
+        }
+        if class == Some("relationships") {
+            self.relationships
+                .push(decode_entities(&node.inner_text(parser)).into_owned());
+        }
+        if class == Some("characters") {
+            self.characters
+                .push(decode_entities(&node.inner_text(parser)).into_owned());
+        }
+        if class == Some("warnings") {
+            self.warnings
+                .push(decode_entities(&node.inner_text(parser)).into_owned());
+        }
+        if self.rating.is_none() && is_member("rating") {
+            self.rating = attributes
+                .get("title")
+                .flatten()
+                .and_then(|t| t.try_as_utf8_str())
+                .and_then(Rating::from_name);
+        }
+        if self.date.is_none() && is_member("datetime") {
+            self.date =
+                chrono::NaiveDate::parse_from_str(node.inner_text(parser).trim(), "%d %b %Y").ok();
+        }
+        if self.summary.is_none() && is_member("summary") {
+            self.summary = Some(node.inner_html(parser).trim().to_string());
+        }
+        if tag.name() == "dd" {
+            if self.word_count.is_none() && is_member("words") {
+                self.word_count = Some(word_count_of(&node.inner_text(parser)));
+            }
+            for (stat, class) in self.stats.iter_mut().zip(STAT_CLASSES) {
+                if stat.is_none() && is_member(class) {
+                    *stat = Some(word_count_of(&node.inner_text(parser)));
+                }
+            }
+            if self.chapter_count.is_none() && is_member("chapters") {
+                self.chapter_count =
+                    Some(chapter_count_of(&node.inner_text(parser)).unwrap_or_default());
+            }
+        }
+        let starts_fandoms = !self.has_fandoms && class == Some("fandoms heading");
+        self.has_fandoms |= starts_fandoms;
+        for child in tag.children().top().iter().filter_map(|nh| nh.get(parser)) {
+            self.visit(parser, child, in_fandoms || starts_fandoms);
+        }
+    }
+
+    fn finish(self, id: &str) -> Result<AO3Work, Error> {
+        let title = self
+            .title
+            .ok_or(ParsingError::CouldNotFind("the title of the work."))?;
+        if !self.has_fandoms {
+            return Err(ParsingError::CouldNotFind("the fandoms of the work.").into());
+        }
+        let mut work = AO3Work::default();
+        work.url = format!("{BASE_AO3_URL}/works/{id}");
+        work.id = id.to_string();
+        work.title = title;
+        work.date = self.date.unwrap_or_default();
+        work.relationships = self.relationships;
+        work.characters = self.characters;
+        work.tags = self.tags;
+        work.warnings = self.warnings;
+        work.set_rating(self.rating.unwrap_or_default());
+        work.summary = self.summary.unwrap_or_default();
+        work.authors = self.authors;
+        work.fandoms = self.fandoms;
+        work.word_count = self.word_count.unwrap_or_default();
+        let [kudos, hits, bookmarks, comments] = self.stats.map(Option::unwrap_or_default);
+        work.kudos = kudos;
+        work.hits = hits;
+        work.bookmarks = bookmarks;
+        work.comments = comments;
+        if let Some((chapter_count, is_complete)) = self.chapter_count {
+            work.chapter_count = chapter_count;
+            work.is_complete = is_complete;
+        }
+        Ok(work)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blurbs() {
+        let html = include_str!("parse_test/search.html");
+        let page = BlurbPage::parse(html).unwrap();
+        let blurbs: Vec<Blurb> = page.blurbs().collect();
+        assert_eq!(blurbs[0].id(), Some("45221314"));
+        assert_eq!(blurbs[0].word_count(), 842);
+        assert!(!blurbs[1].is_complete());
+        assert_eq!(blurbs[0].rating(), Rating::TeenAndUp);
+        assert_eq!(blurbs[0].warnings(), vec!["Graphic Depictions Of Violence"]);
+        // the single pass finds the same as asking for each field
+        for blurb in &blurbs {
+            let work = blurb.to_work().unwrap();
+            assert_eq!(blurb.rating(), work.get_rating());
+            assert_eq!(blurb.title(), Some(work.title));
+            assert_eq!(blurb.authors(), work.authors);
+            assert_eq!(blurb.fandoms(), work.fandoms);
+            assert_eq!(blurb.relationships(), work.relationships);
+            assert_eq!(blurb.characters(), work.characters);
+            assert_eq!(blurb.tags(), work.tags);
+            assert_eq!(blurb.warnings(), work.warnings);
+            assert_eq!(blurb.date(), Some(work.date));
+            assert_eq!(blurb.summary(), work.summary);
+            assert_eq!(blurb.word_count(), work.word_count);
+            assert_eq!(blurb.chapter_count(), work.chapter_count);
+            assert_eq!(blurb.is_complete(), work.is_complete);
+        }
+    }
+
+    #[test]
+    fn test_blurb_stats() {
+        let html = include_str!("parse_test/search_stats.html");
+        let work = BlurbPage::parse(html)
+            .unwrap()
+            .blurbs()
+            .next()
+            .unwrap()
+            .to_work()
+            .unwrap();
+        assert_eq!(work.kudos, 1204);
+        assert_eq!(work.hits, 23456);
+        assert_eq!(work.bookmarks, 87);
+        assert_eq!(work.comments, 12);
+        // blurbs without them have none
+        let work = BlurbPage::parse(include_str!("parse_test/search.html"))
+            .unwrap()
+            .blurbs()
+            .next()
+            .unwrap()
+            .to_work()
+            .unwrap();
+        assert_eq!((work.kudos, work.bookmarks, work.comments), (0, 0, 0));
+    }
+}
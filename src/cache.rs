@@ -0,0 +1,286 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use crate::client::AO3Client;
+use crate::error::Error;
+use crate::query::AO3QueryBuilder;
+use crate::search::Search;
+
+/// Search results of one page, and when they were fetched
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct CachedPage<T> {
+    fetched_at: SystemTime,
+    // files written before caches held more than works call them works
+    #[cfg_attr(feature = "serde", serde(alias = "works"))]
+    items: Vec<T>,
+}
+
+/// What a [ResultCache] can keep, with the `serde` feature it has to go in a file too
+#[cfg(feature = "serde")]
+pub trait Cacheable: Clone + serde::Serialize + serde::de::DeserializeOwned {}
+
+#[cfg(feature = "serde")]
+impl<T: Clone + serde::Serialize + serde::de::DeserializeOwned> Cacheable for T {}
+
+/// What a [ResultCache] can keep, with the `serde` feature it has to go in a file too
+#[cfg(not(feature = "serde"))]
+pub trait Cacheable: Clone {}
+
+#[cfg(not(feature = "serde"))]
+impl<T: Clone> Cacheable for T {}
+
+/// Cache of search result pages, so paging back and forth doesn't ask AO3 again
+///
+/// Pages are keyed by the url of the query, with its parameters and tag names sorted so
+/// the order they were added in doesn't matter, and kept for `ttl`. With the `serde` feature the cache can be kept in a file
+/// so it survives restarts. A cache holds the results of one kind of [Search],
+/// work searches by default.
+/// ```rust,no_run
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// use ao3rs::cache::ResultCache;
+/// use ao3rs::query::AO3QueryBuilder;
+///
+/// let cache = ResultCache::new(std::time::Duration::from_secs(10 * 60));
+/// let query = AO3QueryBuilder::new().push_fandom("Original Work");
+/// let works = query.clone().send_cached(&cache).await?;
+/// // within ten minutes this doesn't make a request
+/// let same_works = query.send_cached(&cache).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ResultCache<S: Search = AO3QueryBuilder> {
+    pages: Mutex<HashMap<String, CachedPage<S::Item>>>,
+    ttl: Duration,
+    #[cfg(feature = "serde")]
+    path: Option<std::path::PathBuf>,
+}
+
+impl<S: Search> ResultCache<S>
+where
+    S::Item: Cacheable,
+{
+    /// A cache that lives in memory only
+    pub fn new(ttl: Duration) -> Self {
+        ResultCache {
+            pages: Mutex::new(HashMap::new()),
+            ttl,
+            #[cfg(feature = "serde")]
+            path: None,
+        }
+    }
+
+    /// A cache kept in the json file at `path`, which is read now if it exists
+    /// and written every time a page is added
+    #[cfg(feature = "serde")]
+    pub fn open(path: impl AsRef<std::path::Path>, ttl: Duration) -> Result<Self, Error> {
+        let path = path.as_ref().to_path_buf();
+        let pages = match std::fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json)?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(err) => return Err(err.into()),
+        };
+        let cache = ResultCache {
+            pages: Mutex::new(pages),
+            ttl,
+            path: Some(path),
+        };
+        cache.purge_expired();
+        Ok(cache)
+    }
+
+    pub fn get_ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Results of a page of the search, unless they aren't cached or are too old
+    pub fn get(&self, search: &S, page: usize) -> Option<Vec<S::Item>> {
+        let pages = self.pages.lock().unwrap();
+        let cached = pages.get(&cache_key(&search.path(page)))?;
+        if self.is_expired(cached) {
+            return None;
+        }
+        Some(cached.items.clone())
+    }
+
+    pub fn insert(&self, search: &S, page: usize, items: Vec<S::Item>) -> Result<(), Error> {
+        let mut pages = self.pages.lock().unwrap();
+        pages.insert(
+            cache_key(&search.path(page)),
+            CachedPage {
+                fetched_at: SystemTime::now(),
+                items,
+            },
+        );
+        self.persist(&pages)
+    }
+
+    /// Forget every page of every query
+    pub fn clear(&self) -> Result<(), Error> {
+        let mut pages = self.pages.lock().unwrap();
+        pages.clear();
+        self.persist(&pages)
+    }
+
+    /// Drop the pages that are too old to be used
+    pub fn purge_expired(&self) {
+        self.pages
+            .lock()
+            .unwrap()
+            .retain(|_, cached| !self.is_expired(cached));
+    }
+
+    pub fn len(&self) -> usize {
+        self.pages.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn is_expired(&self, cached: &CachedPage<S::Item>) -> bool {
+        // a page from the future, after the clock was turned back, is as fresh as it gets
+        cached.fetched_at.elapsed().unwrap_or_default() >= self.ttl
+    }
+
+    #[cfg(feature = "serde")]
+    fn persist(&self, pages: &HashMap<String, CachedPage<S::Item>>) -> Result<(), Error> {
+        if let Some(path) = &self.path {
+            // a crash halfway through writing leaves the old file as it was
+            let mut tmp = path.clone().into_os_string();
+            tmp.push(".tmp");
+            std::fs::write(&tmp, serde_json::to_string(pages)?)?;
+            std::fs::rename(&tmp, path)?;
+        }
+        Ok(())
+    }
+
+    #[cfg(not(feature = "serde"))]
+    fn persist(&self, _pages: &HashMap<String, CachedPage<S::Item>>) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl AO3Client {
+    /// [search_page](AO3Client::search_page)'s results, taken from `cache` when it has them
+    /// and added to it when it doesn't
+    pub async fn search_page_cached<S: Search>(
+        &self,
+        search: &S,
+        page: usize,
+        cache: &ResultCache<S>,
+    ) -> Result<Vec<S::Item>, Error>
+    where
+        S::Item: Cacheable,
+    {
+        if let Some(items) = cache.get(search, page) {
+            return Ok(items);
+        }
+        let items = self.search_page(search, page).await?.items;
+        cache.insert(search, page, items.clone())?;
+        Ok(items)
+    }
+}
+
+/// Key of a page, `url` with its parameters and the names in lists of tags sorted
+fn cache_key(url: &str) -> String {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let mut params = query
+        .split('&')
+        .filter(|param| !param.is_empty())
+        .map(|param| match param.split_once('=') {
+            Some((name, names)) if name.ends_with("_names]") || name.ends_with("_name]") => {
+                let mut names = names.split(',').collect::<Vec<&str>>();
+                names.sort_unstable();
+                format!("{name}={}", names.join(","))
+            }
+            _ => param.to_string(),
+        })
+        .collect::<Vec<String>>();
+    params.sort_unstable();
+    format!("{path}?{}", params.join("&"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::AO3Work;
+
+    #[test]
+    fn test_result_cache() {
+        let query = AO3QueryBuilder::new().push_fandom("Original Work");
+        let mut work = AO3Work::default();
+        work.id = String::from("45221314");
+
+        let cache = ResultCache::new(Duration::from_secs(60));
+        assert!(cache.get(&query, 1).is_none());
+        cache.insert(&query, 1, vec![work.clone()]).unwrap();
+        assert_eq!(cache.get(&query, 1), Some(vec![work.clone()]));
+        assert!(cache.get(&query, 2).is_none());
+        // a different query is a different key
+        let other = query.clone().push_fandom("Hollow Knight");
+        assert!(cache.get(&other, 1).is_none());
+
+        // the same query, with the tags added in another order
+        let reordered = AO3QueryBuilder::new()
+            .push_fandom("Hollow Knight")
+            .push_fandom("Original Work");
+        cache.insert(&other, 1, vec![]).unwrap();
+        assert_eq!(cache.get(&reordered, 1), Some(vec![]));
+        assert_eq!(cache.len(), 2);
+
+        // less than a second is kept for less than a second
+        let short = ResultCache::new(Duration::from_millis(200));
+        short.insert(&query, 1, vec![]).unwrap();
+        assert!(short.get(&query, 1).is_some());
+        std::thread::sleep(Duration::from_millis(250));
+        assert!(short.get(&query, 1).is_none());
+
+        let expired = ResultCache::new(Duration::ZERO);
+        expired.insert(&query, 1, vec![work]).unwrap();
+        assert!(expired.get(&query, 1).is_none());
+        expired.purge_expired();
+        assert!(expired.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_search_page_cached() {
+        let query = AO3QueryBuilder::new().set_any_field("horror");
+        let mock = crate::testing::MockAo3::start()
+            .await
+            .unwrap()
+            .page(&query.path(1), include_str!("parse_test/search.html"));
+        let client = mock.client();
+        let cache = ResultCache::new(Duration::from_secs(60));
+        let works = client.search_page_cached(&query, 1, &cache).await.unwrap();
+        assert_eq!(works.len(), 20);
+        let again = client.search_page_cached(&query, 1, &cache).await.unwrap();
+        assert_eq!(again, works);
+        assert_eq!(mock.requests().len(), 1);
+    }
+
+    #[test]
+    fn test_cache_key() {
+        assert_eq!(
+            cache_key("/works/search?work_search[fandom_names]=b,a&page=2&work_search[query]=b,a"),
+            "/works/search?page=2&work_search[fandom_names]=a,b&work_search[query]=b,a"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_persistent_result_cache() {
+        let path = std::env::temp_dir().join(format!("ao3rs-cache-{}.json", std::process::id()));
+        let query = AO3QueryBuilder::new().push_fandom("Original Work");
+        let cache = ResultCache::open(&path, Duration::from_secs(60)).unwrap();
+        cache.insert(&query, 1, vec![AO3Work::default()]).unwrap();
+        let reopened = ResultCache::open(&path, Duration::from_secs(60)).unwrap();
+        assert_eq!(reopened.get(&query, 1).map(|works| works.len()), Some(1));
+        let mut tmp = path.clone().into_os_string();
+        tmp.push(".tmp");
+        assert!(!std::path::Path::new(&tmp).exists());
+        std::fs::remove_file(path).unwrap();
+    }
+}
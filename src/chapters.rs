@@ -0,0 +1,159 @@
+use futures_util::stream::{self, Stream, StreamExt};
+
+use tokio::sync::mpsc;
+
+use crate::client::AO3Client;
+use crate::error::Error;
+use crate::models::{AO3Chapter, AO3Work};
+
+impl AO3Work {
+    /// The chapters of the work in order, each fetched when the stream gets to it
+    ///
+    /// For showing a chapter at a time, instead of [fetch_chapters](AO3Client::fetch_chapters)
+    /// downloading the whole work at once. The chapter index is fetched first.
+    /// ```rust,no_run
+    /// # async fn example(work: ao3rs::models::AO3Work) -> Result<(), ao3rs::Error> {
+    /// use ao3rs::client::AO3Client;
+    /// use futures_util::StreamExt;
+    ///
+    /// let client = AO3Client::new();
+    /// let mut chapters = std::pin::pin!(work.chapters_prefetching(&client, 1));
+    /// while let Some(chapter) = chapters.next().await {
+    ///     println!("{}", chapter?.title);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn chapters<'a>(
+        &'a self,
+        client: &'a AO3Client,
+    ) -> impl Stream<Item = Result<AO3Chapter, Error>> + 'a {
+        self.chapters_prefetching(client, 0)
+    }
+
+    /// [chapters](AO3Work::chapters), fetching up to `ahead` chapters past the one
+    /// being read so the next one is there right away
+    ///
+    /// With `ahead` above 0 the chapters are fetched by a task spawned on the tokio runtime,
+    /// which stops when the stream is dropped.
+    pub fn chapters_prefetching<'a>(
+        &'a self,
+        client: &'a AO3Client,
+        ahead: usize,
+    ) -> impl Stream<Item = Result<AO3Chapter, Error>> + 'a {
+        if ahead == 0 {
+            return stream::once(client.fetch_chapter_ids(&self.id))
+                .map(move |ids| match ids {
+                    Ok(ids) => stream::iter(ids)
+                        .then(move |id| async move { client.fetch_chapter(&self.id, &id).await })
+                        .left_stream(),
+                    Err(err) => stream::iter([Err(err)]).right_stream(),
+                })
+                .flatten()
+                .left_stream();
+        }
+        let (sender, receiver) = mpsc::channel(ahead);
+        tokio::spawn(fetch_chapters_into(client.clone(), self.id.clone(), sender));
+        stream::unfold(receiver, |mut receiver| async move {
+            Some((receiver.recv().await?, receiver))
+        })
+        .right_stream()
+    }
+}
+
+/// Send the chapters of a work in order, each one fetched only once there's room for it,
+/// until they're all sent or the receiver is gone
+async fn fetch_chapters_into(
+    client: AO3Client,
+    work_id: String,
+    sender: mpsc::Sender<Result<AO3Chapter, Error>>,
+) {
+    let ids = match client.fetch_chapter_ids(&work_id).await {
+        Ok(ids) => ids,
+        Err(err) => {
+            let _ = sender.send(Err(err)).await;
+            return;
+        }
+    };
+    for id in ids {
+        let Ok(room) = sender.reserve().await else {
+            return;
+        };
+        room.send(client.fetch_chapter(&work_id, &id).await);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockAo3;
+
+    #[tokio::test]
+    async fn test_chapters() {
+        let html = include_str!("parse_test/work.html");
+        let mock = MockAo3::start()
+            .await
+            .unwrap()
+            .page(
+                "/works/45221314/navigate",
+                include_str!("parse_test/navigate.html"),
+            )
+            .page("/works/45221314/chapters/113700001?view_adult=true", html)
+            .page("/works/45221314/chapters/113700002?view_adult=true", html)
+            .page("/works/45221314/chapters/113700003?view_adult=true", html);
+        let client = mock.client();
+        let mut work = AO3Work::default();
+        work.id = "45221314".to_string();
+
+        let mut chapters = std::pin::pin!(work.chapters(&client));
+        let first = chapters.next().await.unwrap().unwrap();
+        assert_eq!(first.title, "Chapter 1: Before");
+        // nothing past the chapter that was asked for
+        assert_eq!(mock.requests().len(), 2);
+        let rest: Vec<_> = chapters.map(Result::unwrap).collect().await;
+        let titles: Vec<_> = rest.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(titles, vec!["Chapter 2: During", "Chapter 3: After"]);
+
+        let mut missing = AO3Work::default();
+        missing.id = "1".to_string();
+        let failed: Vec<_> = missing.chapters_prefetching(&client, 1).collect().await;
+        assert!(matches!(failed[..], [Err(Error::NotFound)]));
+    }
+
+    #[tokio::test]
+    async fn test_chapters_prefetching() {
+        let html = include_str!("parse_test/work.html");
+        let mock = MockAo3::start()
+            .await
+            .unwrap()
+            .page(
+                "/works/45221314/navigate",
+                include_str!("parse_test/navigate.html"),
+            )
+            .page("/works/45221314/chapters/113700001?view_adult=true", html)
+            .page("/works/45221314/chapters/113700002?view_adult=true", html)
+            .page("/works/45221314/chapters/113700003?view_adult=true", html);
+        let client = mock.client();
+        let mut work = AO3Work::default();
+        work.id = "45221314".to_string();
+
+        let mut chapters = std::pin::pin!(work.chapters_prefetching(&client, 1));
+        let first = chapters.next().await.unwrap().unwrap();
+        assert_eq!(first.title, "Chapter 1: Before");
+        // the second chapter is fetched while the first one is read, not the third
+        let second = "/works/45221314/chapters/113700002?view_adult=true";
+        tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            while !mock.requests().iter().any(|r| r == second) {
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        assert_eq!(mock.requests().len(), 3);
+
+        let rest: Vec<_> = chapters.map(Result::unwrap).collect().await;
+        let titles: Vec<_> = rest.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(titles, vec!["Chapter 2: During", "Chapter 3: After"]);
+    }
+}
@@ -0,0 +1,135 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Progress of a long running operation, kept in a file so it can resume after a crash
+///
+/// The file is a few lines of text: what the operation is, the page it's on
+/// and the items of that page already done.
+/// ```text
+/// operation: mirror_tag Horror
+/// page: 12
+/// done: 45221314
+/// done: 45221302
+/// ```
+/// A checkpoint of a different operation found at the path is ignored and overwritten,
+/// so mirroring one tag never resumes from where another one stopped.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Checkpoint {
+    path: PathBuf,
+    operation: String,
+    page: usize,
+    done: HashSet<String>,
+}
+
+impl Checkpoint {
+    /// Load the checkpoint of `operation` stored at `path`, or start one at page 1
+    pub fn load(path: impl AsRef<Path>, operation: &str) -> std::io::Result<Self> {
+        let mut checkpoint = Checkpoint {
+            path: path.as_ref().to_path_buf(),
+            operation: operation.to_string(),
+            page: 1,
+            done: HashSet::new(),
+        };
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(checkpoint),
+            Err(err) => return Err(err),
+        };
+        let mut lines = text.lines().filter_map(|line| line.split_once(": "));
+        if lines.next() != Some(("operation", operation)) {
+            return Ok(checkpoint);
+        }
+        for (key, value) in lines {
+            match key {
+                "page" => checkpoint.page = value.parse().unwrap_or(1),
+                "done" => {
+                    checkpoint.done.insert(value.to_string());
+                }
+                _ => {}
+            }
+        }
+        Ok(checkpoint)
+    }
+
+    pub fn get_operation(&self) -> &str {
+        &self.operation
+    }
+
+    /// Page the operation is on, starting at 1
+    pub fn page(&self) -> usize {
+        self.page
+    }
+
+    /// Whether the operation resumes from an earlier run
+    pub fn is_resumed(&self) -> bool {
+        self.page > 1 || !self.done.is_empty()
+    }
+
+    /// Whether `item` of the current page was already done
+    pub fn is_done(&self, item: &str) -> bool {
+        self.done.contains(item)
+    }
+
+    /// Record that `item` of the current page is done
+    pub fn mark_done(&mut self, item: &str) -> std::io::Result<()> {
+        self.done.insert(item.to_string());
+        self.save()
+    }
+
+    /// Move on to the next page
+    pub fn next_page(&mut self) -> std::io::Result<()> {
+        self.page += 1;
+        self.done.clear();
+        self.save()
+    }
+
+    /// The operation is done, remove the file so the next run starts over
+    pub fn finish(self) -> std::io::Result<()> {
+        match std::fs::remove_file(&self.path) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    fn save(&self) -> std::io::Result<()> {
+        let mut text = format!("operation: {}\npage: {}\n", self.operation, self.page);
+        for item in &self.done {
+            text.push_str(&format!("done: {item}\n"));
+        }
+        // written next to the checkpoint and moved over it, so a crash never leaves half a file
+        let temp = self.path.with_extension("tmp");
+        std::fs::write(&temp, text)?;
+        std::fs::rename(temp, &self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkpoint() {
+        let path = std::env::temp_dir().join(format!("ao3rs-checkpoint-{}", std::process::id()));
+        let mut checkpoint = Checkpoint::load(&path, "mirror_tag Horror").unwrap();
+        assert_eq!(checkpoint.page(), 1);
+        assert!(!checkpoint.is_resumed());
+        checkpoint.next_page().unwrap();
+        checkpoint.mark_done("45221314").unwrap();
+
+        let checkpoint = Checkpoint::load(&path, "mirror_tag Horror").unwrap();
+        assert_eq!(checkpoint.page(), 2);
+        assert!(checkpoint.is_resumed());
+        assert!(checkpoint.is_done("45221314"));
+        assert!(!checkpoint.is_done("45221302"));
+        // a checkpoint of something else starts over
+        assert_eq!(
+            Checkpoint::load(&path, "mirror_tag Original Work")
+                .unwrap()
+                .page(),
+            1
+        );
+
+        checkpoint.finish().unwrap();
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,1100 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::stream::{self, StreamExt};
+use tokio::io::AsyncWriteExt;
+
+use crate::error::{Error, ParsingError};
+use crate::form::BASE_AO3_URL;
+use crate::models::{
+    AO3Bookmark, AO3Chapter, AO3Comment, AO3Series, AO3Tag, AO3Work, DownloadFormat, TagKind,
+    WorkRef, WorkUpdate,
+};
+use crate::parse::{
+    check_unavailable, parse_chapter_ids, parse_chapters, parse_comments, parse_download_path,
+    parse_page_count, parse_search, parse_series, parse_tag, parse_tag_id, parse_work,
+    parse_work_bookmarks,
+};
+use crate::progress::{Monitor, Progress};
+
+mod handles;
+mod in_flight;
+#[cfg(feature = "tower")]
+mod service;
+mod tag_ids;
+
+pub use handles::{SeriesHandle, TagHandle, UserHandle, WorkHandle};
+use in_flight::InFlight;
+#[cfg(feature = "tower")]
+pub use service::{AO3Request, AO3Response};
+use tag_ids::TagIds;
+
+/// Where to stop when fetching comments newest first
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub enum CommentsSince {
+    /// Fetch every comment
+    #[default]
+    Beginning,
+
+    /// Stop at the first comment posted at or before this time
+    Date(chrono::NaiveDateTime),
+
+    /// Stop at the comment with this id
+    Comment(String),
+}
+
+impl CommentsSince {
+    fn is_reached(&self, comment: &AO3Comment) -> bool {
+        match self {
+            CommentsSince::Beginning => false,
+            CommentsSince::Date(date) => comment.date.is_some_and(|d| d <= *date),
+            CommentsSince::Comment(id) => &comment.id == id,
+        }
+    }
+}
+
+/// A response slower than this makes an adaptive rate limit slow down
+const SLOW_RESPONSE: Duration = Duration::from_secs(5);
+
+/// The smallest step an adaptive rate limit slows down by, when it had no interval yet
+const MIN_SLOWDOWN: Duration = Duration::from_secs(1);
+
+/// Spaces requests out, shared by every clone of a client
+#[derive(Debug, Default)]
+struct RateLimiter {
+    min_interval: Duration,
+
+    /// Set when the interval adapts to how AO3 is doing, it never goes past this
+    max_interval: Option<Duration>,
+
+    pace: std::sync::Mutex<Pace>,
+}
+
+#[derive(Debug, Default)]
+struct Pace {
+    /// When the next request may be sent
+    next: Option<tokio::time::Instant>,
+
+    interval: Duration,
+}
+
+impl RateLimiter {
+    fn new(min_interval: Duration) -> Self {
+        RateLimiter {
+            min_interval,
+            pace: std::sync::Mutex::new(Pace {
+                next: None,
+                interval: min_interval,
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn adaptive(min_interval: Duration, max_interval: Duration) -> Self {
+        RateLimiter {
+            max_interval: Some(max_interval.max(min_interval)),
+            ..RateLimiter::new(min_interval)
+        }
+    }
+
+    /// Wait for the turn of a request
+    async fn wait(&self) {
+        if self.min_interval.is_zero() && self.max_interval.is_none() {
+            return;
+        }
+        // every request books the next free turn, so they go out in the order they came in,
+        // and the lock isn't held while waiting for it
+        let at = {
+            let mut pace = self.pace.lock().unwrap();
+            let now = tokio::time::Instant::now();
+            let at = pace.next.map_or(now, |at| at.max(now));
+            pace.next = Some(at + pace.interval);
+            at
+        };
+        tokio::time::sleep_until(at).await;
+    }
+
+    /// Adapt the interval to a response: back off when AO3 struggles, speed up slowly when it doesn't
+    fn record(&self, status: reqwest::StatusCode, elapsed: Duration) {
+        let Some(max_interval) = self.max_interval else {
+            return;
+        };
+        let mut pace = self.pace.lock().unwrap();
+        let struggling = status == reqwest::StatusCode::TOO_MANY_REQUESTS
+            || status.is_server_error()
+            || elapsed > SLOW_RESPONSE;
+        pace.interval = if struggling {
+            (pace.interval * 2).max(MIN_SLOWDOWN).min(max_interval)
+        } else {
+            (pace.interval * 9 / 10).max(self.min_interval)
+        };
+    }
+}
+
+/// Client for reading public pages of AO3
+///
+/// Cheap to clone, clones share the same connection pool and rate limit.
+#[derive(Debug, Default, Clone)]
+pub struct AO3Client {
+    client: reqwest::Client,
+    limiter: Arc<RateLimiter>,
+    max_response_size: Option<u64>,
+    tag_ids: Arc<TagIds>,
+    snapshot_dir: Option<PathBuf>,
+    base_url: Option<String>,
+    dry_run: bool,
+    refetch_on_parse_error: bool,
+    keep_raw: bool,
+    in_flight: Arc<InFlight>,
+}
+
+impl AO3Client {
+    pub fn new() -> Self {
+        AO3Client {
+            ..Default::default()
+        }
+    }
+
+    /// Send at most one request every `min_interval`, however many are made at once
+    ///
+    /// There is no limit by default.
+    pub fn set_rate_limit(mut self, min_interval: Duration) -> Self {
+        self.limiter = Arc::new(RateLimiter::new(min_interval));
+        self
+    }
+
+    /// Like [set_rate_limit](AO3Client::set_rate_limit), but waiting longer between requests
+    /// while AO3 is under load
+    ///
+    /// The interval doubles, up to `max_interval`, whenever AO3 answers with 429 Too Many Requests
+    /// or a server error or takes more than a few seconds to answer. After that every quick answer
+    /// takes a tenth off of it, until it's back to `min_interval`.
+    /// Meant for long jobs like mirroring a tag.
+    pub fn set_adaptive_rate_limit(
+        mut self,
+        min_interval: Duration,
+        max_interval: Duration,
+    ) -> Self {
+        self.limiter = Arc::new(RateLimiter::adaptive(min_interval, max_interval));
+        self
+    }
+
+    /// Give up on responses bigger than `max_size` bytes, pages as well as downloads
+    ///
+    /// There is no limit by default. Whole works with hundreds of thousands of words
+    /// make pages of several megabytes, so this keeps memory in check where it's scarce.
+    pub fn set_max_response_size(mut self, max_size: u64) -> Self {
+        self.max_response_size = Some(max_size);
+        self
+    }
+
+    /// Build requests without sending them, every fetch fails with [DryRun](Error::DryRun)
+    /// holding the request it would have sent
+    pub fn set_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Send requests to `base_url` instead of AO3, e.g. a mirror or a mock server
+    ///
+    /// Urls in what is parsed, like [AO3Work::url], still point to AO3.
+    pub fn set_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = Some(base_url.trim_end_matches('/').to_string());
+        self
+    }
+
+    /// Fetch a page again once when it doesn't parse, before giving up on it, off by default
+    ///
+    /// Pages that don't parse are often just cut off, or a variant AO3 served only that once.
+    /// The second request has an extra parameter so no cache along the way answers it.
+    pub fn set_refetch_on_parse_error(mut self, refetch: bool) -> Self {
+        self.refetch_on_parse_error = refetch;
+        self
+    }
+
+    /// Keep the html of the pages works, chapters and search results are parsed from
+    /// on them, off by default
+    ///
+    /// For archiving pages as they were, and parsing them again once the parser knows more.
+    /// Pages are big, so only turn it on when they're needed.
+    pub fn set_keep_raw(mut self, keep_raw: bool) -> Self {
+        self.keep_raw = keep_raw;
+        self
+    }
+
+    /// `html` when pages are [kept](AO3Client::set_keep_raw)
+    pub(crate) fn raw(&self, html: &str) -> Option<String> {
+        self.keep_raw.then(|| html.to_string())
+    }
+
+    fn base_url(&self) -> &str {
+        self.base_url.as_deref().unwrap_or(BASE_AO3_URL)
+    }
+
+    /// Save the html of every page that fails to parse to a file in `dir`, off by default
+    ///
+    /// The path of the file is in the [context](crate::error::ParseContext::snapshot) of the error.
+    /// Meant for debugging, e.g. pages that only break with logged in or logged out markup.
+    pub fn set_snapshot_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.snapshot_dir = Some(dir.into());
+        self
+    }
+
+    /// Remember the ids of the last `capacity` tags resolved, 256 by default
+    ///
+    /// A [file](AO3Client::set_tag_id_file) set before is kept, it has every id anyway.
+    pub fn set_tag_id_cache_size(mut self, capacity: usize) -> Self {
+        self.tag_ids = Arc::new(self.tag_ids.resized(capacity));
+        self
+    }
+
+    /// Keep the ids of resolved tags in the file at `path`, so later runs don't resolve them again
+    ///
+    /// Ids that are in the file already are used right away.
+    pub fn set_tag_id_file(mut self, path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        self.tag_ids = Arc::new(TagIds::open(path)?);
+        Ok(self)
+    }
+
+    /// Fetch a page, `path` is relative to the root of AO3
+    ///
+    /// A page that's being fetched already, by this client or a clone of it, isn't requested again.
+    pub(crate) async fn get_html(&self, path: &str) -> Result<String, Error> {
+        self.in_flight
+            .get_or_fetch(path, || async {
+                let resp = self.get(path).await?;
+                read_html(resp, self.max_response_size).await
+            })
+            .await
+    }
+
+    /// Fetch a page and parse it, parse errors tell which page it was
+    pub(crate) async fn get_parsed<T>(
+        &self,
+        path: &str,
+        parse: impl Fn(&str) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        let mut fetched = path.to_string();
+        let mut html = self.get_html(&fetched).await?;
+        let mut parsed = parse(&html);
+        if self.refetch_on_parse_error && matches!(parsed, Err(Error::Parse { .. })) {
+            // the error is about the page that was fetched last
+            fetched = cache_busting(path);
+            html = self.get_html(&fetched).await?;
+            parsed = parse(&html);
+        }
+        parsed.map_err(|err| {
+            err.in_page(&format!("{}{fetched}", self.base_url()), &html)
+                .save_snapshot(self.snapshot_dir.as_deref(), &html)
+        })
+    }
+
+    /// Send a request once the rate limit allows it, `path` is relative to the root of AO3
+    async fn get(&self, path: &str) -> Result<reqwest::Response, Error> {
+        let request = self
+            .client
+            .get(format!("{}{path}", self.base_url()))
+            .build()?;
+        if self.dry_run {
+            return Err(Error::DryRun(Box::new(BuiltRequest::from(&request))));
+        }
+        self.limiter.wait().await;
+        let sent_at = tokio::time::Instant::now();
+        let resp = self.client.execute(request).await?;
+        self.limiter.record(resp.status(), sent_at.elapsed());
+        check_response(resp).await
+    }
+
+    /// Fetch a page of comments on a work, with their replies
+    ///
+    /// Threads AO3 cuts short ("N more comments in this thread") are followed,
+    /// so every reply is included.
+    pub async fn fetch_comments(
+        &self,
+        work_id: &str,
+        page: usize,
+    ) -> Result<Vec<AO3Comment>, Error> {
+        let mut comments = self
+            .get_parsed(
+                &format!("/works/{work_id}?show_comments=true&view_full_work=true&page={page}"),
+                parse_comments,
+            )
+            .await?;
+        self.fetch_remaining_replies(&mut comments).await?;
+        Ok(comments)
+    }
+
+    /// Fetch the comments on a work starting from the newest one, stopping once `since` is reached
+    ///
+    /// Only the comments starting a thread are compared with `since`, threads are returned
+    /// with all their replies. This lets pollers only download what's new since their last run.
+    pub async fn fetch_comments_newest_first(
+        &self,
+        work_id: &str,
+        since: &CommentsSince,
+    ) -> Result<Vec<AO3Comment>, Error> {
+        let comments_path =
+            |page| format!("/works/{work_id}?show_comments=true&view_full_work=true&page={page}");
+        // the first page tells how many pages there are, it's the last one we need though
+        let (page_count, first_comments) = self
+            .get_parsed(&comments_path(1), |html| {
+                Ok((parse_page_count(html)?, parse_comments(html)?))
+            })
+            .await?;
+        let mut first_page = Some(first_comments);
+        let mut newest = vec![];
+        'pages: for page in (1..=page_count).rev() {
+            let comments = match first_page.take_if(|_| page == 1) {
+                Some(comments) => comments,
+                None => {
+                    self.get_parsed(&comments_path(page), parse_comments)
+                        .await?
+                }
+            };
+            for comment in comments.into_iter().rev() {
+                if since.is_reached(&comment) {
+                    break 'pages;
+                }
+                newest.push(comment);
+            }
+        }
+        self.fetch_remaining_replies(&mut newest).await?;
+        Ok(newest)
+    }
+
+    /// Fetch the metadata of a work from its page
+    pub async fn fetch_work(&self, work_id: &str) -> Result<AO3Work, Error> {
+        self.get_parsed(&format!("/works/{work_id}?view_adult=true"), |html| {
+            let mut work = parse_work(html)?;
+            work.raw_html = self.raw(html);
+            Ok(work)
+        })
+        .await
+    }
+
+    /// Fetch a series with the blurbs of the works in it
+    pub async fn fetch_series(&self, series_id: &str) -> Result<AO3Series, Error> {
+        self.get_parsed(&format!("/series/{series_id}"), parse_series)
+            .await
+    }
+
+    /// Fetch a page of the works a user posted, most recently updated first
+    pub async fn fetch_user_works(
+        &self,
+        username: &str,
+        page: usize,
+    ) -> Result<Vec<AO3Work>, Error> {
+        Ok(self.fetch_user_page(username, page).await?.0)
+    }
+
+    /// Works on a page of a user's works, and how many pages there are
+    pub(crate) async fn fetch_user_page(
+        &self,
+        username: &str,
+        page: usize,
+    ) -> Result<(Vec<AO3Work>, usize), Error> {
+        self.get_parsed(&format!("/users/{username}/works?page={page}"), |html| {
+            Ok((parse_search(html)?, parse_page_count(html)?))
+        })
+        .await
+    }
+
+    /// Fetch many works, up to `concurrency` at a time
+    ///
+    /// Results are in the order of `work_ids`, a work that failed doesn't stop the others.
+    /// Requests still respect the [rate limit](AO3Client::set_rate_limit).
+    /// ```rust,no_run
+    /// # async fn example() {
+    /// use ao3rs::client::AO3Client;
+    ///
+    /// let client = AO3Client::new().set_rate_limit(std::time::Duration::from_secs(1));
+    /// for result in client.fetch_works(&["45221314", "45221302"], 4).await {
+    ///     match result {
+    ///         Ok(work) => println!("{}", work.title),
+    ///         Err(err) => eprintln!("{err}"),
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub async fn fetch_works<S: AsRef<str>>(
+        &self,
+        work_ids: &[S],
+        concurrency: usize,
+    ) -> Vec<Result<AO3Work, Error>> {
+        stream::iter(work_ids)
+            .map(|work_id| self.fetch_work(work_id.as_ref()))
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    /// Look at stored works again and report the ones that gained (or lost) chapters
+    /// or whose updated date changed since they were stored
+    ///
+    /// Only the first chapter of each work is downloaded along with its metadata,
+    /// at most `concurrency` at a time like [fetch_works](AO3Client::fetch_works).
+    /// There is a result for every work in the order given, `None` when it didn't change.
+    pub async fn check_updates(
+        &self,
+        works: &[WorkRef],
+        concurrency: usize,
+    ) -> Vec<Result<Option<WorkUpdate>, Error>> {
+        let work_ids: Vec<_> = works.iter().map(|stored| stored.id.as_str()).collect();
+        let fetched = self.fetch_works(&work_ids, concurrency).await;
+        works
+            .iter()
+            .zip(fetched)
+            .map(|(stored, work)| {
+                let work = work?;
+                let new_chapters = work.chapter_count as isize - stored.chapter_count as isize;
+                let date_changed = work.date != stored.date;
+                Ok((new_chapters != 0 || date_changed).then_some(WorkUpdate {
+                    work,
+                    new_chapters,
+                    date_changed,
+                }))
+            })
+            .collect()
+    }
+
+    /// Fetch the ids of the chapters of a work, in order
+    pub async fn fetch_chapter_ids(&self, work_id: &str) -> Result<Vec<String>, Error> {
+        self.get_parsed(&format!("/works/{work_id}/navigate"), parse_chapter_ids)
+            .await
+    }
+
+    /// Fetch every chapter of a work, in order
+    ///
+    /// They all come from the same page, when [kept](AO3Client::set_keep_raw)
+    /// it's on the first chapter only.
+    pub async fn fetch_chapters(&self, work_id: &str) -> Result<Vec<AO3Chapter>, Error> {
+        self.get_parsed(
+            &format!("/works/{work_id}?view_full_work=true&view_adult=true"),
+            |html| {
+                let mut chapters = parse_chapters(html)?;
+                if let Some(first) = chapters.first_mut() {
+                    first.raw_html = self.raw(html);
+                }
+                Ok(chapters)
+            },
+        )
+        .await
+    }
+
+    /// Fetch a single chapter of a work
+    ///
+    /// `chapter_id` is the id AO3 gives the chapter, see [fetch_chapter_ids](AO3Client::fetch_chapter_ids)
+    pub async fn fetch_chapter(
+        &self,
+        work_id: &str,
+        chapter_id: &str,
+    ) -> Result<AO3Chapter, Error> {
+        self.get_parsed(
+            &format!("/works/{work_id}/chapters/{chapter_id}?view_adult=true"),
+            |html| {
+                let chapters = parse_chapters(html)?;
+                // a work's only chapter has no id of its own on the page
+                let i = chapters
+                    .iter()
+                    .position(|c| c.id == chapter_id)
+                    .unwrap_or_default();
+                let chapter = chapters
+                    .into_iter()
+                    .nth(i)
+                    .ok_or(ParsingError::CouldNotFind("the text of the chapter."))?;
+                Ok(AO3Chapter {
+                    raw_html: self.raw(html),
+                    ..chapter
+                })
+            },
+        )
+        .await
+    }
+
+    /// Fetch a page of comments left on a single chapter, with their replies
+    ///
+    /// `chapter_id` is the id AO3 gives the chapter, not its number,
+    /// see [fetch_chapter_ids](AO3Client::fetch_chapter_ids)
+    pub async fn fetch_chapter_comments(
+        &self,
+        chapter_id: &str,
+        page: usize,
+    ) -> Result<Vec<AO3Comment>, Error> {
+        let mut comments = self
+            .get_parsed(
+                &format!(
+                    "/chapters/{chapter_id}?show_comments=true&view_full_work=false&page={page}"
+                ),
+                parse_comments,
+            )
+            .await?;
+        self.fetch_remaining_replies(&mut comments).await?;
+        Ok(comments)
+    }
+
+    /// Fetch a page of the works listed under a tag, newest first
+    ///
+    /// Works tagged with a synonym of `tag` are listed too.
+    pub async fn fetch_tag_works(&self, tag: &str, page: usize) -> Result<Vec<AO3Work>, Error> {
+        Ok(self.fetch_tag_page(tag, page).await?.0)
+    }
+
+    /// Get the id AO3 gives a tag, which its filters use instead of the name
+    ///
+    /// Ids are cached, see [set_tag_id_cache_size](AO3Client::set_tag_id_cache_size)
+    /// and [set_tag_id_file](AO3Client::set_tag_id_file), so only the first lookup of a tag
+    /// fetches its page. A synonym gets the id of its canonical tag.
+    pub async fn fetch_tag_id(&self, tag: &str) -> Result<String, Error> {
+        if let Some(id) = self.tag_ids.get(tag) {
+            return Ok(id);
+        }
+        let id = self
+            .get_parsed(&format!("/tags/{}/works", tag_path(tag)), parse_tag_id)
+            .await?;
+        self.tag_ids.insert(tag, &id)?;
+        Ok(id)
+    }
+
+    /// Canonical tags of the kind `kind` that match `term`, best match first,
+    /// like AO3 suggests them while typing in a filter
+    #[cfg(feature = "serde")]
+    pub async fn autocomplete(&self, kind: TagKind, term: &str) -> Result<Vec<String>, Error> {
+        #[derive(serde::Deserialize)]
+        struct Suggestion {
+            name: String,
+        }
+        let path = format!(
+            "/autocomplete/{}?term={}",
+            kind.name(),
+            encode_query_value(term)
+        );
+        self.get_parsed(&path, |json| {
+            let suggestions: Vec<Suggestion> = serde_json::from_str(json)?;
+            Ok(suggestions.into_iter().map(|s| s.name).collect())
+        })
+        .await
+    }
+
+    /// The id of the canonical tag for `name`, to filter on it
+    ///
+    /// With the `serde` feature the name goes through `autocomplete` first, which picks
+    /// the tag with the same name in any case or else the best match, so "hollow knight"
+    /// finds "Hollow Knight (Video Game)". Without it the name has to be a tag already,
+    /// synonyms get the id of their canonical tag either way.
+    #[cfg_attr(not(feature = "serde"), allow(unused_variables))]
+    pub async fn resolve_tag_id(&self, kind: TagKind, name: &str) -> Result<String, Error> {
+        #[cfg(feature = "serde")]
+        {
+            let suggestions = self.autocomplete(kind, name).await?;
+            let best = suggestions
+                .iter()
+                .find(|s| s.eq_ignore_ascii_case(name))
+                .or(suggestions.first());
+            if let Some(best) = best {
+                return self.fetch_tag_id(best).await;
+            }
+        }
+        self.fetch_tag_id(name).await
+    }
+
+    /// Fetch a tag's page, which says what it's a synonym of or what its synonyms are
+    ///
+    /// See [TagResolver](crate::tags::TagResolver) for treating synonyms as one tag.
+    pub async fn fetch_tag(&self, tag: &str) -> Result<AO3Tag, Error> {
+        self.get_parsed(&format!("/tags/{}", tag_path(tag)), parse_tag)
+            .await
+    }
+
+    /// Works on a page of a tag's listing, and how many pages there are
+    pub(crate) async fn fetch_tag_page(
+        &self,
+        tag: &str,
+        page: usize,
+    ) -> Result<(Vec<AO3Work>, usize), Error> {
+        self.get_parsed(
+            &format!("/tags/{}/works?page={page}&view_adult=true", tag_path(tag)),
+            |html| Ok((parse_search(html)?, parse_page_count(html)?)),
+        )
+        .await
+    }
+
+    /// Fetch a page of the public bookmarks of a work
+    pub async fn fetch_work_bookmarks(
+        &self,
+        work_id: &str,
+        page: usize,
+    ) -> Result<Vec<AO3Bookmark>, Error> {
+        self.get_parsed(
+            &format!("/works/{work_id}/bookmarks?page={page}"),
+            parse_work_bookmarks,
+        )
+        .await
+    }
+
+    /// Download a work in `format` and save it to `path`, returns the size of the file
+    ///
+    /// The file is written as it arrives, so big works aren't held in memory.
+    pub async fn download_work(
+        &self,
+        work_id: &str,
+        format: DownloadFormat,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<u64, Error> {
+        self.download_work_with_progress(work_id, format, path, &Monitor::default())
+            .await
+    }
+
+    /// [download_work](AO3Client::download_work), reporting the bytes written to `monitor`
+    ///
+    /// When cancelled the partly written file is removed.
+    pub async fn download_work_with_progress(
+        &self,
+        work_id: &str,
+        format: DownloadFormat,
+        path: impl AsRef<std::path::Path>,
+        monitor: &Monitor,
+    ) -> Result<u64, Error> {
+        // the download link changes whenever the work is updated, so it has to be read from the work
+        let download_path = self
+            .get_parsed(&format!("/works/{work_id}?view_adult=true"), |html| {
+                parse_download_path(html, &format)
+            })
+            .await?;
+        let mut resp = self.get(&download_path).await?;
+        check_size(resp.content_length(), self.max_response_size)?;
+        let path = path.as_ref();
+        let mut file = tokio::fs::File::create(path).await?;
+        let mut progress = Progress {
+            total_bytes: resp.content_length(),
+            ..Default::default()
+        };
+        while let Some(chunk) = resp.chunk().await? {
+            progress.bytes_downloaded += chunk.len() as u64;
+            let checked = check_size(Some(progress.bytes_downloaded), self.max_response_size)
+                .and_then(|_| monitor.check());
+            if let Err(err) = checked {
+                // don't leave half an epub behind
+                drop(file);
+                tokio::fs::remove_file(path).await?;
+                return Err(err);
+            }
+            file.write_all(&chunk).await?;
+            monitor.report(&progress);
+        }
+        file.flush().await?;
+        Ok(progress.bytes_downloaded)
+    }
+
+    async fn fetch_remaining_replies(&self, comments: &mut [AO3Comment]) -> Result<(), Error> {
+        while let Some(comment) = find_cut_off_comment(comments) {
+            // the thread page starts with the comment itself
+            comment.replies = self
+                .get_parsed(&format!("/comments/{}", comment.id), parse_comments)
+                .await?
+                .into_iter()
+                .next()
+                .map(|c| c.replies)
+                .unwrap_or_default();
+            comment.has_more_replies = false;
+        }
+        Ok(())
+    }
+}
+
+/// `path` with a parameter that's different every time
+fn cache_busting(path: &str) -> String {
+    let separator = if path.contains('?') { '&' } else { '?' };
+    format!(
+        "{path}{separator}_={}",
+        chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+    )
+}
+
+/// A request as it would have been sent, see [set_dry_run](AO3Client::set_dry_run)
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct BuiltRequest {
+    pub method: String,
+    pub url: String,
+    /// Headers set for this request, the ones the client adds to every request aren't included
+    pub headers: Vec<(String, String)>,
+    /// Body of the request, url encoded for forms
+    pub body: Option<String>,
+}
+
+impl From<&reqwest::Request> for BuiltRequest {
+    fn from(request: &reqwest::Request) -> Self {
+        BuiltRequest {
+            method: request.method().to_string(),
+            url: request.url().to_string(),
+            headers: request
+                .headers()
+                .iter()
+                .map(|(name, value)| {
+                    let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+                    (name.to_string(), value)
+                })
+                .collect(),
+            body: request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .map(|bytes| String::from_utf8_lossy(bytes).into_owned()),
+        }
+    }
+}
+
+/// What [check_status] looks at in a response, async or blocking
+pub(crate) trait ResponseHead {
+    fn status(&self) -> reqwest::StatusCode;
+    fn url(&self) -> &reqwest::Url;
+    fn headers(&self) -> &reqwest::header::HeaderMap;
+    fn status_error(&self) -> Option<reqwest::Error>;
+}
+
+impl ResponseHead for reqwest::Response {
+    fn status(&self) -> reqwest::StatusCode {
+        self.status()
+    }
+
+    fn url(&self) -> &reqwest::Url {
+        self.url()
+    }
+
+    fn headers(&self) -> &reqwest::header::HeaderMap {
+        self.headers()
+    }
+
+    fn status_error(&self) -> Option<reqwest::Error> {
+        self.error_for_status_ref().err()
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl ResponseHead for reqwest::blocking::Response {
+    fn status(&self) -> reqwest::StatusCode {
+        self.status()
+    }
+
+    fn url(&self) -> &reqwest::Url {
+        self.url()
+    }
+
+    fn headers(&self) -> &reqwest::header::HeaderMap {
+        self.headers()
+    }
+
+    fn status_error(&self) -> Option<reqwest::Error> {
+        self.error_for_status_ref().err()
+    }
+}
+
+/// Turn the statuses AO3 answers with into errors
+///
+/// Other error statuses come back as `Ok(Some(_))`, their page still has to be
+/// [checked](check_unavailable) for AO3 being down before going with the status.
+pub(crate) fn check_status(resp: &impl ResponseHead) -> Result<Option<reqwest::Error>, Error> {
+    match resp.status() {
+        reqwest::StatusCode::NOT_FOUND => Err(Error::NotFound),
+        reqwest::StatusCode::TOO_MANY_REQUESTS => Err(Error::RateLimited {
+            retry_after: retry_after(resp),
+        }),
+        // restricted works send visitors to the login page instead
+        _ if resp.url().path() == "/users/login"
+            && resp
+                .url()
+                .query()
+                .is_some_and(|q| q.contains("restricted=true")) =>
+        {
+            Err(Error::Restricted)
+        }
+        _ => Ok(resp.status_error()),
+    }
+}
+
+/// [check_status], and the page of an error status
+pub(crate) async fn check_response(resp: reqwest::Response) -> Result<reqwest::Response, Error> {
+    match check_status(&resp)? {
+        // Cloudflare and the maintenance page come with 403 or 503
+        Some(err) => {
+            check_unavailable(&resp.text().await.unwrap_or_default())?;
+            Err(err.into())
+        }
+        None => Ok(resp),
+    }
+}
+
+/// The wait asked for in a `Retry-After` header, AO3 gives it in seconds
+pub(crate) fn retry_after(resp: &impl ResponseHead) -> Option<Duration> {
+    let seconds = resp.headers().get(reqwest::header::RETRY_AFTER)?;
+    Some(Duration::from_secs(
+        seconds.to_str().ok()?.trim().parse().ok()?,
+    ))
+}
+
+/// Read the body of a page as it arrives, stopping as soon as it goes over `max_size`
+pub(crate) async fn read_html(
+    mut resp: reqwest::Response,
+    max_size: Option<u64>,
+) -> Result<String, Error> {
+    check_size(resp.content_length(), max_size)?;
+    let mut body = Vec::with_capacity(resp.content_length().unwrap_or_default() as usize);
+    while let Some(chunk) = resp.chunk().await? {
+        check_size(Some((body.len() + chunk.len()) as u64), max_size)?;
+        body.extend_from_slice(&chunk);
+    }
+    // AO3 always answers in utf-8, stray bytes are replaced like `Response::text` does
+    let html = String::from_utf8(body)
+        .unwrap_or_else(|err| String::from_utf8_lossy(err.as_bytes()).into_owned());
+    check_unavailable(&html)?;
+    Ok(html)
+}
+
+fn check_size(size: Option<u64>, max_size: Option<u64>) -> Result<(), Error> {
+    match (size, max_size) {
+        (Some(size), Some(max_size)) if size > max_size => Err(Error::ResponseTooLarge(max_size)),
+        _ => Ok(()),
+    }
+}
+
+/// AO3 escapes the characters of a tag that mean something in urls with its own codes
+/// Percent-encode everything but letters, digits and `-._~` so `value` can go in a query
+pub(crate) fn encode_query_value(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+pub(crate) fn tag_path(tag: &str) -> String {
+    tag.replace('/', "*s*")
+        .replace('&', "*a*")
+        .replace('.', "*d*")
+        .replace('?', "*q*")
+        .replace('#', "*h*")
+}
+
+fn find_cut_off_comment(comments: &mut [AO3Comment]) -> Option<&mut AO3Comment> {
+    for comment in comments {
+        if comment.has_more_replies {
+            return Some(comment);
+        }
+        if let Some(found) = find_cut_off_comment(&mut comment.replies) {
+            return Some(found);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_tag_id_file_and_cache_size() {
+        let path =
+            std::env::temp_dir().join(format!("ao3rs_client_tag_ids_{}", std::process::id()));
+        std::fs::write(&path, "116\tHorror\n").unwrap();
+        // the file is still used after the cache is resized, without any request
+        let client = AO3Client::new()
+            .set_tag_id_file(&path)
+            .unwrap()
+            .set_tag_id_cache_size(10)
+            .set_dry_run(true);
+        assert_eq!(client.fetch_tag_id("Horror").await.unwrap(), "116");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_dry_run() {
+        let client = AO3Client::new().set_dry_run(true);
+        let Err(Error::DryRun(request)) = client.fetch_work("45221314").await else {
+            panic!("a request was sent");
+        };
+        assert_eq!(request.method, "GET");
+        assert_eq!(
+            request.url,
+            "https://archiveofourown.org/works/45221314?view_adult=true"
+        );
+        assert_eq!(request.body, None);
+    }
+
+    #[tokio::test]
+    async fn test_refetch_on_parse_error() {
+        let html = include_str!("parse_test/work.html");
+        // cut off before the work's metadata, the refetch gets the whole page
+        let cut_off = &html[..html.find("<dl class=\"work meta group\"").unwrap()];
+        let mock = crate::testing::MockAo3::start()
+            .await
+            .unwrap()
+            .page("/works/45221314?view_adult=true", cut_off)
+            .page("/works/45221314", html);
+        assert!(matches!(
+            mock.client().fetch_work("45221314").await,
+            Err(Error::Parse { .. })
+        ));
+        let client = mock.client().set_refetch_on_parse_error(true);
+        assert_eq!(
+            client.fetch_work("45221314").await.unwrap().title,
+            "Salvation"
+        );
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[2].starts_with("/works/45221314?view_adult=true&_="));
+
+        // still cut off the second time, the error is about the page fetched again
+        let mock = crate::testing::MockAo3::start()
+            .await
+            .unwrap()
+            .page("/works/45221314", cut_off);
+        let client = mock.client().set_refetch_on_parse_error(true);
+        let Err(Error::Parse {
+            context: Some(context),
+            ..
+        }) = client.fetch_work("45221314").await
+        else {
+            panic!("the page parsed");
+        };
+        assert_eq!(context.url, format!("{}{}", mock.url(), mock.requests()[1]));
+        assert!(context.url.contains("&_="));
+    }
+
+    #[tokio::test]
+    async fn test_keep_raw() {
+        let html = include_str!("parse_test/work.html");
+        let mock = crate::testing::MockAo3::start()
+            .await
+            .unwrap()
+            .page("/works/45221314?view_adult=true", html);
+        let work = mock.client().fetch_work("45221314").await.unwrap();
+        assert_eq!(work.raw_html, None);
+        let client = mock.client().set_keep_raw(true);
+        let work = client.fetch_work("45221314").await.unwrap();
+        assert_eq!(work.raw_html.as_deref(), Some(html));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_comments_newest_first() {
+        let older = include_str!("parse_test/comments.html");
+        // the same comments posted years later, without the cut off thread
+        let newer = older
+            .replace(
+                "<li><a href=\"/comments/600002\">2 more comments in this thread</a></li>",
+                "",
+            )
+            .replace("6000", "6001")
+            .replace("2023", "2034");
+        let page =
+            |page| format!("/works/45221314?show_comments=true&view_full_work=true&page={page}");
+        let mock = crate::testing::MockAo3::start()
+            .await
+            .unwrap()
+            .page(&page(1), older)
+            .page(&page(2), &newer);
+        let client = mock.client();
+        let ids = |comments: Vec<AO3Comment>| -> Vec<String> {
+            comments.into_iter().map(|comment| comment.id).collect()
+        };
+
+        // from the end of the last page back, the first page isn't fetched again
+        let since = CommentsSince::Comment(String::from("600001"));
+        let comments = client
+            .fetch_comments_newest_first("45221314", &since)
+            .await
+            .unwrap();
+        assert_eq!(ids(comments), ["600110", "600101", "600010"]);
+        assert_eq!(mock.requests(), [page(1), page(2)]);
+
+        let date = chrono::NaiveDate::from_ymd_opt(2034, 2, 16)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let comments = client
+            .fetch_comments_newest_first("45221314", &CommentsSince::Date(date))
+            .await
+            .unwrap();
+        assert_eq!(ids(comments), ["600110"]);
+    }
+
+    #[tokio::test]
+    async fn test_check_updates() {
+        let mock = crate::testing::MockAo3::start().await.unwrap().page(
+            "/works/45221314?view_adult=true",
+            include_str!("parse_test/work.html"),
+        );
+        let client = mock.client();
+        let current = WorkRef::from(&client.fetch_work("45221314").await.unwrap());
+        let stale = WorkRef {
+            chapter_count: current.chapter_count - 1,
+            ..current.clone()
+        };
+        let missing = WorkRef {
+            id: String::from("1"),
+            ..current.clone()
+        };
+        let results = client.check_updates(&[current, stale, missing], 2).await;
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap(), &None);
+        let update = results[1].as_ref().unwrap().as_ref().unwrap();
+        assert_eq!(update.work.title, "Salvation");
+        assert_eq!(update.new_chapters, 1);
+        assert!(!update.date_changed);
+        assert!(matches!(results[2], Err(Error::NotFound)));
+    }
+
+    #[test]
+    fn test_check_size() {
+        assert!(check_size(Some(10), None).is_ok());
+        assert!(check_size(None, Some(5)).is_ok());
+        assert!(check_size(Some(5), Some(5)).is_ok());
+        assert!(matches!(
+            check_size(Some(6), Some(5)),
+            Err(Error::ResponseTooLarge(5))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter() {
+        let limiter = RateLimiter::new(Duration::from_millis(20));
+        let start = tokio::time::Instant::now();
+        tokio::join!(limiter.wait(), limiter.wait(), limiter.wait());
+        assert!(start.elapsed() >= Duration::from_millis(40));
+
+        let adaptive = RateLimiter::adaptive(Duration::ZERO, Duration::from_secs(4));
+        adaptive.record(reqwest::StatusCode::TOO_MANY_REQUESTS, Duration::ZERO);
+        assert_eq!(
+            adaptive.pace.lock().unwrap().interval,
+            Duration::from_secs(1)
+        );
+        adaptive.record(reqwest::StatusCode::SERVICE_UNAVAILABLE, Duration::ZERO);
+        adaptive.record(reqwest::StatusCode::OK, Duration::from_secs(30));
+        assert_eq!(
+            adaptive.pace.lock().unwrap().interval,
+            Duration::from_secs(4)
+        );
+        adaptive.record(reqwest::StatusCode::OK, Duration::from_millis(300));
+        assert_eq!(
+            adaptive.pace.lock().unwrap().interval,
+            Duration::from_millis(3600)
+        );
+
+        // a response is recorded while the next request waits for its turn
+        let waiting = RateLimiter::adaptive(Duration::from_millis(500), Duration::from_secs(4));
+        waiting.wait().await;
+        let start = tokio::time::Instant::now();
+        tokio::select! {
+            _ = waiting.wait() => panic!("the request didn't wait its turn"),
+            _ = async { waiting.record(reqwest::StatusCode::OK, Duration::ZERO) } => {}
+        }
+        assert!(start.elapsed() < Duration::from_millis(100));
+
+        let unlimited = RateLimiter::default();
+        let start = tokio::time::Instant::now();
+        tokio::join!(unlimited.wait(), unlimited.wait());
+        assert!(start.elapsed() < Duration::from_millis(20));
+    }
+}
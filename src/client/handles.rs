@@ -0,0 +1,223 @@
+use tokio::sync::OnceCell;
+
+use super::AO3Client;
+use crate::error::Error;
+use crate::models::{
+    AO3Bookmark, AO3Chapter, AO3Comment, AO3Series, AO3Tag, AO3Work, DownloadFormat,
+};
+
+/// A work on AO3, see [AO3Client::work]
+///
+/// Nothing is fetched until one of its methods is called. The work's page is fetched once,
+/// by the first call to [fetch](WorkHandle::fetch) or [kudos](WorkHandle::kudos),
+/// the other calls make their own requests.
+#[derive(Debug, Clone)]
+pub struct WorkHandle<'a> {
+    client: &'a AO3Client,
+    id: String,
+    work: OnceCell<AO3Work>,
+}
+
+/// A user on AO3, see [AO3Client::user]
+#[derive(Debug, Clone)]
+pub struct UserHandle<'a> {
+    client: &'a AO3Client,
+    name: String,
+}
+
+/// A series on AO3, see [AO3Client::series]
+#[derive(Debug, Clone)]
+pub struct SeriesHandle<'a> {
+    client: &'a AO3Client,
+    id: String,
+}
+
+/// A tag on AO3, see [AO3Client::tag]
+#[derive(Debug, Clone)]
+pub struct TagHandle<'a> {
+    client: &'a AO3Client,
+    name: String,
+}
+
+impl AO3Client {
+    /// The work with id `work_id`, to fetch it, its chapters, comments, ...
+    /// ```rust,no_run
+    /// # async fn example() -> Result<(), ao3rs::Error> {
+    /// use ao3rs::client::AO3Client;
+    ///
+    /// let client = AO3Client::new();
+    /// let work = client.work("45221314");
+    /// println!("{} has {} kudos", work.fetch().await?.title, work.kudos().await?);
+    /// let chapters = work.chapters().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn work(&self, work_id: &str) -> WorkHandle<'_> {
+        WorkHandle {
+            client: self,
+            id: work_id.to_string(),
+            work: OnceCell::new(),
+        }
+    }
+
+    /// The user `username`
+    pub fn user(&self, username: &str) -> UserHandle<'_> {
+        UserHandle {
+            client: self,
+            name: username.to_string(),
+        }
+    }
+
+    /// The series with id `series_id`
+    pub fn series(&self, series_id: &str) -> SeriesHandle<'_> {
+        SeriesHandle {
+            client: self,
+            id: series_id.to_string(),
+        }
+    }
+
+    /// The tag `tag`, by name
+    pub fn tag(&self, tag: &str) -> TagHandle<'_> {
+        TagHandle {
+            client: self,
+            name: tag.to_string(),
+        }
+    }
+}
+
+impl WorkHandle<'_> {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// See [fetch_work](AO3Client::fetch_work), the work fetched the first time is kept
+    pub async fn fetch(&self) -> Result<AO3Work, Error> {
+        Ok(self.fetched().await?.clone())
+    }
+
+    async fn fetched(&self) -> Result<&AO3Work, Error> {
+        self.work
+            .get_or_try_init(|| self.client.fetch_work(&self.id))
+            .await
+    }
+
+    /// See [fetch_chapters](AO3Client::fetch_chapters)
+    pub async fn chapters(&self) -> Result<Vec<AO3Chapter>, Error> {
+        self.client.fetch_chapters(&self.id).await
+    }
+
+    /// See [fetch_chapter_ids](AO3Client::fetch_chapter_ids)
+    pub async fn chapter_ids(&self) -> Result<Vec<String>, Error> {
+        self.client.fetch_chapter_ids(&self.id).await
+    }
+
+    /// See [fetch_comments](AO3Client::fetch_comments)
+    pub async fn comments(&self, page: usize) -> Result<Vec<AO3Comment>, Error> {
+        self.client.fetch_comments(&self.id, page).await
+    }
+
+    /// See [fetch_work_bookmarks](AO3Client::fetch_work_bookmarks)
+    pub async fn bookmarks(&self, page: usize) -> Result<Vec<AO3Bookmark>, Error> {
+        self.client.fetch_work_bookmarks(&self.id, page).await
+    }
+
+    /// How many kudos the work has, from its page
+    pub async fn kudos(&self) -> Result<usize, Error> {
+        Ok(self.fetched().await?.kudos)
+    }
+
+    /// See [download_work](AO3Client::download_work)
+    pub async fn download(
+        &self,
+        format: DownloadFormat,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<u64, Error> {
+        self.client.download_work(&self.id, format, path).await
+    }
+}
+
+impl UserHandle<'_> {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// See [fetch_user_works](AO3Client::fetch_user_works)
+    pub async fn works(&self, page: usize) -> Result<Vec<AO3Work>, Error> {
+        self.client.fetch_user_works(&self.name, page).await
+    }
+}
+
+impl SeriesHandle<'_> {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// See [fetch_series](AO3Client::fetch_series)
+    pub async fn fetch(&self) -> Result<AO3Series, Error> {
+        self.client.fetch_series(&self.id).await
+    }
+
+    /// The works of the series, as their blurbs show them
+    pub async fn works(&self) -> Result<Vec<AO3Work>, Error> {
+        Ok(self.fetch().await?.works)
+    }
+}
+
+impl TagHandle<'_> {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// See [fetch_tag](AO3Client::fetch_tag)
+    pub async fn fetch(&self) -> Result<AO3Tag, Error> {
+        self.client.fetch_tag(&self.name).await
+    }
+
+    /// See [fetch_tag_id](AO3Client::fetch_tag_id)
+    pub async fn id(&self) -> Result<String, Error> {
+        self.client.fetch_tag_id(&self.name).await
+    }
+
+    /// See [fetch_tag_works](AO3Client::fetch_tag_works)
+    pub async fn works(&self, page: usize) -> Result<Vec<AO3Work>, Error> {
+        self.client.fetch_tag_works(&self.name, page).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::MockAo3;
+
+    #[tokio::test]
+    async fn test_handles() {
+        let mock = MockAo3::start()
+            .await
+            .unwrap()
+            .page(
+                "/works/45221314?view_adult=true",
+                include_str!("../parse_test/work.html"),
+            )
+            .page(
+                "/users/Starchild719/works?page=1",
+                include_str!("../parse_test/search.html"),
+            );
+        let client = mock.client();
+        let work = client.work("45221314");
+        assert_eq!(work.fetch().await.unwrap().id, "45221314");
+        assert_eq!(
+            work.kudos().await.unwrap(),
+            work.fetch().await.unwrap().kudos
+        );
+        assert_eq!(
+            client.user("Starchild719").works(1).await.unwrap().len(),
+            20
+        );
+        assert_eq!(
+            mock.requests(),
+            vec![
+                "/works/45221314?view_adult=true",
+                "/users/Starchild719/works?page=1"
+            ]
+        );
+    }
+}
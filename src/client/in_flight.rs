@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use futures_util::future::{BoxFuture, FutureExt, Shared};
+use tokio::sync::oneshot;
+
+use crate::error::Error;
+
+/// What a request in flight will get, `None` when it was dropped before it finished
+type Pending = Shared<BoxFuture<'static, Option<Result<Arc<str>, Arc<Error>>>>>;
+
+/// Pages being fetched right now, so asking for one again waits for it instead
+/// of sending the same request twice
+///
+/// Everyone waiting gets the page, or the error, of the one request. Only when that request
+/// is dropped halfway do the others send their own.
+#[derive(Default)]
+pub(super) struct InFlight {
+    pending: Mutex<HashMap<String, Pending>>,
+}
+
+impl std::fmt::Debug for InFlight {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pending = self.pending.lock().unwrap();
+        f.debug_struct("InFlight")
+            .field("pending", &pending.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl InFlight {
+    /// The page at `key`, fetched with `fetch` unless it's being fetched already
+    pub(super) async fn get_or_fetch<F, Fut>(&self, key: &str, fetch: F) -> Result<String, Error>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<String, Error>>,
+    {
+        // the one fetching doesn't keep a handle on the page itself, else the error it
+        // sends would always look like someone else is still waiting for it
+        let sender = {
+            let mut pending = self.pending.lock().unwrap();
+            match pending.get(key) {
+                Some(page) => Err(page.clone()),
+                None => {
+                    let (sender, receiver) = oneshot::channel();
+                    let page = receiver.map(|page| page.ok().flatten()).boxed().shared();
+                    pending.insert(key.to_string(), page);
+                    Ok(sender)
+                }
+            }
+        };
+        let sender = match sender {
+            Ok(sender) => sender,
+            Err(page) => {
+                return match page.await {
+                    Some(Ok(page)) => Ok(page.to_string()),
+                    Some(Err(err)) => Err(Error::share(&err)),
+                    None => fetch().await,
+                };
+            }
+        };
+        // removes the request even when this future is dropped halfway, the others then fetch it themselves
+        let guard = Remove {
+            in_flight: self,
+            key,
+        };
+        let result = fetch().await;
+        drop(guard);
+        match result {
+            Ok(page) => {
+                let _ = sender.send(Some(Ok(Arc::from(page.as_str()))));
+                Ok(page)
+            }
+            Err(err) => {
+                let err = Arc::new(err);
+                let _ = sender.send(Some(Err(err.clone())));
+                // the error is only ours again if nobody else was waiting for it
+                Err(Arc::try_unwrap(err).unwrap_or_else(|err| Error::share(&err)))
+            }
+        }
+    }
+}
+
+struct Remove<'a> {
+    in_flight: &'a InFlight,
+    key: &'a str,
+}
+
+impl Drop for Remove<'_> {
+    fn drop(&mut self) {
+        self.in_flight.pending.lock().unwrap().remove(self.key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_in_flight() {
+        let in_flight = InFlight::default();
+        let fetches = AtomicUsize::new(0);
+        let fetch = || async {
+            fetches.fetch_add(1, Ordering::SeqCst);
+            tokio::task::yield_now().await;
+            Ok("<html></html>".to_string())
+        };
+        let pages = futures_util::future::join_all(
+            (0..5).map(|_| in_flight.get_or_fetch("/works/1", fetch)),
+        )
+        .await;
+        assert!(pages
+            .iter()
+            .all(|p| p.as_deref().ok() == Some("<html></html>")));
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+        assert!(in_flight.pending.lock().unwrap().is_empty());
+
+        // so are failures, one fetch fails for everyone
+        fetches.store(0, Ordering::SeqCst);
+        let failing = || async {
+            fetches.fetch_add(1, Ordering::SeqCst);
+            tokio::task::yield_now().await;
+            Err(Error::NotFound)
+        };
+        let results = futures_util::future::join_all(
+            (0..3).map(|_| in_flight.get_or_fetch("/works/2", failing)),
+        )
+        .await;
+        assert!(results.iter().all(|r| matches!(r, Err(Error::NotFound))));
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+
+        // errors that can't be copied are shared behind an Arc
+        fetches.store(0, Ordering::SeqCst);
+        let broken = || async {
+            fetches.fetch_add(1, Ordering::SeqCst);
+            tokio::task::yield_now().await;
+            Err(Error::Io(std::io::ErrorKind::ConnectionReset.into()))
+        };
+        let results = futures_util::future::join_all(
+            (0..3).map(|_| in_flight.get_or_fetch("/works/3", broken)),
+        )
+        .await;
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+        assert!(results.iter().all(|r| matches!(
+            r,
+            Err(Error::Shared(err)) if matches!(**err, Error::Io(_))
+        )));
+        assert!(in_flight.pending.lock().unwrap().is_empty());
+
+        // with nobody waiting the error comes back as it was
+        let result = in_flight.get_or_fetch("/works/4", broken).await;
+        assert!(matches!(result, Err(Error::Io(_))));
+        assert!(in_flight.pending.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,112 @@
+use std::task::{Context, Poll};
+
+use futures_util::future::BoxFuture;
+
+use super::AO3Client;
+use crate::error::Error;
+use crate::models::{AO3Bookmark, AO3Chapter, AO3Comment, AO3Series, AO3Work};
+
+/// What [AO3Client] can be asked for as a [tower_service::Service]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AO3Request {
+    Work { work_id: String },
+    Series { series_id: String },
+    Chapters { work_id: String },
+    Comments { work_id: String, page: usize },
+    WorkBookmarks { work_id: String, page: usize },
+    TagWorks { tag: String, page: usize },
+}
+
+/// What an [AO3Request] is answered with, the variant goes with the request's
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AO3Response {
+    Work(AO3Work),
+    Series(AO3Series),
+    Chapters(Vec<AO3Chapter>),
+    Comments(Vec<AO3Comment>),
+    WorkBookmarks(Vec<AO3Bookmark>),
+    TagWorks(Vec<AO3Work>),
+}
+
+/// Lets the client be wrapped in tower middleware (timeouts, load shedding, tracing, ...)
+///
+/// The client is always ready, its own [rate limit](AO3Client::set_rate_limit) is waited for
+/// inside the returned future.
+/// ```rust,no_run
+/// # async fn example() -> Result<(), ao3rs::Error> {
+/// use ao3rs::client::{AO3Client, AO3Request, AO3Response};
+/// use tower_service::Service;
+///
+/// let mut service = AO3Client::new();
+/// let request = AO3Request::Work { work_id: "45221314".to_string() };
+/// if let AO3Response::Work(work) = service.call(request).await? {
+///     println!("{}", work.title);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+impl tower_service::Service<AO3Request> for AO3Client {
+    type Response = AO3Response;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<AO3Response, Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: AO3Request) -> Self::Future {
+        // clones share the connection pool and rate limit
+        let client = self.clone();
+        Box::pin(async move {
+            Ok(match request {
+                AO3Request::Work { work_id } => {
+                    AO3Response::Work(client.fetch_work(&work_id).await?)
+                }
+                AO3Request::Series { series_id } => {
+                    AO3Response::Series(client.fetch_series(&series_id).await?)
+                }
+                AO3Request::Chapters { work_id } => {
+                    AO3Response::Chapters(client.fetch_chapters(&work_id).await?)
+                }
+                AO3Request::Comments { work_id, page } => {
+                    AO3Response::Comments(client.fetch_comments(&work_id, page).await?)
+                }
+                AO3Request::WorkBookmarks { work_id, page } => {
+                    AO3Response::WorkBookmarks(client.fetch_work_bookmarks(&work_id, page).await?)
+                }
+                AO3Request::TagWorks { tag, page } => {
+                    AO3Response::TagWorks(client.fetch_tag_works(&tag, page).await?)
+                }
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_service::Service;
+
+    use super::*;
+    use crate::testing::MockAo3;
+
+    #[tokio::test]
+    async fn test_service() {
+        let mock = MockAo3::start().await.unwrap().with_recorded_pages();
+        let mut service = mock.client();
+        let response = service
+            .call(AO3Request::Work {
+                work_id: "45221314".to_string(),
+            })
+            .await
+            .unwrap();
+        assert!(matches!(response, AO3Response::Work(work) if work.title == "Salvation"));
+        let response = service
+            .call(AO3Request::Series {
+                series_id: "1".to_string(),
+            })
+            .await;
+        assert!(matches!(response, Err(Error::NotFound)));
+    }
+}
@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use lru::LruCache;
+
+/// How many tag ids a client remembers when they aren't kept in a file
+const DEFAULT_CAPACITY: usize = 256;
+
+/// Tag names already resolved to their ids, shared by every clone of a client
+///
+/// Recently used ids are kept in memory. With a file every id ever resolved is kept,
+/// in lines of `<id>\t<name>`, so they survive the program.
+#[derive(Debug)]
+pub(super) struct TagIds {
+    recent: Mutex<LruCache<String, String>>,
+    file: Option<(PathBuf, Mutex<HashMap<String, String>>)>,
+}
+
+impl Default for TagIds {
+    fn default() -> Self {
+        TagIds::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl TagIds {
+    pub(super) fn new(capacity: usize) -> Self {
+        TagIds {
+            recent: Mutex::new(LruCache::new(
+                NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN),
+            )),
+            file: None,
+        }
+    }
+
+    /// Keep the ids in the file at `path`, with the ones it already has
+    pub(super) fn open(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let ids = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents
+                .lines()
+                .filter_map(|line| line.split_once('\t'))
+                .map(|(id, name)| (name.to_string(), id.to_string()))
+                .collect(),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(err) => return Err(err),
+        };
+        Ok(TagIds {
+            file: Some((path, Mutex::new(ids))),
+            ..Default::default()
+        })
+    }
+
+    /// The same ids with room for `capacity` recent ones, the file stays
+    pub(super) fn resized(&self, capacity: usize) -> Self {
+        TagIds {
+            file: self
+                .file
+                .as_ref()
+                .map(|(path, ids)| (path.clone(), Mutex::new(ids.lock().unwrap().clone()))),
+            ..TagIds::new(capacity)
+        }
+    }
+
+    pub(super) fn get(&self, name: &str) -> Option<String> {
+        if let Some((_, ids)) = &self.file {
+            return ids.lock().unwrap().get(name).cloned();
+        }
+        self.recent.lock().unwrap().get(name).cloned()
+    }
+
+    pub(super) fn insert(&self, name: &str, id: &str) -> std::io::Result<()> {
+        let Some((path, ids)) = &self.file else {
+            self.recent
+                .lock()
+                .unwrap()
+                .put(name.to_string(), id.to_string());
+            return Ok(());
+        };
+        let mut ids = ids.lock().unwrap();
+        if ids.get(name).is_some_and(|known| known == id) {
+            return Ok(());
+        }
+        // appending is enough, a later line for the same name wins when the file is read
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        writeln!(file, "{id}\t{name}")?;
+        ids.insert(name.to_string(), id.to_string());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tag_ids() {
+        let recent = TagIds::new(1);
+        recent.insert("Horror", "116").unwrap();
+        recent.insert("Hurt/Comfort", "110").unwrap();
+        assert_eq!(recent.get("Horror"), None);
+        assert_eq!(recent.get("Hurt/Comfort").as_deref(), Some("110"));
+
+        let path = std::env::temp_dir().join(format!("ao3rs_tag_ids_{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let stored = TagIds::open(&path).unwrap();
+        stored.insert("Horror", "116").unwrap();
+        stored.insert("Horror", "117").unwrap();
+        let reopened = TagIds::open(&path).unwrap();
+        assert_eq!(reopened.get("Horror").as_deref(), Some("117"));
+        assert_eq!(reopened.get("Hurt/Comfort"), None);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
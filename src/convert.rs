@@ -0,0 +1,294 @@
+use std::borrow::Cow;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Style {
+    PlainText,
+    Markdown,
+}
+
+/// Convert html of a chapter, comment, summary, ... into plain text
+///
+/// AO3 only allows a small set of tags, so this is not a general html renderer.
+/// Paragraphs are separated by an empty line, all formatting is dropped.
+pub fn html_to_plain_text(html: &str) -> String {
+    convert(html, Style::PlainText)
+}
+
+/// Convert html of a chapter, comment, summary, ... into markdown
+pub fn html_to_markdown(html: &str) -> String {
+    convert(html, Style::Markdown)
+}
+
+fn convert(html: &str, style: Style) -> String {
+    let Ok(dom) = tl::parse(html, tl::ParserOptions::default()) else {
+        return decode_entities(html).into_owned();
+    };
+    let parser = dom.parser();
+    let mut out = String::new();
+    for node in dom.children().iter().filter_map(|nh| nh.get(parser)) {
+        render(parser, node, style, &mut out);
+    }
+    clean_up(&out)
+}
+
+fn render_children(parser: &tl::Parser, node: &tl::Node, style: Style, out: &mut String) {
+    if let Some(children) = node.children() {
+        for child in children.top().iter().filter_map(|nh| nh.get(parser)) {
+            render(parser, child, style, out);
+        }
+    }
+}
+
+fn render_to_string(parser: &tl::Parser, node: &tl::Node, style: Style) -> String {
+    let mut inner = String::new();
+    render_children(parser, node, style, &mut inner);
+    inner
+}
+
+fn render(parser: &tl::Parser, node: &tl::Node, style: Style, out: &mut String) {
+    let tag = match node {
+        tl::Node::Raw(text) => {
+            let text = collapse_whitespace(&decode_entities(&text.as_utf8_str()));
+            if out.is_empty() || out.ends_with('\n') {
+                out.push_str(text.trim_start());
+            } else {
+                out.push_str(&text);
+            }
+            return;
+        }
+        tl::Node::Comment(_) => return,
+        tl::Node::Tag(tag) => tag,
+    };
+    let name = tag.name().as_utf8_str().to_ascii_lowercase();
+    let md = style == Style::Markdown;
+    match name.as_str() {
+        "br" => out.push('\n'),
+        "script" | "style" => {}
+        "p" | "div" | "center" | "dl" | "dt" | "dd" | "table" | "tr" | "pre" => {
+            out.push_str("\n\n");
+            render_children(parser, node, style, out);
+            out.push_str("\n\n");
+        }
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+            out.push_str("\n\n");
+            if md {
+                let level = name[1..].parse().unwrap_or(1);
+                out.push_str(&"#".repeat(level));
+                out.push(' ');
+            }
+            out.push_str(render_to_string(parser, node, style).trim());
+            out.push_str("\n\n");
+        }
+        "hr" => out.push_str(if md { "\n\n---\n\n" } else { "\n\n* * *\n\n" }),
+        "em" | "i" | "cite" if md => wrap(parser, node, style, "*", out),
+        "strong" | "b" if md => wrap(parser, node, style, "**", out),
+        "s" | "strike" | "del" if md => wrap(parser, node, style, "~~", out),
+        "code" if md => wrap(parser, node, style, "`", out),
+        "a" if md => {
+            let text = render_to_string(parser, node, style);
+            match attribute(tag, "href") {
+                Some(href) if !text.trim().is_empty() => {
+                    out.push_str(&format!("[{}]({})", text.trim(), href))
+                }
+                _ => out.push_str(&text),
+            }
+        }
+        "img" => {
+            let alt = attribute(tag, "alt").unwrap_or_default();
+            if md {
+                let src = attribute(tag, "src").unwrap_or_default();
+                out.push_str(&format!("![{alt}]({src})"));
+            } else if !alt.is_empty() {
+                out.push_str(&format!("[{alt}]"));
+            }
+        }
+        "blockquote" => {
+            let inner = clean_up(&render_to_string(parser, node, style));
+            let prefix = if md { "> " } else { "    " };
+            out.push_str("\n\n");
+            out.push_str(&prefix_lines(&inner, prefix, prefix));
+            out.push_str("\n\n");
+        }
+        "ul" | "ol" => {
+            out.push_str("\n\n");
+            let items = node
+                .children()
+                .map(|ch| ch.top().to_vec())
+                .unwrap_or_default();
+            let items = items.iter().filter_map(|nh| nh.get(parser)).filter(|n| {
+                n.as_tag()
+                    .is_some_and(|t| t.name().as_utf8_str().eq_ignore_ascii_case("li"))
+            });
+            for (i, item) in items.enumerate() {
+                let bullet = if name == "ol" {
+                    format!("{}. ", i + 1)
+                } else {
+                    String::from("- ")
+                };
+                let inner = clean_up(&render_to_string(parser, item, style));
+                let indent = " ".repeat(bullet.len());
+                out.push_str(&prefix_lines(&inner, &bullet, &indent));
+                out.push('\n');
+            }
+            out.push('\n');
+        }
+        _ => render_children(parser, node, style, out),
+    }
+}
+
+/// Put markdown markers around inline content, keeping surrounding whitespace outside of them
+fn wrap(parser: &tl::Parser, node: &tl::Node, style: Style, marker: &str, out: &mut String) {
+    let inner = render_to_string(parser, node, style);
+    let core = inner.trim();
+    if core.is_empty() {
+        out.push_str(&inner);
+        return;
+    }
+    if inner.starts_with(char::is_whitespace) && !(out.is_empty() || out.ends_with('\n')) {
+        out.push(' ');
+    }
+    out.push_str(marker);
+    out.push_str(core);
+    out.push_str(marker);
+    if inner.ends_with(char::is_whitespace) {
+        out.push(' ');
+    }
+}
+
+fn attribute(tag: &tl::HTMLTag, name: &str) -> Option<String> {
+    tag.attributes()
+        .get(name)
+        .flatten()
+        .map(|v| decode_entities(&v.as_utf8_str()).into_owned())
+}
+
+fn prefix_lines(text: &str, first: &str, rest: &str) -> String {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| {
+            let prefix = if i == 0 { first } else { rest };
+            if line.is_empty() {
+                prefix.trim_end().to_string()
+            } else {
+                format!("{prefix}{line}")
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    let mut last_was_space = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !last_was_space {
+                collapsed.push(' ');
+            }
+            last_was_space = true;
+        } else {
+            collapsed.push(c);
+            last_was_space = false;
+        }
+    }
+    collapsed
+}
+
+/// Trim the ends of lines and keep at most one empty line between blocks
+fn clean_up(text: &str) -> String {
+    let mut cleaned = String::with_capacity(text.len());
+    let mut empty_lines = 0;
+    for line in text.lines().map(str::trim_end) {
+        if line.is_empty() {
+            empty_lines += 1;
+            continue;
+        }
+        if !cleaned.is_empty() {
+            cleaned.push_str(if empty_lines > 0 { "\n\n" } else { "\n" });
+        }
+        cleaned.push_str(line);
+        empty_lines = 0;
+    }
+    cleaned
+}
+
+/// Decode the html entities that show up in AO3 pages
+pub(crate) fn decode_entities(text: &str) -> Cow<'_, str> {
+    if !text.contains('&') {
+        return Cow::Borrowed(text);
+    }
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest[1..]
+            .find(';')
+            .filter(|end| *end <= 10)
+            .map(|end| &rest[1..=end]);
+        match entity.and_then(decode_entity) {
+            Some(c) => {
+                decoded.push(c);
+                rest = &rest[entity.map_or(0, str::len) + 2..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    Cow::Owned(decoded)
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    if let Some(hex) = entity
+        .strip_prefix("#x")
+        .or_else(|| entity.strip_prefix("#X"))
+    {
+        return char::from_u32(u32::from_str_radix(hex, 16).ok()?);
+    }
+    if let Some(dec) = entity.strip_prefix('#') {
+        return char::from_u32(dec.parse().ok()?);
+    }
+    Some(match entity {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        "hellip" => '…',
+        "mdash" => '—',
+        "ndash" => '–',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "larr" => '←',
+        "rarr" => '→',
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert() {
+        let html = r#"<p>This was <em>chilling</em>,
+            see <a href="https://example.com">here</a>.</p>
+            <p>&nbsp;</p>
+            <blockquote><p>Quoted &amp; <strong>loud</strong></p></blockquote>
+            <ul><li>one</li><li>two<br />lines</li></ul>"#;
+        assert_eq!(
+            html_to_plain_text(html),
+            "This was chilling, see here.\n\n    Quoted & loud\n\n- one\n- two\n  lines"
+        );
+        assert_eq!(
+            html_to_markdown(html),
+            "This was *chilling*, see [here](https://example.com).\n\n> Quoted & **loud**\n\n- one\n- two\n  lines"
+        );
+    }
+}
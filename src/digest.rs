@@ -0,0 +1,176 @@
+use crate::client::AO3Client;
+use crate::convert::html_to_plain_text;
+use crate::error::Error;
+use crate::models::{AO3Work, SubscriptionKind};
+use crate::session::AO3Session;
+
+/// The works of some authors posted or updated since a day, grouped by author
+///
+/// Made to be rendered into an email or a chat message, with [to_text](Digest::to_text)
+/// or with serde.
+/// ```rust,no_run
+/// # async fn example(session: &ao3rs::session::AO3Session) -> Result<(), Box<dyn std::error::Error>> {
+/// use ao3rs::client::AO3Client;
+/// use ao3rs::digest::Digest;
+///
+/// let since = chrono::Utc::now().date_naive() - chrono::Duration::days(7);
+/// let digest = Digest::for_subscriptions(session, &AO3Client::new(), since).await?;
+/// if !digest.is_empty() {
+///     println!("{}", digest.to_text());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Digest {
+    /// Works updated on this day or later are in the digest
+    pub since: chrono::NaiveDate,
+
+    /// Authors with new works, in the order they were asked for
+    pub authors: Vec<AuthorDigest>,
+}
+
+/// The new works of one author
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AuthorDigest {
+    pub author: String,
+
+    /// Most recently updated first
+    pub works: Vec<AO3Work>,
+}
+
+impl Digest {
+    /// The works of `authors` (usernames) updated since `since`
+    ///
+    /// The works of each author are fetched a page at a time, until a work older than `since`.
+    pub async fn for_authors<S: AsRef<str>>(
+        client: &AO3Client,
+        authors: &[S],
+        since: chrono::NaiveDate,
+    ) -> Result<Digest, Error> {
+        let mut digest = Digest {
+            since,
+            authors: vec![],
+        };
+        for author in authors {
+            let works = recent_works(client, author.as_ref(), since).await?;
+            if !works.is_empty() {
+                digest.authors.push(AuthorDigest {
+                    author: author.as_ref().to_string(),
+                    works,
+                });
+            }
+        }
+        Ok(digest)
+    }
+
+    /// [for_authors](Digest::for_authors) with the users the logged in user subscribed to
+    pub async fn for_subscriptions(
+        session: &AO3Session,
+        client: &AO3Client,
+        since: chrono::NaiveDate,
+    ) -> Result<Digest, Error> {
+        let mut authors = vec![];
+        for page in 1.. {
+            let subscriptions = session.fetch_subscriptions(page).await?;
+            if subscriptions.is_empty() {
+                break;
+            }
+            authors.extend(
+                subscriptions
+                    .into_iter()
+                    .filter(|s| s.kind == SubscriptionKind::User)
+                    .map(|s| s.target),
+            );
+        }
+        Digest::for_authors(client, &authors, since).await
+    }
+
+    /// Whether no author has anything new
+    pub fn is_empty(&self) -> bool {
+        self.authors.is_empty()
+    }
+
+    /// The digest as markdown, which reads fine as plain text too
+    pub fn to_text(&self) -> String {
+        let mut text = format!("New and updated works since {}\n", self.since);
+        for author in &self.authors {
+            text.push_str(&format!("\n## {}\n", author.author));
+            for work in &author.works {
+                text.push_str(&format!(
+                    "\n- [{}]({}), {} words, {} chapters{}, updated {}\n",
+                    work.title,
+                    work.url,
+                    work.word_count,
+                    work.chapter_count,
+                    if work.is_complete { ", complete" } else { "" },
+                    work.date
+                ));
+                let summary = html_to_plain_text(&work.summary);
+                for line in summary.lines().filter(|line| !line.trim().is_empty()) {
+                    text.push_str(&format!("  > {}\n", line.trim()));
+                }
+            }
+        }
+        text
+    }
+}
+
+/// The works of `author` updated since `since`, a page at a time since they're newest first
+async fn recent_works(
+    client: &AO3Client,
+    author: &str,
+    since: chrono::NaiveDate,
+) -> Result<Vec<AO3Work>, Error> {
+    let mut recent = vec![];
+    for page in 1.. {
+        let (works, page_count) = client.fetch_user_page(author, page).await?;
+        let is_last = page >= page_count || works.iter().any(|w| w.date < since);
+        recent.extend(works.into_iter().filter(|w| w.date >= since));
+        if is_last {
+            break;
+        }
+    }
+    Ok(recent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockAo3;
+
+    #[tokio::test]
+    async fn test_digest() {
+        let search = include_str!("parse_test/search.html");
+        let older = search.replace("21 Feb 2023", "01 Jan 2020");
+        let mock = MockAo3::start()
+            .await
+            .unwrap()
+            .page("/users/Starchild719/works?page=1", search)
+            .page("/users/Starchild719/works?page=2", &older)
+            .page("/users/nobody/works?page=1", "<html><body></body></html>");
+        let since = chrono::NaiveDate::from_ymd_opt(2023, 1, 1).unwrap();
+        let digest = Digest::for_authors(&mock.client(), &["Starchild719", "nobody"], since)
+            .await
+            .unwrap();
+        assert_eq!(digest.authors.len(), 1);
+        assert_eq!(digest.authors[0].author, "Starchild719");
+        assert_eq!(digest.authors[0].works.len(), 20);
+        // the second page has older works, the ones after it aren't fetched
+        assert_eq!(
+            mock.requests(),
+            vec![
+                "/users/Starchild719/works?page=1",
+                "/users/Starchild719/works?page=2",
+                "/users/nobody/works?page=1"
+            ]
+        );
+
+        let text = digest.to_text();
+        assert!(text.contains("## Starchild719"));
+        assert!(text.contains("- [Salvation](https://archiveofourown.org/works/45221314)"));
+        assert!(text.contains("  > In the wake of a mass casualty event"));
+    }
+}
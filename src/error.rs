@@ -0,0 +1,305 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::client::BuiltRequest;
+#[cfg(feature = "library")]
+use crate::library::LibraryError;
+use crate::session::{BookmarkFileError, SessionError};
+
+/// Everything that can go wrong in ao3rs
+///
+/// Holds no borrows, so it can be sent between tasks, kept around or boxed with the
+/// errors of other crates.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// The request couldn't be made or AO3 answered with an unexpected status
+    #[error("Network error: {0}")]
+    Network(#[from] reqwest::Error),
+
+    /// AO3 answered with 429 Too Many Requests
+    #[error("AO3 said there were too many requests")]
+    RateLimited {
+        /// How long AO3 asked to wait, when it did
+        retry_after: Option<Duration>,
+    },
+
+    /// A page didn't look the way it was expected to
+    ///
+    /// Pages fetched by the crate come with the [context](ParseContext) of where it happened.
+    #[error("{source}{}", context.as_ref().map(|c| c.to_string()).unwrap_or_default())]
+    Parse {
+        source: ParsingError,
+        context: Option<Box<ParseContext>>,
+    },
+
+    /// The html of a page couldn't be read at all
+    #[error("Invalid html: {0}")]
+    Html(#[from] tl::ParseError),
+
+    /// AO3 is down for maintenance
+    #[error("AO3 is down for maintenance")]
+    Maintenance,
+
+    /// Cloudflare answered with a challenge page instead of letting the request through
+    #[error("Cloudflare asked to check the browser")]
+    Challenge,
+
+    /// AO3's search backend failed, trying again a bit later usually works
+    ///
+    /// AO3 shows an error banner, or a results page without any results count, instead of the
+    /// results. That isn't the same as a search that found nothing.
+    #[error("AO3's search is unavailable right now")]
+    SearchUnavailable,
+
+    /// The work, user, tag, ... doesn't exist (or was deleted)
+    #[error("Not found on AO3")]
+    NotFound,
+
+    /// The work is only shown to logged in users
+    #[error("Only logged in users can see this")]
+    Restricted,
+
+    /// The session isn't logged in (anymore)
+    #[error("This needs a logged in session")]
+    LoginRequired,
+
+    /// A response was bigger than the [maximum size](crate::client::AO3Client::set_max_response_size), in bytes
+    #[error("The response is bigger than {0} bytes")]
+    ResponseTooLarge(u64),
+
+    /// The operation was cancelled through its [Monitor](crate::progress::Monitor)
+    #[error("The operation was cancelled")]
+    Cancelled,
+
+    /// The client is in dry-run mode, this is the request it would have sent
+    #[error("Dry run: {} {}", .0.method, .0.url)]
+    DryRun(Box<BuiltRequest>),
+
+    /// An argument that can't be used, e.g. a url that isn't the url of a work
+    #[error("{0}")]
+    InvalidInput(String),
+
+    /// A search url had parameters [from_url](crate::query::AO3QueryBuilder::from_url)
+    /// couldn't read, as `key=value`
+    #[error("Unrecognized search parameters: {}", .0.join(", "))]
+    UnrecognizedParams(Vec<String>),
+
+    #[error(transparent)]
+    Session(#[from] SessionError),
+
+    #[error(transparent)]
+    BookmarkFile(#[from] BookmarkFileError),
+
+    #[cfg(feature = "library")]
+    #[error(transparent)]
+    Library(#[from] LibraryError),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[cfg(feature = "serde")]
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// The error of a request someone else made for the same page at the same time,
+    /// for errors that can't be copied
+    #[error(transparent)]
+    Shared(Arc<Error>),
+}
+
+impl Error {
+    /// Attach the url of the page and a bit of its html to a parse error, other errors are left as they are
+    pub(crate) fn in_page(self, url: &str, html: &str) -> Self {
+        match self {
+            Error::Parse { source, .. } => Error::Parse {
+                source,
+                context: Some(Box::new(ParseContext::new(url, html))),
+            },
+            err => err,
+        }
+    }
+
+    /// Write the whole html of the page to a file in `dir` when this is a parse error,
+    /// its path is kept in the [context](ParseContext::snapshot)
+    ///
+    /// Failing to write the file doesn't hide the parse error, there is just no snapshot then.
+    pub(crate) fn save_snapshot(mut self, dir: Option<&Path>, html: &str) -> Self {
+        if let (Some(dir), Error::Parse { context, .. }) = (dir, &mut self) {
+            let path = dir.join(format!(
+                "ao3rs-{}.html",
+                chrono::Utc::now().format("%Y%m%dT%H%M%S%.6f")
+            ));
+            if std::fs::create_dir_all(dir)
+                .and_then(|_| std::fs::write(&path, html))
+                .is_ok()
+            {
+                context
+                    .get_or_insert_with(|| Box::new(ParseContext::new("", html)))
+                    .snapshot = Some(path);
+            }
+        }
+        self
+    }
+
+    /// A copy of `err` for everyone who waited on the same request,
+    /// [Shared](Error::Shared) when it can't be copied
+    pub(crate) fn share(err: &Arc<Error>) -> Self {
+        match &**err {
+            Error::RateLimited { retry_after } => Error::RateLimited {
+                retry_after: *retry_after,
+            },
+            Error::Parse { source, context } => Error::Parse {
+                source: source.clone(),
+                context: context.clone(),
+            },
+            Error::Maintenance => Error::Maintenance,
+            Error::Challenge => Error::Challenge,
+            Error::SearchUnavailable => Error::SearchUnavailable,
+            Error::NotFound => Error::NotFound,
+            Error::Restricted => Error::Restricted,
+            Error::LoginRequired => Error::LoginRequired,
+            Error::ResponseTooLarge(size) => Error::ResponseTooLarge(*size),
+            Error::Cancelled => Error::Cancelled,
+            Error::DryRun(request) => Error::DryRun(request.clone()),
+            Error::InvalidInput(message) => Error::InvalidInput(message.clone()),
+            Error::UnrecognizedParams(params) => Error::UnrecognizedParams(params.clone()),
+            Error::Shared(err) => Error::Shared(err.clone()),
+            _ => Error::Shared(err.clone()),
+        }
+    }
+
+    /// Whether the same request may well succeed if it's made again later
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::Shared(err) => err.is_retryable(),
+            Error::RateLimited { .. } | Error::Maintenance | Error::SearchUnavailable => true,
+            Error::Network(err) => {
+                err.is_timeout() || err.status().is_some_and(|s| s.is_server_error())
+            }
+            _ => false,
+        }
+    }
+
+    /// Where a parse error happened, when it is known
+    pub fn parse_context(&self) -> Option<&ParseContext> {
+        match self {
+            Error::Parse { context, .. } => context.as_deref(),
+            Error::Shared(err) => err.parse_context(),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParsingError> for Error {
+    fn from(source: ParsingError) -> Self {
+        Error::Parse {
+            source,
+            context: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum ParsingError {
+    #[error("Could not find: {0}\nThis is a problem with the parsing!")]
+    CouldNotFind(&'static str),
+}
+
+/// The page a parse error happened on, enough to reproduce a change in AO3's layout
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseContext {
+    /// Url of the page
+    pub url: String,
+
+    /// The start of the main content of the page
+    pub snippet: String,
+
+    /// File the whole page was saved to, when snapshots are turned on
+    pub snapshot: Option<PathBuf>,
+}
+
+/// Characters of html kept in a [ParseContext]
+const SNIPPET_LENGTH: usize = 500;
+
+impl ParseContext {
+    pub(crate) fn new(url: &str, html: &str) -> Self {
+        // the header and navigation are the same on every page, the main content is what changed
+        let start = html
+            .find("<div id=\"main\"")
+            .or_else(|| html.find("<body"))
+            .unwrap_or_default();
+        let snippet = html[start..].chars().take(SNIPPET_LENGTH).collect();
+        Self {
+            url: url.to_string(),
+            snippet,
+            snapshot: None,
+        }
+    }
+}
+
+impl std::fmt::Display for ParseContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "\nUrl: {}\nHtml: {}", self.url, self.snippet)?;
+        if let Some(snapshot) = &self.snapshot {
+            write!(f, "\nThe whole page was saved to {}", snapshot.display())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_is_send_sync() {
+        fn assert_send_sync<T: Send + Sync + 'static>() {}
+        assert_send_sync::<Error>();
+        let err: Error = ParsingError::CouldNotFind("the list of works.").into();
+        assert!(err
+            .to_string()
+            .starts_with("Could not find: the list of works."));
+    }
+
+    #[test]
+    fn test_parse_context() {
+        let html = format!(
+            "<html><body><div id=\"header\"></div><div id=\"main\" class=\"works-show\">{}</div></body></html>",
+            "a".repeat(1000)
+        );
+        let err = Error::from(ParsingError::CouldNotFind("the title of the work."))
+            .in_page("https://archiveofourown.org/works/1", &html);
+        let context = err.parse_context().unwrap();
+        assert_eq!(context.url, "https://archiveofourown.org/works/1");
+        assert!(context
+            .snippet
+            .starts_with("<div id=\"main\" class=\"works-show\">"));
+        assert_eq!(context.snippet.chars().count(), SNIPPET_LENGTH);
+        let message = err.to_string();
+        assert!(message.contains("the title of the work."));
+        assert!(message.contains("Url: https://archiveofourown.org/works/1"));
+
+        // other errors don't get a context
+        assert!(Error::NotFound.in_page("", &html).parse_context().is_none());
+    }
+
+    #[test]
+    fn test_save_snapshot() {
+        let dir = std::env::temp_dir().join(format!("ao3rs-snapshot-{}", std::process::id()));
+        let html = "<html><body>Retry later</body></html>";
+        let err = Error::from(ParsingError::CouldNotFind("the list of works."))
+            .in_page("https://archiveofourown.org/works", html)
+            .save_snapshot(Some(&dir), html);
+        let snapshot = err.parse_context().unwrap().snapshot.clone().unwrap();
+        assert_eq!(std::fs::read_to_string(&snapshot).unwrap(), html);
+        assert!(err.to_string().contains(&snapshot.display().to_string()));
+
+        // without a directory nothing is written
+        let err =
+            Error::from(ParsingError::CouldNotFind("the list of works.")).save_snapshot(None, html);
+        assert!(err.parse_context().is_none());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,17 @@
+#[cfg(feature = "serde")]
+mod fanficfare;
+mod html;
+#[cfg(feature = "serde")]
+mod json;
+mod markdown;
+#[cfg(feature = "parquet")]
+mod parquet;
+
+#[cfg(feature = "parquet")]
+pub use self::parquet::ParquetWriter;
+#[cfg(feature = "serde")]
+pub use fanficfare::fanficfare_metadata;
+pub use html::work_to_html;
+#[cfg(feature = "serde")]
+pub use json::{JsonLinesWriter, JsonWriter, DATASET_SCHEMA_VERSION};
+pub use markdown::export_markdown;
@@ -0,0 +1,56 @@
+use serde_json::{json, Value};
+
+use crate::form::BASE_AO3_URL;
+use crate::models::AO3Work;
+
+/// Metadata of a work in the layout FanFicFare's `--json-meta` prints
+///
+/// Values are strings like FanFicFare's, lists are joined with ", ".
+/// Fields ao3rs doesn't know about are left out.
+pub fn fanficfare_metadata(work: &AO3Work) -> Value {
+    let author_urls = work
+        .authors
+        .iter()
+        .map(|a| format!("{BASE_AO3_URL}/users/{a}/pseuds/{a}"))
+        .collect::<Vec<String>>();
+    json!({
+        "site": "archiveofourown.org",
+        "storyId": work.id,
+        "storyUrl": work.url,
+        "title": work.title,
+        "author": work.authors.join(", "),
+        "authorId": work.authors.join(", "),
+        "authorUrl": author_urls.join(", "),
+        "category": work.fandoms.join(", "),
+        "fandoms": work.fandoms.join(", "),
+        "status": if work.is_complete { "Completed" } else { "In-Progress" },
+        "dateUpdated": work.date.format("%Y-%m-%d").to_string(),
+        "numWords": work.word_count.to_string(),
+        "freeformtags": work.tags.join(", "),
+        "description": work.summary,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fanficfare_metadata() {
+        let mut work = AO3Work::default();
+        work.id = String::from("45221314");
+        work.authors = vec![String::from("Starchild719")];
+        work.fandoms = vec![String::from("Original Work"), String::from("Horror")];
+        work.word_count = 2842;
+        work.is_complete = true;
+        let meta = fanficfare_metadata(&work);
+        assert_eq!(meta["storyId"], "45221314");
+        assert_eq!(
+            meta["authorUrl"],
+            "https://archiveofourown.org/users/Starchild719/pseuds/Starchild719"
+        );
+        assert_eq!(meta["category"], "Original Work, Horror");
+        assert_eq!(meta["status"], "Completed");
+        assert_eq!(meta["numWords"], "2842");
+    }
+}
@@ -0,0 +1,143 @@
+use crate::models::{AO3Chapter, AO3Work};
+
+/// Make a single, self-contained html page out of a work
+///
+/// The page has the summary, tags and every chapter inlined, and the metadata
+/// of the work as a schema.org JSON-LD block, so it can be indexed without ao3rs.
+/// Unlike AO3's own html download it doesn't depend on what AO3 puts in it.
+pub fn work_to_html(work: &AO3Work, chapters: &[AO3Chapter]) -> String {
+    let mut html = String::from("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n", escape(&work.title)));
+    html.push_str("<script type=\"application/ld+json\">\n");
+    html.push_str(&json_ld(work, chapters));
+    html.push_str("\n</script>\n</head>\n<body>\n");
+    html.push_str(&format!("<h1>{}</h1>\n", escape(&work.title)));
+    if !work.authors.is_empty() {
+        html.push_str(&format!(
+            "<p class=\"byline\">by {}</p>\n",
+            escape(&work.authors.join(", "))
+        ));
+    }
+    html.push_str("<dl class=\"meta\">\n");
+    let meta = [
+        ("Fandoms", work.fandoms.join(", ")),
+        ("Tags", work.tags.join(", ")),
+        ("Words", work.word_count.to_string()),
+        ("Updated", work.date.to_string()),
+        ("Source", work.url.clone()),
+    ];
+    for (name, value) in meta.iter().filter(|(_, v)| !v.is_empty()) {
+        html.push_str(&format!("<dt>{name}</dt><dd>{}</dd>\n", escape(value)));
+    }
+    html.push_str("</dl>\n");
+    if !work.summary.is_empty() {
+        html.push_str("<h2>Summary</h2>\n<blockquote class=\"summary\">\n");
+        html.push_str(&work.summary);
+        html.push_str("\n</blockquote>\n");
+    }
+    for chapter in chapters {
+        html.push_str("<section class=\"chapter\">\n");
+        if !chapter.title.is_empty() {
+            html.push_str(&format!("<h2>{}</h2>\n", escape(&chapter.title)));
+        }
+        html.push_str(&chapter.text);
+        html.push_str("\n</section>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn json_ld(work: &AO3Work, chapters: &[AO3Chapter]) -> String {
+    let list = |items: &[String]| {
+        items
+            .iter()
+            .map(|i| json_string(i))
+            .collect::<Vec<String>>()
+            .join(", ")
+    };
+    let authors = work
+        .authors
+        .iter()
+        .map(|a| format!("{{\"@type\": \"Person\", \"name\": {}}}", json_string(a)))
+        .collect::<Vec<String>>()
+        .join(", ");
+    let fields = [
+        ("\"@context\"", json_string("https://schema.org")),
+        ("\"@type\"", json_string("CreativeWork")),
+        ("\"identifier\"", json_string(&work.id)),
+        ("\"name\"", json_string(&work.title)),
+        ("\"url\"", json_string(&work.url)),
+        ("\"author\"", format!("[{authors}]")),
+        ("\"genre\"", format!("[{}]", list(&work.fandoms))),
+        ("\"keywords\"", format!("[{}]", list(&work.tags))),
+        ("\"dateModified\"", json_string(&work.date.to_string())),
+        ("\"wordCount\"", work.word_count.to_string()),
+        ("\"numberOfChapters\"", chapters.len().to_string()),
+        (
+            "\"creativeWorkStatus\"",
+            json_string(if work.is_complete {
+                "Complete"
+            } else {
+                "In progress"
+            }),
+        ),
+    ];
+    let fields = fields
+        .iter()
+        .map(|(k, v)| format!("  {k}: {v}"))
+        .collect::<Vec<String>>()
+        .join(",\n");
+    format!("{{\n{fields}\n}}")
+}
+
+/// Quote a string for JSON, `<` is escaped so the block can't close its script tag
+fn json_string(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            '<' => quoted.push_str("\\u003c"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_work_to_html() {
+        let mut work = AO3Work::default();
+        work.title = String::from("Salvation");
+        work.authors = vec![String::from("Starchild719")];
+        work.tags = vec![String::from("</script> Horror")];
+        work.summary = String::from("<p>A soldier spots a victim.</p>");
+        let chapters = vec![AO3Chapter {
+            title: String::from("Chapter 1: Before"),
+            text: String::from("<p>The smoke had not yet cleared.</p>"),
+            ..Default::default()
+        }];
+        let html = work_to_html(&work, &chapters);
+        assert!(html.contains("<title>Salvation</title>"));
+        assert!(html.contains("\"keywords\": [\"\\u003c/script> Horror\"]"));
+        assert!(html.contains("\"author\": [{\"@type\": \"Person\", \"name\": \"Starchild719\"}]"));
+        assert!(html.contains("<p>A soldier spots a victim.</p>"));
+        assert!(html.contains("<h2>Chapter 1: Before</h2>\n<p>The smoke had not yet cleared.</p>"));
+        assert_eq!(html.matches("</script>").count(), 1);
+    }
+}
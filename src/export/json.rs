@@ -0,0 +1,193 @@
+use std::io::Write;
+
+use crate::models::{AO3Chapter, AO3Work};
+
+/// Writes works as a JSON array, one work at a time
+///
+/// Lets big result sets go straight into a file or another process
+/// without building the whole document in memory first.
+/// ```rust
+/// use ao3rs::export::JsonWriter;
+/// use ao3rs::models::AO3Work;
+///
+/// let mut writer = JsonWriter::new(vec![]);
+/// writer.write_work(&AO3Work::default()).unwrap();
+/// let json = writer.finish().unwrap();
+/// assert!(json.starts_with(b"[{"));
+/// ```
+#[derive(Debug)]
+pub struct JsonWriter<W: Write> {
+    writer: W,
+    written: usize,
+}
+
+impl<W: Write> JsonWriter<W> {
+    pub fn new(writer: W) -> Self {
+        JsonWriter { writer, written: 0 }
+    }
+
+    pub fn write_work(&mut self, work: &AO3Work) -> std::io::Result<()> {
+        self.writer
+            .write_all(if self.written == 0 { b"[" } else { b"," })?;
+        serde_json::to_writer(&mut self.writer, work)?;
+        self.written += 1;
+        Ok(())
+    }
+
+    pub fn write_works<'a>(
+        &mut self,
+        works: impl IntoIterator<Item = &'a AO3Work>,
+    ) -> std::io::Result<()> {
+        for work in works {
+            self.write_work(work)?;
+        }
+        Ok(())
+    }
+
+    /// Number of works written so far
+    pub fn written(&self) -> usize {
+        self.written
+    }
+
+    /// Close the array and hand back the writer
+    pub fn finish(mut self) -> std::io::Result<W> {
+        if self.written == 0 {
+            self.writer.write_all(b"[")?;
+        }
+        self.writer.write_all(b"]")?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Version of the records written by [JsonLinesWriter](JsonLinesWriter)
+///
+/// Bumped whenever a field is renamed, removed or changes shape, adding fields doesn't change it.
+///
+/// - 2: `chapters` holds [AO3Chapter](AO3Chapter)s with their id, title and text instead of the html alone
+pub const DATASET_SCHEMA_VERSION: u32 = 2;
+
+/// One line of a JSON Lines dataset
+#[derive(Debug, serde::Serialize)]
+struct DatasetRecord<'a> {
+    schema_version: u32,
+    #[serde(flatten)]
+    work: &'a AO3Work,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    chapters: Option<&'a [AO3Chapter]>,
+}
+
+/// Writes works as JSON Lines, one work per line
+///
+/// Every line carries a `schema_version` field (see [DATASET_SCHEMA_VERSION](DATASET_SCHEMA_VERSION))
+/// so datasets built over time can be told apart. Open the file in append mode to keep adding to a corpus.
+#[derive(Debug)]
+pub struct JsonLinesWriter<W: Write> {
+    writer: W,
+    written: usize,
+}
+
+impl<W: Write> JsonLinesWriter<W> {
+    pub fn new(writer: W) -> Self {
+        JsonLinesWriter { writer, written: 0 }
+    }
+
+    /// Write the metadata of a work
+    pub fn write_work(&mut self, work: &AO3Work) -> std::io::Result<()> {
+        self.write_record(work, None)
+    }
+
+    /// Write the metadata of a work along with its chapters,
+    /// see [fetch_chapters](crate::client::AO3Client::fetch_chapters)
+    pub fn write_work_with_chapters(
+        &mut self,
+        work: &AO3Work,
+        chapters: &[AO3Chapter],
+    ) -> std::io::Result<()> {
+        self.write_record(work, Some(chapters))
+    }
+
+    fn write_record(
+        &mut self,
+        work: &AO3Work,
+        chapters: Option<&[AO3Chapter]>,
+    ) -> std::io::Result<()> {
+        let record = DatasetRecord {
+            schema_version: DATASET_SCHEMA_VERSION,
+            work,
+            chapters,
+        };
+        serde_json::to_writer(&mut self.writer, &record)?;
+        self.writer.write_all(b"\n")?;
+        self.written += 1;
+        Ok(())
+    }
+
+    /// Number of works written so far
+    pub fn written(&self) -> usize {
+        self.written
+    }
+
+    /// Flush and hand back the writer
+    pub fn finish(mut self) -> std::io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SearchResults;
+
+    #[test]
+    fn test_json_writer() {
+        let mut first = AO3Work::default();
+        first.id = String::from("1");
+        first.title = String::from("First");
+        let mut second = AO3Work::default();
+        second.id = String::from("2");
+        let works = vec![first, second];
+        let mut writer = JsonWriter::new(vec![]);
+        writer.write_works(&works).unwrap();
+        assert_eq!(writer.written(), 2);
+        let json = writer.finish().unwrap();
+        let parsed: Vec<AO3Work> = serde_json::from_slice(&json).unwrap();
+        assert_eq!(parsed, works);
+
+        let empty = JsonWriter::new(vec![]).finish().unwrap();
+        assert_eq!(empty, b"[]");
+
+        let results = SearchResults::from(works.clone());
+        let parsed: SearchResults = serde_json::from_str(&results.to_json().unwrap()).unwrap();
+        assert_eq!(parsed, results);
+        assert!(works[0].to_json().unwrap().contains(r#""title":"First""#));
+    }
+
+    #[test]
+    fn test_json_lines_writer() {
+        let mut work = AO3Work::default();
+        work.id = String::from("45221314");
+        let mut writer = JsonLinesWriter::new(vec![]);
+        writer.write_work(&work).unwrap();
+        writer
+            .write_work_with_chapters(
+                &work,
+                &[AO3Chapter {
+                    text: String::from("<p>Text</p>"),
+                    ..Default::default()
+                }],
+            )
+            .unwrap();
+        let lines = String::from_utf8(writer.finish().unwrap()).unwrap();
+        let lines = lines.lines().collect::<Vec<&str>>();
+        assert_eq!(lines.len(), 2);
+        let first: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(first["schema_version"], DATASET_SCHEMA_VERSION);
+        assert_eq!(first["id"], "45221314");
+        assert!(first.get("chapters").is_none());
+        let second: serde_json::Value = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(second["schema_version"], 2);
+        assert_eq!(second["chapters"][0]["text"], "<p>Text</p>");
+    }
+}
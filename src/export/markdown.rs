@@ -0,0 +1,84 @@
+use crate::convert::html_to_markdown;
+use crate::models::{AO3Chapter, AO3Work};
+
+/// Make a single Markdown document out of a work
+///
+/// Metadata goes into YAML front matter, the way Obsidian and static site generators read it,
+/// followed by the summary and every chapter under its own heading.
+pub fn export_markdown(work: &AO3Work, chapters: &[AO3Chapter]) -> String {
+    let mut md = String::from("---\n");
+    md.push_str(&format!("title: {}\n", yaml_string(&work.title)));
+    md.push_str(&format!("id: {}\n", yaml_string(&work.id)));
+    md.push_str(&format!("url: {}\n", yaml_string(&work.url)));
+    md.push_str(&yaml_list("authors", &work.authors));
+    md.push_str(&yaml_list("fandoms", &work.fandoms));
+    md.push_str(&yaml_list("tags", &work.tags));
+    md.push_str(&format!("updated: {}\n", work.date));
+    md.push_str(&format!("words: {}\n", work.word_count));
+    md.push_str(&format!("chapters: {}\n", chapters.len()));
+    md.push_str(&format!("complete: {}\n", work.is_complete));
+    md.push_str("---\n\n");
+    md.push_str(&format!("# {}\n", work.title));
+    if !work.summary.is_empty() {
+        md.push_str("\n## Summary\n\n");
+        md.push_str(&html_to_markdown(&work.summary));
+        md.push('\n');
+    }
+    for (i, chapter) in chapters.iter().enumerate() {
+        let title = if chapter.title.is_empty() {
+            format!("Chapter {}", i + 1)
+        } else {
+            chapter.title.clone()
+        };
+        md.push_str(&format!("\n## {title}\n\n"));
+        md.push_str(&html_to_markdown(&chapter.text));
+        md.push('\n');
+    }
+    md
+}
+
+fn yaml_list(key: &str, items: &[String]) -> String {
+    if items.is_empty() {
+        return format!("{key}: []\n");
+    }
+    let mut list = format!("{key}:\n");
+    for item in items {
+        list.push_str(&format!("  - {}\n", yaml_string(item)));
+    }
+    list
+}
+
+/// Double quoted YAML string, safe for any title or tag
+fn yaml_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_markdown() {
+        let mut work = AO3Work::default();
+        work.title = String::from("Salvation \"Redux\"");
+        work.authors = vec![String::from("Starchild719")];
+        work.summary = String::from("<p>A soldier spots a <em>victim</em>.</p>");
+        let chapters = vec![
+            AO3Chapter {
+                title: String::from("Chapter 1: Before"),
+                text: String::from("<p>The smoke had not yet cleared.</p>"),
+                ..Default::default()
+            },
+            AO3Chapter {
+                text: String::from("<p>They both made it out.</p>"),
+                ..Default::default()
+            },
+        ];
+        let md = export_markdown(&work, &chapters);
+        assert!(md.starts_with("---\ntitle: \"Salvation \\\"Redux\\\"\"\n"));
+        assert!(md.contains("authors:\n  - \"Starchild719\"\nfandoms: []\n"));
+        assert!(md.contains("## Summary\n\nA soldier spots a *victim*.\n"));
+        assert!(md.contains("## Chapter 1: Before\n\nThe smoke had not yet cleared.\n"));
+        assert!(md.ends_with("## Chapter 2\n\nThey both made it out.\n"));
+    }
+}
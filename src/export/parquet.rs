@@ -0,0 +1,196 @@
+use std::io::Write;
+use std::sync::Arc;
+
+use arrow_array::builder::{
+    BooleanBuilder, Date32Builder, ListBuilder, StringBuilder, UInt64Builder,
+};
+use arrow_array::types::Date32Type;
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+
+use crate::models::AO3Work;
+
+/// Number of works kept in memory before they're written out as a row group
+const BATCH_SIZE: usize = 1024;
+
+/// Writes works to a Parquet file, one row per work
+///
+/// The file can be loaded as is by pandas, polars, DuckDB, ...
+/// Works are buffered and written in batches, call [finish](ParquetWriter::finish)
+/// at the end or the file will be missing its footer.
+pub struct ParquetWriter<W: Write + Send> {
+    writer: ArrowWriter<W>,
+    schema: SchemaRef,
+    pending: Vec<AO3Work>,
+    written: usize,
+}
+
+impl<W: Write + Send> ParquetWriter<W> {
+    pub fn new(writer: W) -> Result<Self, parquet::errors::ParquetError> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Utf8, false),
+            Field::new("url", DataType::Utf8, false),
+            Field::new("title", DataType::Utf8, false),
+            string_list("authors"),
+            Field::new("date", DataType::Date32, false),
+            Field::new("is_complete", DataType::Boolean, false),
+            Field::new("is_crossover", DataType::Boolean, false),
+            Field::new("word_count", DataType::UInt64, false),
+            Field::new("chapter_count", DataType::UInt64, false),
+            string_list("fandoms"),
+            string_list("relationships"),
+            string_list("characters"),
+            string_list("tags"),
+            string_list("warnings"),
+            Field::new("summary", DataType::Utf8, false),
+            Field::new("kudos", DataType::UInt64, false),
+            Field::new("hits", DataType::UInt64, false),
+            Field::new("bookmarks", DataType::UInt64, false),
+            Field::new("comments", DataType::UInt64, false),
+        ]));
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+        Ok(ParquetWriter {
+            writer: ArrowWriter::try_new(writer, schema.clone(), Some(properties))?,
+            schema,
+            pending: vec![],
+            written: 0,
+        })
+    }
+
+    pub fn write_work(&mut self, work: &AO3Work) -> Result<(), parquet::errors::ParquetError> {
+        self.pending.push(work.clone());
+        self.written += 1;
+        if self.pending.len() >= BATCH_SIZE {
+            self.flush_batch()?;
+        }
+        Ok(())
+    }
+
+    pub fn write_works<'a>(
+        &mut self,
+        works: impl IntoIterator<Item = &'a AO3Work>,
+    ) -> Result<(), parquet::errors::ParquetError> {
+        for work in works {
+            self.write_work(work)?;
+        }
+        Ok(())
+    }
+
+    /// Number of works written so far
+    pub fn written(&self) -> usize {
+        self.written
+    }
+
+    /// Write the remaining works and the file footer, then hand back the writer
+    pub fn finish(mut self) -> Result<W, parquet::errors::ParquetError> {
+        self.flush_batch()?;
+        self.writer.into_inner()
+    }
+
+    fn flush_batch(&mut self) -> Result<(), parquet::errors::ParquetError> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        let mut id = StringBuilder::new();
+        let mut url = StringBuilder::new();
+        let mut title = StringBuilder::new();
+        let mut authors = ListBuilder::new(StringBuilder::new());
+        let mut date = Date32Builder::new();
+        let mut is_complete = BooleanBuilder::new();
+        let mut is_crossover = BooleanBuilder::new();
+        let mut word_count = UInt64Builder::new();
+        let mut chapter_count = UInt64Builder::new();
+        let mut fandoms = ListBuilder::new(StringBuilder::new());
+        let mut relationships = ListBuilder::new(StringBuilder::new());
+        let mut characters = ListBuilder::new(StringBuilder::new());
+        let mut tags = ListBuilder::new(StringBuilder::new());
+        let mut warnings = ListBuilder::new(StringBuilder::new());
+        let mut summary = StringBuilder::new();
+        let mut kudos = UInt64Builder::new();
+        let mut hits = UInt64Builder::new();
+        let mut bookmarks = UInt64Builder::new();
+        let mut comments = UInt64Builder::new();
+        for work in self.pending.drain(..) {
+            id.append_value(work.id);
+            url.append_value(work.url);
+            title.append_value(work.title);
+            authors.append_value(work.authors.into_iter().map(Some));
+            date.append_value(Date32Type::from_naive_date(work.date));
+            is_complete.append_value(work.is_complete);
+            is_crossover.append_value(work.is_crossover);
+            word_count.append_value(work.word_count as u64);
+            chapter_count.append_value(work.chapter_count as u64);
+            fandoms.append_value(work.fandoms.into_iter().map(Some));
+            relationships.append_value(work.relationships.into_iter().map(Some));
+            characters.append_value(work.characters.into_iter().map(Some));
+            tags.append_value(work.tags.into_iter().map(Some));
+            warnings.append_value(work.warnings.into_iter().map(Some));
+            summary.append_value(work.summary);
+            kudos.append_value(work.kudos as u64);
+            hits.append_value(work.hits as u64);
+            bookmarks.append_value(work.bookmarks as u64);
+            comments.append_value(work.comments as u64);
+        }
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(id.finish()),
+            Arc::new(url.finish()),
+            Arc::new(title.finish()),
+            Arc::new(authors.finish()),
+            Arc::new(date.finish()),
+            Arc::new(is_complete.finish()),
+            Arc::new(is_crossover.finish()),
+            Arc::new(word_count.finish()),
+            Arc::new(chapter_count.finish()),
+            Arc::new(fandoms.finish()),
+            Arc::new(relationships.finish()),
+            Arc::new(characters.finish()),
+            Arc::new(tags.finish()),
+            Arc::new(warnings.finish()),
+            Arc::new(summary.finish()),
+            Arc::new(kudos.finish()),
+            Arc::new(hits.finish()),
+            Arc::new(bookmarks.finish()),
+            Arc::new(comments.finish()),
+        ];
+        let batch = RecordBatch::try_new(self.schema.clone(), columns)?;
+        self.writer.write(&batch)
+    }
+}
+
+/// A column holding a list of strings, like the authors or the tags of a work
+fn string_list(name: &str) -> Field {
+    Field::new_list(name, Field::new_list_field(DataType::Utf8, true), false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    #[test]
+    fn test_parquet_writer() {
+        let mut work = AO3Work::default();
+        work.id = String::from("45221314");
+        work.authors = vec![String::from("Starchild719")];
+        work.tags = vec![String::from("Horror"), String::from("Death")];
+        work.kudos = 42;
+        let mut writer = ParquetWriter::new(vec![]).unwrap();
+        writer.write_works(&vec![work; 3]).unwrap();
+        let file = writer.finish().unwrap();
+        let reader = SerializedFileReader::new(bytes::Bytes::from(file)).unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 3);
+        assert_eq!(
+            reader
+                .metadata()
+                .file_metadata()
+                .schema_descr()
+                .num_columns(),
+            19
+        );
+    }
+}
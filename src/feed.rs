@@ -0,0 +1,192 @@
+use crate::error::Error;
+use crate::filter::FilterProfile;
+use crate::models::AO3Work;
+use crate::query::{AO3QueryBuilder, SortBy, SortDirection};
+
+/// Format of a [Feed](Feed) document
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum FeedFormat {
+    #[default]
+    Atom,
+    Rss,
+}
+
+/// A feed of works, so any search can be followed in a feed reader
+///
+/// ```rust,no_run
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// use ao3rs::feed::{Feed, FeedFormat};
+/// use ao3rs::query::AO3QueryBuilder;
+///
+/// let query = AO3QueryBuilder::new().push_fandom("Original Work");
+/// let feed = Feed::from_query("Original Work", query).await?;
+/// feed.write_to("original_work.xml", FeedFormat::Atom)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Feed {
+    pub title: String,
+
+    /// Page the feed is about, for a search this is the url of the search
+    pub link: String,
+
+    /// Works in the feed, newest first
+    pub works: Vec<AO3Work>,
+}
+
+impl Feed {
+    pub fn new(title: &str, link: &str, mut works: Vec<AO3Work>) -> Self {
+        works.sort_by_key(|w| std::cmp::Reverse(w.date));
+        Feed {
+            title: title.to_string(),
+            link: link.to_string(),
+            works,
+        }
+    }
+
+    /// Run `query` and make a feed out of the most recently updated works it finds
+    ///
+    /// The sort order of the query is replaced, the search limit is kept.
+    pub async fn from_query(title: &str, query: AO3QueryBuilder) -> Result<Self, Error> {
+        let query = query
+            .set_sort_by(SortBy::DateUpdated)
+            .set_sort_direction(SortDirection::Descending);
+        let link = query.create_url(1);
+        let works = query.send().await?;
+        Ok(Feed::new(title, &link, works))
+    }
+
+    /// Drop the works that don't get through `profile`
+    pub fn filtered(mut self, profile: &FilterProfile) -> Self {
+        self.works = profile.apply(self.works);
+        self
+    }
+
+    pub fn render(&self, format: FeedFormat) -> String {
+        match format {
+            FeedFormat::Atom => self.to_atom(),
+            FeedFormat::Rss => self.to_rss(),
+        }
+    }
+
+    /// Render the feed as an Atom document
+    pub fn to_atom(&self) -> String {
+        let updated = self.works.iter().map(|w| w.date).max().unwrap_or_default();
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+        xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+        xml.push_str(&format!("  <title>{}</title>\n", escape(&self.title)));
+        xml.push_str(&format!("  <id>{}</id>\n", escape(&self.link)));
+        xml.push_str(&format!("  <link href=\"{}\"/>\n", escape(&self.link)));
+        xml.push_str(&format!(
+            "  <updated>{}</updated>\n",
+            updated.format("%Y-%m-%dT00:00:00Z")
+        ));
+        for work in &self.works {
+            xml.push_str("  <entry>\n");
+            xml.push_str(&format!("    <title>{}</title>\n", escape(&work.title)));
+            xml.push_str(&format!("    <id>{}</id>\n", escape(&work.url)));
+            xml.push_str(&format!("    <link href=\"{}\"/>\n", escape(&work.url)));
+            xml.push_str(&format!(
+                "    <updated>{}</updated>\n",
+                work.date.format("%Y-%m-%dT00:00:00Z")
+            ));
+            // every entry needs an author, anonymous works don't list any
+            if work.authors.is_empty() {
+                xml.push_str("    <author><name>Anonymous</name></author>\n");
+            }
+            for author in &work.authors {
+                xml.push_str(&format!(
+                    "    <author><name>{}</name></author>\n",
+                    escape(author)
+                ));
+            }
+            for fandom in &work.fandoms {
+                xml.push_str(&format!("    <category term=\"{}\"/>\n", escape(fandom)));
+            }
+            xml.push_str("  </entry>\n");
+        }
+        xml.push_str("</feed>\n");
+        xml
+    }
+
+    /// Render the feed as an RSS 2.0 document
+    pub fn to_rss(&self) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+        xml.push_str("<rss version=\"2.0\">\n<channel>\n");
+        xml.push_str(&format!("  <title>{}</title>\n", escape(&self.title)));
+        xml.push_str(&format!("  <link>{}</link>\n", escape(&self.link)));
+        xml.push_str(&format!(
+            "  <description>{}</description>\n",
+            escape(&self.title)
+        ));
+        for work in &self.works {
+            xml.push_str("  <item>\n");
+            xml.push_str(&format!("    <title>{}</title>\n", escape(&work.title)));
+            xml.push_str(&format!("    <link>{}</link>\n", escape(&work.url)));
+            xml.push_str(&format!("    <guid>{}</guid>\n", escape(&work.url)));
+            xml.push_str(&format!(
+                "    <pubDate>{}</pubDate>\n",
+                work.date.format("%a, %d %b %Y 00:00:00 +0000")
+            ));
+            if !work.authors.is_empty() {
+                xml.push_str(&format!(
+                    "    <dc:creator xmlns:dc=\"http://purl.org/dc/elements/1.1/\">{}</dc:creator>\n",
+                    escape(&work.authors.join(", "))
+                ));
+            }
+            for fandom in &work.fandoms {
+                xml.push_str(&format!("    <category>{}</category>\n", escape(fandom)));
+            }
+            xml.push_str("  </item>\n");
+        }
+        xml.push_str("</channel>\n</rss>\n");
+        xml
+    }
+
+    /// Write the feed to a file, e.g. into the directory a web server serves
+    pub fn write_to(
+        &self,
+        path: impl AsRef<std::path::Path>,
+        format: FeedFormat,
+    ) -> std::io::Result<()> {
+        std::fs::write(path, self.render(format))
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feed() {
+        let mut older = AO3Work::default();
+        older.title = String::from("Older");
+        older.date = chrono::NaiveDate::from_ymd_opt(2023, 1, 5).unwrap();
+        let mut newer = AO3Work::default();
+        newer.title = String::from("Salt & <Smoke>");
+        newer.url = String::from("https://archiveofourown.org/works/45221314");
+        newer.authors = vec![String::from("Starchild719")];
+        newer.fandoms = vec![String::from("Original Work")];
+        newer.date = chrono::NaiveDate::from_ymd_opt(2023, 2, 21).unwrap();
+        let feed = Feed::new("Horror", "https://example.com", vec![older, newer]);
+        assert_eq!(feed.works[0].title, "Salt & <Smoke>");
+
+        let atom = feed.to_atom();
+        assert!(atom.contains("<updated>2023-02-21T00:00:00Z</updated>\n  <entry>"));
+        assert!(atom.contains("<title>Salt &amp; &lt;Smoke&gt;</title>"));
+        assert!(atom.contains("<author><name>Anonymous</name></author>"));
+        assert!(atom.contains("<category term=\"Original Work\"/>"));
+
+        let rss = feed.to_rss();
+        assert!(rss.contains("<pubDate>Tue, 21 Feb 2023 00:00:00 +0000</pubDate>"));
+        assert_eq!(rss.matches("<item>").count(), 2);
+    }
+}
@@ -0,0 +1,357 @@
+use crate::models::{AO3Work, Rating};
+
+/// Filters works by their tags on this side, for lists AO3 can't filter itself
+/// (feeds, a user's works, bookmarks, ...)
+///
+/// A work is kept when it has every included tag and none of the excluded ones.
+/// Fandoms, relationships, characters and archive warnings count as tags. Tags are compared [normalized](normalize_tag).
+/// ```rust
+/// use ao3rs::filter::WorkFilter;
+/// use ao3rs::models::AO3Work;
+///
+/// let filter = WorkFilter::new()
+///     .include_tag("Fluff")
+///     .exclude_tag("Major Character Death");
+/// let works: Vec<AO3Work> = vec![];
+/// let kept = filter.apply(works);
+/// ```
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "SavedWorkFilter")
+)]
+pub struct WorkFilter {
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+/// A [WorkFilter] as it's saved, tags in files written by hand may not be normalized
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct SavedWorkFilter {
+    #[serde(default)]
+    include: Vec<String>,
+    #[serde(default)]
+    exclude: Vec<String>,
+}
+
+#[cfg(feature = "serde")]
+impl From<SavedWorkFilter> for WorkFilter {
+    fn from(saved: SavedWorkFilter) -> Self {
+        let filter = saved
+            .include
+            .iter()
+            .fold(WorkFilter::new(), |filter, tag| filter.include_tag(tag));
+        saved
+            .exclude
+            .iter()
+            .fold(filter, |filter, tag| filter.exclude_tag(tag))
+    }
+}
+
+impl WorkFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only keep works that have `tag`
+    pub fn include_tag(mut self, tag: &str) -> Self {
+        self.include.push(normalize_tag(tag));
+        self
+    }
+
+    /// Drop works that have `tag`
+    pub fn exclude_tag(mut self, tag: &str) -> Self {
+        self.exclude.push(normalize_tag(tag));
+        self
+    }
+
+    /// The included tags, normalized
+    pub fn get_included(&self) -> &[String] {
+        &self.include
+    }
+
+    /// The excluded tags, normalized
+    pub fn get_excluded(&self) -> &[String] {
+        &self.exclude
+    }
+
+    /// Whether `work` gets through the filter
+    pub fn matches(&self, work: &AO3Work) -> bool {
+        let tags: Vec<String> = work
+            .fandoms
+            .iter()
+            .chain(&work.relationships)
+            .chain(&work.characters)
+            .chain(&work.tags)
+            .chain(&work.warnings)
+            .map(|tag| normalize_tag(tag))
+            .collect();
+        self.include.iter().all(|tag| tags.contains(tag))
+            && !self.exclude.iter().any(|tag| tags.contains(tag))
+    }
+
+    /// Keep the works that get through the filter, in their order
+    pub fn apply(&self, works: Vec<AO3Work>) -> Vec<AO3Work> {
+        works
+            .into_iter()
+            .filter(|work| self.matches(work))
+            .collect()
+    }
+}
+
+/// A named [WorkFilter] with a maximum rating, like "no MCD, no A/B/O, max rating Mature"
+///
+/// A profile applies the same way to search results, feeds and library views,
+/// and with the `serde` feature profiles can be kept in a file with `FilterProfiles`.
+/// ```rust
+/// use ao3rs::filter::FilterProfile;
+/// use ao3rs::models::{AO3Work, Rating};
+///
+/// let profile = FilterProfile::new("no squicks")
+///     .exclude_tag("Major Character Death")
+///     .exclude_tag("Alpha/Beta/Omega Dynamics")
+///     .set_max_rating(Rating::Mature);
+/// let works: Vec<AO3Work> = vec![];
+/// let kept = profile.apply(works);
+/// ```
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FilterProfile {
+    pub name: String,
+    pub filter: WorkFilter,
+
+    /// Works rated above this are dropped, no limit when it's `None`
+    pub max_rating: Option<Rating>,
+}
+
+impl FilterProfile {
+    pub fn new(name: &str) -> Self {
+        FilterProfile {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Only keep works that have `tag`
+    pub fn include_tag(mut self, tag: &str) -> Self {
+        self.filter = self.filter.include_tag(tag);
+        self
+    }
+
+    /// Drop works that have `tag`
+    pub fn exclude_tag(mut self, tag: &str) -> Self {
+        self.filter = self.filter.exclude_tag(tag);
+        self
+    }
+
+    /// Drop works rated above `rating`
+    ///
+    /// Works that aren't rated, or whose rating isn't known, could be anything
+    /// and count as Explicit.
+    pub fn set_max_rating(mut self, rating: Rating) -> Self {
+        self.max_rating = Some(rating);
+        self
+    }
+
+    /// Whether `work` gets through the profile
+    pub fn matches(&self, work: &AO3Work) -> bool {
+        let rated_ok = self
+            .max_rating
+            .is_none_or(|max| rating_level(work.get_rating()) <= rating_level(max));
+        rated_ok && self.filter.matches(work)
+    }
+
+    /// Keep the works that get through the profile, in their order
+    pub fn apply(&self, works: Vec<AO3Work>) -> Vec<AO3Work> {
+        works
+            .into_iter()
+            .filter(|work| self.matches(work))
+            .collect()
+    }
+}
+
+/// How far up a rating is, the ratings that say nothing about the work are the highest
+fn rating_level(rating: Rating) -> u8 {
+    match rating {
+        Rating::General => 0,
+        Rating::TeenAndUp => 1,
+        Rating::Mature => 2,
+        Rating::Explicit | Rating::NotRated | Rating::None => 3,
+    }
+}
+
+/// Filter profiles kept in a json file, by name
+///
+/// ```rust,no_run
+/// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// use ao3rs::filter::{FilterProfile, FilterProfiles};
+///
+/// let mut profiles = FilterProfiles::load("profiles.json")?;
+/// profiles.insert(FilterProfile::new("no mcd").exclude_tag("Major Character Death"));
+/// profiles.save("profiles.json")?;
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "serde")]
+#[derive(Debug, Default, PartialEq, Eq, Clone, serde::Serialize, serde::Deserialize)]
+pub struct FilterProfiles {
+    profiles: Vec<FilterProfile>,
+}
+
+#[cfg(feature = "serde")]
+impl FilterProfiles {
+    /// Read the profiles saved at `path`, there are none when the file doesn't exist
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self, crate::Error> {
+        match std::fs::read_to_string(path) {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> Result<(), crate::Error> {
+        Ok(std::fs::write(path, serde_json::to_string_pretty(self)?)?)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&FilterProfile> {
+        self.profiles.iter().find(|p| p.name == name)
+    }
+
+    /// Add a profile, replacing the one with the same name
+    pub fn insert(&mut self, profile: FilterProfile) {
+        match self.profiles.iter_mut().find(|p| p.name == profile.name) {
+            Some(existing) => *existing = profile,
+            None => self.profiles.push(profile),
+        }
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<FilterProfile> {
+        let position = self.profiles.iter().position(|p| p.name == name)?;
+        Some(self.profiles.remove(position))
+    }
+
+    /// The profiles in the order they were added
+    pub fn profiles(&self) -> &[FilterProfile] {
+        &self.profiles
+    }
+}
+
+/// Tag name the way tags are compared: lowercase, with whitespace collapsed to single spaces
+///
+/// AO3 matches tags without regard to case, "Angst" and "angst " are the same tag.
+pub fn normalize_tag(tag: &str) -> String {
+    tag.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::WorkBuilder;
+
+    #[test]
+    fn test_work_filter() {
+        let works = vec![
+            WorkBuilder::new("1")
+                .push_fandom("Original Work")
+                .set_tags(&["Fluff", "Hurt/Comfort"])
+                .build(),
+            WorkBuilder::new("2")
+                .push_fandom("Original Work")
+                .set_tags(&["Fluff", "Major Character Death"])
+                .build(),
+            WorkBuilder::new("3")
+                .push_fandom("Good Omens")
+                .set_tags(&["Angst"])
+                .build(),
+        ];
+        let filter = WorkFilter::new()
+            .include_tag("  fluff ")
+            .exclude_tag("major character   death");
+        let kept: Vec<_> = filter
+            .apply(works.clone())
+            .into_iter()
+            .map(|w| w.id)
+            .collect();
+        assert_eq!(kept, vec!["1"]);
+
+        let by_fandom = WorkFilter::new().exclude_tag("original work");
+        assert_eq!(by_fandom.apply(works.clone()).len(), 1);
+        assert_eq!(WorkFilter::new().apply(works).len(), 3);
+    }
+
+    #[test]
+    fn test_filter_profile() {
+        let mcd = WorkBuilder::new("1")
+            .push_fandom("Original Work")
+            .set_tags(&["Angst"])
+            .push_warning("Major Character Death")
+            .set_rating(Rating::TeenAndUp)
+            .build();
+        let explicit = WorkBuilder::new("2")
+            .push_fandom("Original Work")
+            .set_tags(&["Fluff"])
+            .set_rating(Rating::Explicit)
+            .build();
+        let mature = WorkBuilder::new("3")
+            .push_fandom("Original Work")
+            .set_tags(&["Fluff"])
+            .set_rating(Rating::Mature)
+            .build();
+        let unknown = WorkBuilder::new("4")
+            .push_fandom("Original Work")
+            .set_tags(&["Fluff"])
+            .build();
+        let works = vec![mcd, explicit, mature, unknown];
+
+        let profile = FilterProfile::new("safe")
+            .exclude_tag("major character death")
+            .set_max_rating(Rating::Mature);
+        let kept: Vec<_> = profile
+            .apply(works.clone())
+            .into_iter()
+            .map(|w| w.id)
+            .collect();
+        assert_eq!(kept, vec!["3"]);
+        assert_eq!(FilterProfile::new("all").apply(works).len(), 4);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_filter_profiles() {
+        let path = std::env::temp_dir().join(format!("ao3rs-profiles-{}.json", std::process::id()));
+        let mut profiles = FilterProfiles::load(&path).unwrap();
+        assert!(profiles.profiles().is_empty());
+        profiles.insert(FilterProfile::new("safe").exclude_tag("Major Character Death"));
+        profiles.insert(FilterProfile::new("fluff").include_tag("Fluff"));
+        profiles.insert(FilterProfile::new("safe").set_max_rating(Rating::General));
+        profiles.save(&path).unwrap();
+
+        let mut loaded = FilterProfiles::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, profiles);
+        assert_eq!(loaded.profiles().len(), 2);
+        assert_eq!(
+            loaded.get("safe").unwrap().max_rating,
+            Some(Rating::General)
+        );
+        assert!(loaded.remove("fluff").is_some());
+        assert!(loaded.get("fluff").is_none());
+
+        // tags written by hand are normalized too
+        let written: FilterProfile = serde_json::from_str(
+            r#"{"name": "safe", "filter": {"exclude": ["Major Character  Death"]}, "max_rating": null}"#,
+        )
+        .unwrap();
+        assert_eq!(written.filter.get_excluded(), ["major character death"]);
+        let mcd = WorkBuilder::new("1")
+            .push_fandom("Original Work")
+            .push_warning("Major Character Death")
+            .build();
+        assert!(!written.matches(&mcd));
+    }
+}
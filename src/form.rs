@@ -0,0 +1,248 @@
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::client::{read_html, BuiltRequest};
+use crate::error::Error;
+use crate::parse::{parse_authenticity_token, parse_form_errors};
+
+pub(crate) const BASE_AO3_URL: &str = "https://archiveofourown.org";
+
+/// Stands in for the `authenticity_token` of forms built in a dry run
+const DRY_RUN_TOKEN: &str = "DRY_RUN";
+
+/// Page AO3 sent back after a request made through a [FormSession](FormSession)
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct FormResponse {
+    /// Url of the page we ended up on, after following redirects
+    pub url: String,
+
+    /// HTTP status code
+    pub status: u16,
+
+    /// Html of the page
+    pub html: String,
+
+    /// Messages of the error banners on the page, empty when there were none
+    pub errors: Vec<String>,
+}
+
+impl FormResponse {
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+}
+
+/// Submits AO3 forms
+///
+/// Write actions on AO3 all work the same way: get the page with the form,
+/// take its `authenticity_token`, post the form and look for error banners in the response.
+/// The token is valid for the whole session, so it is cached after the first time it's seen.
+///
+/// Can be used directly to submit forms the crate doesn't wrap yet:
+/// ```rust,no_run
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// use ao3rs::form::FormSession;
+///
+/// let form = FormSession::new()?;
+/// let resp = form
+///     .submit("/works/123/kudos", "/kudos", &[("kudo[commentable_id]", "123"), ("kudo[commentable_type]", "Work")])
+///     .await?;
+/// println!("{:?}", resp.errors);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct FormSession {
+    client: reqwest::Client,
+    token: Mutex<Option<String>>,
+    max_response_size: Option<u64>,
+    snapshot_dir: Option<PathBuf>,
+    dry_run: bool,
+    base_url: Option<String>,
+}
+
+impl FormSession {
+    /// Create a session with its own cookie store
+    pub fn new() -> Result<Self, reqwest::Error> {
+        Ok(Self {
+            client: reqwest::Client::builder().cookie_store(true).build()?,
+            token: Mutex::new(None),
+            max_response_size: None,
+            snapshot_dir: None,
+            dry_run: false,
+            base_url: None,
+        })
+    }
+
+    /// Give up on pages bigger than `max_size` bytes, there is no limit by default
+    pub fn set_max_response_size(mut self, max_size: u64) -> Self {
+        self.max_response_size = Some(max_size);
+        self
+    }
+
+    /// Build requests without sending them, every request fails with [DryRun](Error::DryRun)
+    /// holding the request it would have sent
+    ///
+    /// Forms are built with a placeholder token unless one was cached before,
+    /// so [submit](FormSession::submit) shows the form that would be posted.
+    pub fn set_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Send requests to `base_url` instead of AO3, e.g. a mock server
+    pub fn set_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = Some(base_url.trim_end_matches('/').to_string());
+        self
+    }
+
+    fn base_url(&self) -> &str {
+        self.base_url.as_deref().unwrap_or(BASE_AO3_URL)
+    }
+
+    /// Save the html of every page that fails to parse to a file in `dir`, off by default
+    pub fn set_snapshot_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.snapshot_dir = Some(dir.into());
+        self
+    }
+
+    /// Parse a page that came through this session, parse errors tell which page it was
+    pub(crate) fn parse<T>(
+        &self,
+        resp: &FormResponse,
+        parse: impl FnOnce(&str) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        parse(&resp.html).map_err(|err| {
+            err.in_page(&resp.url, &resp.html)
+                .save_snapshot(self.snapshot_dir.as_deref(), &resp.html)
+        })
+    }
+
+    /// Fetch a page, `path` is relative to the root of AO3
+    ///
+    /// Fails with [LoginRequired](Error::LoginRequired) when AO3 sends us to the login page instead.
+    pub async fn get(&self, path: &str) -> Result<FormResponse, Error> {
+        let resp = self
+            .send(self.client.get(format!("{}{path}", self.base_url())))
+            .await?;
+        if resp.url().path() == "/users/login" && !path.starts_with("/users/login") {
+            return Err(Error::LoginRequired);
+        }
+        self.to_form_response(resp).await
+    }
+
+    /// Get the `authenticity_token`, fetching `form_page` only if there isn't one cached yet
+    pub async fn authenticity_token(&self, form_page: &str) -> Result<String, Error> {
+        if let Some(token) = self.token.lock().unwrap().clone() {
+            return Ok(token);
+        }
+        if self.dry_run {
+            return Ok(DRY_RUN_TOKEN.to_string());
+        }
+        let page = self.get(form_page).await?;
+        let token = parse_authenticity_token(&page.html)?;
+        *self.token.lock().unwrap() = Some(token.clone());
+        Ok(token)
+    }
+
+    /// Forget the cached token, e.g. after logging in or out
+    pub fn clear_token(&self) {
+        *self.token.lock().unwrap() = None;
+    }
+
+    /// Post `fields` to `action`, using the token from `form_page`
+    ///
+    /// If AO3 rejects the token it is fetched again and the form is resubmitted once.
+    pub async fn submit<K, V>(
+        &self,
+        form_page: &str,
+        action: &str,
+        fields: &[(K, V)],
+    ) -> Result<FormResponse, Error>
+    where
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let resp = self.post(form_page, action, fields).await?;
+        // Rails answers with 422 Unprocessable Entity for a stale token
+        if resp.status == 422 {
+            self.clear_token();
+            return self.post(form_page, action, fields).await;
+        }
+        Ok(resp)
+    }
+
+    async fn post<K, V>(
+        &self,
+        form_page: &str,
+        action: &str,
+        fields: &[(K, V)],
+    ) -> Result<FormResponse, Error>
+    where
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let token = self.authenticity_token(form_page).await?;
+        let mut form = vec![("authenticity_token", token.as_str())];
+        form.extend(fields.iter().map(|(k, v)| (k.as_ref(), v.as_ref())));
+        let resp = self
+            .send(
+                self.client
+                    .post(format!("{}{action}", self.base_url()))
+                    .form(&form),
+            )
+            .await?;
+        self.to_form_response(resp).await
+    }
+
+    /// Send a request, unless this is a dry run
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<reqwest::Response, Error> {
+        let request = request.build()?;
+        if self.dry_run {
+            return Err(Error::DryRun(Box::new(BuiltRequest::from(&request))));
+        }
+        Ok(self.client.execute(request).await?)
+    }
+
+    async fn to_form_response(&self, resp: reqwest::Response) -> Result<FormResponse, Error> {
+        let url = resp.url().to_string();
+        let status = resp.status().as_u16();
+        let html = read_html(resp, self.max_response_size).await?;
+        // every logged in page carries the token, so keep the freshest one around
+        if let Ok(token) = parse_authenticity_token(&html) {
+            *self.token.lock().unwrap() = Some(token);
+        }
+        Ok(FormResponse {
+            url,
+            status,
+            errors: parse_form_errors(&html)?,
+            html,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_dry_run() {
+        let form = FormSession::new().unwrap().set_dry_run(true);
+        let Err(Error::DryRun(request)) = form
+            .submit(
+                "/works/123/kudos",
+                "/kudos",
+                &[("kudo[commentable_id]", "123")],
+            )
+            .await
+        else {
+            panic!("a request was sent");
+        };
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.url, "https://archiveofourown.org/kudos");
+        assert_eq!(
+            request.body.as_deref(),
+            Some("authenticity_token=DRY_RUN&kudo%5Bcommentable_id%5D=123")
+        );
+    }
+}
@@ -1,6 +1,39 @@
+pub mod analysis;
+pub mod blurb;
+pub mod cache;
+mod chapters;
+pub mod checkpoint;
+pub mod client;
+pub mod convert;
+pub mod digest;
+pub mod error;
+pub mod export;
+pub mod feed;
+pub mod filter;
+pub mod form;
+#[cfg(feature = "library")]
+pub mod library;
+pub mod merge;
+pub mod offline;
+pub mod progress;
 pub mod query;
+pub mod ranking;
+pub mod recommend;
+pub mod refresh;
+#[cfg(feature = "content-scan")]
+pub mod scan;
+pub mod selftest;
+pub mod search;
+pub mod session;
+pub mod tags;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod watch;
 mod parse;
-mod models;
+pub mod models;
+
+pub use error::Error;
+pub use selftest::selftest;
 
 
 #[cfg(test)]
@@ -0,0 +1,1003 @@
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+
+use crate::models::{AO3Chapter, AO3Work, Rating, WorkRef};
+
+mod diff;
+mod mirror;
+mod stats;
+#[cfg(feature = "full-text")]
+mod text_index;
+
+pub use diff::{ChapterDiff, DiffLine};
+pub use mirror::{MirrorOptions, MirrorReport};
+pub use stats::StatsSnapshot;
+
+#[cfg(feature = "full-text")]
+pub use text_index::TextMatch;
+
+#[derive(Debug)]
+pub enum LibraryError {
+    Sqlite(rusqlite::Error),
+
+    /// The archive was written by a newer version of the library, with this schema version
+    NewerArchive(i64),
+
+    /// The full-text index failed
+    #[cfg(feature = "full-text")]
+    TextIndex(tantivy::TantivyError),
+
+    /// Searching text needs [with_text_index](Library::with_text_index) first
+    #[cfg(feature = "full-text")]
+    NoTextIndex,
+}
+
+impl std::fmt::Display for LibraryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LibraryError::Sqlite(err) => write!(f, "Library database error: {err}"),
+            LibraryError::NewerArchive(version) => write!(
+                f,
+                "The archive has schema version {version}, newer than {SCHEMA_VERSION}"
+            ),
+            #[cfg(feature = "full-text")]
+            LibraryError::TextIndex(err) => write!(f, "Library text index error: {err}"),
+            #[cfg(feature = "full-text")]
+            LibraryError::NoTextIndex => write!(f, "The library has no text index"),
+        }
+    }
+}
+
+impl std::error::Error for LibraryError {}
+
+impl From<rusqlite::Error> for LibraryError {
+    fn from(err: rusqlite::Error) -> Self {
+        LibraryError::Sqlite(err)
+    }
+}
+
+#[cfg(feature = "full-text")]
+impl From<tantivy::TantivyError> for LibraryError {
+    fn from(err: tantivy::TantivyError) -> Self {
+        LibraryError::TextIndex(err)
+    }
+}
+
+/// Version of the database schema, stored in SQLite's `user_version`
+const SCHEMA_VERSION: i64 = 5;
+
+/// How far the user got with a stored work
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ReadStatus {
+    #[default]
+    Unread,
+    Reading,
+    Read,
+    /// Stopped reading and not planning to go back
+    Dropped,
+}
+
+impl ReadStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ReadStatus::Unread => "unread",
+            ReadStatus::Reading => "reading",
+            ReadStatus::Read => "read",
+            ReadStatus::Dropped => "dropped",
+        }
+    }
+
+    fn from_str(status: &str) -> Self {
+        match status {
+            "reading" => ReadStatus::Reading,
+            "read" => ReadStatus::Read,
+            "dropped" => ReadStatus::Dropped,
+            _ => ReadStatus::Unread,
+        }
+    }
+}
+
+/// Local library of works, stored in SQLite
+///
+/// Keeps fetched works, their chapters and tags around so apps don't each
+/// have to come up with their own storage.
+/// ```rust
+/// use ao3rs::library::Library;
+/// use ao3rs::models::AO3Work;
+///
+/// let library = Library::open_in_memory().unwrap();
+/// let mut work = AO3Work::default();
+/// work.id = String::from("45221314");
+/// work.fandoms = vec![String::from("Original Work")];
+/// library.save_work(&work).unwrap();
+/// assert_eq!(library.works_by_fandom("Original Work").unwrap().len(), 1);
+/// assert_eq!(library.unread().unwrap().len(), 1);
+///
+/// // the user's own shelves are kept next to what AO3 says about the work
+/// library.set_folder("45221314", Some("To read")).unwrap();
+/// library.add_label("45221314", "comfort read").unwrap();
+/// assert_eq!(library.works_with_label("comfort read").unwrap().len(), 1);
+/// ```
+#[derive(Debug)]
+pub struct Library {
+    conn: Connection,
+    #[cfg(feature = "full-text")]
+    text_index: Option<text_index::TextIndex>,
+}
+
+impl Library {
+    /// Open the library stored at `path`, creating it if it doesn't exist
+    pub fn open(path: impl AsRef<std::path::Path>) -> Result<Self, LibraryError> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    /// Open a library that only lives as long as the value
+    pub fn open_in_memory() -> Result<Self, LibraryError> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self, LibraryError> {
+        conn.pragma_update(None, "foreign_keys", true)?;
+        let library = Library {
+            conn,
+            #[cfg(feature = "full-text")]
+            text_index: None,
+        };
+        library.migrate()?;
+        Ok(library)
+    }
+
+    fn migrate(&self) -> Result<(), LibraryError> {
+        let version: i64 = self
+            .conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))?;
+        if version < 1 {
+            self.migrate_to(
+                1,
+                "CREATE TABLE works (
+                    id TEXT PRIMARY KEY,
+                    url TEXT NOT NULL,
+                    title TEXT NOT NULL,
+                    date TEXT NOT NULL,
+                    is_complete INTEGER NOT NULL,
+                    is_crossover INTEGER NOT NULL,
+                    word_count INTEGER NOT NULL,
+                    summary TEXT NOT NULL,
+                    is_read INTEGER NOT NULL DEFAULT 0
+                );
+                CREATE TABLE work_tags (
+                    work_id TEXT NOT NULL REFERENCES works(id) ON DELETE CASCADE,
+                    kind TEXT NOT NULL,
+                    name TEXT NOT NULL,
+                    position INTEGER NOT NULL,
+                    PRIMARY KEY (work_id, kind, position)
+                );
+                CREATE INDEX work_tags_name ON work_tags(kind, name);
+                CREATE TABLE chapters (
+                    work_id TEXT NOT NULL REFERENCES works(id) ON DELETE CASCADE,
+                    position INTEGER NOT NULL,
+                    id TEXT NOT NULL,
+                    title TEXT NOT NULL,
+                    text TEXT NOT NULL,
+                    PRIMARY KEY (work_id, position)
+                );",
+            )?;
+        }
+        if version < 2 {
+            self.migrate_to(
+                2,
+                "ALTER TABLE works ADD COLUMN chapter_count INTEGER NOT NULL DEFAULT 0;",
+            )?;
+        }
+        if version < 3 {
+            self.migrate_to(
+                3,
+                "ALTER TABLE works ADD COLUMN read_status TEXT NOT NULL DEFAULT 'unread';
+                UPDATE works SET read_status = 'read' WHERE is_read = 1;
+                ALTER TABLE works DROP COLUMN is_read;
+                ALTER TABLE works ADD COLUMN folder TEXT;
+                CREATE INDEX works_folder ON works(folder);
+                CREATE TABLE work_labels (
+                    work_id TEXT NOT NULL REFERENCES works(id) ON DELETE CASCADE,
+                    label TEXT NOT NULL,
+                    PRIMARY KEY (work_id, label)
+                );
+                CREATE INDEX work_labels_label ON work_labels(label);",
+            )?;
+        }
+        if version < 4 {
+            self.migrate_to(
+                4,
+                "CREATE TABLE chapter_diffs (
+                    work_id TEXT NOT NULL REFERENCES works(id) ON DELETE CASCADE,
+                    position INTEGER NOT NULL,
+                    saved_at TEXT NOT NULL,
+                    diff TEXT NOT NULL
+                );
+                CREATE INDEX chapter_diffs_work ON chapter_diffs(work_id);",
+            )?;
+        }
+        if version < 5 {
+            self.migrate_to(
+                5,
+                "ALTER TABLE works ADD COLUMN kudos INTEGER NOT NULL DEFAULT 0;
+                ALTER TABLE works ADD COLUMN hits INTEGER NOT NULL DEFAULT 0;
+                ALTER TABLE works ADD COLUMN bookmarks INTEGER NOT NULL DEFAULT 0;
+                ALTER TABLE works ADD COLUMN comments INTEGER NOT NULL DEFAULT 0;
+                CREATE TABLE work_stats (
+                    work_id TEXT NOT NULL REFERENCES works(id) ON DELETE CASCADE,
+                    date TEXT NOT NULL,
+                    kudos INTEGER NOT NULL,
+                    hits INTEGER NOT NULL,
+                    bookmarks INTEGER NOT NULL,
+                    comments INTEGER NOT NULL,
+                    PRIMARY KEY (work_id, date)
+                );",
+            )?;
+        }
+        self.conn
+            .pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        Ok(())
+    }
+
+    /// Run the migration to schema `version`, it's either done whole or not at all
+    fn migrate_to(&self, version: i64, sql: &str) -> Result<(), LibraryError> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute_batch(sql)?;
+        tx.pragma_update(None, "user_version", version)?;
+        Ok(tx.commit()?)
+    }
+
+    /// Save a work, replacing what was stored for it before
+    ///
+    /// Chapters and what the user set on the work (read status, folder, labels) are kept.
+    /// Its kudos, hits, bookmarks and comments are recorded for today when they're known
+    /// and changed since the last time, see [stats_history](Library::stats_history).
+    pub fn save_work(&self, work: &AO3Work) -> Result<(), LibraryError> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO works (id, url, title, date, is_complete, is_crossover, word_count, summary, chapter_count,
+                kudos, hits, bookmarks, comments)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+            ON CONFLICT(id) DO UPDATE SET url = ?2, title = ?3, date = ?4, is_complete = ?5,
+                is_crossover = ?6, word_count = ?7, summary = ?8, chapter_count = ?9,
+                kudos = ?10, hits = ?11, bookmarks = ?12, comments = ?13",
+            params![
+                work.id,
+                work.url,
+                work.title,
+                work.date.to_string(),
+                work.is_complete,
+                work.is_crossover,
+                work.word_count as i64,
+                work.summary,
+                work.chapter_count as i64,
+                work.kudos as i64,
+                work.hits as i64,
+                work.bookmarks as i64,
+                work.comments as i64,
+            ],
+        )?;
+        tx.execute("DELETE FROM work_tags WHERE work_id = ?1", [&work.id])?;
+        // the rating is kept with the tags, so older libraries don't need another column
+        let rating = match work.get_rating() {
+            Rating::None => vec![],
+            rating => vec![rating.name().to_string()],
+        };
+        let tags = [
+            ("author", &work.authors),
+            ("fandom", &work.fandoms),
+            ("relationship", &work.relationships),
+            ("character", &work.characters),
+            ("freeform", &work.tags),
+            ("warning", &work.warnings),
+            ("rating", &rating),
+        ];
+        for (kind, names) in tags {
+            for (position, name) in names.iter().enumerate() {
+                tx.execute(
+                    "INSERT INTO work_tags (work_id, kind, name, position) VALUES (?1, ?2, ?3, ?4)",
+                    params![work.id, kind, name, position as i64],
+                )?;
+            }
+        }
+        self.record_changed_stats(work, chrono::Utc::now().date_naive())?;
+        Ok(tx.commit()?)
+    }
+
+    /// Save the chapters of a stored work, replacing the ones stored before
+    ///
+    /// Chapters whose text changed since they were stored get a diff, so do removed chapters,
+    /// see [chapter_diffs](Library::chapter_diffs).
+    pub fn save_chapters(
+        &self,
+        work_id: &str,
+        chapters: &[AO3Chapter],
+    ) -> Result<(), LibraryError> {
+        let old_chapters = self.chapters(work_id)?;
+        let saved_at = chrono::Utc::now()
+            .naive_utc()
+            .format(DATETIME_FORMAT)
+            .to_string();
+        let tx = self.conn.unchecked_transaction()?;
+        for (position, old) in old_chapters.iter().enumerate() {
+            // chapters are matched by id, so removing one doesn't make every later one a change,
+            // only works with a single chapter have none
+            let new = if old.id.is_empty() {
+                chapters.get(position)
+            } else {
+                chapters.iter().find(|c| c.id == old.id)
+            };
+            let new_text = new.map(|c| c.text.as_str()).unwrap_or_default();
+            if old.text != new_text {
+                tx.execute(
+                    "INSERT INTO chapter_diffs (work_id, position, saved_at, diff) VALUES (?1, ?2, ?3, ?4)",
+                    params![
+                        work_id,
+                        position as i64,
+                        saved_at,
+                        diff::encode(&diff::diff_chapter(&old.text, new_text)),
+                    ],
+                )?;
+            }
+        }
+        tx.execute("DELETE FROM chapters WHERE work_id = ?1", [work_id])?;
+        for (position, chapter) in chapters.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO chapters (work_id, position, id, title, text) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![work_id, position as i64, chapter.id, chapter.title, chapter.text],
+            )?;
+        }
+        tx.commit()?;
+        #[cfg(feature = "full-text")]
+        if let Some(index) = &self.text_index {
+            index.replace_chapters(work_id, chapters)?;
+        }
+        Ok(())
+    }
+
+    /// Remove a work and everything stored with it
+    pub fn remove_work(&self, work_id: &str) -> Result<(), LibraryError> {
+        self.conn
+            .execute("DELETE FROM works WHERE id = ?1", [work_id])?;
+        #[cfg(feature = "full-text")]
+        if let Some(index) = &self.text_index {
+            index.replace_chapters(work_id, &[])?;
+        }
+        Ok(())
+    }
+
+    pub fn work(&self, work_id: &str) -> Result<Option<AO3Work>, LibraryError> {
+        let work = self
+            .conn
+            .query_row(
+                &format!("{SELECT_WORKS} WHERE id = ?1"),
+                [work_id],
+                row_to_work,
+            )
+            .optional()?;
+        work.map(|w| self.with_tags(w)).transpose()
+    }
+
+    /// Every stored work, ordered by title
+    pub fn works(&self) -> Result<Vec<AO3Work>, LibraryError> {
+        self.query_works(&format!("{SELECT_WORKS} ORDER BY title"), [])
+    }
+
+    pub fn works_by_fandom(&self, fandom: &str) -> Result<Vec<AO3Work>, LibraryError> {
+        self.works_by_tag_kind("fandom", fandom)
+    }
+
+    pub fn works_by_author(&self, author: &str) -> Result<Vec<AO3Work>, LibraryError> {
+        self.works_by_tag_kind("author", author)
+    }
+
+    /// Works with the additional (freeform) tag `tag`
+    pub fn works_by_tag(&self, tag: &str) -> Result<Vec<AO3Work>, LibraryError> {
+        self.works_by_tag_kind("freeform", tag)
+    }
+
+    fn works_by_tag_kind(&self, kind: &str, name: &str) -> Result<Vec<AO3Work>, LibraryError> {
+        self.query_works(
+            &format!(
+                "{SELECT_WORKS} WHERE id IN
+                (SELECT work_id FROM work_tags WHERE kind = ?1 AND name = ?2)
+                ORDER BY title"
+            ),
+            [kind, name],
+        )
+    }
+
+    /// What is stored about every work, to look for updates with
+    /// [check_updates](crate::client::AO3Client::check_updates)
+    pub fn work_refs(&self) -> Result<Vec<WorkRef>, LibraryError> {
+        Ok(self.works()?.iter().map(WorkRef::from).collect())
+    }
+
+    /// Works the user hasn't started reading, see [works_with_status](Library::works_with_status)
+    pub fn unread(&self) -> Result<Vec<AO3Work>, LibraryError> {
+        self.works_with_status(ReadStatus::Unread)
+    }
+
+    /// Mark a work as read, or back as unread
+    pub fn set_read(&self, work_id: &str, is_read: bool) -> Result<(), LibraryError> {
+        let status = if is_read {
+            ReadStatus::Read
+        } else {
+            ReadStatus::Unread
+        };
+        self.set_read_status(work_id, status)
+    }
+
+    pub fn is_read(&self, work_id: &str) -> Result<bool, LibraryError> {
+        Ok(self.read_status(work_id)? == ReadStatus::Read)
+    }
+
+    pub fn set_read_status(&self, work_id: &str, status: ReadStatus) -> Result<(), LibraryError> {
+        self.conn.execute(
+            "UPDATE works SET read_status = ?2 WHERE id = ?1",
+            params![work_id, status.as_str()],
+        )?;
+        Ok(())
+    }
+
+    /// Read status of a work, works that aren't stored are unread
+    pub fn read_status(&self, work_id: &str) -> Result<ReadStatus, LibraryError> {
+        let status: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT read_status FROM works WHERE id = ?1",
+                [work_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(status.map(|s| ReadStatus::from_str(&s)).unwrap_or_default())
+    }
+
+    pub fn works_with_status(&self, status: ReadStatus) -> Result<Vec<AO3Work>, LibraryError> {
+        self.query_works(
+            &format!("{SELECT_WORKS} WHERE read_status = ?1 ORDER BY title"),
+            [status.as_str()],
+        )
+    }
+
+    /// Put a work in a folder, or take it out of the one it's in with `None`
+    ///
+    /// A work is in at most one folder, use labels to file it in several places.
+    pub fn set_folder(&self, work_id: &str, folder: Option<&str>) -> Result<(), LibraryError> {
+        self.conn.execute(
+            "UPDATE works SET folder = ?2 WHERE id = ?1",
+            params![work_id, folder],
+        )?;
+        Ok(())
+    }
+
+    pub fn folder(&self, work_id: &str) -> Result<Option<String>, LibraryError> {
+        Ok(self
+            .conn
+            .query_row("SELECT folder FROM works WHERE id = ?1", [work_id], |row| {
+                row.get(0)
+            })
+            .optional()?
+            .flatten())
+    }
+
+    /// Every folder that has works in it, sorted
+    pub fn folders(&self) -> Result<Vec<String>, LibraryError> {
+        self.query_strings(
+            "SELECT DISTINCT folder FROM works WHERE folder IS NOT NULL ORDER BY folder",
+            [],
+        )
+    }
+
+    pub fn works_in_folder(&self, folder: &str) -> Result<Vec<AO3Work>, LibraryError> {
+        self.query_works(
+            &format!("{SELECT_WORKS} WHERE folder = ?1 ORDER BY title"),
+            [folder],
+        )
+    }
+
+    /// Attach one of the user's own labels to a work, nothing happens if it already has it
+    pub fn add_label(&self, work_id: &str, label: &str) -> Result<(), LibraryError> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO work_labels (work_id, label) VALUES (?1, ?2)",
+            [work_id, label],
+        )?;
+        Ok(())
+    }
+
+    pub fn remove_label(&self, work_id: &str, label: &str) -> Result<(), LibraryError> {
+        self.conn.execute(
+            "DELETE FROM work_labels WHERE work_id = ?1 AND label = ?2",
+            [work_id, label],
+        )?;
+        Ok(())
+    }
+
+    /// Labels of a work, sorted
+    pub fn labels(&self, work_id: &str) -> Result<Vec<String>, LibraryError> {
+        self.query_strings(
+            "SELECT label FROM work_labels WHERE work_id = ?1 ORDER BY label",
+            [work_id],
+        )
+    }
+
+    /// Every label in use, sorted
+    pub fn all_labels(&self) -> Result<Vec<String>, LibraryError> {
+        self.query_strings("SELECT DISTINCT label FROM work_labels ORDER BY label", [])
+    }
+
+    pub fn works_with_label(&self, label: &str) -> Result<Vec<AO3Work>, LibraryError> {
+        self.query_works(
+            &format!(
+                "{SELECT_WORKS} WHERE id IN (SELECT work_id FROM work_labels WHERE label = ?1)
+                ORDER BY title"
+            ),
+            [label],
+        )
+    }
+
+    /// Keep a full-text index of the stored chapters in `dir`, or in memory when there is no `dir`
+    ///
+    /// An empty index is filled with every chapter already in the library,
+    /// after that chapters are indexed as they're saved.
+    #[cfg(feature = "full-text")]
+    pub fn with_text_index(mut self, dir: Option<&std::path::Path>) -> Result<Self, LibraryError> {
+        let index = text_index::TextIndex::open(dir)?;
+        if index.is_empty() {
+            let work_ids = self
+                .conn
+                .prepare("SELECT DISTINCT work_id FROM chapters")?
+                .query_map([], |row| row.get::<_, String>(0))?
+                .collect::<rusqlite::Result<Vec<String>>>()?;
+            for work_id in work_ids {
+                index.replace_chapters(&work_id, &self.chapters(&work_id)?)?;
+            }
+        }
+        self.text_index = Some(index);
+        Ok(self)
+    }
+
+    /// Find the chapters containing `query`, best matches first
+    ///
+    /// The query is matched word by word, so half remembered quotes still turn up.
+    #[cfg(feature = "full-text")]
+    pub fn search_text(&self, query: &str) -> Result<Vec<TextMatch>, LibraryError> {
+        let index = self.text_index.as_ref().ok_or(LibraryError::NoTextIndex)?;
+        let mut matches = vec![];
+        for (work_id, chapter, offset) in index.search(query)? {
+            if let Some(work) = self.work(&work_id)? {
+                matches.push(TextMatch {
+                    work,
+                    chapter,
+                    offset,
+                });
+            }
+        }
+        Ok(matches)
+    }
+
+    /// Stored chapters of a work, in order
+    pub fn chapters(&self, work_id: &str) -> Result<Vec<AO3Chapter>, LibraryError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, title, text FROM chapters WHERE work_id = ?1 ORDER BY position")?;
+        let chapters = stmt
+            .query_map([work_id], |row| {
+                Ok(AO3Chapter {
+                    id: row.get(0)?,
+                    title: row.get(1)?,
+                    text: row.get(2)?,
+                    raw_html: None,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<AO3Chapter>>>()?;
+        Ok(chapters)
+    }
+
+    /// Write the whole library, annotations and chapter diffs included, into a single file
+    ///
+    /// The archive is a SQLite database itself, it can be opened with [open](Library::open)
+    /// or merged into another library with [import_archive](Library::import_archive).
+    /// `path` must not exist yet.
+    pub fn export_archive(&self, path: impl AsRef<std::path::Path>) -> Result<(), LibraryError> {
+        self.conn
+            .execute("VACUUM INTO ?1", [path.as_ref().to_string_lossy()])?;
+        Ok(())
+    }
+
+    /// Merge an archive made by [export_archive](Library::export_archive) into the library
+    ///
+    /// Works in both are replaced by the archive's version, with everything stored for them.
+    /// The archive is only read, archives made by older versions are left as they are.
+    /// Returns how many works were imported.
+    pub fn import_archive(&self, path: impl AsRef<std::path::Path>) -> Result<usize, LibraryError> {
+        let version: i64 =
+            Connection::open_with_flags(path.as_ref(), OpenFlags::SQLITE_OPEN_READ_ONLY)?
+                .pragma_query_value(None, "user_version", |row| row.get(0))?;
+        if version > SCHEMA_VERSION {
+            return Err(LibraryError::NewerArchive(version));
+        }
+        self.conn.execute(
+            "ATTACH DATABASE ?1 AS archive",
+            [read_only_uri(path.as_ref())],
+        )?;
+        let imported = self.merge_archive(version);
+        self.conn.execute("DETACH DATABASE archive", [])?;
+        let work_ids = imported?;
+        #[cfg(feature = "full-text")]
+        if let Some(index) = &self.text_index {
+            for work_id in &work_ids {
+                index.replace_chapters(work_id, &self.chapters(work_id)?)?;
+            }
+        }
+        Ok(work_ids.len())
+    }
+
+    /// Copy the works of the attached archive, which has schema `version`
+    fn merge_archive(&self, version: i64) -> Result<Vec<String>, LibraryError> {
+        // what older schemas didn't have yet gets the defaults their migrations give it
+        let chapter_count = if version >= 2 { "chapter_count" } else { "0" };
+        let (read_status, folder) = if version >= 3 {
+            ("read_status", "folder")
+        } else {
+            (
+                "CASE WHEN is_read = 1 THEN 'read' ELSE 'unread' END",
+                "NULL",
+            )
+        };
+        let stats = if version >= 5 {
+            "kudos, hits, bookmarks, comments"
+        } else {
+            "0, 0, 0, 0"
+        };
+        let tx = self.conn.unchecked_transaction()?;
+        // deleting cascades to everything else stored for the works
+        tx.execute_batch(&format!(
+            "DELETE FROM main.works WHERE id IN (SELECT id FROM archive.works);
+            INSERT INTO main.works (id, url, title, date, is_complete, is_crossover, word_count,
+                summary, chapter_count, read_status, folder, kudos, hits, bookmarks, comments)
+            SELECT id, url, title, date, is_complete, is_crossover, word_count,
+                summary, {chapter_count}, {read_status}, {folder}, {stats}
+            FROM archive.works;
+            INSERT INTO main.work_tags (work_id, kind, name, position)
+            SELECT work_id, kind, name, position FROM archive.work_tags;
+            INSERT INTO main.chapters (work_id, position, id, title, text)
+            SELECT work_id, position, id, title, text FROM archive.chapters;"
+        ))?;
+        if version >= 3 {
+            tx.execute_batch(
+                "INSERT INTO main.work_labels (work_id, label)
+                SELECT work_id, label FROM archive.work_labels;",
+            )?;
+        }
+        if version >= 4 {
+            tx.execute_batch(
+                "INSERT INTO main.chapter_diffs (work_id, position, saved_at, diff)
+                SELECT work_id, position, saved_at, diff FROM archive.chapter_diffs;",
+            )?;
+        }
+        if version >= 5 {
+            tx.execute_batch(
+                "INSERT INTO main.work_stats (work_id, date, kudos, hits, bookmarks, comments)
+                SELECT work_id, date, kudos, hits, bookmarks, comments FROM archive.work_stats;",
+            )?;
+        }
+        let work_ids = tx
+            .prepare("SELECT id FROM archive.works")?
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+        tx.commit()?;
+        Ok(work_ids)
+    }
+
+    /// Changes to the chapters of a work, oldest first
+    pub fn chapter_diffs(&self, work_id: &str) -> Result<Vec<ChapterDiff>, LibraryError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT position, saved_at, diff FROM chapter_diffs WHERE work_id = ?1
+            ORDER BY saved_at, position",
+        )?;
+        let diffs = stmt
+            .query_map([work_id], |row| {
+                Ok(ChapterDiff {
+                    work_id: work_id.to_string(),
+                    chapter: row.get::<_, i64>(0)? as usize,
+                    saved_at: chrono::NaiveDateTime::parse_from_str(
+                        &row.get::<_, String>(1)?,
+                        DATETIME_FORMAT,
+                    )
+                    .unwrap_or_default(),
+                    lines: diff::decode(&row.get::<_, String>(2)?),
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<ChapterDiff>>>()?;
+        Ok(diffs)
+    }
+
+    fn query_works<P: rusqlite::Params>(
+        &self,
+        sql: &str,
+        params: P,
+    ) -> Result<Vec<AO3Work>, LibraryError> {
+        let mut stmt = self.conn.prepare(sql)?;
+        let works = stmt
+            .query_map(params, row_to_work)?
+            .collect::<rusqlite::Result<Vec<AO3Work>>>()?;
+        works.into_iter().map(|w| self.with_tags(w)).collect()
+    }
+
+    fn query_strings<P: rusqlite::Params>(
+        &self,
+        sql: &str,
+        params: P,
+    ) -> Result<Vec<String>, LibraryError> {
+        let mut stmt = self.conn.prepare(sql)?;
+        let strings = stmt
+            .query_map(params, |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+        Ok(strings)
+    }
+
+    fn with_tags(&self, mut work: AO3Work) -> Result<AO3Work, LibraryError> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT kind, name FROM work_tags WHERE work_id = ?1 ORDER BY position",
+        )?;
+        let tags = stmt.query_map([&work.id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        for tag in tags {
+            let (kind, name) = tag?;
+            match kind.as_str() {
+                "author" => work.authors.push(name),
+                "fandom" => work.fandoms.push(name),
+                "relationship" => work.relationships.push(name),
+                "character" => work.characters.push(name),
+                "warning" => work.warnings.push(name),
+                "rating" => work.set_rating(Rating::from_name(&name).unwrap_or_default()),
+                _ => work.tags.push(name),
+            }
+        }
+        Ok(work)
+    }
+}
+
+const DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f";
+
+/// Uri that opens the database at `path` read-only when attached
+fn read_only_uri(path: &std::path::Path) -> String {
+    let path = path
+        .to_string_lossy()
+        .replace('%', "%25")
+        .replace('?', "%3f")
+        .replace('#', "%23");
+    format!("file:{path}?mode=ro")
+}
+
+const SELECT_WORKS: &str =
+    "SELECT id, url, title, date, is_complete, is_crossover, word_count, summary, chapter_count,
+    kudos, hits, bookmarks, comments FROM works";
+
+fn row_to_work(row: &rusqlite::Row) -> rusqlite::Result<AO3Work> {
+    let mut work = AO3Work::default();
+    work.id = row.get(0)?;
+    work.url = row.get(1)?;
+    work.title = row.get(2)?;
+    work.date = row.get::<_, String>(3)?.parse().unwrap_or_default();
+    work.is_complete = row.get(4)?;
+    work.is_crossover = row.get(5)?;
+    work.word_count = row.get::<_, i64>(6)? as usize;
+    work.summary = row.get(7)?;
+    work.chapter_count = row.get::<_, i64>(8)? as usize;
+    work.kudos = row.get::<_, i64>(9)? as usize;
+    work.hits = row.get::<_, i64>(10)? as usize;
+    work.bookmarks = row.get::<_, i64>(11)? as usize;
+    work.comments = row.get::<_, i64>(12)? as usize;
+    Ok(work)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_library() {
+        let library = Library::open_in_memory().unwrap();
+        let mut work = AO3Work::default();
+        work.id = String::from("45221314");
+        work.title = String::from("Salvation");
+        work.date = chrono::NaiveDate::from_ymd_opt(2023, 2, 21).unwrap();
+        work.authors = vec![String::from("Starchild719")];
+        work.fandoms = vec![String::from("Original Work")];
+        work.tags = vec![String::from("Horror"), String::from("Death")];
+        work.characters = vec![String::from("Original Characters")];
+        work.warnings = vec![String::from("Graphic Depictions Of Violence")];
+        work.set_rating(Rating::TeenAndUp);
+        work.word_count = 842;
+        work.chapter_count = 1;
+        library.save_work(&work).unwrap();
+        let mut other = AO3Work::default();
+        other.id = String::from("45221302");
+        other.fandoms = vec![String::from("Hollow Knight")];
+        library.save_work(&other).unwrap();
+
+        assert_eq!(library.work("45221314").unwrap(), Some(work.clone()));
+        assert_eq!(library.work("1").unwrap(), None);
+        assert_eq!(library.works().unwrap().len(), 2);
+        assert_eq!(
+            library.works_by_fandom("Original Work").unwrap(),
+            vec![work.clone()]
+        );
+        assert_eq!(library.works_by_tag("Death").unwrap(), vec![work.clone()]);
+        assert!(library.works_by_author("nobody").unwrap().is_empty());
+        assert!(library.work_refs().unwrap().contains(&WorkRef::from(&work)));
+
+        library.set_read("45221314", true).unwrap();
+        assert!(library.is_read("45221314").unwrap());
+        assert_eq!(library.unread().unwrap(), vec![other]);
+
+        let chapters = vec![AO3Chapter {
+            id: String::from("113700001"),
+            title: String::from("Chapter 1: Before"),
+            text: String::from("<p>The smoke had not yet cleared.</p>"),
+            ..Default::default()
+        }];
+        library.save_chapters("45221314", &chapters).unwrap();
+        // saving the work again keeps its chapters and read status
+        library.save_work(&work).unwrap();
+        assert_eq!(library.chapters("45221314").unwrap(), chapters);
+        assert!(library.is_read("45221314").unwrap());
+
+        library.remove_work("45221314").unwrap();
+        assert!(library.chapters("45221314").unwrap().is_empty());
+        assert_eq!(library.works().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_shelves() {
+        let library = Library::open_in_memory().unwrap();
+        let mut work = AO3Work::default();
+        work.id = String::from("45221314");
+        library.save_work(&work).unwrap();
+
+        library
+            .set_read_status("45221314", ReadStatus::Reading)
+            .unwrap();
+        library.set_folder("45221314", Some("Horror")).unwrap();
+        library.add_label("45221314", "reread").unwrap();
+        library.add_label("45221314", "favourite").unwrap();
+        library.add_label("45221314", "reread").unwrap();
+        // none of it is lost when the work is saved again after a refresh
+        library.save_work(&work).unwrap();
+
+        assert_eq!(
+            library.read_status("45221314").unwrap(),
+            ReadStatus::Reading
+        );
+        assert!(!library.is_read("45221314").unwrap());
+        assert!(library.unread().unwrap().is_empty());
+        assert_eq!(
+            library.works_with_status(ReadStatus::Reading).unwrap(),
+            vec![work.clone()]
+        );
+        assert_eq!(
+            library.folder("45221314").unwrap().as_deref(),
+            Some("Horror")
+        );
+        assert_eq!(library.folders().unwrap(), vec!["Horror"]);
+        assert_eq!(
+            library.works_in_folder("Horror").unwrap(),
+            vec![work.clone()]
+        );
+        assert_eq!(
+            library.labels("45221314").unwrap(),
+            vec!["favourite", "reread"]
+        );
+        assert_eq!(library.works_with_label("reread").unwrap(), vec![work]);
+
+        library.set_folder("45221314", None).unwrap();
+        library.remove_label("45221314", "reread").unwrap();
+        assert!(library.folders().unwrap().is_empty());
+        assert_eq!(library.all_labels().unwrap(), vec!["favourite"]);
+        library.remove_work("45221314").unwrap();
+        assert!(library.all_labels().unwrap().is_empty());
+        assert_eq!(library.read_status("45221314").unwrap(), ReadStatus::Unread);
+    }
+
+    #[test]
+    fn test_failed_migration() {
+        let path = std::env::temp_dir().join(format!("ao3rs-migration-{}.db", std::process::id()));
+        let conn = Connection::open(&path).unwrap();
+        // a version 2 library the migration to 3 can't finish on
+        conn.execute_batch(
+            "CREATE TABLE works (id TEXT PRIMARY KEY, is_read INTEGER NOT NULL DEFAULT 0);
+            CREATE TABLE work_labels (work_id TEXT);
+            PRAGMA user_version = 2;",
+        )
+        .unwrap();
+        drop(conn);
+        assert!(Library::open(&path).is_err());
+        let conn = Connection::open(&path).unwrap();
+        let version: i64 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, 2);
+        // nothing of the half done migration is left
+        let columns = conn
+            .prepare("SELECT name FROM pragma_table_info('works')")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<Vec<String>>>()
+            .unwrap();
+        assert_eq!(columns, vec!["id", "is_read"]);
+        drop(conn);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_archive() {
+        let dir = std::env::temp_dir().join(format!("ao3rs-archive-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let archive = dir.join("library.ao3rs");
+
+        let library = Library::open_in_memory().unwrap();
+        let mut work = AO3Work::default();
+        work.id = String::from("45221314");
+        work.title = String::from("Salvation");
+        work.fandoms = vec![String::from("Original Work")];
+        library.save_work(&work).unwrap();
+        let chapters = vec![AO3Chapter {
+            id: String::from("113700001"),
+            title: String::from("Chapter 1: Before"),
+            text: String::from("<p>The smoke had not yet cleared.</p>"),
+            ..Default::default()
+        }];
+        library.save_chapters("45221314", &chapters).unwrap();
+        library.set_read("45221314", true).unwrap();
+        library.add_label("45221314", "favourite").unwrap();
+        library.export_archive(&archive).unwrap();
+
+        let other = Library::open_in_memory().unwrap();
+        let mut stale = work.clone();
+        stale.title = String::from("Old title");
+        other.save_work(&stale).unwrap();
+        other.add_label("45221314", "stale").unwrap();
+        assert_eq!(other.import_archive(&archive).unwrap(), 1);
+        assert_eq!(other.work("45221314").unwrap(), Some(work));
+        assert_eq!(other.chapters("45221314").unwrap(), chapters);
+        assert!(other.is_read("45221314").unwrap());
+        assert_eq!(other.labels("45221314").unwrap(), vec!["favourite"]);
+
+        // an archive of a version 2 library is read as it is
+        let old = dir.join("old #1.ao3rs");
+        let conn = Connection::open(&old).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE works (id TEXT PRIMARY KEY, url TEXT NOT NULL, title TEXT NOT NULL,
+                date TEXT NOT NULL, is_complete INTEGER NOT NULL, is_crossover INTEGER NOT NULL,
+                word_count INTEGER NOT NULL, summary TEXT NOT NULL, is_read INTEGER NOT NULL DEFAULT 0,
+                chapter_count INTEGER NOT NULL DEFAULT 0);
+            CREATE TABLE work_tags (work_id TEXT NOT NULL, kind TEXT NOT NULL, name TEXT NOT NULL,
+                position INTEGER NOT NULL);
+            CREATE TABLE chapters (work_id TEXT NOT NULL, position INTEGER NOT NULL, id TEXT NOT NULL,
+                title TEXT NOT NULL, text TEXT NOT NULL);
+            INSERT INTO works VALUES ('45221302', '', 'Embers', '2023-03-07', 0, 0, 100, '', 1, 2);
+            INSERT INTO work_tags VALUES ('45221302', 'fandom', 'Hollow Knight', 0);
+            PRAGMA user_version = 2;",
+        )
+        .unwrap();
+        drop(conn);
+        assert_eq!(other.import_archive(&old).unwrap(), 1);
+        let embers = other.work("45221302").unwrap().unwrap();
+        assert_eq!(embers.title, "Embers");
+        assert_eq!(embers.chapter_count, 2);
+        assert_eq!(embers.fandoms, vec!["Hollow Knight"]);
+        assert!(other.is_read("45221302").unwrap());
+        let version: i64 = Connection::open(&old)
+            .unwrap()
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, 2);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
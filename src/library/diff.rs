@@ -0,0 +1,190 @@
+use crate::convert::html_to_markdown;
+
+/// A line of a [ChapterDiff](ChapterDiff)
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub enum DiffLine {
+    Same(String),
+    Added(String),
+    Removed(String),
+}
+
+/// What changed in a chapter between two saves of it
+///
+/// Chapters are compared paragraph by paragraph, as markdown so changed
+/// formatting shows up too.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct ChapterDiff {
+    pub work_id: String,
+
+    /// Position the chapter had in the work before it changed, starting at 0
+    pub chapter: usize,
+
+    /// When the changed chapter was saved, in UTC
+    pub saved_at: chrono::NaiveDateTime,
+    pub lines: Vec<DiffLine>,
+}
+
+impl ChapterDiff {
+    /// Only the lines that were added or removed
+    pub fn changes(&self) -> impl Iterator<Item = &DiffLine> {
+        self.lines
+            .iter()
+            .filter(|line| !matches!(line, DiffLine::Same(_)))
+    }
+}
+
+/// Diff the text of two versions of a chapter
+pub(super) fn diff_chapter(old: &str, new: &str) -> Vec<DiffLine> {
+    let old = paragraphs(old);
+    let new = paragraphs(new);
+    // longest common subsequence, lcs[i][j] is the length of the one of old[i..] and new[j..]
+    let mut lcs = vec![vec![0u32; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut lines = vec![];
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(DiffLine::Same(old[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            lines.push(DiffLine::Added(new[j].to_string()));
+            j += 1;
+        } else {
+            lines.push(DiffLine::Removed(old[i].to_string()));
+            i += 1;
+        }
+    }
+    lines
+}
+
+fn paragraphs(html: &str) -> Vec<String> {
+    html_to_markdown(html)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Store a diff as text, one line each prefixed with ' ', '+' or '-'
+pub(super) fn encode(lines: &[DiffLine]) -> String {
+    lines
+        .iter()
+        .map(|line| match line {
+            DiffLine::Same(text) => format!(" {text}"),
+            DiffLine::Added(text) => format!("+{text}"),
+            DiffLine::Removed(text) => format!("-{text}"),
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+pub(super) fn decode(text: &str) -> Vec<DiffLine> {
+    text.lines()
+        .filter_map(|line| {
+            let mut chars = line.chars();
+            let kind = chars.next()?;
+            let text = chars.as_str().to_string();
+            Some(match kind {
+                '+' => DiffLine::Added(text),
+                '-' => DiffLine::Removed(text),
+                _ => DiffLine::Same(text),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Library;
+    use super::*;
+    use crate::models::{AO3Chapter, AO3Work};
+
+    #[test]
+    fn test_chapter_diffs() {
+        let library = Library::open_in_memory().unwrap();
+        let mut work = AO3Work::default();
+        work.id = String::from("45221314");
+        library.save_work(&work).unwrap();
+        let chapter = |text: &str| AO3Chapter {
+            text: text.to_string(),
+            ..Default::default()
+        };
+        let first = vec![chapter(
+            "<p>The smoke had not yet cleared.</p><p>She walked on.</p>",
+        )];
+        library.save_chapters("45221314", &first).unwrap();
+        // saving the same text again is not a change
+        library.save_chapters("45221314", &first).unwrap();
+        assert!(library.chapter_diffs("45221314").unwrap().is_empty());
+
+        library
+            .save_chapters(
+                "45221314",
+                &[
+                    chapter("<p>The smoke had not yet cleared.</p><p>She <em>ran</em>.</p>"),
+                    chapter("<p>A new chapter isn't a diff.</p>"),
+                ],
+            )
+            .unwrap();
+        let diffs = library.chapter_diffs("45221314").unwrap();
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].chapter, 0);
+        assert_eq!(
+            diffs[0].lines,
+            vec![
+                DiffLine::Same(String::from("The smoke had not yet cleared.")),
+                DiffLine::Added(String::from("She *ran*.")),
+                DiffLine::Removed(String::from("She walked on.")),
+            ]
+        );
+        assert_eq!(diffs[0].changes().count(), 2);
+        assert_eq!(decode(&encode(&diffs[0].lines)), diffs[0].lines);
+
+        library.remove_work("45221314").unwrap();
+        assert!(library.chapter_diffs("45221314").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_removed_chapter() {
+        let library = Library::open_in_memory().unwrap();
+        let mut work = AO3Work::default();
+        work.id = String::from("45221314");
+        library.save_work(&work).unwrap();
+        let chapter = |id: &str, text: &str| AO3Chapter {
+            id: id.to_string(),
+            text: text.to_string(),
+            ..Default::default()
+        };
+        let before = chapter("113700001", "<p>Before.</p>");
+        let after = chapter("113700003", "<p>After.</p>");
+        library
+            .save_chapters(
+                "45221314",
+                &[
+                    before.clone(),
+                    chapter("113700002", "<p>During.</p>"),
+                    after.clone(),
+                ],
+            )
+            .unwrap();
+        library.save_chapters("45221314", &[before, after]).unwrap();
+        // the last chapter moved up but didn't change
+        let diffs = library.chapter_diffs("45221314").unwrap();
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].chapter, 1);
+        assert_eq!(
+            diffs[0].lines,
+            vec![DiffLine::Removed(String::from("During."))]
+        );
+    }
+}
@@ -0,0 +1,260 @@
+use std::time::Duration;
+
+use super::Library;
+use crate::checkpoint::Checkpoint;
+use crate::client::AO3Client;
+use crate::error::Error;
+use crate::progress::{Monitor, Progress};
+
+/// How [mirror_tag](Library::mirror_tag) goes through a tag
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MirrorOptions {
+    /// Stop after this many pages of the listing, there are 20 works on a page
+    pub max_pages: Option<usize>,
+
+    /// Time to wait between requests, so AO3 isn't hammered
+    pub request_delay: Duration,
+
+    /// Fetch the chapters of works again even if they weren't updated since they were stored
+    pub refetch_unchanged: bool,
+
+    /// [Checkpoint](Checkpoint) file to keep the progress in, an interrupted mirror resumes from it
+    pub checkpoint: Option<std::path::PathBuf>,
+}
+
+impl Default for MirrorOptions {
+    fn default() -> Self {
+        MirrorOptions {
+            max_pages: None,
+            request_delay: Duration::from_secs(5),
+            refetch_unchanged: false,
+            checkpoint: None,
+        }
+    }
+}
+
+impl MirrorOptions {
+    pub fn new() -> Self {
+        MirrorOptions {
+            ..Default::default()
+        }
+    }
+
+    pub fn set_max_pages(mut self, max_pages: usize) -> Self {
+        self.max_pages = Some(max_pages);
+        self
+    }
+
+    pub fn set_request_delay(mut self, request_delay: Duration) -> Self {
+        self.request_delay = request_delay;
+        self
+    }
+
+    pub fn refetch_unchanged(mut self, refetch_unchanged: bool) -> Self {
+        self.refetch_unchanged = refetch_unchanged;
+        self
+    }
+
+    pub fn set_checkpoint(mut self, path: impl AsRef<std::path::Path>) -> Self {
+        self.checkpoint = Some(path.as_ref().to_path_buf());
+        self
+    }
+}
+
+/// What a [mirror_tag](Library::mirror_tag) run did
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct MirrorReport {
+    /// Pages of the listing gone through in this run
+    pub pages: usize,
+
+    /// Works saved with their chapters
+    pub saved: usize,
+
+    /// Works already stored as they are now
+    pub unchanged: usize,
+
+    /// Works whose chapters could not be fetched, with the reason
+    pub failed: Vec<(String, String)>,
+
+    /// Page the run started from, more than 1 when it resumed an interrupted one
+    pub started_at_page: usize,
+}
+
+impl Library {
+    /// Store every work of a tag with its chapters, e.g. to preserve a whole fandom
+    ///
+    /// A work that can't be fetched is recorded in the report's `failed` and skipped.
+    /// With a checkpoint file every stored work is recorded in it, running the mirror
+    /// again with the same file picks up from the work it stopped at.
+    /// Once the last page is done the file is removed, so the next run starts over and
+    /// only fetches what was updated in the meantime.
+    /// ```rust,no_run
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use ao3rs::client::AO3Client;
+    /// use ao3rs::library::{Library, MirrorOptions};
+    ///
+    /// let library = Library::open("hollow_knight.db")?;
+    /// let options = MirrorOptions::new().set_checkpoint("hollow_knight.checkpoint");
+    /// let report = library
+    ///     .mirror_tag(&AO3Client::new(), "Hollow Knight (Video Game)", &options)
+    ///     .await?;
+    /// println!("saved {} works", report.saved);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn mirror_tag(
+        &self,
+        client: &AO3Client,
+        tag: &str,
+        options: &MirrorOptions,
+    ) -> Result<MirrorReport, Error> {
+        self.mirror_tag_with_progress(client, tag, options, &Monitor::default())
+            .await
+    }
+
+    /// [mirror_tag](Library::mirror_tag), reporting the pages and works done to `monitor`
+    ///
+    /// Cancelling stops before the next request, with a checkpoint the mirror can be resumed.
+    pub async fn mirror_tag_with_progress(
+        &self,
+        client: &AO3Client,
+        tag: &str,
+        options: &MirrorOptions,
+        monitor: &Monitor,
+    ) -> Result<MirrorReport, Error> {
+        let mut checkpoint = match &options.checkpoint {
+            Some(path) => Some(Checkpoint::load(path, &format!("mirror_tag {tag}"))?),
+            None => None,
+        };
+        let mut page = checkpoint.as_ref().map_or(1, |c| c.page());
+        let mut report = MirrorReport {
+            started_at_page: page,
+            ..Default::default()
+        };
+        let mut progress = Progress {
+            pages_done: page - 1,
+            ..Default::default()
+        };
+        loop {
+            monitor.check()?;
+            let (works, page_count) = client.fetch_tag_page(tag, page).await?;
+            progress.page_count = Some(page_count);
+            for work in works {
+                if checkpoint.as_ref().is_some_and(|c| c.is_done(&work.id)) {
+                    continue;
+                }
+                let stored = self.work(&work.id)?;
+                let is_unchanged = stored.is_some_and(|stored| {
+                    stored.date == work.date && stored.chapter_count == work.chapter_count
+                });
+                if is_unchanged && !options.refetch_unchanged {
+                    // the listing has fresh stats even when the work didn't change
+                    self.record_stats(&work, chrono::Utc::now().date_naive())?;
+                    report.unchanged += 1;
+                } else {
+                    monitor.sleep(options.request_delay).await?;
+                    match client.fetch_chapters(&work.id).await {
+                        Ok(chapters) => {
+                            self.save_work(&work)?;
+                            self.save_chapters(&work.id, &chapters)?;
+                            report.saved += 1;
+                            progress.works_fetched += 1;
+                        }
+                        Err(err) => report.failed.push((work.id.clone(), err.to_string())),
+                    }
+                }
+                if let Some(checkpoint) = &mut checkpoint {
+                    checkpoint.mark_done(&work.id)?;
+                }
+                monitor.report(&progress);
+            }
+            report.pages += 1;
+            progress.pages_done += 1;
+            monitor.report(&progress);
+            if page >= page_count {
+                if let Some(checkpoint) = checkpoint {
+                    checkpoint.finish()?;
+                }
+                return Ok(report);
+            }
+            if let Some(checkpoint) = &mut checkpoint {
+                checkpoint.next_page()?;
+            }
+            if options.max_pages.is_some_and(|max| report.pages >= max) {
+                return Ok(report);
+            }
+            page += 1;
+            monitor.sleep(options.request_delay).await?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    use crate::parse::parse_search;
+    use crate::testing::MockAo3;
+
+    #[tokio::test]
+    async fn test_mirror_tag() {
+        let search = include_str!("../parse_test/search.html");
+        let ids: Vec<_> = parse_search(search)
+            .unwrap()
+            .into_iter()
+            .map(|work| work.id)
+            .collect();
+        let (saved, failed) = (&ids[0], &ids[1]);
+        // resuming on page 2 with all but two works of it already done
+        let path = std::env::temp_dir().join(format!("ao3rs-mirror-{}", std::process::id()));
+        let mut checkpoint = Checkpoint::load(&path, "mirror_tag Horror").unwrap();
+        checkpoint.next_page().unwrap();
+        for id in &ids[2..] {
+            checkpoint.mark_done(id).unwrap();
+        }
+        let mock = MockAo3::start()
+            .await
+            .unwrap()
+            .page("/tags/Horror/works?page=2&view_adult=true", search)
+            .page(
+                &format!("/works/{saved}?view_full_work=true&view_adult=true"),
+                include_str!("../parse_test/work.html"),
+            );
+        let library = Library::open_in_memory().unwrap();
+        let options = MirrorOptions::new()
+            .set_request_delay(Duration::ZERO)
+            .set_max_pages(1)
+            .set_checkpoint(&path);
+        // the failed work is in the checkpoint too, so resuming doesn't try it again
+        let failed_is_done = Arc::new(AtomicBool::new(false));
+        let monitor = Monitor::new().on_progress({
+            let (path, failed, failed_is_done) =
+                (path.clone(), failed.clone(), failed_is_done.clone());
+            move |_| {
+                let checkpoint = Checkpoint::load(&path, "mirror_tag Horror").unwrap();
+                if checkpoint.is_done(&failed) {
+                    failed_is_done.store(true, Ordering::Relaxed);
+                }
+            }
+        });
+        let report = library
+            .mirror_tag_with_progress(&mock.client(), "Horror", &options, &monitor)
+            .await
+            .unwrap();
+
+        assert_eq!(report.started_at_page, 2);
+        assert_eq!(report.pages, 1);
+        assert_eq!(report.saved, 1);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(&report.failed[0].0, failed);
+        assert_eq!(mock.requests().len(), 3);
+        assert!(library.work(saved).unwrap().is_some());
+        assert!(library.work(failed).unwrap().is_none());
+        assert!(failed_is_done.load(Ordering::Relaxed));
+        let checkpoint = Checkpoint::load(&path, "mirror_tag Horror").unwrap();
+        assert_eq!(checkpoint.page(), 3);
+        checkpoint.finish().unwrap();
+    }
+}
@@ -0,0 +1,157 @@
+use rusqlite::{params, OptionalExtension};
+
+use super::{Library, LibraryError};
+use crate::models::AO3Work;
+
+/// The kudos, hits, bookmarks and comments of a work on a day
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StatsSnapshot {
+    pub date: chrono::NaiveDate,
+    pub kudos: usize,
+    pub hits: usize,
+    pub bookmarks: usize,
+    pub comments: usize,
+}
+
+impl Library {
+    /// Record the stats `work` has on `date`, replacing the ones recorded that day
+    ///
+    /// [save_work](Library::save_work) records them for today already,
+    /// this is for stats from some other day, e.g. from an older export.
+    pub fn record_stats(
+        &self,
+        work: &AO3Work,
+        date: chrono::NaiveDate,
+    ) -> Result<(), LibraryError> {
+        self.conn.execute(
+            "INSERT INTO work_stats (work_id, date, kudos, hits, bookmarks, comments)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            ON CONFLICT(work_id, date) DO UPDATE SET kudos = ?3, hits = ?4, bookmarks = ?5,
+                comments = ?6",
+            params![
+                work.id,
+                date.to_string(),
+                work.kudos as i64,
+                work.hits as i64,
+                work.bookmarks as i64,
+                work.comments as i64,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// [record_stats](Library::record_stats), unless they're all 0 (a page without stats)
+    /// or the same as the last ones recorded
+    pub(super) fn record_changed_stats(
+        &self,
+        work: &AO3Work,
+        date: chrono::NaiveDate,
+    ) -> Result<(), LibraryError> {
+        let stats = [work.kudos, work.hits, work.bookmarks, work.comments];
+        if stats == [0; 4] {
+            return Ok(());
+        }
+        let last = self
+            .conn
+            .query_row(
+                "SELECT kudos, hits, bookmarks, comments FROM work_stats WHERE work_id = ?1
+                ORDER BY date DESC LIMIT 1",
+                [&work.id],
+                |row| {
+                    Ok([
+                        row.get::<_, i64>(0)? as usize,
+                        row.get::<_, i64>(1)? as usize,
+                        row.get::<_, i64>(2)? as usize,
+                        row.get::<_, i64>(3)? as usize,
+                    ])
+                },
+            )
+            .optional()?;
+        if last == Some(stats) {
+            return Ok(());
+        }
+        self.record_stats(work, date)
+    }
+
+    /// The stats recorded for a work, a snapshot a day at most, oldest first
+    ///
+    /// ```rust,no_run
+    /// # fn example(library: &ao3rs::library::Library) -> Result<(), ao3rs::library::LibraryError> {
+    /// for snapshot in library.stats_history("45221314")? {
+    ///     println!("{}: {} kudos, {} hits", snapshot.date, snapshot.kudos, snapshot.hits);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn stats_history(&self, work_id: &str) -> Result<Vec<StatsSnapshot>, LibraryError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT date, kudos, hits, bookmarks, comments FROM work_stats WHERE work_id = ?1
+            ORDER BY date",
+        )?;
+        let history = stmt
+            .query_map([work_id], |row| {
+                Ok(StatsSnapshot {
+                    date: row.get::<_, String>(0)?.parse().unwrap_or_default(),
+                    kudos: row.get::<_, i64>(1)? as usize,
+                    hits: row.get::<_, i64>(2)? as usize,
+                    bookmarks: row.get::<_, i64>(3)? as usize,
+                    comments: row.get::<_, i64>(4)? as usize,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<StatsSnapshot>>>()?;
+        Ok(history)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_history() {
+        let library = Library::open_in_memory().unwrap();
+        let mut work = AO3Work::default();
+        work.id = String::from("45221314");
+        work.kudos = 10;
+        work.hits = 100;
+        library.save_work(&work).unwrap();
+        let day = |d| chrono::NaiveDate::from_ymd_opt(2024, 1, d).unwrap();
+        library.record_stats(&work, day(2)).unwrap();
+        work.kudos = 12;
+        library.record_stats(&work, day(1)).unwrap();
+        work.kudos = 15;
+        library.record_stats(&work, day(2)).unwrap();
+
+        let history = library.stats_history("45221314").unwrap();
+        let kudos: Vec<_> = history.iter().map(|s| (s.date, s.kudos)).collect();
+        assert_eq!(kudos[..2], [(day(1), 12), (day(2), 15)]);
+        // and the one save_work recorded today
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[2].kudos, 10);
+        assert_eq!(library.work("45221314").unwrap().unwrap().hits, 100);
+        assert!(library.stats_history("1").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_stats_snapshot_skipped() {
+        let library = Library::open_in_memory().unwrap();
+        let mut work = AO3Work::default();
+        work.id = String::from("45221314");
+        // no stats on the page it came from
+        library.save_work(&work).unwrap();
+        assert!(library.stats_history("45221314").unwrap().is_empty());
+
+        work.kudos = 10;
+        let yesterday = chrono::Utc::now().date_naive().pred_opt().unwrap();
+        library.record_stats(&work, yesterday).unwrap();
+        library.save_work(&work).unwrap();
+        assert_eq!(library.stats_history("45221314").unwrap().len(), 1);
+
+        work.kudos = 11;
+        library.save_work(&work).unwrap();
+        let history = library.stats_history("45221314").unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1].kudos, 11);
+    }
+}
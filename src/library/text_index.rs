@@ -0,0 +1,178 @@
+use std::sync::Mutex;
+
+use tantivy::collector::TopDocs;
+use tantivy::query::QueryParser;
+use tantivy::schema::{Field, Schema, Value, STORED, STRING, TEXT};
+use tantivy::{doc, Index, IndexReader, IndexWriter, ReloadPolicy, TantivyDocument, Term};
+
+use crate::convert::html_to_plain_text;
+use crate::models::{AO3Chapter, AO3Work};
+
+/// Most matches [search_text](super::Library::search_text) returns
+const SEARCH_LIMIT: usize = 50;
+
+/// A chapter of a stored work that matched a text search
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct TextMatch {
+    pub work: AO3Work,
+
+    /// Position of the chapter in the work, starting at 0
+    pub chapter: usize,
+
+    /// Where the match starts in the chapter's plain text, counted in characters
+    pub offset: Option<usize>,
+}
+
+/// Tantivy index with one document per chapter
+pub(super) struct TextIndex {
+    reader: IndexReader,
+    writer: Mutex<IndexWriter>,
+    parser: QueryParser,
+    work_id: Field,
+    chapter: Field,
+    text: Field,
+}
+
+impl std::fmt::Debug for TextIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TextIndex").finish_non_exhaustive()
+    }
+}
+
+impl TextIndex {
+    pub(super) fn open(dir: Option<&std::path::Path>) -> tantivy::Result<Self> {
+        let mut schema = Schema::builder();
+        let work_id = schema.add_text_field("work_id", STRING | STORED);
+        let chapter = schema.add_u64_field("chapter", STORED);
+        let text = schema.add_text_field("text", TEXT | STORED);
+        let schema = schema.build();
+        let index = match dir {
+            Some(dir) => {
+                std::fs::create_dir_all(dir)?;
+                Index::open_or_create(tantivy::directory::MmapDirectory::open(dir)?, schema)?
+            }
+            None => Index::create_in_ram(schema),
+        };
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        Ok(TextIndex {
+            reader,
+            writer: Mutex::new(index.writer_with_num_threads(1, 15_000_000)?),
+            parser: QueryParser::for_index(&index, vec![text]),
+            work_id,
+            chapter,
+            text,
+        })
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.reader.searcher().num_docs() == 0
+    }
+
+    /// Put the chapters of a work in the index, in place of the ones it had
+    pub(super) fn replace_chapters(
+        &self,
+        work_id: &str,
+        chapters: &[AO3Chapter],
+    ) -> tantivy::Result<()> {
+        let mut writer = self.writer.lock().unwrap();
+        writer.delete_term(Term::from_field_text(self.work_id, work_id));
+        for (position, chapter) in chapters.iter().enumerate() {
+            writer.add_document(doc!(
+                self.work_id => work_id,
+                self.chapter => position as u64,
+                self.text => html_to_plain_text(&chapter.text),
+            ))?;
+        }
+        writer.commit()?;
+        self.reader.reload()
+    }
+
+    /// Returns the work id, chapter position and offset of the best matches
+    pub(super) fn search(
+        &self,
+        query: &str,
+    ) -> tantivy::Result<Vec<(String, usize, Option<usize>)>> {
+        // quotes people half remember are full of stray punctuation, so don't fail on syntax
+        let (parsed, _) = self.parser.parse_query_lenient(query);
+        let searcher = self.reader.searcher();
+        let mut matches = vec![];
+        for (_, address) in searcher.search(&parsed, &TopDocs::with_limit(SEARCH_LIMIT))? {
+            let doc: TantivyDocument = searcher.doc(address)?;
+            let work_id = doc.get_first(self.work_id).and_then(|v| v.as_str());
+            let chapter = doc.get_first(self.chapter).and_then(|v| v.as_u64());
+            let text = doc
+                .get_first(self.text)
+                .and_then(|v| v.as_str())
+                .unwrap_or_default();
+            if let (Some(work_id), Some(chapter)) = (work_id, chapter) {
+                matches.push((
+                    work_id.to_string(),
+                    chapter as usize,
+                    find_offset(text, query),
+                ));
+            }
+        }
+        Ok(matches)
+    }
+}
+
+/// Where the whole query shows up in `text`, or else its first word that does
+fn find_offset(text: &str, query: &str) -> Option<usize> {
+    let text = text.to_lowercase();
+    let query = query.to_lowercase();
+    let byte_offset = text.find(query.trim()).or_else(|| {
+        query
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .find_map(|word| text.find(word))
+    })?;
+    Some(text[..byte_offset].chars().count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Library;
+    use super::*;
+
+    #[test]
+    fn test_search_text() {
+        let library = Library::open_in_memory().unwrap();
+        let mut work = AO3Work::default();
+        work.id = String::from("45221314");
+        library.save_work(&work).unwrap();
+        let chapter = |text: &str| AO3Chapter {
+            text: text.to_string(),
+            ..Default::default()
+        };
+        library
+            .save_chapters(
+                "45221314",
+                &[
+                    chapter("<p>The smoke had not yet cleared.</p>"),
+                    chapter("<p>Someone was <em>moving</em> under the rubble.</p>"),
+                ],
+            )
+            .unwrap();
+        // chapters saved before the index existed get indexed too
+        let library = library.with_text_index(None).unwrap();
+        let matches = library.search_text("moving under rubble").unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].work.id, "45221314");
+        assert_eq!(matches[0].chapter, 1);
+        assert_eq!(matches[0].offset, Some(12));
+
+        library
+            .save_chapters(
+                "45221314",
+                &[chapter("<p>Rewritten, the rubble is gone.</p>")],
+            )
+            .unwrap();
+        assert_eq!(library.search_text("\"moving").unwrap().len(), 0);
+        assert_eq!(library.search_text("rubble").unwrap()[0].chapter, 0);
+        library.remove_work("45221314").unwrap();
+        assert!(library.search_text("rubble").unwrap().is_empty());
+    }
+}
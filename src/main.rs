@@ -0,0 +1,131 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use ao3rs::client::AO3Client;
+use ao3rs::models::{AO3Work, DownloadFormat};
+use ao3rs::query::AO3QueryBuilder;
+use ao3rs::watch::{SavedSearch, WatchEvent, Watcher};
+use clap::{Parser, Subcommand};
+
+/// Search, read and download works from the Archive of Our Own
+#[derive(Debug, Parser)]
+#[command(name = "ao3rs", version)]
+struct Cli {
+    /// Seconds to wait between requests to AO3
+    #[arg(long, global = true, default_value_t = 1.0)]
+    delay: f64,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Search works, like the search box at the top of AO3
+    Search {
+        query: String,
+
+        /// How many works to list
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+
+    /// Show the metadata of a work
+    Work { id: String },
+
+    /// Download a work
+    Download {
+        id: String,
+
+        /// epub, mobi, pdf, html or azw3
+        #[arg(long, default_value = "epub")]
+        format: DownloadFormat,
+
+        /// Where to save the work, `<id>.<format>` by default
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+
+    /// Run a search over and over and list the works that are new or updated
+    Watch {
+        query: String,
+
+        /// Minutes between runs
+        #[arg(long, default_value_t = 30)]
+        interval: u64,
+    },
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(cli: Cli) -> Result<(), ao3rs::Error> {
+    let client = AO3Client::new().set_rate_limit(Duration::from_secs_f64(cli.delay.max(0.0)));
+    match cli.command {
+        Command::Search { query, limit } => {
+            let works = AO3QueryBuilder::new()
+                .set_any_field(&query)
+                .set_search_limit(limit)
+                .send_with(&client)
+                .await?;
+            works.iter().for_each(print_blurb);
+        }
+        Command::Work { id } => {
+            let work = client.fetch_work(&id).await?;
+            print_blurb(&work);
+            if !work.tags.is_empty() {
+                println!("  Tags: {}", work.tags.join(", "));
+            }
+            if !work.summary.is_empty() {
+                println!(
+                    "  Summary: {}",
+                    ao3rs::convert::html_to_plain_text(&work.summary)
+                );
+            }
+        }
+        Command::Download { id, format, output } => {
+            let path = output.unwrap_or_else(|| format!("{id}.{}", format.extension()).into());
+            let size = client.download_work(&id, format, &path).await?;
+            println!("Saved {} ({size} bytes)", path.display());
+        }
+        Command::Watch { query, interval } => {
+            let search = SavedSearch::new(&query, AO3QueryBuilder::new().set_any_field(&query));
+            Watcher::new(Duration::from_secs(interval * 60))
+                .set_client(client)
+                .watch(search)
+                .run(|event| match event {
+                    WatchEvent::NewWorks { works, .. } => works.iter().for_each(print_blurb),
+                    WatchEvent::Error { message, .. } => eprintln!("{message}"),
+                })
+                .await;
+        }
+    }
+    Ok(())
+}
+
+fn print_blurb(work: &AO3Work) {
+    println!(
+        "{} - {} by {} [{}]",
+        work.id,
+        work.title,
+        work.authors.join(", "),
+        work.fandoms.join(", ")
+    );
+    println!(
+        "  {} words, {} chapters{}, updated {}",
+        work.word_count,
+        work.chapter_count,
+        if work.is_complete { ", complete" } else { "" },
+        work.date
+    );
+}
@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+
+use crate::models::AO3Work;
+
+/// Works of several queries or pages, each work once
+///
+/// When a work shows up more than once the most recent copy is kept: the one with the
+/// latest update date, or the one added last when they were updated on the same day,
+/// since it was fetched last. Works keep the place they were first seen at.
+/// ```rust,no_run
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// use ao3rs::merge::MergedWorks;
+/// use ao3rs::query::AO3QueryBuilder;
+///
+/// let mut merged = MergedWorks::new();
+/// merged.add(AO3QueryBuilder::new().push_fandom("Good Omens").send().await?);
+/// merged.add(AO3QueryBuilder::new().push_character("Aziraphale").send().await?);
+/// let works = merged.into_works();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct MergedWorks {
+    works: Vec<AO3Work>,
+    /// Index of each work in `works`, by id
+    positions: HashMap<String, usize>,
+}
+
+impl MergedWorks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the works of a query or page
+    pub fn add(&mut self, works: impl IntoIterator<Item = AO3Work>) {
+        for work in works {
+            match self.positions.get(&work.id) {
+                Some(&i) if self.works[i].date > work.date => {}
+                Some(&i) => self.works[i] = work,
+                None => {
+                    self.positions.insert(work.id.clone(), self.works.len());
+                    self.works.push(work);
+                }
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.works.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.works.is_empty()
+    }
+
+    pub fn get(&self, id: &str) -> Option<&AO3Work> {
+        self.positions.get(id).map(|&i| &self.works[i])
+    }
+
+    pub fn works(&self) -> &[AO3Work] {
+        &self.works
+    }
+
+    pub fn into_works(self) -> Vec<AO3Work> {
+        self.works
+    }
+}
+
+/// Merge result sets into one, see [MergedWorks]
+pub fn merge_works<I>(result_sets: I) -> Vec<AO3Work>
+where
+    I: IntoIterator,
+    I::Item: IntoIterator<Item = AO3Work>,
+{
+    let mut merged = MergedWorks::new();
+    for works in result_sets {
+        merged.add(works);
+    }
+    merged.into_works()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::WorkBuilder;
+
+    #[test]
+    fn test_merge_works() {
+        let first = vec![
+            WorkBuilder::new("1")
+                .set_title("Old title")
+                .set_date(2024, 1, 1)
+                .build(),
+            WorkBuilder::new("2")
+                .set_title("Two")
+                .set_date(2024, 1, 5)
+                .build(),
+        ];
+        let second = vec![
+            WorkBuilder::new("3")
+                .set_title("Three")
+                .set_date(2024, 1, 1)
+                .build(),
+            WorkBuilder::new("1")
+                .set_title("New title")
+                .set_date(2024, 1, 2)
+                .build(),
+            WorkBuilder::new("2")
+                .set_title("Stale")
+                .set_date(2024, 1, 4)
+                .build(),
+        ];
+        let third = vec![WorkBuilder::new("2")
+            .set_title("Refetched")
+            .set_date(2024, 1, 5)
+            .build()];
+        let merged = merge_works([first, second, third]);
+        let titles: Vec<_> = merged.iter().map(|w| w.title.as_str()).collect();
+        assert_eq!(titles, vec!["New title", "Refetched", "Three"]);
+    }
+}
@@ -1,5 +1,6 @@
 /// Rating given to a specific work
-#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Rating {
     /// We don't care what the rating is
     #[default]
@@ -21,7 +22,86 @@ pub enum Rating {
     Explicit = 13,
 }
 
+impl Rating {
+    /// Name of the rating on AO3, empty for [None](Rating::None)
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rating::None => "",
+            Rating::NotRated => "Not Rated",
+            Rating::General => "General Audiences",
+            Rating::TeenAndUp => "Teen And Up Audiences",
+            Rating::Mature => "Mature",
+            Rating::Explicit => "Explicit",
+        }
+    }
+
+    /// Read a rating from its [name](Rating::name)
+    pub(crate) fn from_name(name: &str) -> Option<Rating> {
+        [
+            Rating::NotRated,
+            Rating::General,
+            Rating::TeenAndUp,
+            Rating::Mature,
+            Rating::Explicit,
+        ]
+        .into_iter()
+        .find(|rating| rating.name() == name.trim())
+    }
+}
+
+/// File formats AO3 offers works for download in
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DownloadFormat {
+    /// For most e-readers
+    #[default]
+    Epub,
+
+    /// For older Kindles
+    Mobi,
+
+    Pdf,
+
+    /// A single html page with every chapter
+    Html,
+
+    /// For newer Kindles
+    Azw3,
+}
+
+impl DownloadFormat {
+    /// File extension AO3 uses for the format, without the dot
+    pub fn extension(&self) -> &'static str {
+        match self {
+            DownloadFormat::Epub => "epub",
+            DownloadFormat::Mobi => "mobi",
+            DownloadFormat::Pdf => "pdf",
+            DownloadFormat::Html => "html",
+            DownloadFormat::Azw3 => "azw3",
+        }
+    }
+}
+
+impl std::str::FromStr for DownloadFormat {
+    type Err = crate::Error;
+
+    /// Read a format from its [extension](DownloadFormat::extension), in any case
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "epub" => Ok(DownloadFormat::Epub),
+            "mobi" => Ok(DownloadFormat::Mobi),
+            "pdf" => Ok(DownloadFormat::Pdf),
+            "html" => Ok(DownloadFormat::Html),
+            "azw3" => Ok(DownloadFormat::Azw3),
+            _ => Err(crate::Error::InvalidInput(format!(
+                "Not a download format: {s}"
+            ))),
+        }
+    }
+}
+
 #[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AO3Work {
     pub id: String,
     pub url: String,
@@ -31,16 +111,535 @@ pub struct AO3Work {
     pub is_complete: bool,
     pub is_crossover: bool,
     pub word_count: usize,
+    /// Number of chapters posted so far
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub chapter_count: usize,
     pub fandoms: Vec<String>,
-    rating: Option<Rating>
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub relationships: Vec<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub characters: Vec<String>,
+    /// Additional (freeform) tags
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub tags: Vec<String>,
+    /// Archive warnings, e.g. "Major Character Death"
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub warnings: Vec<String>,
+    /// Summary as html
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub summary: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub kudos: usize,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub hits: usize,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub bookmarks: usize,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub comments: usize,
+    rating: Option<Rating>,
+
+    /// Html of the page the work was parsed from, when the client
+    /// [keeps it](crate::client::AO3Client::set_keep_raw)
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub raw_html: Option<String>,
 }
 
 impl AO3Work {
-    fn parse_entire() {
+    /// Serialize the work into a JSON object
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    /// Rating of the work, [None](Rating::None) when it isn't known
+    pub fn get_rating(&self) -> Rating {
+        self.rating.unwrap_or_default()
+    }
+
+    pub fn set_rating(&mut self, rating: Rating) {
+        self.rating = Some(rating).filter(|rating| *rating != Rating::None);
+    }
+}
+
+/// Works for tests, with only the fields a test looks at set
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub(crate) struct WorkBuilder(AO3Work);
+
+#[cfg(test)]
+impl WorkBuilder {
+    pub(crate) fn new(id: &str) -> Self {
+        WorkBuilder(AO3Work {
+            id: id.to_string(),
+            ..Default::default()
+        })
+    }
+
+    pub(crate) fn set_title(mut self, title: &str) -> Self {
+        self.0.title = title.to_string();
+        self
+    }
+
+    pub(crate) fn set_date(mut self, year: i32, month: u32, day: u32) -> Self {
+        self.0.date = chrono::NaiveDate::from_ymd_opt(year, month, day).unwrap();
+        self
+    }
+
+    pub(crate) fn push_fandom(mut self, fandom: &str) -> Self {
+        self.0.fandoms.push(fandom.to_string());
+        self
+    }
+
+    pub(crate) fn set_tags(mut self, tags: &[&str]) -> Self {
+        self.0.tags = tags.iter().map(|tag| tag.to_string()).collect();
+        self
+    }
+
+    pub(crate) fn push_warning(mut self, warning: &str) -> Self {
+        self.0.warnings.push(warning.to_string());
+        self
+    }
+
+    pub(crate) fn set_rating(mut self, rating: Rating) -> Self {
+        self.0.set_rating(rating);
+        self
+    }
+
+    pub(crate) fn set_word_count(mut self, word_count: usize) -> Self {
+        self.0.word_count = word_count;
+        self
+    }
+
+    pub(crate) fn set_kudos(mut self, kudos: usize) -> Self {
+        self.0.kudos = kudos;
+        self
+    }
+
+    pub(crate) fn set_hits(mut self, hits: usize) -> Self {
+        self.0.hits = hits;
+        self
+    }
+
+    pub(crate) fn build(self) -> AO3Work {
+        self.0
+    }
+}
+
+/// What a tag's page (`/tags/<name>`) says about it
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AO3Tag {
+    pub name: String,
+
+    /// Whether the tag can be used in filters, synonyms and unwrangled tags can't
+    pub is_canonical: bool,
+
+    /// The canonical tag this one is a synonym of
+    pub canonical: Option<String>,
+
+    /// Tags that are synonyms of this one, only canonical tags have them
+    pub synonyms: Vec<String>,
+}
+
+/// A series of works
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AO3Series {
+    pub id: String,
+    pub title: String,
+    pub creators: Vec<String>,
+
+    /// Description as html
+    pub description: String,
+
+    /// When a work of the series was last posted or updated
+    pub date: chrono::NaiveDate,
+    pub is_complete: bool,
+
+    /// Works in the order of the series
+    pub works: Vec<AO3Work>,
+}
+
+/// What was known about a work when it was last looked at, see
+/// [check_updates](crate::client::AO3Client::check_updates)
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WorkRef {
+    pub id: String,
+    pub chapter_count: usize,
+    pub date: chrono::NaiveDate,
+}
+
+impl From<&AO3Work> for WorkRef {
+    fn from(work: &AO3Work) -> Self {
+        WorkRef {
+            id: work.id.clone(),
+            chapter_count: work.chapter_count,
+            date: work.date,
+        }
+    }
+}
+
+/// A work that changed since it was stored
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WorkUpdate {
+    /// The work as it is now
+    pub work: AO3Work,
+
+    /// Chapters posted since, negative if chapters were deleted
+    pub new_chapters: isize,
+
+    /// Whether the updated date is different
+    pub date_changed: bool,
+}
+
+/// A chapter of a work
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AO3Chapter {
+    /// Id AO3 gives the chapter, empty for works with a single chapter
+    pub id: String,
+
+    /// Title as shown above the chapter, e.g. "Chapter 1: Before"
+    pub title: String,
+
+    /// Text of the chapter as html
+    pub text: String,
+
+    /// Html of the page the chapter was parsed from, when the client
+    /// [keeps it](crate::client::AO3Client::set_keep_raw)
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub raw_html: Option<String>,
+}
+
+/// A work that has been saved as a draft but not posted yet
+///
+/// AO3 purges drafts about a month after they were created,
+/// `expires` holds the date the draft is scheduled for deletion on.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AO3Draft {
+    pub work: AO3Work,
+    pub expires: Option<chrono::NaiveDate>,
+}
+
+/// Outcome of importing works through AO3's import form
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AO3ImportReport {
+    /// Ids of the works that were imported
+    pub imported: Vec<String>,
+
+    /// Urls that could not be imported, with the reason AO3 gave
+    pub failed: Vec<(String, String)>,
+}
+
+/// An assignment of a gift exchange the user took part in
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AO3Assignment {
+    pub id: String,
+
+    /// Name of the exchange's collection, as used in urls
+    pub collection: String,
+
+    /// Title of the exchange's collection
+    pub collection_title: String,
+
+    /// Who the gift is for, pinch hits might not say
+    pub recipient: Option<String>,
+}
+
+impl AO3Assignment {
+    /// Path of the new work form that fulfills this assignment, posting the work to the exchange's collection
+    pub fn fulfill_path(&self) -> String {
+        format!(
+            "/collections/{}/works/new?assignment_id={}",
+            self.collection, self.id
+        )
+    }
+}
+
+/// One of the requests the recipient of an assignment made
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AO3AssignmentRequest {
+    pub fandoms: Vec<String>,
+
+    /// Every other tag of the request (characters, relationships, additional tags)
+    pub tags: Vec<String>,
+
+    /// The prompt the recipient wrote
+    pub description: String,
+}
+
+/// A prompt posted to a prompt meme
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AO3Prompt {
+    pub id: String,
+
+    /// Name of the prompt meme's collection, as used in urls
+    pub collection: String,
+
+    pub title: String,
+    pub fandoms: Vec<String>,
+    pub tags: Vec<String>,
+    pub description: String,
+}
+
+/// A prompt the user claimed
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AO3Claim {
+    pub id: String,
+
+    /// Name of the prompt meme's collection, as used in urls
+    pub collection: String,
+
+    pub prompt_id: String,
+    pub prompt_title: String,
+}
+
+/// A comment left on a work, along with the replies to it
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AO3Comment {
+    pub id: String,
+
+    /// Pseud of the commenter, or the name a guest gave
+    pub author: String,
 
+    /// Chapter the comment was left on
+    pub chapter: Option<usize>,
+
+    pub date: Option<chrono::NaiveDateTime>,
+
+    /// Html of the comment
+    pub body: String,
+
+    pub replies: Vec<AO3Comment>,
+
+    /// AO3 cuts off deep threads with a "N more comments in this thread" link,
+    /// set when the replies shown here are not all of them
+    pub has_more_replies: bool,
+}
+
+impl AO3Comment {
+    /// Body of the comment as plain text
+    pub fn to_plain_text(&self) -> String {
+        crate::convert::html_to_plain_text(&self.body)
     }
 
-    pub fn get_rating(&mut self) -> Rating {
-        todo!()
+    /// Body of the comment as markdown
+    pub fn to_markdown(&self) -> String {
+        crate::convert::html_to_markdown(&self.body)
+    }
+}
+
+/// A bookmark a user made of a work
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AO3Bookmark {
+    pub id: String,
+
+    /// Work that was bookmarked
+    pub work_id: String,
+
+    /// Pseud of the user who made the bookmark
+    pub bookmarker: String,
+
+    pub date: Option<chrono::NaiveDate>,
+
+    /// Tags the bookmarker gave the work
+    pub tags: Vec<String>,
+
+    /// Html of the bookmarker's notes
+    pub notes: String,
+
+    /// Whether the bookmarker recommends the work
+    pub is_rec: bool,
+}
+
+/// Kind of tag, as AO3's autocomplete sorts them
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TagKind {
+    Fandom,
+    Character,
+    Relationship,
+    Freeform,
+}
+
+impl TagKind {
+    /// Name of the kind in AO3's urls
+    pub fn name(&self) -> &'static str {
+        match self {
+            TagKind::Fandom => "fandom",
+            TagKind::Character => "character",
+            TagKind::Relationship => "relationship",
+            TagKind::Freeform => "freeform",
+        }
     }
-}
\ No newline at end of file
+}
+
+/// What a subscription is to
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SubscriptionKind {
+    #[default]
+    Work,
+    Series,
+    User,
+}
+
+/// Something the user subscribed to, to be notified when it's updated
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AO3Subscription {
+    pub id: String,
+    pub kind: SubscriptionKind,
+
+    /// Id of the work or series, or the username
+    pub target: String,
+
+    /// Title of the work or series, or the username
+    pub name: String,
+}
+
+/// Works found by a search
+///
+/// Can be used like the slice of its works, or turned into the [Vec] of them:
+/// ```rust
+/// use ao3rs::models::{AO3Work, SearchResults};
+///
+/// let results = SearchResults::from(vec![AO3Work::default()]);
+/// assert_eq!(results.len(), 1);
+/// let first: &AO3Work = &results[0];
+/// for work in &results {}
+/// let works: Vec<AO3Work> = results.into();
+/// ```
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SearchResults {
+    pub works: Vec<AO3Work>,
+
+    /// How many works AO3 found in all, when it says
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub total: Option<usize>,
+
+    /// The last page of results the works are from, 0 when they aren't from a search
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub page: usize,
+
+    /// How many pages of results there are
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub page_count: usize,
+
+    /// Url of the search on AO3, at the first page the works are from
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub url: String,
+
+    /// Html of the pages of results, in order, when the client
+    /// [keeps them](crate::client::AO3Client::set_keep_raw)
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub raw_pages: Vec<String>,
+}
+
+impl SearchResults {
+    /// Serialize the results into a JSON object
+    ///
+    /// For a lot of works, see [JsonWriter](crate::export::JsonWriter) instead.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    pub fn len(&self) -> usize {
+        self.works.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.works.is_empty()
+    }
+
+    /// Whether there are no pages of results after the ones the works are from
+    pub fn is_last_page(&self) -> bool {
+        self.page >= self.page_count
+    }
+}
+
+impl From<Vec<AO3Work>> for SearchResults {
+    fn from(works: Vec<AO3Work>) -> Self {
+        SearchResults {
+            works,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<SearchResults> for Vec<AO3Work> {
+    fn from(results: SearchResults) -> Self {
+        results.works
+    }
+}
+
+impl FromIterator<AO3Work> for SearchResults {
+    fn from_iter<I: IntoIterator<Item = AO3Work>>(iter: I) -> Self {
+        SearchResults {
+            works: iter.into_iter().collect(),
+            ..Default::default()
+        }
+    }
+}
+
+impl std::ops::Deref for SearchResults {
+    type Target = [AO3Work];
+
+    fn deref(&self) -> &Self::Target {
+        &self.works
+    }
+}
+
+impl std::ops::DerefMut for SearchResults {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.works
+    }
+}
+
+impl IntoIterator for SearchResults {
+    type Item = AO3Work;
+    type IntoIter = std::vec::IntoIter<AO3Work>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.works.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a SearchResults {
+    type Item = &'a AO3Work;
+    type IntoIter = std::slice::Iter<'a, AO3Work>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.works.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut SearchResults {
+    type Item = &'a mut AO3Work;
+    type IntoIter = std::slice::IterMut<'a, AO3Work>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.works.iter_mut()
+    }
+}
@@ -0,0 +1,187 @@
+use std::borrow::Cow;
+
+use crate::error::Error;
+use crate::models::{AO3Bookmark, AO3Chapter, AO3Comment, AO3Series, AO3Tag, AO3Work};
+use crate::parse;
+pub use crate::parse::{ParseMode, Parsed};
+
+/// Parse a work page (`/works/<id>`) saved from AO3
+///
+/// Works on pages saved with a browser's "Save Page As", which turns links absolute,
+/// as well as on html fetched some other way. No client or network is involved,
+/// that goes for every function of this module.
+/// ```rust
+/// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let html = std::fs::read_to_string("Salvation.html")?;
+/// let work = ao3rs::offline::parse_work_html(&html)?;
+/// let chapters = ao3rs::offline::parse_chapters_html(&html)?;
+/// println!("{} has {} chapters", work.title, chapters.len());
+/// # Ok(())
+/// # }
+/// ```
+pub fn parse_work_html(html: &str) -> Result<AO3Work, Error> {
+    parse::parse_work(&relative_links(html))
+}
+
+/// [parse_work_html], failing on any field that's missing or listing the missing ones
+/// depending on `mode`
+///
+/// ```rust
+/// # fn example(html: &str) -> Result<(), Box<dyn std::error::Error>> {
+/// use ao3rs::offline::{parse_work_html_with, ParseMode};
+///
+/// let parsed = parse_work_html_with(html, ParseMode::Lenient)?;
+/// for missing in &parsed.missing {
+///     eprintln!("{missing}");
+/// }
+/// println!("{}", parsed.value.title);
+/// # Ok(())
+/// # }
+/// ```
+pub fn parse_work_html_with(html: &str, mode: ParseMode) -> Result<Parsed<AO3Work>, Error> {
+    parse::parse_work_with(&relative_links(html), mode)
+}
+
+/// Parse the chapters of a saved work page, all of them if it was saved with "Entire Work"
+pub fn parse_chapters_html(html: &str) -> Result<Vec<AO3Chapter>, Error> {
+    parse::parse_chapters(&relative_links(html))
+}
+
+/// Parse the works of a saved search results page, or of any other list of work blurbs
+pub fn parse_search_html(html: &str) -> Result<Vec<AO3Work>, Error> {
+    parse::parse_search(&relative_links(html))
+}
+
+/// [parse_search_html], skipping the works that can't be parsed in [lenient](ParseMode::Lenient) mode
+pub fn parse_search_html_with(html: &str, mode: ParseMode) -> Result<Parsed<Vec<AO3Work>>, Error> {
+    parse::parse_search_with(&relative_links(html), mode)
+}
+
+/// Parse a saved series page (`/series/<id>`)
+pub fn parse_series_html(html: &str) -> Result<AO3Series, Error> {
+    parse::parse_series(&relative_links(html))
+}
+
+/// Parse the comments of a saved work page, opened with "Comments" shown
+pub fn parse_comments_html(html: &str) -> Result<Vec<AO3Comment>, Error> {
+    parse::parse_comments(&relative_links(html))
+}
+
+/// Parse a saved bookmarks page of a work (`/works/<id>/bookmarks`)
+pub fn parse_bookmarks_html(html: &str) -> Result<Vec<AO3Bookmark>, Error> {
+    parse::parse_work_bookmarks(&relative_links(html))
+}
+
+/// Parse a saved tag page (`/tags/<name>`)
+pub fn parse_tag_html(html: &str) -> Result<AO3Tag, Error> {
+    parse::parse_tag(&relative_links(html))
+}
+
+/// Turn links to AO3 back into the relative links AO3 serves
+///
+/// Only the links of the page itself are turned, the ones in what creators wrote
+/// (`.userstuff`: chapters, summaries, notes, comments) are left as they were written.
+pub(crate) fn relative_links(html: &str) -> Cow<'_, str> {
+    if !ABSOLUTE.iter().any(|url| html.contains(url)) {
+        return Cow::Borrowed(html);
+    }
+    let mut relative = String::with_capacity(html.len());
+    let mut last = 0;
+    for (start, end) in userstuff_ranges(html) {
+        relative.push_str(&page_links(&html[last..start]));
+        relative.push_str(&html[start..end]);
+        last = end;
+    }
+    relative.push_str(&page_links(&html[last..]));
+    Cow::Owned(relative)
+}
+
+const ABSOLUTE: [&str; 4] = [
+    "https://archiveofourown.org/",
+    "http://archiveofourown.org/",
+    "https://www.archiveofourown.org/",
+    "http://www.archiveofourown.org/",
+];
+
+fn page_links(html: &str) -> String {
+    let mut html = html.to_string();
+    for url in ABSOLUTE {
+        html = html
+            .replace(&format!("\"{url}"), "\"/")
+            .replace(&format!("'{url}"), "'/");
+    }
+    html
+}
+
+/// Byte ranges of the `.userstuff` elements of the page, outermost ones only
+fn userstuff_ranges(html: &str) -> Vec<(usize, usize)> {
+    let Ok(dom) = tl::parse(html, tl::ParserOptions::default()) else {
+        return vec![];
+    };
+    let parser = dom.parser();
+    let mut ranges = dom
+        .query_selector(".userstuff")
+        .into_iter()
+        .flatten()
+        .filter_map(|handle| handle.get(parser)?.as_tag())
+        .map(|tag| {
+            let (start, end) = tag.boundaries(parser);
+            (start, end + 1)
+        })
+        .collect::<Vec<_>>();
+    ranges.sort();
+    // a chapter's userstuff can sit inside the work's, the outer one covers it
+    ranges.dedup_by(|inner, outer| inner.0 < outer.1);
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_series_html() {
+        let series = parse_series_html(include_str!("parse_test/series.html")).unwrap();
+        assert_eq!(series.id, "3001234");
+        assert_eq!(series.title, "Ashes Trilogy");
+        assert_eq!(series.creators, vec!["Starchild719"]);
+        assert_eq!(
+            series.description,
+            "<p>What happened before, during &amp; after.</p>"
+        );
+        assert_eq!(
+            series.date,
+            chrono::NaiveDate::from_ymd_opt(2023, 3, 7).unwrap()
+        );
+        assert!(!series.is_complete);
+        assert_eq!(series.works.len(), 2);
+        assert_eq!(series.works[0].id, "45221314");
+        assert_eq!(series.works[0].title, "Salvation");
+        assert_eq!(series.works[0].authors, vec!["Starchild719"]);
+        assert_eq!(series.works[0].chapter_count, 3);
+        assert_eq!(series.works[1].title, "Embers");
+    }
+
+    #[test]
+    fn test_relative_links() {
+        assert_eq!(
+            relative_links(r#"<a href="https://archiveofourown.org/works/1">"#),
+            r#"<a href="/works/1">"#
+        );
+        let saved = r#"<a href="https://archiveofourown.org/works/1/chapters/2">Next Chapter</a>
+<div class="userstuff"><p><a href="https://archiveofourown.org/works/3">the prequel</a></p></div>
+<blockquote class="userstuff"><a href='https://archiveofourown.org/users/someone'>them</a></blockquote>
+<a href='https://archiveofourown.org/works/1/kudos'>Kudos</a>"#;
+        assert_eq!(
+            relative_links(saved),
+            r#"<a href="/works/1/chapters/2">Next Chapter</a>
+<div class="userstuff"><p><a href="https://archiveofourown.org/works/3">the prequel</a></p></div>
+<blockquote class="userstuff"><a href='https://archiveofourown.org/users/someone'>them</a></blockquote>
+<a href='/works/1/kudos'>Kudos</a>"#
+        );
+        assert!(matches!(
+            relative_links("<p>/works/1</p>"),
+            Cow::Borrowed(_)
+        ));
+    }
+}
@@ -1,56 +1,75 @@
-use crate::models::AO3Work;
+use crate::blurb::{Blurb, BlurbPage};
+use crate::error::{Error, ParsingError};
+use crate::form::BASE_AO3_URL;
+use crate::models::{
+    AO3Assignment, AO3AssignmentRequest, AO3Bookmark, AO3Chapter, AO3Claim, AO3Comment, AO3Draft,
+    AO3ImportReport, AO3Prompt, AO3Series, AO3Subscription, AO3Tag, AO3Work, DownloadFormat,
+    Rating, SubscriptionKind,
+};
 
-#[derive(Debug)]
-pub enum ParsingError<'a> {
-    CouldNotFind(&'a str),
+mod fields;
+
+use fields::{
+    Lookup, SERIES_META, SERIES_TITLE, TAG_NAME, WORK_BYLINE, WORK_CHAPTERS, WORK_META,
+    WORK_PREFACE, WORK_SUMMARY, WORK_TITLE,
+};
+
+/// How to deal with fields missing from a page
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Fail on the first field that's missing, e.g. to check saved pages in CI
+    #[default]
+    Strict,
+    /// Leave missing fields empty and list them in [Parsed::missing], e.g. for scraping
+    Lenient,
 }
 
-impl std::fmt::Display for ParsingError<'_> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ParsingError::CouldNotFind(thing) => write!(
-                f,
-                "Could not find: {}\nThis is a problem with the parsing!",
-                thing
-            ),
-        }
-    }
+/// What was parsed from a page, along with what was missing from it
+#[derive(Debug)]
+pub struct Parsed<T> {
+    pub value: T,
+
+    /// The fields that weren't found, always empty in [strict](ParseMode::Strict) mode
+    pub missing: Vec<ParsingError>,
 }
 
-impl std::error::Error for ParsingError<'_> {}
+pub fn parse_search(html_code: &str) -> Result<Vec<AO3Work>, Error> {
+    BlurbPage::parse(html_code)?
+        .blurbs()
+        .map(|blurb| blurb.to_work())
+        .collect()
+}
 
-pub fn parse_search(html_code: &str) -> Result<Vec<AO3Work>, Box<dyn std::error::Error>> {
-    let dom = tl::parse(
-        html_code,
-        tl::ParserOptions::new().track_classes().track_ids(),
-    )?;
-    let parser = dom.parser();
-    let work_list_nodes = dom
-        .query_selector("[role=article]")
-        .ok_or(ParsingError::CouldNotFind("the list of works."))?;
+/// [parse_search], skipping the works that can't be parsed in [lenient](ParseMode::Lenient) mode
+pub fn parse_search_with(html_code: &str, mode: ParseMode) -> Result<Parsed<Vec<AO3Work>>, Error> {
     let mut works = vec![];
-    for work_node in work_list_nodes {
-        works.push(parse_search_single_work(
-            parser,
-            work_node.get(parser).unwrap(),
-        )?);
+    let mut missing = vec![];
+    for blurb in BlurbPage::parse(html_code)?.blurbs() {
+        match (blurb.to_work(), mode) {
+            (Ok(work), _) => works.push(work),
+            (Err(Error::Parse { source, .. }), ParseMode::Lenient) => missing.push(source),
+            (Err(err), _) => return Err(err),
+        }
     }
-    Ok(works)
+    Ok(Parsed {
+        value: works,
+        missing,
+    })
 }
 
-fn search_by_attrib<'a, 'b>(
+pub(crate) fn search_by_attrib<'b>(
     parser: &'b tl::Parser<'b>,
     node: &tl::Node,
-    attrib: &'a str,
+    attrib: &'static str,
     value: &str,
-) -> Result<&'b tl::Node<'b>, ParsingError<'a>> {
+) -> Result<&'b tl::Node<'b>, ParsingError> {
     Ok(node
         .find_node(parser, &mut |n| {
-            n.as_tag().map_or(false, |t| {
+            n.as_tag().is_some_and(|t| {
                 t.attributes()
                     .get(attrib)
                     .flatten()
-                    .map_or(false, |a| a == value)
+                    .is_some_and(|a| a == value)
             })
         })
         .ok_or(ParsingError::CouldNotFind(attrib))?
@@ -58,90 +77,1632 @@ fn search_by_attrib<'a, 'b>(
         .unwrap())
 }
 
-fn get_all_nodes<'a>(parser: &'a tl::Parser, node: &'a tl::Node) -> Vec<&'a tl::Node<'a>> {
-    if let Some(children) = node.children() {
-        let mut nodes = vec![];
-        for ch in children.all(parser) {
-            nodes.push(ch);
-            nodes.append(&mut get_all_nodes(parser, ch));
+/// Find the first node that has `class` among its classes
+pub(crate) fn search_by_class<'b>(
+    parser: &'b tl::Parser<'b>,
+    node: &tl::Node,
+    class: &str,
+) -> Option<&'b tl::Node<'b>> {
+    node.find_node(parser, &mut |n| {
+        n.as_tag()
+            .is_some_and(|t| t.attributes().is_class_member(class))
+    })?
+    .get(parser)
+}
+
+fn get_all_nodes<'a>(parser: &'a tl::Parser<'a>, node: &'a tl::Node<'a>) -> &'a [tl::Node<'a>] {
+    // `all` already goes through every nested node, and borrows them from the parser
+    node.children().map_or(&[], |children| children.all(parser))
+}
+
+pub(crate) fn search_all_by_attrib<'b>(
+    parser: &'b tl::Parser<'b>,
+    node: &'b tl::Node<'b>,
+    attrib: &'b str,
+    value: &'b str,
+) -> impl Iterator<Item = &'b tl::Node<'b>> {
+    get_all_nodes(parser, node).iter().filter(move |n| {
+        n.as_tag()
+            .and_then(|t| t.attributes().get(attrib).flatten())
+            .is_some_and(|a| a == value)
+    })
+}
+
+fn parse_search_single_work<'b>(
+    parser: &'b tl::Parser<'b>,
+    node: &'b tl::Node<'b>,
+) -> Result<AO3Work, Error> {
+    Blurb::new(parser, node).to_work()
+}
+
+/// Find the `dd` of a definition list with `class`, the `dt` before it has the same class
+fn search_dd<'b>(
+    parser: &'b tl::Parser<'b>,
+    node: &tl::Node,
+    class: &str,
+) -> Option<&'b tl::Node<'b>> {
+    node.find_node(parser, &mut |n| {
+        n.as_tag()
+            .is_some_and(|t| t.name() == "dd" && t.attributes().is_class_member(class))
+    })?
+    .get(parser)
+}
+
+pub(crate) fn parse_word_count(parser: &tl::Parser, node: &tl::Node) -> Option<usize> {
+    let words = search_dd(parser, node, "words")?;
+    Some(word_count_of(&words.inner_text(parser)))
+}
+
+/// Chapters posted so far and whether that's all of them
+pub(crate) fn parse_chapter_count(parser: &tl::Parser, node: &tl::Node) -> Option<(usize, bool)> {
+    chapter_count_of(&search_dd(parser, node, "chapters")?.inner_text(parser))
+}
+
+/// Read a number of words like "12,345"
+pub(crate) fn word_count_of(text: &str) -> usize {
+    text.replace(',', "").trim().parse().unwrap_or_default()
+}
+
+pub(crate) fn chapter_count_of(text: &str) -> Option<(usize, bool)> {
+    // "2/?" while the author doesn't know how many chapters there'll be
+    let chapters = text.replace(',', "");
+    let (posted, expected) = chapters.trim().split_once('/')?;
+    Some((
+        posted.trim().parse().unwrap_or_default(),
+        posted.trim() == expected.trim(),
+    ))
+}
+
+/// Parse the metadata of a work from its page (`/works/<id>`)
+///
+/// Fails when the page has no metadata or preface at all, other missing fields are left empty.
+pub fn parse_work(html_code: &str) -> Result<AO3Work, Error> {
+    let parsed = parse_work_with(html_code, ParseMode::Lenient)?;
+    // without either of them it isn't a work page
+    if let Some(missing) = parsed.missing.into_iter().find(|missing| {
+        let ParsingError::CouldNotFind(name) = missing;
+        [WORK_META.name, WORK_PREFACE.name].contains(name)
+    }) {
+        return Err(missing.into());
+    }
+    Ok(parsed.value)
+}
+
+/// [parse_work], failing on any missing field or noting which ones are missing depending on `mode`
+///
+/// The id of the work is needed in either mode.
+pub fn parse_work_with(html_code: &str, mode: ParseMode) -> Result<Parsed<AO3Work>, Error> {
+    let dom = tl::parse(html_code, tl::ParserOptions::new().track_classes())?;
+    let parser = dom.parser();
+    let mut lookup = Lookup::new(mode);
+    let meta = lookup.field(&WORK_META, dom.nodes())?;
+    let preface = lookup.field(&WORK_PREFACE, dom.nodes())?;
+    let preface_nodes = preface.map_or(&[][..], |preface| get_all_nodes(parser, preface));
+    let id = dom
+        .nodes()
+        .iter()
+        .filter_map(|n| n.as_tag()?.attributes().get("href")??.try_as_utf8_str())
+        .filter_map(|href| href.strip_prefix("/works/"))
+        .map(|rest| {
+            rest.split(|c: char| !c.is_ascii_digit())
+                .next()
+                .unwrap_or_default()
+        })
+        .find(|id| !id.is_empty())
+        .ok_or(ParsingError::CouldNotFind("the id of the work."))?
+        .to_string();
+    let dd_of = |class: &str| meta.and_then(|meta| search_dd(parser, meta, class));
+    let tags_in = |dd: &tl::Node| -> Vec<String> {
+        search_all_by_attrib(parser, dd, "class", "tag")
+            .map(|n| crate::convert::decode_entities(&n.inner_text(parser)).into_owned())
+            .collect()
+    };
+    let date_of = |class: &str| {
+        dd_of(class).and_then(|dd| {
+            chrono::NaiveDate::parse_from_str(dd.inner_text(parser).trim(), "%Y-%m-%d").ok()
+        })
+    };
+
+    let mut work = AO3Work::default();
+    work.url = format!("{BASE_AO3_URL}/works/{id}");
+    work.id = id;
+    work.title = lookup
+        .field(&WORK_TITLE, preface_nodes)?
+        .map(|n| crate::convert::decode_entities(n.inner_text(parser).trim()).into_owned())
+        .unwrap_or_default();
+    work.authors = lookup
+        .field(&WORK_BYLINE, preface_nodes)?
+        .map(|byline| {
+            search_all_by_attrib(parser, byline, "rel", "author")
+                .map(|n| n.inner_text(parser).to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    // not every work has a summary
+    work.summary = WORK_SUMMARY
+        .find(preface_nodes)
+        .and_then(|summary| search_by_class(parser, summary, "userstuff"))
+        .map(|n| n.inner_html(parser).trim().to_string())
+        .unwrap_or_default();
+    work.fandoms = lookup
+        .expect("the fandoms of the work.", dd_of("fandom"))?
+        .map(tags_in)
+        .unwrap_or_default();
+    work.relationships = dd_of("relationship").map(tags_in).unwrap_or_default();
+    work.characters = dd_of("character").map(tags_in).unwrap_or_default();
+    work.tags = dd_of("freeform").map(tags_in).unwrap_or_default();
+    work.warnings = dd_of("warning").map(tags_in).unwrap_or_default();
+    if let Some(rating) =
+        dd_of("rating").and_then(|dd| tags_in(dd).first().and_then(|name| Rating::from_name(name)))
+    {
+        work.set_rating(rating);
+    }
+    // "status" is the date of the last update, it's missing until there's a second chapter
+    work.date = lookup
+        .expect(
+            "the date of the work.",
+            date_of("status").or(date_of("published")),
+        )?
+        .unwrap_or_default();
+    let word_count = meta.and_then(|meta| parse_word_count(parser, meta));
+    work.word_count = lookup
+        .expect("the word count of the work.", word_count)?
+        .unwrap_or_default();
+    // works without kudos (or hits, ...) don't list them at all
+    let stat = |class: &str| dd_of(class).map_or(0, |dd| word_count_of(&dd.inner_text(parser)));
+    work.kudos = stat("kudos");
+    work.hits = stat("hits");
+    work.bookmarks = stat("bookmarks");
+    work.comments = stat("comments");
+    let chapter_count = meta.and_then(|meta| parse_chapter_count(parser, meta));
+    if let Some((chapter_count, is_complete)) =
+        lookup.expect("the chapter count of the work.", chapter_count)?
+    {
+        work.chapter_count = chapter_count;
+        work.is_complete = is_complete;
+    }
+    Ok(Parsed {
+        value: work,
+        missing: lookup.missing,
+    })
+}
+
+/// Parse a series page (`/series/<id>`), with the blurbs of its works
+pub fn parse_series(html_code: &str) -> Result<AO3Series, Error> {
+    let dom = tl::parse(
+        html_code,
+        tl::ParserOptions::new().track_classes().track_ids(),
+    )?;
+    let parser = dom.parser();
+    let meta = SERIES_META.require(dom.nodes())?;
+    let id = dom
+        .nodes()
+        .iter()
+        .filter_map(|n| n.as_tag()?.attributes().get("href")??.try_as_utf8_str())
+        .filter_map(|href| href.strip_prefix("/series/"))
+        .map(|rest| rest.split('/').next().unwrap_or_default().to_string())
+        .next()
+        .ok_or(ParsingError::CouldNotFind("the id of the series."))?;
+    let title = SERIES_TITLE
+        .find(dom.nodes())
+        .map(|n| crate::convert::decode_entities(n.inner_text(parser).trim()).into_owned())
+        .unwrap_or_default();
+    let creators = search_all_by_attrib(parser, meta, "rel", "author")
+        .map(|n| n.inner_text(parser).to_string())
+        .collect();
+    let description = search_by_class(parser, meta, "userstuff")
+        .map(|n| n.inner_html(parser).trim().to_string())
+        .unwrap_or_default();
+    let mut series = AO3Series {
+        id,
+        title,
+        creators,
+        description,
+        ..Default::default()
+    };
+    // the metadata is a list of "label: value" pairs without classes
+    let mut label = String::new();
+    for node in get_all_nodes(parser, meta) {
+        match node.as_tag().map(|t| t.name().as_utf8_str()) {
+            Some(name) if name == "dt" => label = node.inner_text(parser).trim().to_string(),
+            Some(name) if name == "dd" => {
+                let value = node.inner_text(parser);
+                match label.as_str() {
+                    "Series Begun:" | "Series Updated:" => {
+                        if let Ok(date) =
+                            chrono::NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+                        {
+                            series.date = date;
+                        }
+                    }
+                    "Complete:" => series.is_complete = value.trim() == "Yes",
+                    _ => {}
+                }
+            }
+            _ => {}
         }
-        nodes
-    } else {
-        vec![]
     }
+    if let Some(work_nodes) = dom.query_selector("[role=article]") {
+        for work_node in work_nodes.filter_map(|nh| nh.get(parser)) {
+            series
+                .works
+                .push(parse_search_single_work(parser, work_node)?);
+        }
+    }
+    Ok(series)
 }
 
-fn search_all_by_attrib<'a, 'b>(
-    parser: &'b tl::Parser<'b>,
-    node: &'b tl::Node,
-    attrib: &'a str,
-    value: &str,
-) -> Result<Vec<&'b tl::Node<'b>>, ParsingError<'a>> {
-    let a = get_all_nodes(parser, node);
-    Ok(a.into_iter()
-        .filter_map(|nh| {
-            if nh.as_tag()?.attributes().get(attrib)?? == value {
-                Some(nh)
-            } else {
-                None
+/// Find the `authenticity_token` AO3 puts in every form
+pub fn parse_authenticity_token(html_code: &str) -> Result<String, Error> {
+    let dom = tl::parse(html_code, tl::ParserOptions::default())?;
+    let parser = dom.parser();
+    let token = dom
+        .query_selector("input[name=authenticity_token]")
+        .and_then(|mut nodes| nodes.next())
+        .and_then(|nh| {
+            nh.get(parser)?
+                .as_tag()?
+                .attributes()
+                .get("value")??
+                .try_as_utf8_str()
+        })
+        .ok_or(ParsingError::CouldNotFind("the authenticity token."))?;
+    Ok(token.to_string())
+}
+
+pub fn parse_drafts(html_code: &str) -> Result<Vec<AO3Draft>, Error> {
+    let dom = tl::parse(
+        html_code,
+        tl::ParserOptions::new().track_classes().track_ids(),
+    )?;
+    let parser = dom.parser();
+    let draft_nodes = dom
+        .query_selector("[role=article]")
+        .ok_or(ParsingError::CouldNotFind("the list of drafts."))?;
+    let mut drafts = vec![];
+    for draft_node in draft_nodes {
+        let node = draft_node.get(parser).unwrap();
+        drafts.push(AO3Draft {
+            work: parse_search_single_work(parser, node)?,
+            expires: parse_draft_expiry(&node.inner_text(parser)),
+        });
+    }
+    Ok(drafts)
+}
+
+/// Drafts have a notice like "This draft will be scheduled for deletion on Wed 22 Mar 2023."
+fn parse_draft_expiry(text: &str) -> Option<chrono::NaiveDate> {
+    let (_, rest) = text.split_once("deletion on")?;
+    let date = rest.trim_start().split('.').next()?.trim();
+    chrono::NaiveDate::parse_from_str(date, "%a %d %b %Y")
+        .or_else(|_| chrono::NaiveDate::parse_from_str(date, "%d %b %Y"))
+        .ok()
+}
+
+/// Get the chapter contents of a work page (or a draft's preview page) as html
+pub fn parse_work_preview(html_code: &str) -> Result<String, Error> {
+    let dom = tl::parse(html_code, tl::ParserOptions::new().track_ids())?;
+    let parser = dom.parser();
+    let chapters = WORK_CHAPTERS.require(dom.nodes())?;
+    Ok(chapters.inner_html(parser).trim().to_string())
+}
+
+/// Get every chapter of a work page, in order
+///
+/// The work has to be fetched with `view_full_work=true` to get all of them.
+/// Works with a single chapter have no chapter title or id on their page.
+pub fn parse_chapters(html_code: &str) -> Result<Vec<AO3Chapter>, Error> {
+    let dom = tl::parse(html_code, tl::ParserOptions::new().track_ids())?;
+    let parser = dom.parser();
+    let chapters_node = WORK_CHAPTERS.require(dom.nodes())?;
+    let mut chapters = vec![];
+    let mut heading = AO3Chapter::default();
+    // the title of a chapter always comes before its text
+    for node in get_all_nodes(parser, chapters_node) {
+        let Some(tag) = node.as_tag() else {
+            continue;
+        };
+        if tag.name() == "h3" && tag.attributes().is_class_member("title") {
+            heading.title = node
+                .inner_text(parser)
+                .split_whitespace()
+                .collect::<Vec<&str>>()
+                .join(" ");
+            heading.id = search_by_attrib_prefix(parser, node, "href", "/works/")
+                .and_then(|a| a.as_tag()?.attributes().get("href")??.try_as_utf8_str())
+                .and_then(|href| Some(href.split_once("/chapters/")?.1.to_string()))
+                .unwrap_or_default();
+        }
+        // chapter notes are blockquotes, the text itself is always in a div
+        if tag.name() == "div" && tag.attributes().is_class_member("userstuff") {
+            let text = tag
+                .children()
+                .top()
+                .iter()
+                .filter_map(|nh| nh.get(parser))
+                .filter(|n| {
+                    !n.as_tag()
+                        .is_some_and(|t| t.attributes().is_class_member("landmark"))
+                })
+                .map(|n| n.outer_html(parser))
+                .collect::<String>()
+                .trim()
+                .to_string();
+            chapters.push(AO3Chapter {
+                text,
+                ..std::mem::take(&mut heading)
+            });
+        }
+    }
+    Ok(chapters)
+}
+
+/// Collect the messages of the error banners on a page
+///
+/// AO3 shows form validation errors in `div#error` as a list and other failures in `div.flash.error`
+pub fn parse_form_errors(html_code: &str) -> Result<Vec<String>, Error> {
+    let dom = tl::parse(
+        html_code,
+        tl::ParserOptions::new().track_classes().track_ids(),
+    )?;
+    let parser = dom.parser();
+    let mut errors = vec![];
+    if let Some(error_node) = dom.get_element_by_id("error").and_then(|nh| nh.get(parser)) {
+        let items: Vec<String> = error_node
+            .children()
+            .map_or(&[][..], |ch| ch.all(parser))
+            .iter()
+            .filter(|n| n.as_tag().is_some_and(|t| t.name() == "li"))
+            .map(|n| n.inner_text(parser).trim().to_string())
+            .collect();
+        if items.is_empty() {
+            errors.push(error_node.inner_text(parser).trim().to_string());
+        } else {
+            errors.extend(items);
+        }
+    }
+    errors.extend(
+        dom.get_elements_by_class_name("flash")
+            .filter_map(|nh| nh.get(parser))
+            .filter(|n| {
+                n.as_tag()
+                    .is_some_and(|t| t.attributes().is_class_member("error"))
+            })
+            .map(|n| n.inner_text(parser).trim().to_string()),
+    );
+    Ok(errors)
+}
+
+/// Parse the page AO3 shows after submitting the import form
+///
+/// `url` is the url of the page, since importing a single work redirects straight to it
+pub fn parse_import_report(html_code: &str, url: &str) -> Result<AO3ImportReport, Error> {
+    let dom = tl::parse(
+        html_code,
+        tl::ParserOptions::new().track_classes().track_ids(),
+    )?;
+    let parser = dom.parser();
+    let mut report = AO3ImportReport::default();
+
+    if let Some(id) = url
+        .split("/works/")
+        .nth(1)
+        .and_then(|rest| rest.split(['/', '?']).next())
+        .filter(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()))
+    {
+        report.imported.push(id.to_string());
+    } else if let Some(work_nodes) = dom.query_selector("[role=article]") {
+        for work_node in work_nodes {
+            if let Some(id) = work_node
+                .get(parser)
+                .and_then(|n| n.as_tag()?.attributes().id()?.try_as_utf8_str())
+            {
+                report.imported.push(id.replace("work_", ""));
             }
+        }
+    }
+
+    let error_nodes = dom
+        .get_elements_by_class_name("flash")
+        .filter_map(|nh| nh.get(parser))
+        .filter(|n| {
+            n.as_tag()
+                .is_some_and(|t| t.attributes().is_class_member("error"))
         })
+        .chain(dom.get_element_by_id("error").and_then(|nh| nh.get(parser)));
+    for error_node in error_nodes {
+        // failed imports of multiple works are listed as <dt>url</dt><dd>reason</dd>
+        let failed_before = report.failed.len();
+        let mut url = None;
+        for n in error_node.children().map_or(&[][..], |ch| ch.all(parser)) {
+            match n.as_tag().map(|t| t.name().as_bytes()) {
+                Some(b"dt") => url = Some(n.inner_text(parser).trim().to_string()),
+                Some(b"dd") => report.failed.push((
+                    url.take().unwrap_or_default(),
+                    n.inner_text(parser).trim().to_string(),
+                )),
+                _ => {}
+            }
+        }
+        if report.failed.len() == failed_before {
+            report.failed.push((
+                String::new(),
+                error_node.inner_text(parser).trim().to_string(),
+            ));
+        }
+    }
+    Ok(report)
+}
+
+/// Parse the assignments page of a user (`/users/<name>/assignments`)
+pub fn parse_assignments(html_code: &str) -> Result<Vec<AO3Assignment>, Error> {
+    let dom = tl::parse(html_code, tl::ParserOptions::default())?;
+    let parser = dom.parser();
+    let mut assignments = vec![];
+    let mut current: Option<AO3Assignment> = None;
+    // every assignment starts with a link to its collection, followed by the recipient
+    // and the actions, one of which carries the assignment's id
+    for tag in dom.nodes().iter().filter_map(|n| n.as_tag()) {
+        if tag.name() != "a" {
+            continue;
+        }
+        let Some(href) = tag
+            .attributes()
+            .get("href")
+            .flatten()
+            .and_then(|h| h.try_as_utf8_str())
+        else {
+            continue;
+        };
+        let segments: Vec<&str> = href.trim_start_matches('/').split('/').collect();
+        match segments.as_slice() {
+            ["collections", collection] => {
+                current = Some(AO3Assignment {
+                    collection: collection.to_string(),
+                    collection_title: tag.inner_text(parser).trim().to_string(),
+                    ..Default::default()
+                })
+            }
+            ["users", ..] => {
+                if let Some(assignment) = current.as_mut().filter(|a| a.recipient.is_none()) {
+                    assignment.recipient = Some(tag.inner_text(parser).trim().to_string());
+                }
+            }
+            _ => {
+                if let Some((_, id)) = href.split_once("assignment_id=") {
+                    if let Some(mut assignment) = current.take() {
+                        assignment.id = id.split('&').next().unwrap_or_default().to_string();
+                        assignments.push(assignment);
+                    }
+                }
+            }
+        }
+    }
+    Ok(assignments)
+}
+
+/// Parse the requests shown on the page of a single assignment
+pub fn parse_assignment_requests(html_code: &str) -> Result<Vec<AO3AssignmentRequest>, Error> {
+    let dom = tl::parse(html_code, tl::ParserOptions::new().track_classes())?;
+    let parser = dom.parser();
+    Ok(dom
+        .get_elements_by_class_name("request")
+        .filter_map(|nh| nh.get(parser))
+        .map(|request_node| parse_request_blurb(parser, request_node))
         .collect())
 }
 
-fn parse_search_single_work(
+/// Requests of exchanges and prompt memes are shown the same way
+fn parse_request_blurb(parser: &tl::Parser, request_node: &tl::Node) -> AO3AssignmentRequest {
+    let fandoms = search_by_attrib(parser, request_node, "class", "fandoms heading")
+        .map(|fandoms_node| {
+            search_all_by_attrib(parser, fandoms_node, "class", "tag")
+                .map(|n| n.inner_text(parser).to_string())
+                .collect::<Vec<String>>()
+        })
+        .unwrap_or_default();
+    let tags = search_all_by_attrib(parser, request_node, "class", "tag")
+        .map(|n| n.inner_text(parser).to_string())
+        .filter(|t| !fandoms.contains(t))
+        .collect();
+    let description = search_by_attrib(parser, request_node, "class", "userstuff")
+        .map(|n| n.inner_text(parser).trim().to_string())
+        .unwrap_or_default();
+    AO3AssignmentRequest {
+        fandoms,
+        tags,
+        description,
+    }
+}
+
+/// Parse the prompts of a prompt meme (`/collections/<name>/requests`)
+pub fn parse_prompts(html_code: &str) -> Result<Vec<AO3Prompt>, Error> {
+    let dom = tl::parse(html_code, tl::ParserOptions::new().track_classes())?;
+    let parser = dom.parser();
+    let mut prompts = vec![];
+    for prompt_node in dom
+        .get_elements_by_class_name("request")
+        .filter_map(|nh| nh.get(parser))
+    {
+        // the heading links to the prompt: /collections/<name>/prompts/<id>
+        let Some((collection, id, title)) = get_all_nodes(parser, prompt_node)
+            .iter()
+            .filter_map(|n| {
+                let href = n.as_tag()?.attributes().get("href")??.try_as_utf8_str()?;
+                match href
+                    .trim_start_matches('/')
+                    .split('/')
+                    .collect::<Vec<&str>>()[..]
+                {
+                    ["collections", collection, "prompts", id] => Some((
+                        collection.to_string(),
+                        id.to_string(),
+                        n.inner_text(parser).trim().to_string(),
+                    )),
+                    _ => None,
+                }
+            })
+            .next()
+        else {
+            continue;
+        };
+        let request = parse_request_blurb(parser, prompt_node);
+        prompts.push(AO3Prompt {
+            id,
+            collection,
+            title,
+            fandoms: request.fandoms,
+            tags: request.tags,
+            description: request.description,
+        });
+    }
+    Ok(prompts)
+}
+
+/// Parse the claims page of a user (`/users/<name>/claims`)
+pub fn parse_claims(html_code: &str) -> Result<Vec<AO3Claim>, Error> {
+    let dom = tl::parse(html_code, tl::ParserOptions::default())?;
+    let parser = dom.parser();
+    let mut claims: Vec<AO3Claim> = vec![];
+    // each claim links to its prompt first, then to the claim itself
+    let mut prompt = None;
+    for tag in dom.nodes().iter().filter_map(|n| n.as_tag()) {
+        let Some(href) = tag
+            .attributes()
+            .get("href")
+            .flatten()
+            .and_then(|h| h.try_as_utf8_str())
+        else {
+            continue;
+        };
+        match href
+            .trim_start_matches('/')
+            .split('/')
+            .collect::<Vec<&str>>()[..]
+        {
+            ["collections", _, "prompts", prompt_id] => {
+                prompt = Some((
+                    prompt_id.to_string(),
+                    tag.inner_text(parser).trim().to_string(),
+                ));
+            }
+            ["collections", collection, "claims", id] if !claims.iter().any(|c| c.id == id) => {
+                let (prompt_id, prompt_title) = prompt.take().unwrap_or_default();
+                claims.push(AO3Claim {
+                    id: id.to_string(),
+                    collection: collection.to_string(),
+                    prompt_id,
+                    prompt_title,
+                })
+            }
+            _ => {}
+        }
+    }
+    Ok(claims)
+}
+
+/// Parse the comments on a work page (or a comment's thread page `/comments/<id>`)
+pub fn parse_comments(html_code: &str) -> Result<Vec<AO3Comment>, Error> {
+    let dom = tl::parse(
+        html_code,
+        tl::ParserOptions::new().track_classes().track_ids(),
+    )?;
+    let parser = dom.parser();
+    // threads are nested in each other, the one that comes first holds all the others
+    let Some(thread_node) = dom
+        .get_elements_by_class_name("thread")
+        .min_by_key(|nh| nh.get_inner())
+        .and_then(|nh| nh.get(parser))
+    else {
+        // works without comments don't have a thread
+        return Ok(vec![]);
+    };
+    let mut cut_off = vec![];
+    let mut comments = parse_comment_thread(parser, thread_node, &mut cut_off);
+    for id in cut_off {
+        mark_more_replies(&mut comments, &id);
+    }
+    Ok(comments)
+}
+
+fn mark_more_replies(comments: &mut [AO3Comment], id: &str) {
+    for comment in comments {
+        if comment.id == id {
+            comment.has_more_replies = true;
+        }
+        mark_more_replies(&mut comment.replies, id);
+    }
+}
+
+fn parse_comment_thread(
     parser: &tl::Parser,
+    thread_node: &tl::Node,
+    cut_off: &mut Vec<String>,
+) -> Vec<AO3Comment> {
+    let mut comments: Vec<AO3Comment> = vec![];
+    let items = thread_node
+        .children()
+        .map(|ch| ch.top().to_vec())
+        .unwrap_or_default();
+    for item in items.iter().filter_map(|nh| nh.get(parser)) {
+        let Some(tag) = item.as_tag().filter(|t| t.name() == "li") else {
+            continue;
+        };
+        if tag.attributes().is_class_member("comment") {
+            comments.push(parse_single_comment(parser, item));
+            continue;
+        }
+        // replies to a comment are in a thread of their own right after it
+        let nested_thread = tag
+            .children()
+            .top()
+            .iter()
+            .filter_map(|nh| nh.get(parser))
+            .find(|n| {
+                n.as_tag()
+                    .is_some_and(|t| t.attributes().is_class_member("thread"))
+            });
+        if let Some(nested_thread) = nested_thread {
+            let replies = parse_comment_thread(parser, nested_thread, cut_off);
+            match comments.last_mut() {
+                Some(parent) => parent.replies.extend(replies),
+                None => comments.extend(replies),
+            }
+        } else if item.inner_text(parser).contains("more comment") {
+            if let Some(id) = get_all_nodes(parser, item)
+                .iter()
+                .filter_map(|n| n.as_tag()?.attributes().get("href")??.try_as_utf8_str())
+                .find_map(|href| href.strip_prefix("/comments/"))
+            {
+                cut_off.push(id.to_string());
+            }
+        }
+    }
+    comments
+}
+
+fn parse_single_comment(parser: &tl::Parser, node: &tl::Node) -> AO3Comment {
+    let mut comment = AO3Comment {
+        id: node
+            .as_tag()
+            .and_then(|t| t.attributes().id()?.try_as_utf8_str())
+            .unwrap_or_default()
+            .replace("comment_", ""),
+        ..Default::default()
+    };
+    if let Ok(byline) = search_by_attrib(parser, node, "class", "heading byline") {
+        // guests don't have a link to their pseud, just their name
+        comment.author = get_all_nodes(parser, byline)
+            .iter()
+            .find(|n| {
+                n.as_tag()
+                    .is_some_and(|t| t.name() == "a" || t.name() == "span")
+            })
+            .map(|n| n.inner_text(parser).trim().to_string())
+            .unwrap_or_default();
+    }
+    comment.chapter = search_by_attrib(parser, node, "class", "parent")
+        .ok()
+        .and_then(|n| {
+            n.inner_text(parser)
+                .trim()
+                .strip_prefix("on Chapter ")?
+                .parse()
+                .ok()
+        });
+    comment.date = search_by_attrib(parser, node, "class", "posted datetime")
+        .ok()
+        .and_then(|n| parse_posted_datetime(&n.inner_text(parser)));
+    comment.body = search_by_attrib(parser, node, "class", "userstuff")
+        .map(|n| n.inner_html(parser).trim().to_string())
+        .unwrap_or_default();
+    comment
+}
+
+/// Dates of comments look like "Wed 15 Feb 2023 09:32PM UTC"
+fn parse_posted_datetime(text: &str) -> Option<chrono::NaiveDateTime> {
+    let text = text.split_whitespace().collect::<Vec<&str>>();
+    let without_timezone = text.get(..5)?.join(" ");
+    chrono::NaiveDateTime::parse_from_str(&without_timezone, "%a %d %b %Y %I:%M%p").ok()
+}
+
+/// Parse the chapter index of a work (`/works/<id>/navigate`), returns the ids of the chapters in order
+pub fn parse_chapter_ids(html_code: &str) -> Result<Vec<String>, Error> {
+    let dom = tl::parse(html_code, tl::ParserOptions::default())?;
+    let chapter_ids = dom
+        .nodes()
+        .iter()
+        .filter_map(|n| n.as_tag()?.attributes().get("href")??.try_as_utf8_str())
+        .filter_map(|href| Some(href.split_once("/chapters/")?.1.to_string()))
+        .collect::<Vec<String>>();
+    if chapter_ids.is_empty() {
+        return Err(ParsingError::CouldNotFind("the chapters of the work.").into());
+    }
+    Ok(chapter_ids)
+}
+
+/// Parse the bookmarks of a work (`/works/<id>/bookmarks`) or of a user (`/users/<name>/bookmarks`)
+pub fn parse_work_bookmarks(html_code: &str) -> Result<Vec<AO3Bookmark>, Error> {
+    let dom = tl::parse(
+        html_code,
+        tl::ParserOptions::new().track_classes().track_ids(),
+    )?;
+    let parser = dom.parser();
+    let Some(bookmark_nodes) = dom.query_selector("[role=article]") else {
+        return Ok(vec![]);
+    };
+    // on a work's bookmarks page only the heading links to the work
+    let page_work_id = dom
+        .nodes()
+        .iter()
+        .find_map(|n| work_id_of_link(n.as_tag()?.attributes().get("href")??.try_as_utf8_str()?));
+    let mut bookmarks = vec![];
+    for bookmark_node in bookmark_nodes.filter_map(|nh| nh.get(parser)) {
+        let id = bookmark_node
+            .as_tag()
+            .and_then(|t| t.attributes().id()?.try_as_utf8_str())
+            .ok_or(ParsingError::CouldNotFind("the id of the bookmark."))?
+            .replace("bookmark_", "");
+        let work_id = get_all_nodes(parser, bookmark_node)
+            .iter()
+            .find_map(|n| {
+                work_id_of_link(n.as_tag()?.attributes().get("href")??.try_as_utf8_str()?)
+            })
+            .or(page_work_id.clone())
+            .unwrap_or_default();
+        // a user's bookmarks show the work's blurb first, the bookmark itself is in its own module
+        let bookmark_node = search_by_class(parser, bookmark_node, "user").unwrap_or(bookmark_node);
+        let bookmarker = search_by_class(parser, bookmark_node, "byline")
+            .and_then(|byline| search_by_attrib_prefix(parser, byline, "href", "/users/"))
+            .map(|n| n.inner_text(parser).trim().to_string())
+            .unwrap_or_default();
+        let date = search_by_class(parser, bookmark_node, "datetime").and_then(|n| {
+            chrono::NaiveDate::parse_from_str(n.inner_text(parser).trim(), "%d %b %Y").ok()
+        });
+        let tags = search_by_class(parser, bookmark_node, "meta")
+            .map(|meta| {
+                search_all_by_attrib(parser, meta, "class", "tag")
+                    .map(|n| n.inner_text(parser).to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let notes = search_by_class(parser, bookmark_node, "notes")
+            .map(|n| n.inner_html(parser).trim().to_string())
+            .unwrap_or_default();
+        bookmarks.push(AO3Bookmark {
+            id,
+            work_id,
+            bookmarker,
+            date,
+            tags,
+            notes,
+            is_rec: search_by_class(parser, bookmark_node, "rec").is_some(),
+        });
+    }
+    Ok(bookmarks)
+}
+
+/// Find the pseud a new bookmark is made with, on the form at `/works/<id>/bookmarks/new`
+///
+/// Users with one pseud get a hidden field, the others a select with their default pseud selected.
+pub fn parse_bookmark_pseud_id(html_code: &str) -> Result<String, Error> {
+    parse_selected_value(html_code, "bookmark[pseud_id]")
+        .ok_or(ParsingError::CouldNotFind("the pseud of the bookmark.").into())
+}
+
+/// Find the pseud a new work is posted with, on the form at `/works/new`
+pub fn parse_work_pseud_id(html_code: &str) -> Result<String, Error> {
+    parse_selected_value(html_code, "work[author_attributes][ids][]")
+        .ok_or(ParsingError::CouldNotFind("the pseud of the work.").into())
+}
+
+/// Find the id the form at `/works/new` gives the language with the code `code`, like `en`
+pub fn parse_work_language_id(html_code: &str, code: &str) -> Result<String, Error> {
+    let dom = tl::parse(html_code, tl::ParserOptions::default())?;
+    dom.nodes()
+        .iter()
+        .filter_map(|n| n.as_tag())
+        .filter(|t| t.name() == "option")
+        .find(|t| {
+            t.attributes()
+                .get("lang")
+                .flatten()
+                .is_some_and(|lang| lang == code)
+        })
+        .and_then(|t| t.attributes().get("value")??.try_as_utf8_str())
+        .map(str::to_string)
+        .ok_or(ParsingError::CouldNotFind("the language of the work.").into())
+}
+
+/// Value of the form field `name`, a hidden input or a select with the default option selected
+fn parse_selected_value(html_code: &str, name: &str) -> Option<String> {
+    let dom = tl::parse(html_code, tl::ParserOptions::default()).ok()?;
+    let parser = dom.parser();
+    let field = dom.nodes().iter().filter_map(|n| n.as_tag()).find(|t| {
+        t.attributes()
+            .get("name")
+            .flatten()
+            .is_some_and(|field| field == name)
+    })?;
+    let value_of = |tag: &tl::HTMLTag| {
+        tag.attributes()
+            .get("value")
+            .flatten()
+            .and_then(|v| v.try_as_utf8_str())
+            .map(str::to_string)
+    };
+    if field.name() != "select" {
+        return value_of(field);
+    }
+    let options: Vec<&tl::HTMLTag> = field
+        .children()
+        .all(parser)
+        .iter()
+        .filter_map(|n| n.as_tag())
+        .filter(|t| t.name() == "option")
+        .collect();
+    options
+        .iter()
+        .find(|t| t.attributes().contains("selected"))
+        .or(options.first())
+        .and_then(|t| value_of(t))
+}
+
+/// Id of the work a link like `/works/<id>` or `/works/<id>/chapters/<id>` goes to
+fn work_id_of_link(href: &str) -> Option<String> {
+    let id: String = href
+        .strip_prefix("/works/")?
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    (!id.is_empty()).then_some(id)
+}
+
+/// Parse the subscriptions of a user (`/users/<name>/subscriptions`)
+pub fn parse_subscriptions(html_code: &str) -> Result<Vec<AO3Subscription>, Error> {
+    let dom = tl::parse(html_code, tl::ParserOptions::new().track_classes())?;
+    let parser = dom.parser();
+    let Some(list) = dom
+        .get_elements_by_class_name("subscription")
+        .filter_map(|nh| nh.get(parser))
+        .find(|n| n.as_tag().is_some_and(|t| t.name() == "dl"))
+    else {
+        return Ok(vec![]);
+    };
+    let mut subscriptions = vec![];
+    let mut subscription = None;
+    for node in list
+        .children()
+        .map(|c| c.top().to_vec())
+        .unwrap_or_default()
+    {
+        let Some(tag) = node.get(parser).and_then(|n| n.as_tag()) else {
+            continue;
+        };
+        if tag.name() == "dt" {
+            // the first link is what's subscribed to, the others are its creators
+            let link = tag
+                .query_selector(parser, "a")
+                .and_then(|mut links| links.next())
+                .and_then(|nh| nh.get(parser))
+                .ok_or(ParsingError::CouldNotFind("what the subscription is to."))?;
+            let href = link
+                .as_tag()
+                .and_then(|t| t.attributes().get("href")??.try_as_utf8_str())
+                .unwrap_or_default();
+            let (kind, target) = if let Some(id) = href.strip_prefix("/works/") {
+                (SubscriptionKind::Work, id)
+            } else if let Some(id) = href.strip_prefix("/series/") {
+                (SubscriptionKind::Series, id)
+            } else {
+                (SubscriptionKind::User, href.trim_start_matches("/users/"))
+            };
+            subscription = Some(AO3Subscription {
+                kind,
+                target: target.split('/').next().unwrap_or_default().to_string(),
+                name: crate::convert::decode_entities(link.inner_text(parser).trim()).into_owned(),
+                ..Default::default()
+            });
+        } else if tag.name() == "dd" {
+            // the unsubscribe form posts to the subscription
+            let Some(mut subscription) = subscription.take() else {
+                continue;
+            };
+            subscription.id = tag
+                .query_selector(parser, "form")
+                .and_then(|mut forms| forms.next())
+                .and_then(|nh| {
+                    nh.get(parser)?
+                        .as_tag()?
+                        .attributes()
+                        .get("action")??
+                        .try_as_utf8_str()
+                })
+                .and_then(|action| action.rsplit_once("/subscriptions/"))
+                .map(|(_, id)| id.to_string())
+                .unwrap_or_default();
+            subscriptions.push(subscription);
+        }
+    }
+    Ok(subscriptions)
+}
+
+fn search_by_attrib_prefix<'b>(
+    parser: &'b tl::Parser<'b>,
     node: &tl::Node,
-) -> Result<AO3Work, Box<dyn std::error::Error>> {
-    let id = node
-        .as_tag()
-        .unwrap()
-        .attributes()
-        .id()
-        .unwrap()
-        .as_utf8_str()
-        .to_string()
-        .replace("work_", "");
-
-    let title = search_by_attrib(parser, node, "href", &format!("/works/{id}"))?
-        .inner_text(parser)
-        .to_string();
+    attrib: &str,
+    prefix: &str,
+) -> Option<&'b tl::Node<'b>> {
+    node.find_node(parser, &mut |n| {
+        n.as_tag().is_some_and(|t| {
+            t.attributes()
+                .get(attrib)
+                .flatten()
+                .and_then(|a| a.try_as_utf8_str())
+                .is_some_and(|a| a.starts_with(prefix))
+        })
+    })?
+    .get(parser)
+}
 
-    let authors = search_all_by_attrib(
-        parser,
-        search_by_attrib(parser, node, "class", "fandoms heading")?,
-        "rel",
-        "author",
-    )?
-    .into_iter()
-    .map(|ch| ch.inner_text(parser).to_string())
-    .collect();
-
-    let fandoms = search_all_by_attrib(
-        parser,
-        search_by_attrib(parser, node, "class", "fandoms heading")?,
-        "class",
-        "tag",
-    )?
-    .into_iter()
-    .map(|ch| ch.inner_text(parser).to_string())
-    .collect();
+/// Fail on pages that are served in place of AO3's: a Cloudflare challenge or the maintenance page
+///
+/// Without this they'd only fail later, when the parser can't find what it's looking for.
+pub fn check_unavailable(html_code: &str) -> Result<(), Error> {
+    let title = page_title(html_code).unwrap_or_default().to_lowercase();
+    // works can quote all of this, so only the challenge page's own title and scripts count
+    let head = &html_code[..html_code.find("</head>").unwrap_or(0)];
+    let challenge_script =
+        |html: &str| html.contains("cf_chl_opt") || html.contains("challenges.cloudflare.com");
+    if challenge_script(head) {
+        return Err(Error::Challenge);
+    }
+    let ao3_page = html_code.contains("<div id=\"outer\"") || title.ends_with("archive of our own");
+    let challenge_title =
+        title.starts_with("just a moment") || title.starts_with("attention required");
+    if !ao3_page
+        && (challenge_title
+            || challenge_script(html_code)
+            || html_code.contains("id=\"challenge-form\""))
+    {
+        return Err(Error::Challenge);
+    }
+    // AO3's own pages end their titles with the name of the site, a work can be called "Maintenance"
+    if title.contains("maintenance") && !title.contains("[archive of our own]") {
+        return Err(Error::Maintenance);
+    }
+    Ok(())
+}
 
-    let mut work = AO3Work::default();
-    work.id = id;
-    work.title = title;
-    work.authors = authors;
-    work.fandoms = fandoms;
-    Ok(work)
+/// Fail on search results AO3 serves when its search backend has trouble
+///
+/// That's either an error banner about the search, or a results page that doesn't even say
+/// how many works were found. A search that found nothing says "0 Found" or "No results found".
+pub fn check_search_unavailable(html_code: &str) -> Result<(), Error> {
+    let dom = tl::parse(html_code, tl::ParserOptions::new().track_classes())?;
+    let parser = dom.parser();
+    let has_search_error = dom
+        .get_elements_by_class_name("flash")
+        .filter_map(|nh| nh.get(parser))
+        .filter(|n| {
+            n.as_tag()
+                .is_some_and(|t| t.attributes().is_class_member("error"))
+        })
+        .any(|n| n.inner_text(parser).to_lowercase().contains("search"));
+    if has_search_error {
+        return Err(Error::SearchUnavailable);
+    }
+    let has_count = dom
+        .get_elements_by_class_name("heading")
+        .filter_map(|nh| nh.get(parser))
+        .any(|n| n.inner_text(parser).contains(" Found"));
+    if !has_count && !html_code.contains("No results found") {
+        return Err(Error::SearchUnavailable);
+    }
+    Ok(())
+}
+
+fn page_title(html_code: &str) -> Option<&str> {
+    let start = html_code.find("<title>")? + "<title>".len();
+    let end = html_code[start..].find("</title>")?;
+    Some(html_code[start..start + end].trim())
+}
+
+/// Get the number of pages from the pagination of a page, 1 if there is no pagination
+pub fn parse_page_count(html_code: &str) -> Result<usize, Error> {
+    let dom = tl::parse(html_code, tl::ParserOptions::new().track_classes())?;
+    let parser = dom.parser();
+    let page_count = dom
+        .get_elements_by_class_name("pagination")
+        .filter_map(|nh| nh.get(parser))
+        .flat_map(|n| get_all_nodes(parser, n))
+        .filter_map(|n| n.as_raw()?.as_utf8_str().trim().parse::<usize>().ok())
+        .max()
+        .unwrap_or(1);
+    Ok(page_count)
+}
+
+/// Get the number of results a search or listing says it found ("1,234 Found"),
+/// None when the page doesn't say
+pub fn parse_results_count(html_code: &str) -> Result<Option<usize>, Error> {
+    let dom = tl::parse(html_code, tl::ParserOptions::default())?;
+    let parser = dom.parser();
+    let count = dom
+        .nodes()
+        .iter()
+        .filter(|n| {
+            n.as_tag()
+                .is_some_and(|t| t.attributes().is_class_member("heading"))
+        })
+        .find_map(|n| {
+            let text = n.inner_text(parser);
+            let (count, _) = text.split_once(" Found")?;
+            count
+                .split_whitespace()
+                .last()?
+                .replace(',', "")
+                .parse()
+                .ok()
+        });
+    Ok(count)
+}
+
+/// Find the download link of a work page for `format`, returns its path
+///
+/// The link carries an `updated_at` token, AO3 regenerates the file when it doesn't match.
+pub fn parse_download_path(html_code: &str, format: &DownloadFormat) -> Result<String, Error> {
+    let dom = tl::parse(html_code, tl::ParserOptions::default())?;
+    let extension = format!(".{}", format.extension());
+    dom.nodes()
+        .iter()
+        .filter_map(|n| n.as_tag()?.attributes().get("href")??.try_as_utf8_str())
+        .filter(|href| href.starts_with("/downloads/"))
+        .find(|href| {
+            href.split('?')
+                .next()
+                .is_some_and(|path| path.ends_with(&extension))
+        })
+        .map(|href| crate::convert::decode_entities(href).into_owned())
+        .ok_or_else(|| ParsingError::CouldNotFind("the download link of the work.").into())
+}
+
+/// Parse a tag's page (`/tags/<name>`), for what it's a synonym of or what its synonyms are
+pub fn parse_tag(html_code: &str) -> Result<AO3Tag, Error> {
+    let dom = tl::parse(html_code, tl::ParserOptions::default())?;
+    let parser = dom.parser();
+    let name = TAG_NAME.require(dom.nodes())?;
+    let tags_in = |class: &str| -> Vec<String> {
+        dom.nodes()
+            .iter()
+            .filter(|n| {
+                n.as_tag()
+                    .is_some_and(|t| t.attributes().is_class_member(class))
+            })
+            .flat_map(|n| search_all_by_attrib(parser, n, "class", "tag"))
+            .map(|n| crate::convert::decode_entities(n.inner_text(parser).trim()).into_owned())
+            .collect()
+    };
+    Ok(AO3Tag {
+        name: crate::convert::decode_entities(name.inner_text(parser).trim()).into_owned(),
+        is_canonical: dom
+            .query_selector("p")
+            .into_iter()
+            .flatten()
+            .filter_map(|p| p.get(parser))
+            .any(|p| {
+                crate::convert::decode_entities(&p.inner_text(parser))
+                    .contains("It's a canonical tag")
+            }),
+        canonical: tags_in("merger").into_iter().next(),
+        synonyms: tags_in("synonym"),
+    })
+}
+
+/// Find the id of a tag from one of its pages, in the link to the tag's feed
+///
+/// Only canonical tags have a feed, the pages of synonyms link to the canonical tag's one.
+pub fn parse_tag_id(html_code: &str) -> Result<String, Error> {
+    let dom = tl::parse(html_code, tl::ParserOptions::default())?;
+    dom.nodes()
+        .iter()
+        .filter_map(|n| n.as_tag()?.attributes().get("href")??.try_as_utf8_str())
+        .filter_map(|href| href.strip_prefix("/tags/")?.strip_suffix("/feed.atom"))
+        .find(|id| !id.is_empty() && id.bytes().all(|b| b.is_ascii_digit()))
+        .map(str::to_string)
+        .ok_or_else(|| ParsingError::CouldNotFind("the id of the tag.").into())
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::parse::parse_search;
+    use super::*;
 
     #[test]
     fn test_query_builder() {
         let html = include_str!("parse_test/search.html");
         println!("{:#?}", parse_search(html));
     }
+
+    #[test]
+    fn test_parse_search() {
+        let works = parse_search(include_str!("parse_test/search.html")).unwrap();
+        assert_eq!(works[0].id, "45221314");
+        assert_eq!(works[0].url, "https://archiveofourown.org/works/45221314");
+        assert_eq!(
+            works[0].date,
+            chrono::NaiveDate::from_ymd_opt(2023, 2, 21).unwrap()
+        );
+        assert_eq!(works[0].tags[2], "Religious Imagery & Symbolism");
+        assert_eq!(
+            works[2].relationships,
+            vec![
+                "Will Graham/Hannibal Lecter",
+                "Will Graham & Hannibal Lecter"
+            ]
+        );
+        assert_eq!(works[2].characters[0], "Will Graham");
+        assert_eq!(works[0].warnings, vec!["Graphic Depictions Of Violence"]);
+        assert_eq!(works[0].get_rating(), Rating::TeenAndUp);
+        assert!(works[0].summary.starts_with("<p>In the wake of"));
+        assert_eq!(works[0].authors, vec!["Starchild719"]);
+        assert_eq!(works[0].word_count, 842);
+        assert_eq!(works[0].chapter_count, 1);
+        assert!(works[0].is_complete);
+        assert_eq!(works[1].chapter_count, 1);
+        assert!(!works[1].is_complete);
+        assert_eq!(works[2].chapter_count, 2);
+    }
+
+    #[test]
+    fn test_parse_modes() {
+        let html = include_str!("parse_test/work.html");
+        let strict = parse_work_with(html, ParseMode::Strict).unwrap();
+        assert!(strict.missing.is_empty());
+        assert_eq!(strict.value, parse_work(html).unwrap());
+
+        // a layout change that drops the stats
+        let without_stats = html.replace("class=\"words\"", "class=\"length\"");
+        assert!(matches!(
+            parse_work_with(&without_stats, ParseMode::Strict),
+            Err(Error::Parse {
+                source: ParsingError::CouldNotFind("the word count of the work."),
+                ..
+            })
+        ));
+        let lenient = parse_work_with(&without_stats, ParseMode::Lenient).unwrap();
+        assert_eq!(lenient.value.word_count, 0);
+        assert_eq!(lenient.value.title, strict.value.title);
+        assert!(matches!(
+            lenient.missing.as_slice(),
+            [ParsingError::CouldNotFind("the word count of the work.")]
+        ));
+
+        let search = include_str!("parse_test/search.html");
+        let broken = search.replacen("class=\"fandoms heading\"", "class=\"fandoms\"", 1);
+        assert!(parse_search_with(&broken, ParseMode::Strict).is_err());
+        let lenient = parse_search_with(&broken, ParseMode::Lenient).unwrap();
+        assert_eq!(lenient.value.len(), parse_search(search).unwrap().len() - 1);
+        assert_eq!(lenient.missing.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_work() {
+        let work = parse_work(include_str!("parse_test/work.html")).unwrap();
+        assert_eq!(work.id, "45221314");
+        assert_eq!(work.title, "Salvation");
+        assert_eq!(work.authors, vec!["Starchild719"]);
+        assert_eq!(work.fandoms, vec!["Original Work"]);
+        assert_eq!(work.tags, vec!["Horror", "Psychological Horror"]);
+        assert_eq!(work.characters, vec!["Original Characters"]);
+        assert!(work.relationships.is_empty());
+        assert_eq!(work.warnings, vec!["Graphic Depictions Of Violence"]);
+        assert_eq!(work.get_rating(), Rating::TeenAndUp);
+        assert_eq!(
+            work.date,
+            chrono::NaiveDate::from_ymd_opt(2023, 3, 7).unwrap()
+        );
+        assert_eq!(work.word_count, 2842);
+        assert_eq!(
+            (work.kudos, work.hits, work.bookmarks, work.comments),
+            (154, 1337, 21, 12)
+        );
+        assert_eq!(work.chapter_count, 3);
+        assert!(work.is_complete);
+        assert!(work.summary.starts_with("<p>In the wake of"));
+    }
+
+    #[test]
+    fn test_parse_drafts() {
+        let html = include_str!("parse_test/drafts.html");
+        let drafts = parse_drafts(html).unwrap();
+        assert_eq!(drafts.len(), 2);
+        assert_eq!(drafts[0].work.id, "45300001");
+        assert_eq!(drafts[0].work.title, "Untitled Draft");
+        assert_eq!(
+            drafts[0].expires,
+            chrono::NaiveDate::from_ymd_opt(2023, 3, 22)
+        );
+        assert_eq!(
+            parse_authenticity_token(html).unwrap(),
+            "abc123+token/xyz=="
+        );
+    }
+
+    #[test]
+    fn test_parse_import_report() {
+        let html = include_str!("parse_test/import.html");
+        let report = parse_import_report(html, "https://archiveofourown.org/works/import").unwrap();
+        assert_eq!(report.imported, vec!["45400001".to_string()]);
+        assert_eq!(
+            report.failed,
+            vec![(
+                "http://example.com/story/2".to_string(),
+                "We couldn't successfully import that work, sorry: the site returned an error."
+                    .to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_parse_form_errors() {
+        assert!(parse_form_errors(include_str!("parse_test/drafts.html"))
+            .unwrap()
+            .is_empty());
+        let html = r#"<div id="error" class="error"><h4>Sorry! We couldn't save this work because:</h4>
+            <ul><li>Please enter your story in the text field below.</li><li>Fandom is missing.</li></ul></div>"#;
+        assert_eq!(
+            parse_form_errors(html).unwrap(),
+            vec![
+                "Please enter your story in the text field below.".to_string(),
+                "Fandom is missing.".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_assignments() {
+        let assignments = parse_assignments(include_str!("parse_test/assignments.html")).unwrap();
+        assert_eq!(assignments.len(), 2);
+        assert_eq!(assignments[0].id, "1101");
+        assert_eq!(assignments[0].collection, "yuletide2023");
+        assert_eq!(assignments[0].collection_title, "Yuletide 2023");
+        assert_eq!(assignments[0].recipient.as_deref(), Some("recipient_one"));
+        assert_eq!(
+            assignments[0].fulfill_path(),
+            "/collections/yuletide2023/works/new?assignment_id=1101"
+        );
+        assert_eq!(assignments[1].recipient, None);
+
+        let requests =
+            parse_assignment_requests(include_str!("parse_test/assignment.html")).unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].fandoms, vec!["Original Work".to_string()]);
+        assert_eq!(
+            requests[0].tags,
+            vec!["Original Characters".to_string(), "Fluff".to_string()]
+        );
+        assert_eq!(requests[0].description, "Anything cozy, please!");
+    }
+
+    #[test]
+    fn test_parse_prompts() {
+        let prompts = parse_prompts(include_str!("parse_test/prompts.html")).unwrap();
+        assert_eq!(prompts.len(), 2);
+        assert_eq!(prompts[0].id, "5001");
+        assert_eq!(prompts[0].collection, "kinkmeme");
+        assert_eq!(prompts[0].title, "Snowed in");
+        assert_eq!(prompts[0].tags, vec!["Fluff".to_string()]);
+
+        let claims = parse_claims(include_str!("parse_test/claims.html")).unwrap();
+        assert_eq!(claims.len(), 1);
+        assert_eq!(claims[0].id, "777");
+        assert_eq!(claims[0].prompt_id, "5001");
+        assert_eq!(claims[0].prompt_title, "Snowed in");
+    }
+
+    #[test]
+    fn test_parse_comments() {
+        let comments = parse_comments(include_str!("parse_test/comments.html")).unwrap();
+        assert_eq!(comments.len(), 2);
+        assert_eq!(comments[0].id, "600001");
+        assert_eq!(comments[0].author, "reader_a");
+        assert_eq!(comments[0].chapter, Some(1));
+        assert_eq!(
+            comments[0].date,
+            chrono::NaiveDate::from_ymd_opt(2023, 2, 15).and_then(|d| d.and_hms_opt(21, 32, 0))
+        );
+        assert_eq!(comments[0].body, "<p>This was <em>chilling</em>.</p>");
+        assert_eq!(comments[0].replies.len(), 1);
+        assert!(comments[0].replies[0].has_more_replies);
+        assert_eq!(comments[1].author, "Wanderer (Guest)");
+
+        let thread = parse_comments(include_str!("parse_test/comment_thread.html")).unwrap();
+        assert_eq!(thread[0].id, "600002");
+        assert_eq!(thread[0].replies[0].replies[0].body, "<p>Soon!</p>");
+    }
+
+    #[test]
+    fn test_parse_chapter_ids() {
+        assert_eq!(
+            parse_chapter_ids(include_str!("parse_test/navigate.html")).unwrap(),
+            vec!["113700001", "113700002", "113700003"]
+        );
+    }
+
+    #[test]
+    fn test_parse_work_bookmarks() {
+        let bookmarks =
+            parse_work_bookmarks(include_str!("parse_test/work_bookmarks.html")).unwrap();
+        assert_eq!(bookmarks.len(), 2);
+        assert_eq!(bookmarks[0].id, "900001");
+        assert_eq!(bookmarks[0].bookmarker, "rec_lister");
+        assert_eq!(
+            bookmarks[0].date,
+            chrono::NaiveDate::from_ymd_opt(2023, 2, 22)
+        );
+        assert_eq!(bookmarks[0].tags, vec!["horror", "short and sweet"]);
+        assert!(bookmarks[0].is_rec);
+        assert!(!bookmarks[1].is_rec);
+        assert!(bookmarks[1].tags.is_empty());
+        assert_eq!(bookmarks[1].work_id, "45221314");
+    }
+
+    #[test]
+    fn test_parse_user_bookmarks() {
+        let bookmarks =
+            parse_work_bookmarks(include_str!("parse_test/user_bookmarks.html")).unwrap();
+        assert_eq!(bookmarks.len(), 2);
+        assert_eq!(bookmarks[0].id, "900001");
+        assert_eq!(bookmarks[0].work_id, "45221314");
+        assert_eq!(bookmarks[0].bookmarker, "rec_lister");
+        // not the date of the work in the blurb
+        assert_eq!(
+            bookmarks[0].date,
+            chrono::NaiveDate::from_ymd_opt(2023, 2, 22)
+        );
+        assert_eq!(bookmarks[0].tags, vec!["horror"]);
+        assert_eq!(
+            bookmarks[0].notes,
+            "<p>Read this at night, regretted nothing.</p>"
+        );
+        assert!(bookmarks[0].is_rec);
+        assert_eq!(bookmarks[1].work_id, "45399999");
+        assert!(!bookmarks[1].is_rec);
+    }
+
+    #[test]
+    fn test_parse_bookmark_pseud_id() {
+        let hidden =
+            r#"<form><input type="hidden" name="bookmark[pseud_id]" value="1234" /></form>"#;
+        assert_eq!(parse_bookmark_pseud_id(hidden).unwrap(), "1234");
+        let select = r#"<select name="bookmark[pseud_id]" id="bookmark_pseud_id">
+            <option value="1234">reader</option>
+            <option selected="selected" value="5678">night_reader</option>
+        </select>"#;
+        assert_eq!(parse_bookmark_pseud_id(select).unwrap(), "5678");
+        assert!(parse_bookmark_pseud_id("<form></form>").is_err());
+    }
+
+    #[test]
+    fn test_check_unavailable() {
+        let challenge = r#"<html><head><title>Just a moment...</title></head>
+            <body><script>window._cf_chl_opt={cvId: '3'};</script></body></html>"#;
+        assert!(matches!(
+            check_unavailable(challenge),
+            Err(Error::Challenge)
+        ));
+        let maintenance = "<html><head><title>Archive of Our Own - Site Maintenance</title></head>
+            <body><p>The Archive is down for maintenance.</p></body></html>";
+        assert!(matches!(
+            check_unavailable(maintenance),
+            Err(Error::Maintenance)
+        ));
+        let work =
+            "<title>\n  Maintenance - Starchild719 - Original Work [Archive of Our Own]\n</title>";
+        assert!(check_unavailable(work).is_ok());
+        assert!(check_unavailable(include_str!("parse_test/work.html")).is_ok());
+
+        let head_script = r#"<html><head><title>archiveofourown.org</title>
+            <script src="https://challenges.cloudflare.com/turnstile/v0/api.js"></script></head>
+            <body><form id="challenge-form"></form></body></html>"#;
+        assert!(matches!(
+            check_unavailable(head_script),
+            Err(Error::Challenge)
+        ));
+        // a work about the challenge page is still a work
+        let quoting = include_str!("parse_test/work.html")
+            .replace(
+                "Salvation - Starchild719",
+                "Just a moment... Attention Required! - Starchild719",
+            )
+            .replace(
+                "<p>She walked on.</p>",
+                "<p>Just a moment... she typed window._cf_chl_opt into \
+                <a href=\"https://challenges.cloudflare.com\">challenges.cloudflare.com</a>.</p>",
+            );
+        assert!(check_unavailable(&quoting).is_ok());
+    }
+
+    #[test]
+    fn test_check_search_unavailable() {
+        assert!(check_search_unavailable(include_str!("parse_test/search.html")).is_ok());
+        let nothing_found = r#"<div id="main" class="works-search region" role="main">
+            <h2 class="heading">Search Results</h2>
+            <h3 class="heading">0 Found</h3>
+            <p>No results found. You may want to edit your search to make it less specific.</p>
+            </div>"#;
+        assert!(check_search_unavailable(nothing_found).is_ok());
+        let search_error = r#"<div id="main" class="works-search region" role="main">
+            <div class="flash error">There was an error with your search. Please try again.</div>
+            </div>"#;
+        assert!(matches!(
+            check_search_unavailable(search_error),
+            Err(Error::SearchUnavailable)
+        ));
+        let shell = r#"<div id="main" class="works-search region" role="main">
+            <h2 class="heading">Search Results</h2>
+            <ol class="work index group"></ol>
+            </div>"#;
+        assert!(matches!(
+            check_search_unavailable(shell),
+            Err(Error::SearchUnavailable)
+        ));
+    }
+
+    #[test]
+    fn test_parse_tag() {
+        let synonym = parse_tag(include_str!("parse_test/tag_synonym.html")).unwrap();
+        assert_eq!(synonym.name, "Hermione Granger/Draco Malfoy");
+        assert!(!synonym.is_canonical);
+        assert_eq!(
+            synonym.canonical.as_deref(),
+            Some("Draco Malfoy/Hermione Granger")
+        );
+        assert!(synonym.synonyms.is_empty());
+
+        let canonical = parse_tag(include_str!("parse_test/tag_canonical.html")).unwrap();
+        assert!(canonical.is_canonical);
+        assert_eq!(canonical.canonical, None);
+        assert_eq!(
+            canonical.synonyms,
+            vec![
+                "Dramione",
+                "Draco/Hermione",
+                "Hermione Granger/Draco Malfoy"
+            ]
+        );
+
+        // AO3 escapes the apostrophe
+        let escaped = include_str!("parse_test/tag_canonical.html")
+            .replace("It's a canonical tag", "It&#39;s a canonical tag");
+        assert!(parse_tag(&escaped).unwrap().is_canonical);
+    }
+
+    #[test]
+    fn test_parse_tag_id() {
+        let html = r#"<ul class="navigation actions">
+            <li><a href="/tags/Hollow%20Knight%20(Video%20Game)/works">Works</a></li>
+            <li class="rss"><a title="Subscribe to the feed for this tag" href="/tags/27251507/feed.atom"><span>RSS Feed</span></a></li>
+        </ul>"#;
+        assert_eq!(parse_tag_id(html).unwrap(), "27251507");
+        assert!(parse_tag_id(r#"<a href="/tags/Horror/works">Works</a>"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_subscriptions() {
+        let subscriptions =
+            parse_subscriptions(include_str!("parse_test/subscriptions.html")).unwrap();
+        assert_eq!(
+            subscriptions,
+            vec![
+                AO3Subscription {
+                    id: String::from("70001"),
+                    kind: SubscriptionKind::Work,
+                    target: String::from("45221314"),
+                    name: String::from("Salvation"),
+                },
+                AO3Subscription {
+                    id: String::from("70002"),
+                    kind: SubscriptionKind::Series,
+                    target: String::from("3001234"),
+                    name: String::from("Ashes & Trilogy"),
+                },
+                AO3Subscription {
+                    id: String::from("70003"),
+                    kind: SubscriptionKind::User,
+                    target: String::from("Starchild719"),
+                    name: String::from("Starchild719"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_results_count() {
+        assert_eq!(
+            parse_results_count(include_str!("parse_test/search.html")).unwrap(),
+            Some(10_066_024)
+        );
+        assert_eq!(
+            parse_results_count(include_str!("parse_test/work.html")).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_page_count() {
+        assert_eq!(
+            parse_page_count(include_str!("parse_test/comments.html")).unwrap(),
+            2
+        );
+        assert_eq!(
+            parse_page_count(include_str!("parse_test/search.html")).unwrap(),
+            5000
+        );
+        assert_eq!(
+            parse_page_count(include_str!("parse_test/comment_thread.html")).unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_parse_download_path() {
+        let html = include_str!("parse_test/work.html");
+        assert_eq!(
+            parse_download_path(html, &DownloadFormat::Epub).unwrap(),
+            "/downloads/45221314/Salvation.epub?updated_at=1677020202"
+        );
+        assert_eq!(
+            parse_download_path(html, &DownloadFormat::Azw3).unwrap(),
+            "/downloads/45221314/Salvation.azw3?updated_at=1677020202"
+        );
+        assert!(
+            parse_download_path(include_str!("parse_test/search.html"), &DownloadFormat::Pdf)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_parse_chapters() {
+        let chapters = parse_chapters(include_str!("parse_test/work.html")).unwrap();
+        assert_eq!(chapters.len(), 3);
+        assert_eq!(chapters[0].id, "113700001");
+        assert_eq!(chapters[0].title, "Chapter 1: Before");
+        assert_eq!(
+            chapters[0].text,
+            "<p>The smoke had not yet cleared.</p>\n          <p>She walked on.</p>"
+        );
+        assert_eq!(
+            chapters[1].text,
+            "<p>Someone was <em>moving</em> under the rubble.</p>"
+        );
+    }
+
+    #[test]
+    fn test_parse_new_work_form() {
+        let html = include_str!("parse_test/new_work.html");
+        assert_eq!(parse_work_pseud_id(html).unwrap(), "5678");
+        assert_eq!(parse_work_language_id(html, "en").unwrap(), "1");
+        assert_eq!(parse_work_language_id(html, "es").unwrap(), "2");
+        assert!(parse_work_language_id(html, "tlh").is_err());
+    }
 }
@@ -0,0 +1,189 @@
+use super::ParseMode;
+use crate::error::ParsingError;
+
+/// One way of finding the node a field is in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Find {
+    /// A `tag` element with `class` among its classes, any element when `tag` is empty
+    Class(&'static str, &'static str),
+    /// The element with this id
+    Id(&'static str),
+    /// An element whose attribute has this value
+    Attribute(&'static str, &'static str),
+}
+
+impl Find {
+    fn matches(&self, node: &tl::Node) -> bool {
+        let Some(tag) = node.as_tag() else {
+            return false;
+        };
+        match *self {
+            Find::Class(name, class) => {
+                (name.is_empty() || tag.name() == name) && tag.attributes().is_class_member(class)
+            }
+            Find::Id(id) => tag.attributes().id().is_some_and(|i| i == id),
+            Find::Attribute(attribute, value) => tag
+                .attributes()
+                .get(attribute)
+                .flatten()
+                .is_some_and(|a| a == value),
+        }
+    }
+}
+
+/// A field of a page and the ways of finding it, tried in order
+///
+/// AO3 tweaks its markup every now and then. The current markup comes first,
+/// the others keep the field found when a tweak moves it around.
+#[derive(Debug)]
+pub(crate) struct Field {
+    /// What the field is, for [CouldNotFind](ParsingError::CouldNotFind)
+    pub(crate) name: &'static str,
+    pub(crate) finds: &'static [Find],
+}
+
+impl Field {
+    /// The first node found by the first way that finds one, among `nodes`
+    pub(crate) fn find<'b>(&self, nodes: &'b [tl::Node<'b>]) -> Option<&'b tl::Node<'b>> {
+        self.finds
+            .iter()
+            .find_map(|find| nodes.iter().find(|n| find.matches(n)))
+    }
+
+    /// Like [find](Field::find), but the field has to be there
+    pub(crate) fn require<'b>(
+        &self,
+        nodes: &'b [tl::Node<'b>],
+    ) -> Result<&'b tl::Node<'b>, ParsingError> {
+        self.find(nodes)
+            .ok_or(ParsingError::CouldNotFind(self.name))
+    }
+}
+
+/// Keeps track of the fields missing from a page, depending on the [ParseMode]
+#[derive(Debug)]
+pub(crate) struct Lookup {
+    mode: ParseMode,
+    pub(crate) missing: Vec<ParsingError>,
+}
+
+impl Lookup {
+    pub(crate) fn new(mode: ParseMode) -> Self {
+        Lookup {
+            mode,
+            missing: vec![],
+        }
+    }
+
+    /// A field the page should have, `name` is what it is
+    ///
+    /// When it's missing strict mode fails, lenient mode notes it and goes on without it.
+    pub(crate) fn expect<T>(
+        &mut self,
+        name: &'static str,
+        value: Option<T>,
+    ) -> Result<Option<T>, ParsingError> {
+        if value.is_none() {
+            let err = ParsingError::CouldNotFind(name);
+            match self.mode {
+                ParseMode::Strict => return Err(err),
+                ParseMode::Lenient => self.missing.push(err),
+            }
+        }
+        Ok(value)
+    }
+
+    /// [expect](Lookup::expect) a [Field] among `nodes`
+    pub(crate) fn field<'b>(
+        &mut self,
+        field: &Field,
+        nodes: &'b [tl::Node<'b>],
+    ) -> Result<Option<&'b tl::Node<'b>>, ParsingError> {
+        self.expect(field.name, field.find(nodes))
+    }
+}
+
+/// The metadata (tags, dates, stats) of a work page
+pub(crate) const WORK_META: Field = Field {
+    name: "the metadata of the work.",
+    finds: &[Find::Class("dl", "meta"), Find::Class("dl", "work")],
+};
+
+/// The title, byline and summary of a work page
+pub(crate) const WORK_PREFACE: Field = Field {
+    name: "the preface of the work.",
+    finds: &[Find::Class("div", "preface"), Find::Id("workskin")],
+};
+
+pub(crate) const WORK_TITLE: Field = Field {
+    name: "the title of the work.",
+    finds: &[Find::Class("h2", "title"), Find::Class("", "title")],
+};
+
+pub(crate) const WORK_BYLINE: Field = Field {
+    name: "the authors of the work.",
+    finds: &[Find::Class("h3", "byline"), Find::Class("", "byline")],
+};
+
+pub(crate) const WORK_SUMMARY: Field = Field {
+    name: "the summary of the work.",
+    finds: &[Find::Class("div", "summary"), Find::Class("", "summary")],
+};
+
+/// The chapters of a work page, or its text when there is a single chapter
+pub(crate) const WORK_CHAPTERS: Field = Field {
+    name: "the chapters of the work.",
+    finds: &[Find::Id("chapters"), Find::Attribute("role", "article")],
+};
+
+pub(crate) const SERIES_META: Field = Field {
+    name: "the metadata of the series.",
+    finds: &[Find::Class("dl", "series"), Find::Class("dl", "meta")],
+};
+
+pub(crate) const SERIES_TITLE: Field = Field {
+    name: "the title of the series.",
+    finds: &[Find::Class("h2", "heading"), Find::Class("", "heading")],
+};
+
+/// The name heading of a tag's page
+pub(crate) const TAG_NAME: Field = Field {
+    name: "the name of the tag.",
+    finds: &[Find::Class("h2", "heading")],
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fallbacks() {
+        let html =
+            r#"<div id="workskin"><h3 class="title">Moved</h3><div class="summary">S</div></div>"#;
+        let dom = tl::parse(html, tl::ParserOptions::default()).unwrap();
+        let parser = dom.parser();
+        // no `div.preface` here, the second way finds it
+        let preface = WORK_PREFACE.require(dom.nodes()).unwrap();
+        assert!(Find::Id("workskin").matches(preface));
+        let title = WORK_TITLE.find(dom.nodes()).unwrap();
+        assert_eq!(title.inner_text(parser), "Moved");
+        assert!(matches!(
+            WORK_META.require(dom.nodes()),
+            Err(ParsingError::CouldNotFind("the metadata of the work."))
+        ));
+    }
+
+    #[test]
+    fn test_lookup() {
+        let mut strict = Lookup::new(ParseMode::Strict);
+        assert_eq!(strict.expect("the title.", Some(1)).unwrap(), Some(1));
+        assert!(strict.expect::<usize>("the title.", None).is_err());
+
+        let mut lenient = Lookup::new(ParseMode::Lenient);
+        assert_eq!(lenient.expect::<usize>("the title.", None).unwrap(), None);
+        assert!(matches!(
+            lenient.missing.as_slice(),
+            [ParsingError::CouldNotFind("the title.")]
+        ));
+    }
+}
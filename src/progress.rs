@@ -0,0 +1,146 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::error::Error;
+
+pub use tokio_util::sync::CancellationToken;
+
+/// How far a long operation got
+///
+/// Each operation only fills in what makes sense for it, a download only counts bytes.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Progress {
+    pub pages_done: usize,
+
+    /// Number of pages there are, once it's known
+    pub page_count: Option<usize>,
+
+    pub works_fetched: usize,
+
+    pub bytes_downloaded: u64,
+
+    /// Size of the download, if the server said
+    pub total_bytes: Option<u64>,
+}
+
+type ProgressCallback = Arc<dyn Fn(&Progress) + Send + Sync>;
+
+/// Watches over a long operation: reports its [Progress] and lets it be cancelled
+///
+/// Cancelling makes the operation stop at the next request and return [Error::Cancelled],
+/// operations that keep a [checkpoint](crate::checkpoint::Checkpoint) can be resumed later.
+/// ```rust,no_run
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// use ao3rs::client::AO3Client;
+/// use ao3rs::models::DownloadFormat;
+/// use ao3rs::progress::Monitor;
+///
+/// let monitor = Monitor::new().on_progress(|progress| {
+///     println!("{} of {:?} bytes", progress.bytes_downloaded, progress.total_bytes)
+/// });
+/// // give the token to the cancel button
+/// let token = monitor.cancellation_token();
+/// AO3Client::new()
+///     .download_work_with_progress("45221314", DownloadFormat::Epub, "salvation.epub", &monitor)
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default, Clone)]
+pub struct Monitor {
+    on_progress: Option<ProgressCallback>,
+    cancellation_token: CancellationToken,
+}
+
+impl std::fmt::Debug for Monitor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Monitor")
+            .field("is_cancelled", &self.is_cancelled())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Monitor {
+    pub fn new() -> Self {
+        Monitor {
+            ..Default::default()
+        }
+    }
+
+    /// Call `on_progress` after every step of the operation
+    pub fn on_progress(mut self, on_progress: impl Fn(&Progress) + Send + Sync + 'static) -> Self {
+        self.on_progress = Some(Arc::new(on_progress));
+        self
+    }
+
+    /// Use a token shared with something else, e.g. to cancel several operations at once
+    pub fn set_cancellation_token(mut self, cancellation_token: CancellationToken) -> Self {
+        self.cancellation_token = cancellation_token;
+        self
+    }
+
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancellation_token.clone()
+    }
+
+    pub fn cancel(&self) {
+        self.cancellation_token.cancel();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation_token.is_cancelled()
+    }
+
+    pub(crate) fn report(&self, progress: &Progress) {
+        if let Some(on_progress) = &self.on_progress {
+            on_progress(progress);
+        }
+    }
+
+    /// Fails once the operation was cancelled
+    pub(crate) fn check(&self) -> Result<(), Error> {
+        if self.is_cancelled() {
+            Err(Error::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Sleep, waking up right away when cancelled
+    pub async fn sleep(&self, duration: Duration) -> Result<(), Error> {
+        tokio::select! {
+            _ = tokio::time::sleep(duration) => Ok(()),
+            _ = self.cancellation_token.cancelled() => Err(Error::Cancelled),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[tokio::test]
+    async fn test_monitor() {
+        let seen = Arc::new(Mutex::new(vec![]));
+        let monitor = Monitor::new().on_progress({
+            let seen = seen.clone();
+            move |progress| seen.lock().unwrap().push(progress.pages_done)
+        });
+        monitor.report(&Progress {
+            pages_done: 1,
+            ..Default::default()
+        });
+        assert_eq!(*seen.lock().unwrap(), vec![1]);
+        assert!(monitor.check().is_ok());
+
+        let token = monitor.cancellation_token();
+        let sleeping = tokio::spawn({
+            let monitor = monitor.clone();
+            async move { monitor.sleep(Duration::from_secs(60)).await }
+        });
+        token.cancel();
+        assert!(sleeping.await.unwrap().is_err());
+        assert!(monitor.check().is_err());
+    }
+}
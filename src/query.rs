@@ -1,6 +1,21 @@
-use crate::parse::parse_search;
+use futures_util::stream::{self, Stream, StreamExt};
 
-const BASE_AO3_SEARCH_URL: &'static str = "https://archiveofourown.org/works/search?";
+use crate::cache::ResultCache;
+use crate::client::{encode_query_value, AO3Client, BuiltRequest};
+use crate::error::Error;
+use crate::form::BASE_AO3_URL;
+use crate::progress::{Monitor, Progress};
+
+#[cfg(feature = "blocking")]
+mod blocking;
+mod expr;
+mod from_url;
+mod tag_works;
+
+pub use expr::QueryExpr;
+pub use tag_works::{TagWorksFilterBuilder, TagWorksSortBy};
+
+const SEARCH_PATH: &str = "/works/search?";
 
 trait QueryValue: std::fmt::Display {
     type Output;
@@ -22,7 +37,8 @@ impl QueryValue for String {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Period {
     Years,
     Weeks,
@@ -50,7 +66,8 @@ impl std::fmt::Display for Period {
 /// Examples form AO3 (taking Wednesday 25th April 2012 as the current day):
 /// ```rust
 /// ```
-#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[derive(Debug, Default, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DateRange {
     #[default]
     None,
@@ -58,20 +75,73 @@ pub enum DateRange {
     MoreThan(usize, Period),
     LessThan(usize, Period),
     Between(usize, usize, Period),
+
+    /// On or after a day, turned into days ago when the query is sent
+    Since(chrono::NaiveDate),
+
+    /// Before a day, turned into days ago when the query is sent
+    Before(chrono::NaiveDate),
+
+    /// From a day to another, in either order, turned into days ago when the query is sent
+    BetweenDates(chrono::NaiveDate, chrono::NaiveDate),
+}
+
+impl DateRange {
+    /// Works updated on `date` or later
+    /// ```rust
+    /// use ao3rs::query::{AO3QueryBuilder, DateRange};
+    /// use chrono::NaiveDate;
+    ///
+    /// let query = AO3QueryBuilder::new()
+    ///     .set_date_range(DateRange::since(NaiveDate::from_ymd_opt(2023, 1, 1).unwrap()));
+    /// ```
+    pub fn since(date: chrono::NaiveDate) -> Self {
+        DateRange::Since(date)
+    }
+
+    /// Works last updated before `date`
+    ///
+    /// AO3 counts days ago back from the time the search is made, so to leave out
+    /// all of `date` part of the day before it is left out too.
+    pub fn before(date: chrono::NaiveDate) -> Self {
+        DateRange::Before(date)
+    }
+
+    /// Works last updated between `from` and `to`, both included
+    pub fn between_dates(from: chrono::NaiveDate, to: chrono::NaiveDate) -> Self {
+        DateRange::BetweenDates(from, to)
+    }
+
+    /// The range in days ago as of `today`, the way AO3 takes it, ranges that
+    /// already are relative are left as they are
+    ///
+    /// Queries are sent with today in UTC.
+    pub fn relative_to(&self, today: chrono::NaiveDate) -> DateRange {
+        let days_ago = |date: &chrono::NaiveDate| (today - *date).num_days().max(0) as usize;
+        match self {
+            DateRange::Since(date) => DateRange::LessThan(days_ago(date) + 1, Period::Days),
+            DateRange::Before(date) => DateRange::MoreThan(days_ago(date) + 1, Period::Days),
+            DateRange::BetweenDates(from, to) => {
+                DateRange::Between(days_ago(from.max(to)), days_ago(from.min(to)), Period::Days)
+            }
+            relative => relative.clone(),
+        }
+    }
 }
 
 impl QueryValue for DateRange {
     type Output = String;
     /// Create a query value used for the date field in [QueryBuilder](QueryBuilder)
     fn to_query_value(&self) -> String {
-        match self {
-            DateRange::None => String::new(),
+        match self.relative_to(chrono::Utc::now().date_naive()) {
             DateRange::Exactly(time, period) => format!("{} {} ago", time, period),
             DateRange::MoreThan(time, period) => format!("> {} {} ago", time, period),
             DateRange::LessThan(time, period) => format!("< {} {} ago", time, period),
             DateRange::Between(from_time, to_time, period) => {
                 format!("{}-{} {}", from_time, to_time, period)
             }
+            // the dates are days ago by now
+            _ => String::new(),
         }
     }
 
@@ -90,6 +160,9 @@ impl std::fmt::Display for DateRange {
             DateRange::Between(from_time, to_time, period) => {
                 write!(f, "Between {} and {} {} ago", from_time, to_time, period)
             }
+            DateRange::Since(date) => write!(f, "Since {date}"),
+            DateRange::Before(date) => write!(f, "Before {date}"),
+            DateRange::BetweenDates(from, to) => write!(f, "Between {from} and {to}"),
         }
     }
 }
@@ -101,7 +174,8 @@ impl std::fmt::Display for DateRange {
 /// use ao3rs::query::AO3QueryBuilder;
 ///
 /// ```
-#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[derive(Debug, Default, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CompletionStatus {
     /// Ignore whether work was completed or not
     /// query value: empty string
@@ -151,9 +225,10 @@ impl std::fmt::Display for CompletionStatus {
 ///
 /// Wether a fan fiction is a crossover or not
 /// ```rust
-/// use ao3rs::query::QueryBuilder;
+/// use ao3rs::query::AO3QueryBuilder;
 /// ```
-#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[derive(Debug, Default, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 enum CrossoverStatus {
     /// Don't care if there are crossovers
     ///
@@ -213,7 +288,8 @@ impl QueryValue for bool {
     }
 }
 
-#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[derive(Debug, Default, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum NumericalValueRange {
     #[default]
     None,
@@ -241,6 +317,72 @@ impl QueryValue for NumericalValueRange {
     }
 }
 
+/// Counts in a range, the bounds are turned into the inclusive ones AO3 uses
+///
+/// An empty range, like `5..5` or `5..=3`, becomes `LessThan(0)` which no count is in,
+/// like `..0` already does.
+/// ```rust
+/// use ao3rs::query::{AO3QueryBuilder, NumericalValueRange};
+///
+/// let query = AO3QueryBuilder::new()
+///     .set_kudos((100..500).into())
+///     .set_word_count((1000..).into());
+/// assert_eq!(NumericalValueRange::from(..10), NumericalValueRange::LessThan(10));
+/// assert_eq!(NumericalValueRange::from(5..=5), NumericalValueRange::Exactly(5));
+/// ```
+impl From<std::ops::Range<usize>> for NumericalValueRange {
+    fn from(range: std::ops::Range<usize>) -> Self {
+        if range.is_empty() {
+            return NumericalValueRange::LessThan(0);
+        }
+        (range.start..=range.end - 1).into()
+    }
+}
+
+impl From<std::ops::RangeInclusive<usize>> for NumericalValueRange {
+    fn from(range: std::ops::RangeInclusive<usize>) -> Self {
+        let (start, end) = range.into_inner();
+        match start.cmp(&end) {
+            std::cmp::Ordering::Greater => NumericalValueRange::LessThan(0),
+            std::cmp::Ordering::Equal => NumericalValueRange::Exactly(start),
+            std::cmp::Ordering::Less => NumericalValueRange::Between(start, end),
+        }
+    }
+}
+
+impl From<std::ops::RangeFrom<usize>> for NumericalValueRange {
+    fn from(range: std::ops::RangeFrom<usize>) -> Self {
+        match range.start {
+            0 => NumericalValueRange::None,
+            start => NumericalValueRange::MoreThan(start - 1),
+        }
+    }
+}
+
+impl From<std::ops::RangeTo<usize>> for NumericalValueRange {
+    fn from(range: std::ops::RangeTo<usize>) -> Self {
+        NumericalValueRange::LessThan(range.end)
+    }
+}
+
+impl From<std::ops::RangeToInclusive<usize>> for NumericalValueRange {
+    fn from(range: std::ops::RangeToInclusive<usize>) -> Self {
+        NumericalValueRange::LessThan(range.end.saturating_add(1))
+    }
+}
+
+impl From<std::ops::RangeFull> for NumericalValueRange {
+    fn from(_: std::ops::RangeFull) -> Self {
+        NumericalValueRange::None
+    }
+}
+
+impl From<usize> for NumericalValueRange {
+    fn from(num: usize) -> Self {
+        NumericalValueRange::Exactly(num)
+    }
+}
+
 impl std::fmt::Display for NumericalValueRange {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -255,7 +397,8 @@ impl std::fmt::Display for NumericalValueRange {
     }
 }
 
-#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[derive(Debug, Default, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct MultiString(Vec<String>);
 
 impl QueryValue for MultiString {
@@ -276,7 +419,7 @@ impl std::fmt::Display for MultiString {
     }
 }
 
-use crate::models::{AO3Work, Rating};
+use crate::models::{AO3Work, Rating, SearchResults, TagKind};
 impl QueryValue for Rating {
     type Output = String;
 
@@ -309,7 +452,8 @@ impl std::fmt::Display for Rating {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ArchiveWarning {
     CreatureChoseNotToUseArchiveWarnings = 14,
     GraphicDepictionOfViolence = 17,
@@ -362,10 +506,177 @@ impl std::fmt::Display for ArchiveWarning {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
-struct MultiSelect<T>(Vec<T>)
-where
-    T: QueryValue;
+/// Values picked in a select that takes several, each once
+///
+/// Built with `|` or `+`, e.g. `Category::FF | Category::MM`.
+/// Two selects are equal when they have the same values, whatever order they were added in.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MultiSelect<T>(Vec<T>);
+
+impl<T: PartialEq> MultiSelect<T> {
+    pub fn new() -> Self {
+        Self(vec![])
+    }
+
+    /// Add `value` unless it's there already, returns whether it was added
+    pub fn insert(&mut self, value: T) -> bool {
+        if self.0.contains(&value) {
+            return false;
+        }
+        self.0.push(value);
+        true
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        self.0.contains(value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The values in the order they were added
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.0.iter()
+    }
+}
+
+impl<T: PartialEq> PartialEq for MultiSelect<T> {
+    fn eq(&self, other: &Self) -> bool {
+        // values are there once each, so the same length and values is the same set
+        self.len() == other.len() && self.iter().all(|value| other.contains(value))
+    }
+}
+
+impl<T: Eq> Eq for MultiSelect<T> {}
+
+impl<T: std::hash::Hash> std::hash::Hash for MultiSelect<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        use std::hash::{BuildHasher, BuildHasherDefault};
+        // summed so the order of the values doesn't change the hash
+        let hasher = BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default();
+        let sum = self
+            .0
+            .iter()
+            .map(|value| hasher.hash_one(value))
+            .fold(0u64, u64::wrapping_add);
+        self.0.len().hash(state);
+        sum.hash(state);
+    }
+}
+
+impl<T: PartialEq> From<T> for MultiSelect<T> {
+    fn from(value: T) -> Self {
+        Self(vec![value])
+    }
+}
+
+impl<T: PartialEq> FromIterator<T> for MultiSelect<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut select = Self::new();
+        select.extend(iter);
+        select
+    }
+}
+
+impl<T: PartialEq> Extend<T> for MultiSelect<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<T> IntoIterator for MultiSelect<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a MultiSelect<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<T: PartialEq> std::ops::BitOr<T> for MultiSelect<T> {
+    type Output = MultiSelect<T>;
+
+    fn bitor(mut self, value: T) -> Self::Output {
+        self.insert(value);
+        self
+    }
+}
+
+impl<T: PartialEq> std::ops::BitOr for MultiSelect<T> {
+    type Output = MultiSelect<T>;
+
+    fn bitor(mut self, other: MultiSelect<T>) -> Self::Output {
+        self.extend(other);
+        self
+    }
+}
+
+impl<T: PartialEq> std::ops::Add<T> for MultiSelect<T> {
+    type Output = MultiSelect<T>;
+
+    fn add(mut self, value: T) -> Self::Output {
+        self.insert(value);
+        self
+    }
+}
+
+impl<T: PartialEq> std::ops::Add for MultiSelect<T> {
+    type Output = MultiSelect<T>;
+
+    fn add(mut self, other: MultiSelect<T>) -> Self::Output {
+        self.extend(other);
+        self
+    }
+}
+
+impl std::ops::BitOr for ArchiveWarning {
+    type Output = MultiSelect<ArchiveWarning>;
+
+    fn bitor(self, other: ArchiveWarning) -> Self::Output {
+        MultiSelect::from_iter([self, other])
+    }
+}
+
+impl std::ops::Add for ArchiveWarning {
+    type Output = MultiSelect<ArchiveWarning>;
+
+    fn add(self, other: ArchiveWarning) -> Self::Output {
+        MultiSelect::from_iter([self, other])
+    }
+}
+
+impl std::ops::BitOr for Category {
+    type Output = MultiSelect<Category>;
+
+    fn bitor(self, other: Category) -> Self::Output {
+        MultiSelect::from_iter([self, other])
+    }
+}
+
+impl std::ops::Add for Category {
+    type Output = MultiSelect<Category>;
+
+    fn add(self, other: Category) -> Self::Output {
+        MultiSelect::from_iter([self, other])
+    }
+}
 
 impl<T> std::fmt::Display for MultiSelect<T>
 where
@@ -401,13 +712,14 @@ where
     }
 }
 
-impl<T: QueryValue> Default for MultiSelect<T> {
+impl<T> Default for MultiSelect<T> {
     fn default() -> Self {
         Self(Default::default())
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Category {
     /// Female / Female
     FF = 116,
@@ -460,10 +772,20 @@ impl std::fmt::Display for Category {
     }
 }
 
-#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[derive(Debug, Default, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SortBy {
     #[default]
-    BestMatch, // TODO: the rest of the sort bys
+    BestMatch,
+    Author,
+    Title,
+    DatePosted,
+    DateUpdated,
+    WordCount,
+    Hits,
+    Kudos,
+    Comments,
+    Bookmarks,
 }
 
 impl QueryValue for SortBy {
@@ -471,7 +793,16 @@ impl QueryValue for SortBy {
 
     fn to_query_value(&self) -> Self::Output {
         match self {
-            SortBy::BestMatch => format!("_score"),
+            SortBy::BestMatch => String::from("_score"),
+            SortBy::Author => String::from("authors_to_sort_on"),
+            SortBy::Title => String::from("title_to_sort_on"),
+            SortBy::DatePosted => String::from("created_at"),
+            SortBy::DateUpdated => String::from("revised_at"),
+            SortBy::WordCount => String::from("word_count"),
+            SortBy::Hits => String::from("hits"),
+            SortBy::Kudos => String::from("kudos_count"),
+            SortBy::Comments => String::from("comments_count"),
+            SortBy::Bookmarks => String::from("bookmarks_count"),
         }
     }
 
@@ -484,11 +815,21 @@ impl std::fmt::Display for SortBy {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             SortBy::BestMatch => write!(f, "Best Match"),
+            SortBy::Author => write!(f, "Author"),
+            SortBy::Title => write!(f, "Title"),
+            SortBy::DatePosted => write!(f, "Date Posted"),
+            SortBy::DateUpdated => write!(f, "Date Updated"),
+            SortBy::WordCount => write!(f, "Word Count"),
+            SortBy::Hits => write!(f, "Hits"),
+            SortBy::Kudos => write!(f, "Kudos"),
+            SortBy::Comments => write!(f, "Comments"),
+            SortBy::Bookmarks => write!(f, "Bookmarks"),
         }
     }
 }
 
-#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[derive(Debug, Default, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SortDirection {
     #[default]
     Descending,
@@ -500,8 +841,8 @@ impl QueryValue for SortDirection {
 
     fn to_query_value(&self) -> Self::Output {
         match self {
-            SortDirection::Descending => format!("desc"),
-            SortDirection::Ascending => format!("asc"),
+            SortDirection::Descending => String::from("desc"),
+            SortDirection::Ascending => String::from("asc"),
         }
     }
 
@@ -519,11 +860,105 @@ impl std::fmt::Display for SortDirection {
     }
 }
 
-#[derive(Debug)]
+/// Language a work is written in
+///
+/// AO3 has many more, [Other](Language::Other) takes the code AO3 uses for one,
+/// like `"tlh"` for Klingon.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Language {
+    /// Any language
+    #[default]
+    Any,
+    English,
+    Spanish,
+    French,
+    German,
+    Italian,
+    Portuguese,
+    Russian,
+    Polish,
+    Dutch,
+    Chinese,
+    Japanese,
+    Korean,
+    Vietnamese,
+    Indonesian,
+    Other(String),
+}
+
+impl Language {
+    /// Code AO3 gives the language, like `en`, empty for [Any](Language::Any)
+    pub fn code(&self) -> String {
+        self.to_query_value()
+    }
+}
+
+impl QueryValue for Language {
+    type Output = String;
+
+    fn to_query_value(&self) -> Self::Output {
+        match self {
+            Language::Any => String::new(),
+            Language::English => String::from("en"),
+            Language::Spanish => String::from("es"),
+            Language::French => String::from("fr"),
+            Language::German => String::from("de"),
+            Language::Italian => String::from("it"),
+            Language::Portuguese => String::from("ptBR"),
+            Language::Russian => String::from("ru"),
+            Language::Polish => String::from("pl"),
+            Language::Dutch => String::from("nl"),
+            Language::Chinese => String::from("zh"),
+            Language::Japanese => String::from("ja"),
+            Language::Korean => String::from("ko"),
+            Language::Vietnamese => String::from("vi"),
+            Language::Indonesian => String::from("id"),
+            Language::Other(code) => code.clone(),
+        }
+    }
+
+    fn is_included(&self) -> bool {
+        self != &Self::Any
+    }
+}
+
+impl std::fmt::Display for Language {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Language::Any => write!(f, "Any"),
+            Language::English => write!(f, "English"),
+            Language::Spanish => write!(f, "Español"),
+            Language::French => write!(f, "Français"),
+            Language::German => write!(f, "Deutsch"),
+            Language::Italian => write!(f, "Italiano"),
+            Language::Portuguese => write!(f, "Português brasileiro"),
+            Language::Russian => write!(f, "Русский"),
+            Language::Polish => write!(f, "Polski"),
+            Language::Dutch => write!(f, "Nederlands"),
+            Language::Chinese => write!(f, "中文-普通话 國語"),
+            Language::Japanese => write!(f, "日本語"),
+            Language::Korean => write!(f, "한국어"),
+            Language::Vietnamese => write!(f, "Tiếng Việt"),
+            Language::Indonesian => write!(f, "Bahasa Indonesia"),
+            Language::Other(code) => write!(f, "{code}"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "SavedQuery"))]
 pub struct AO3QueryBuilder {
     /// Query Limit
     limit: usize,
 
+    /// Page of the results to start from
+    page: usize,
+
+    /// Time to wait between two pages when [streaming](AO3QueryBuilder::stream) the results
+    page_delay: std::time::Duration,
+
     /// Searches everything
     any_field: String,
 
@@ -542,8 +977,11 @@ pub struct AO3QueryBuilder {
     /// Wether to include crossovers in the query
     crossover_status: CrossoverStatus,
 
-    /// If turned on only show chapters with just one chapter
-    is_single_chapter: bool,
+    /// Language the work is in
+    language: Language,
+
+    /// If turned on only show works with just one chapter, None leaves it out of the query
+    is_single_chapter: Option<bool>,
 
     /// Word count
     word_count: NumericalValueRange,
@@ -569,6 +1007,12 @@ pub struct AO3QueryBuilder {
     /// Additional Tags
     additional_tags: MultiString,
 
+    /// Tags a work must not have
+    excluded_tags: MultiString,
+
+    /// Ids of canonical tags a work must have, see [resolve_tag_ids](AO3QueryBuilder::resolve_tag_ids)
+    filter_ids: MultiString,
+
     /// Hits
     hits: NumericalValueRange,
 
@@ -586,18 +1030,24 @@ pub struct AO3QueryBuilder {
 
     /// Sort direction
     sort_direction: SortDirection,
+
+    /// Parameters the builder has no setter for, sent as they are
+    raw_params: Vec<(String, String)>,
 }
 
 impl Default for AO3QueryBuilder {
     fn default() -> Self {
         Self {
             limit: 20,
+            page: 1,
+            page_delay: Default::default(),
             any_field: Default::default(),
             title: Default::default(),
             authors: Default::default(),
             date: Default::default(),
             completion_status: Default::default(),
             crossover_status: Default::default(),
+            language: Default::default(),
             is_single_chapter: Default::default(),
             word_count: Default::default(),
             fandoms: Default::default(),
@@ -607,16 +1057,88 @@ impl Default for AO3QueryBuilder {
             characters: Default::default(),
             relationships: Default::default(),
             additional_tags: Default::default(),
+            excluded_tags: Default::default(),
+            filter_ids: Default::default(),
             hits: Default::default(),
             kudos: Default::default(),
             comments: Default::default(),
             bookmarks: Default::default(),
             sort_by: Default::default(),
             sort_direction: Default::default(),
+            raw_params: Default::default(),
         }
     }
 }
 
+/// A query as it's saved, every field is optional so presets written by hand
+/// only need the ones they set
+macro_rules! saved_query {
+    ($($field:ident: $ty:ty,)*) => {
+        #[cfg(feature = "serde")]
+        #[derive(serde::Deserialize)]
+        struct SavedQuery {
+            $(
+                #[serde(default)]
+                $field: Option<$ty>,
+            )*
+        }
+
+        #[cfg(feature = "serde")]
+        impl TryFrom<SavedQuery> for AO3QueryBuilder {
+            type Error = Error;
+
+            fn try_from(saved: SavedQuery) -> Result<Self, Error> {
+                let mut query = AO3QueryBuilder::default();
+                $(
+                    if let Some(value) = saved.$field {
+                        query.$field = value;
+                    }
+                )*
+                // a multi select that was saved with a value twice has it once
+                query.archive_warnings = query.archive_warnings.into_iter().collect();
+                query.categories = query.categories.into_iter().collect();
+                if query.page == 0 {
+                    return Err(Error::InvalidInput(String::from(
+                        "The page of a query starts at 1",
+                    )));
+                }
+                Ok(query)
+            }
+        }
+    };
+}
+
+saved_query! {
+    limit: usize,
+    page: usize,
+    page_delay: std::time::Duration,
+    any_field: String,
+    title: String,
+    authors: MultiString,
+    date: DateRange,
+    completion_status: CompletionStatus,
+    crossover_status: CrossoverStatus,
+    language: Language,
+    is_single_chapter: Option<bool>,
+    word_count: NumericalValueRange,
+    fandoms: MultiString,
+    rating: Rating,
+    archive_warnings: MultiSelect<ArchiveWarning>,
+    categories: MultiSelect<Category>,
+    characters: MultiString,
+    relationships: MultiString,
+    additional_tags: MultiString,
+    excluded_tags: MultiString,
+    filter_ids: MultiString,
+    hits: NumericalValueRange,
+    kudos: NumericalValueRange,
+    comments: NumericalValueRange,
+    bookmarks: NumericalValueRange,
+    sort_by: SortBy,
+    sort_direction: SortDirection,
+    raw_params: Vec<(String, String)>,
+}
+
 impl AO3QueryBuilder {
     pub fn new() -> Self {
         AO3QueryBuilder {
@@ -629,8 +1151,38 @@ impl AO3QueryBuilder {
         self
     }
 
-    pub fn set_title(mut self, title: &dyn AsRef<str>) -> Self {
-        self.title = title.as_ref().to_string();
+    /// Start from the `page`th page of the results, pages start at 1 and have 20 works
+    ///
+    /// To walk through all of the results, see [send_page](AO3QueryBuilder::send_page)
+    /// or [search_pages](crate::client::AO3Client::search_pages).
+    pub fn set_page(mut self, page: usize) -> Self {
+        self.page = page.max(1);
+        self
+    }
+
+    pub fn get_page(&self) -> usize {
+        self.page
+    }
+
+    /// Wait `page_delay` before fetching each page after the first when
+    /// [streaming](AO3QueryBuilder::stream) the results, there is no wait by default
+    pub fn set_page_delay(mut self, page_delay: std::time::Duration) -> Self {
+        self.page_delay = page_delay;
+        self
+    }
+
+    /// Search every field for `any_field`, like the search box at the top of AO3
+    pub fn set_any_field(mut self, any_field: impl Into<String>) -> Self {
+        self.any_field = any_field.into();
+        self
+    }
+
+    pub fn get_any_field(&self) -> &str {
+        &self.any_field
+    }
+
+    pub fn set_title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
         self
     }
 
@@ -638,13 +1190,21 @@ impl AO3QueryBuilder {
         &self.title
     }
 
-    pub fn set_authors(mut self, authors: Vec<String>) -> Self {
-        self.authors = MultiString(authors);
+    /// Works by any of `authors`, AO3 matches them loosely, a name matches pseuds with it
+    /// as a word too, see [push_exact_author](AO3QueryBuilder::push_exact_author)
+    pub fn set_authors<S: Into<String>>(mut self, authors: impl IntoIterator<Item = S>) -> Self {
+        self.authors = MultiString(authors.into_iter().map(Into::into).collect());
         self
     }
 
-    pub fn push_author(mut self, author: String) -> Self {
-        self.authors.0.push(author);
+    pub fn push_author(mut self, author: impl Into<String>) -> Self {
+        self.authors.0.push(author.into());
+        self
+    }
+
+    /// Works by the user or pseud named exactly `author`, not every one with the name in it
+    pub fn push_exact_author(mut self, author: impl Into<String>) -> Self {
+        self.authors.0.push(format!("\"{}\"", author.into()));
         self
     }
 
@@ -702,12 +1262,19 @@ impl AO3QueryBuilder {
     }
 
     pub fn single_chapter(mut self, is_single_chapter: bool) -> Self {
-        self.is_single_chapter = is_single_chapter;
+        self.is_single_chapter = Some(is_single_chapter);
+        self
+    }
+
+    /// Don't send the single chapter filter at all, like the untouched checkbox on AO3
+    pub fn ignore_single_chapter(mut self) -> Self {
+        self.is_single_chapter = None;
         self
     }
 
-    pub fn is_single_chapter(&self) -> &bool {
-        &self.is_single_chapter
+    /// None when the filter isn't sent
+    pub fn is_single_chapter(&self) -> Option<bool> {
+        self.is_single_chapter
     }
 
     pub fn set_word_count(mut self, word_count: NumericalValueRange) -> Self {
@@ -719,13 +1286,13 @@ impl AO3QueryBuilder {
         self.word_count.to_string()
     }
 
-    pub fn set_fandoms(mut self, fandoms: Vec<String>) -> Self {
-        self.fandoms = MultiString(fandoms);
+    pub fn set_fandoms<S: Into<String>>(mut self, fandoms: impl IntoIterator<Item = S>) -> Self {
+        self.fandoms = MultiString(fandoms.into_iter().map(Into::into).collect());
         self
     }
 
-    pub fn push_fandom(mut self, fandom: &dyn AsRef<str>) -> Self {
-        self.fandoms.0.push(fandom.as_ref().to_string());
+    pub fn push_fandom(mut self, fandom: impl Into<String>) -> Self {
+        self.fandoms.0.push(fandom.into());
         self
     }
 
@@ -738,53 +1305,96 @@ impl AO3QueryBuilder {
         self
     }
 
-    pub fn set_archive_warnings(mut self, archive_warnings: Vec<ArchiveWarning>) -> Self {
-        self.archive_warnings = MultiSelect(archive_warnings);
+    pub fn set_archive_warnings(
+        mut self,
+        archive_warnings: impl IntoIterator<Item = ArchiveWarning>,
+    ) -> Self {
+        self.archive_warnings = archive_warnings.into_iter().collect();
         self
     }
 
     pub fn add_archive_warning(mut self, archive_warning: ArchiveWarning) -> Self {
-        self.archive_warnings.0.push(archive_warning);
+        self.archive_warnings.insert(archive_warning);
         self
     }
 
-    pub fn set_categories(mut self, categories: Vec<Category>) -> Self {
-        self.categories = MultiSelect(categories);
+    pub fn set_categories(mut self, categories: impl IntoIterator<Item = Category>) -> Self {
+        self.categories = categories.into_iter().collect();
         self
     }
 
     pub fn push_category(mut self, category: Category) -> Self {
-        self.categories.0.push(category);
+        self.categories.insert(category);
         self
     }
 
-    pub fn set_characters(mut self, characters: Vec<String>) -> Self {
-        self.characters = MultiString(characters);
+    pub fn set_characters<S: Into<String>>(
+        mut self,
+        characters: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.characters = MultiString(characters.into_iter().map(Into::into).collect());
         self
     }
 
-    pub fn push_character(mut self, character: String) -> Self {
-        self.characters.0.push(character);
+    pub fn push_character(mut self, character: impl Into<String>) -> Self {
+        self.characters.0.push(character.into());
         self
     }
 
-    pub fn set_relationships(mut self, relationships: Vec<String>) -> Self {
-        self.relationships = MultiString(relationships);
+    pub fn set_relationships<S: Into<String>>(
+        mut self,
+        relationships: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.relationships = MultiString(relationships.into_iter().map(Into::into).collect());
         self
     }
 
-    pub fn push_relationship(mut self, relationship: String) -> Self {
-        self.relationships.0.push(relationship);
+    pub fn push_relationship(mut self, relationship: impl Into<String>) -> Self {
+        self.relationships.0.push(relationship.into());
         self
     }
 
-    pub fn set_additional_tags(mut self, additional_tags: Vec<String>) -> Self {
-        self.additional_tags = MultiString(additional_tags);
+    pub fn set_additional_tags<S: Into<String>>(
+        mut self,
+        additional_tags: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.additional_tags = MultiString(additional_tags.into_iter().map(Into::into).collect());
         self
     }
 
-    pub fn push_additional_tag(mut self, additional_tag: String) -> Self {
-        self.additional_tags.0.push(additional_tag);
+    pub fn push_additional_tag(mut self, additional_tag: impl Into<String>) -> Self {
+        self.additional_tags.0.push(additional_tag.into());
+        self
+    }
+
+    /// Leave out works with any of these tags
+    pub fn set_excluded_tags<S: Into<String>>(
+        mut self,
+        excluded_tags: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.excluded_tags = MultiString(excluded_tags.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn push_excluded_tag(mut self, excluded_tag: impl Into<String>) -> Self {
+        self.excluded_tags.0.push(excluded_tag.into());
+        self
+    }
+
+    /// Only works with all of these tags, by id, see [resolve_tag_ids](AO3QueryBuilder::resolve_tag_ids)
+    pub fn set_filter_ids<S: Into<String>>(mut self, ids: impl IntoIterator<Item = S>) -> Self {
+        self.filter_ids = MultiString(ids.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn push_filter_id(mut self, id: impl Into<String>) -> Self {
+        self.filter_ids.0.push(id.into());
+        self
+    }
+
+    /// Only works in `language`
+    pub fn set_language(mut self, language: Language) -> Self {
+        self.language = language;
         self
     }
 
@@ -808,6 +1418,81 @@ impl AO3QueryBuilder {
         self
     }
 
+    /// Works with at least `min` words
+    pub fn min_word_count(self, min: usize) -> Self {
+        self.set_word_count((min..).into())
+    }
+
+    /// Works with at most `max` words
+    pub fn max_word_count(self, max: usize) -> Self {
+        self.set_word_count((..=max).into())
+    }
+
+    /// Works with `min` to `max` words, both included, in either order
+    pub fn word_count_between(self, min: usize, max: usize) -> Self {
+        self.set_word_count(ordered_range(min, max).into())
+    }
+
+    /// Works with at least `min` hits
+    pub fn min_hits(self, min: usize) -> Self {
+        self.set_hits((min..).into())
+    }
+
+    /// Works with at most `max` hits
+    pub fn max_hits(self, max: usize) -> Self {
+        self.set_hits((..=max).into())
+    }
+
+    /// Works with `min` to `max` hits, both included, in either order
+    pub fn hits_between(self, min: usize, max: usize) -> Self {
+        self.set_hits(ordered_range(min, max).into())
+    }
+
+    /// Works with at least `min` kudos
+    pub fn min_kudos(self, min: usize) -> Self {
+        self.set_kudos((min..).into())
+    }
+
+    /// Works with at most `max` kudos
+    pub fn max_kudos(self, max: usize) -> Self {
+        self.set_kudos((..=max).into())
+    }
+
+    /// Works with `min` to `max` kudos, both included, in either order
+    pub fn kudos_between(self, min: usize, max: usize) -> Self {
+        self.set_kudos(ordered_range(min, max).into())
+    }
+
+    /// Works with at least `min` comments
+    pub fn min_comments(self, min: usize) -> Self {
+        self.set_comments((min..).into())
+    }
+
+    /// Works with at most `max` comments
+    pub fn max_comments(self, max: usize) -> Self {
+        self.set_comments((..=max).into())
+    }
+
+    /// Works with `min` to `max` comments, both included, in either order
+    pub fn comments_between(self, min: usize, max: usize) -> Self {
+        self.set_comments(ordered_range(min, max).into())
+    }
+
+    /// Works with at least `min` bookmarks
+    pub fn min_bookmarks(self, min: usize) -> Self {
+        self.set_bookmarks((min..).into())
+    }
+
+    /// Works with at most `max` bookmarks
+    pub fn max_bookmarks(self, max: usize) -> Self {
+        self.set_bookmarks((..=max).into())
+    }
+
+    /// Works with `min` to `max` bookmarks, both included, in either order
+    pub fn bookmarks_between(self, min: usize, max: usize) -> Self {
+        self.set_bookmarks(ordered_range(min, max).into())
+    }
+
     pub fn set_sort_by(mut self, sort_by: SortBy) -> Self {
         self.sort_by = sort_by;
         self
@@ -818,19 +1503,284 @@ impl AO3QueryBuilder {
         self
     }
 
-    /// Perform a simple search with a single query
-    pub async fn simple_search(mut self, query: &str) -> Result<(), Box<dyn std::error::Error>> {
-        self.any_field = query.to_string();
-        self.send().await?;
-        Ok(())
+    /// Send `key=value` along with the rest of the query, for fields of AO3's search form
+    /// this crate doesn't know about yet
+    /// ```rust
+    /// use ao3rs::query::AO3QueryBuilder;
+    ///
+    /// let query = AO3QueryBuilder::new().add_raw_param("work_search[words_from]", "1000");
+    /// assert!(query.build_url()?.contains("work_search[words_from]=1000"));
+    /// # Ok::<(), ao3rs::Error>(())
+    /// ```
+    pub fn add_raw_param(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.raw_params.push((key.into(), value.into()));
+        self
+    }
+
+    pub fn clear_raw_params(mut self) -> Self {
+        self.raw_params = Default::default();
+        self
+    }
+
+    pub fn clear_authors(mut self) -> Self {
+        self.authors = Default::default();
+        self
+    }
+
+    pub fn clear_fandoms(mut self) -> Self {
+        self.fandoms = Default::default();
+        self
+    }
+
+    pub fn clear_archive_warnings(mut self) -> Self {
+        self.archive_warnings = Default::default();
+        self
+    }
+
+    pub fn clear_categories(mut self) -> Self {
+        self.categories = Default::default();
+        self
+    }
+
+    pub fn clear_characters(mut self) -> Self {
+        self.characters = Default::default();
+        self
+    }
+
+    pub fn clear_relationships(mut self) -> Self {
+        self.relationships = Default::default();
+        self
+    }
+
+    /// Remove the additional tags
+    pub fn clear_tags(mut self) -> Self {
+        self.additional_tags = Default::default();
+        self
+    }
+
+    pub fn clear_excluded_tags(mut self) -> Self {
+        self.excluded_tags = Default::default();
+        self
+    }
+
+    pub fn clear_filter_ids(mut self) -> Self {
+        self.filter_ids = Default::default();
+        self
+    }
+
+    /// Start over from a new query, the search limit included
+    pub fn reset(self) -> Self {
+        Self::new()
+    }
+
+    /// Use what's set in `other` instead of what's in this query
+    ///
+    /// A field counts as set when it isn't what [new](AO3QueryBuilder::new) has,
+    /// lists are replaced rather than appended to. Made for a saved base query with
+    /// overrides for a single run:
+    /// ```rust
+    /// use ao3rs::query::{AO3QueryBuilder, SortBy};
+    ///
+    /// let base = AO3QueryBuilder::new().push_fandom("Good Omens").only_completed();
+    /// let tonight = base
+    ///     .clone()
+    ///     .merge(AO3QueryBuilder::new().set_sort_by(SortBy::Kudos));
+    /// ```
+    pub fn merge(mut self, other: AO3QueryBuilder) -> Self {
+        let default = Self::default();
+        override_with(&mut self.limit, other.limit, &default.limit);
+        override_with(&mut self.page, other.page, &default.page);
+        override_with(&mut self.page_delay, other.page_delay, &default.page_delay);
+        override_with(&mut self.any_field, other.any_field, &default.any_field);
+        override_with(&mut self.title, other.title, &default.title);
+        override_with(&mut self.authors, other.authors, &default.authors);
+        override_with(&mut self.date, other.date, &default.date);
+        override_with(
+            &mut self.completion_status,
+            other.completion_status,
+            &default.completion_status,
+        );
+        override_with(
+            &mut self.crossover_status,
+            other.crossover_status,
+            &default.crossover_status,
+        );
+        override_with(
+            &mut self.is_single_chapter,
+            other.is_single_chapter,
+            &default.is_single_chapter,
+        );
+        override_with(&mut self.language, other.language, &default.language);
+        override_with(&mut self.word_count, other.word_count, &default.word_count);
+        override_with(&mut self.fandoms, other.fandoms, &default.fandoms);
+        override_with(&mut self.rating, other.rating, &default.rating);
+        override_with(
+            &mut self.archive_warnings,
+            other.archive_warnings,
+            &default.archive_warnings,
+        );
+        override_with(&mut self.categories, other.categories, &default.categories);
+        override_with(&mut self.characters, other.characters, &default.characters);
+        override_with(
+            &mut self.relationships,
+            other.relationships,
+            &default.relationships,
+        );
+        override_with(
+            &mut self.additional_tags,
+            other.additional_tags,
+            &default.additional_tags,
+        );
+        override_with(
+            &mut self.excluded_tags,
+            other.excluded_tags,
+            &default.excluded_tags,
+        );
+        override_with(&mut self.filter_ids, other.filter_ids, &default.filter_ids);
+        override_with(&mut self.hits, other.hits, &default.hits);
+        override_with(&mut self.kudos, other.kudos, &default.kudos);
+        override_with(&mut self.comments, other.comments, &default.comments);
+        override_with(&mut self.bookmarks, other.bookmarks, &default.bookmarks);
+        override_with(&mut self.sort_by, other.sort_by, &default.sort_by);
+        override_with(
+            &mut self.sort_direction,
+            other.sort_direction,
+            &default.sort_direction,
+        );
+        override_with(&mut self.raw_params, other.raw_params, &default.raw_params);
+        self
+    }
+
+    /// The fields that are different in `other`, in the order they're shown in
+    ///
+    /// For showing what changed between two saved queries:
+    /// ```rust
+    /// use ao3rs::query::{AO3QueryBuilder, SortBy};
+    ///
+    /// let before = AO3QueryBuilder::new().push_fandom("Good Omens");
+    /// let after = before.clone().set_sort_by(SortBy::Kudos);
+    /// for change in before.diff(&after) {
+    ///     println!("{}: {} -> {}", change.field, change.before, change.after);
+    /// }
+    /// ```
+    pub fn diff(&self, other: &AO3QueryBuilder) -> Vec<QueryChange> {
+        fn shown(value: &impl std::fmt::Display) -> String {
+            value.to_string()
+        }
+        let fields = [
+            ("search limit", shown(&self.limit), shown(&other.limit)),
+            ("page", shown(&self.page), shown(&other.page)),
+            (
+                "page delay",
+                format!("{:?}", self.page_delay),
+                format!("{:?}", other.page_delay),
+            ),
+            ("any field", shown(&self.any_field), shown(&other.any_field)),
+            ("title", shown(&self.title), shown(&other.title)),
+            ("author", shown(&self.authors), shown(&other.authors)),
+            ("date", shown(&self.date), shown(&other.date)),
+            (
+                "completion status",
+                shown(&self.completion_status),
+                shown(&other.completion_status),
+            ),
+            (
+                "crossover",
+                shown(&self.crossover_status),
+                shown(&other.crossover_status),
+            ),
+            ("language", shown(&self.language), shown(&other.language)),
+            (
+                "is single chapter",
+                format!("{:?}", self.is_single_chapter),
+                format!("{:?}", other.is_single_chapter),
+            ),
+            (
+                "word count",
+                shown(&self.word_count),
+                shown(&other.word_count),
+            ),
+            ("fandoms", shown(&self.fandoms), shown(&other.fandoms)),
+            ("rating", shown(&self.rating), shown(&other.rating)),
+            (
+                "archive warnings",
+                shown(&self.archive_warnings),
+                shown(&other.archive_warnings),
+            ),
+            (
+                "categories",
+                shown(&self.categories),
+                shown(&other.categories),
+            ),
+            (
+                "characters",
+                shown(&self.characters),
+                shown(&other.characters),
+            ),
+            (
+                "relationships",
+                shown(&self.relationships),
+                shown(&other.relationships),
+            ),
+            (
+                "additional tags",
+                shown(&self.additional_tags),
+                shown(&other.additional_tags),
+            ),
+            (
+                "excluded tags",
+                shown(&self.excluded_tags),
+                shown(&other.excluded_tags),
+            ),
+            ("tag ids", shown(&self.filter_ids), shown(&other.filter_ids)),
+            ("hits", shown(&self.hits), shown(&other.hits)),
+            ("kudos", shown(&self.kudos), shown(&other.kudos)),
+            ("comments", shown(&self.comments), shown(&other.comments)),
+            ("bookmarks", shown(&self.bookmarks), shown(&other.bookmarks)),
+            ("sort by", shown(&self.sort_by), shown(&other.sort_by)),
+            (
+                "sort direction",
+                shown(&self.sort_direction),
+                shown(&other.sort_direction),
+            ),
+            (
+                "raw params",
+                format!("{:?}", self.raw_params),
+                format!("{:?}", other.raw_params),
+            ),
+        ];
+        fields
+            .into_iter()
+            .filter(|(_, before, after)| before != after)
+            .map(|(field, before, after)| QueryChange {
+                field,
+                before,
+                after,
+            })
+            .collect()
+    }
+
+    /// Perform a simple search with a single query, on top of what's set in this one
+    ///
+    /// The query is left as it is, so it can be searched again with other words.
+    pub async fn simple_search(&self, query: &str) -> Result<Vec<AO3Work>, Error> {
+        self.clone().set_any_field(query).send().await
     }
 
-    fn create_url(&self, page: usize) -> String {
+    pub(crate) fn create_url(&self, page: usize) -> String {
+        format!("{BASE_AO3_URL}{}", self.create_path(page))
+    }
+
+    /// Path of a page of the results, relative to the root of AO3
+    ///
+    /// Every value is percent-encoded, the names in lists of tags each on their own
+    /// so they're still separated by commas.
+    pub(crate) fn create_path(&self, page: usize) -> String {
         let mut is_first = true;
-        let mut q = String::from(BASE_AO3_SEARCH_URL);
+        let mut q = String::from(SEARCH_PATH);
         fn add_delim(q: &mut String, is_first: &mut bool) {
             if !*is_first {
-                q.push_str("&");
+                q.push('&');
             }
             *is_first = false;
         }
@@ -842,70 +1792,79 @@ impl AO3QueryBuilder {
             add_delim(&mut q, &mut is_first);
             q.push_str(&format!(
                 "work_search[query]={}",
-                self.any_field.to_query_value()
+                encode_query_value(&self.any_field.to_query_value())
             ))
         }
         if self.title.is_included() {
             add_delim(&mut q, &mut is_first);
             q.push_str(&format!(
                 "work_search[title]={}",
-                self.title.to_query_value()
+                encode_query_value(&self.title.to_query_value())
             ))
         }
         if self.authors.is_included() {
+            // AO3 searches the creators field like the any field, so names are OR'ed
+            // instead of joined with commas like the tags
             add_delim(&mut q, &mut is_first);
             q.push_str(&format!(
-                "work_search[authors]={}",
-                self.authors.to_query_value()
+                "work_search[creators]={}",
+                encode_query_value(&self.authors.0.join(" OR "))
             ))
         }
         if self.date.is_included() {
             add_delim(&mut q, &mut is_first);
             q.push_str(&format!(
                 "work_search[revised_at]={}",
-                self.date.to_query_value()
+                encode_query_value(&self.date.to_query_value())
             ))
         }
         if self.completion_status.is_included() {
             add_delim(&mut q, &mut is_first);
             q.push_str(&format!(
                 "work_search[complete]={}",
-                self.completion_status.to_query_value()
+                encode_query_value(&self.completion_status.to_query_value())
             ))
         };
         if self.crossover_status.is_included() {
             add_delim(&mut q, &mut is_first);
             q.push_str(&format!(
                 "work_search[crossover]={}",
-                self.crossover_status.to_query_value()
+                encode_query_value(&self.crossover_status.to_query_value())
             ))
         }
-        if self.is_single_chapter.is_included() {
+        if let Some(is_single_chapter) = self.is_single_chapter {
             add_delim(&mut q, &mut is_first);
             q.push_str(&format!(
                 "work_search[single_chapter]={}",
-                self.is_single_chapter().to_query_value()
+                is_single_chapter.to_query_value()
+            ))
+        }
+        if self.language.is_included() {
+            add_delim(&mut q, &mut is_first);
+            q.push_str(&format!(
+                "work_search[language_id]={}",
+                encode_query_value(&self.language.to_query_value())
             ))
         }
         if self.word_count.is_included() {
             add_delim(&mut q, &mut is_first);
             q.push_str(&format!(
                 "work_search[word_count]={}",
-                self.word_count.to_query_value()
+                encode_query_value(&self.word_count.to_query_value())
             ))
         }
         if self.fandoms.is_included() {
             add_delim(&mut q, &mut is_first);
             q.push_str(&format!(
                 "work_search[fandom_names]={}",
-                self.fandoms.to_query_value()
+                encode_names(&self.fandoms)
             ))
         }
         if self.rating.is_included() {
             add_delim(&mut q, &mut is_first);
             q.push_str(&format!(
                 "work_search[rating_ids]={}",
-                self.rating.to_query_value()
+                encode_query_value(&self.rating.to_query_value())
             ))
         }
         if self.archive_warnings.is_included() {
@@ -914,7 +1873,10 @@ impl AO3QueryBuilder {
                 .into_iter()
                 .for_each(|aw| {
                     add_delim(&mut q, &mut is_first);
-                    q.push_str(&format!("work_search[archive_warning_ids][]={}", aw))
+                    q.push_str(&format!(
+                        "work_search[archive_warning_ids][]={}",
+                        encode_query_value(&aw)
+                    ))
                 });
         }
         if self.categories.is_included() {
@@ -923,85 +1885,470 @@ impl AO3QueryBuilder {
                 .into_iter()
                 .for_each(|cat| {
                     add_delim(&mut q, &mut is_first);
-                    q.push_str(&format!("work_search[category_ids][]={}", cat))
+                    q.push_str(&format!(
+                        "work_search[category_ids][]={}",
+                        encode_query_value(&cat)
+                    ))
                 });
         }
         if self.characters.is_included() {
             add_delim(&mut q, &mut is_first);
             q.push_str(&format!(
                 "work_search[character_names]={}",
-                self.characters.to_query_value()
+                encode_names(&self.characters)
             ))
         }
         if self.relationships.is_included() {
             add_delim(&mut q, &mut is_first);
             q.push_str(&format!(
-                "work_search[relationship_name]={}",
-                self.relationships.to_query_value()
+                "work_search[relationship_names]={}",
+                encode_names(&self.relationships)
             ))
         }
         if self.additional_tags.is_included() {
             add_delim(&mut q, &mut is_first);
             q.push_str(&format!(
                 "work_search[freeform_names]={}",
-                self.additional_tags.to_query_value()
+                encode_names(&self.additional_tags)
+            ))
+        }
+        if self.excluded_tags.is_included() {
+            add_delim(&mut q, &mut is_first);
+            q.push_str(&format!(
+                "work_search[excluded_tag_names]={}",
+                encode_names(&self.excluded_tags)
+            ))
+        }
+        for id in &self.filter_ids.0 {
+            add_delim(&mut q, &mut is_first);
+            q.push_str(&format!(
+                "work_search[filter_ids][]={}",
+                encode_query_value(id)
             ))
         }
         if self.hits.is_included() {
             add_delim(&mut q, &mut is_first);
-            q.push_str(&format!("work_search[hits]={}", self.hits.to_query_value()))
+            q.push_str(&format!(
+                "work_search[hits]={}",
+                encode_query_value(&self.hits.to_query_value())
+            ))
         }
         if self.kudos.is_included() {
             add_delim(&mut q, &mut is_first);
             q.push_str(&format!(
                 "work_search[kudos_count]={}",
-                self.kudos.to_query_value()
+                encode_query_value(&self.kudos.to_query_value())
             ))
         }
         if self.comments.is_included() {
             add_delim(&mut q, &mut is_first);
             q.push_str(&format!(
-                "work_search[commets_count]={}",
-                self.comments.to_query_value()
+                "work_search[comments_count]={}",
+                encode_query_value(&self.comments.to_query_value())
             ))
         }
         if self.bookmarks.is_included() {
             add_delim(&mut q, &mut is_first);
             q.push_str(&format!(
                 "work_search[bookmarks_count]={}",
-                self.bookmarks.to_query_value()
+                encode_query_value(&self.bookmarks.to_query_value())
             ))
         }
         add_delim(&mut q, &mut is_first);
         q.push_str(&format!(
             "work_search[sort_column]={}",
-            self.sort_by.to_query_value()
+            encode_query_value(&self.sort_by.to_query_value())
         ));
         add_delim(&mut q, &mut is_first);
         q.push_str(&format!(
             "work_search[sort_direction]={}",
-            self.sort_direction.to_query_value()
+            encode_query_value(&self.sort_direction.to_query_value())
         ));
+        for (key, value) in &self.raw_params {
+            add_delim(&mut q, &mut is_first);
+            q.push_str(&format!("{key}={}", encode_query_value(value)));
+        }
         q
     }
 
-    /// Send query
-    pub async fn send(self) -> Result<Vec<AO3Work>, Box<dyn std::error::Error>> {
-        let page_needed = (self.limit as f64 / 20_f64).ceil() as usize;
+    /// Filter on the ids of the canonical fandoms, characters and relationships instead
+    /// of their names, which AO3 matches loosely
+    ///
+    /// Each name is looked up with [resolve_tag_id](AO3Client::resolve_tag_id) and moved to the
+    /// [filter ids](AO3QueryBuilder::set_filter_ids). Fails on the first name that isn't a tag.
+    /// ```rust,no_run
+    /// # async fn example() -> Result<(), ao3rs::Error> {
+    /// use ao3rs::client::AO3Client;
+    /// use ao3rs::query::AO3QueryBuilder;
+    ///
+    /// let works = AO3QueryBuilder::new()
+    ///     .push_fandom("Hollow Knight")
+    ///     .resolve_tag_ids(&AO3Client::new())
+    ///     .await?
+    ///     .send()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn resolve_tag_ids(mut self, client: &AO3Client) -> Result<Self, Error> {
+        let names = [
+            (TagKind::Fandom, std::mem::take(&mut self.fandoms)),
+            (TagKind::Character, std::mem::take(&mut self.characters)),
+            (
+                TagKind::Relationship,
+                std::mem::take(&mut self.relationships),
+            ),
+        ];
+        for (kind, names) in names {
+            for name in names.0 {
+                let id = client.resolve_tag_id(kind, &name).await?;
+                if !self.filter_ids.0.contains(&id) {
+                    self.filter_ids.0.push(id);
+                }
+            }
+        }
+        Ok(self)
+    }
+
+    /// Send query, the works found come back parsed, up to the [search limit](AO3QueryBuilder::set_search_limit)
+    /// ```rust,no_run
+    /// # async fn example() -> Result<(), ao3rs::Error> {
+    /// use ao3rs::query::AO3QueryBuilder;
+    ///
+    /// for work in AO3QueryBuilder::new().set_any_field("horror").send().await? {
+    ///     println!("{} by {}", work.title, work.authors.join(", "));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send(self) -> Result<Vec<AO3Work>, Error> {
+        self.send_with(&AO3Client::new()).await
+    }
+
+    /// Send query through `client`, with its rate limit, cookies, base url, ...
+    ///
+    /// The other ways of sending a query use a client of their own, made with
+    /// [AO3Client::new], each time they're called.
+    /// ```rust,no_run
+    /// # async fn example() -> Result<(), ao3rs::Error> {
+    /// use ao3rs::client::AO3Client;
+    /// use ao3rs::query::AO3QueryBuilder;
+    ///
+    /// let client = AO3Client::new().set_rate_limit(std::time::Duration::from_secs(5));
+    /// let horror = AO3QueryBuilder::new().set_any_field("horror").send_with(&client).await?;
+    /// let fluff = AO3QueryBuilder::new().set_any_field("fluff").send_with(&client).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_with(self, client: &AO3Client) -> Result<Vec<AO3Work>, Error> {
+        Ok(self.fetch_results(client, &Monitor::default()).await?.works)
+    }
+
+    /// Send query, reporting every page of results fetched to `monitor`
+    pub async fn send_with_progress(self, monitor: &Monitor) -> Result<Vec<AO3Work>, Error> {
+        Ok(self.search_with_progress(monitor).await?.works)
+    }
+
+    /// [send](AO3QueryBuilder::send), with how many works AO3 found, how many pages
+    /// of them there are and the url of the search
+    pub async fn search(self) -> Result<SearchResults, Error> {
+        self.search_with(&AO3Client::new()).await
+    }
+
+    /// [search](AO3QueryBuilder::search) through `client`, see [send_with](AO3QueryBuilder::send_with)
+    pub async fn search_with(self, client: &AO3Client) -> Result<SearchResults, Error> {
+        self.fetch_results(client, &Monitor::default()).await
+    }
+
+    /// [search](AO3QueryBuilder::search), reporting every page of results fetched to `monitor`
+    pub async fn search_with_progress(self, monitor: &Monitor) -> Result<SearchResults, Error> {
+        self.fetch_results(&AO3Client::new(), monitor).await
+    }
+
+    async fn fetch_results(
+        &self,
+        client: &AO3Client,
+        monitor: &Monitor,
+    ) -> Result<SearchResults, Error> {
+        let mut results = SearchResults {
+            page: self.page,
+            url: self.create_url(self.page),
+            ..Default::default()
+        };
+        let mut progress = Progress {
+            page_count: Some(self.pages_needed().count()),
+            ..Default::default()
+        };
+        for (done, page) in self.pages_needed().enumerate() {
+            monitor.check()?;
+            let fetched = client.search_page(self, page).await?;
+            results.works.extend(fetched.items);
+            results.page = page;
+            results.page_count = fetched.page_count;
+            results.total = fetched.total;
+            progress.pages_done = done + 1;
+            progress.works_fetched = results.len().min(self.limit);
+            monitor.report(&progress);
+        }
+        results.works.truncate(self.limit);
+        Ok(results)
+    }
+
+    /// The url of the [page](AO3QueryBuilder::set_page) of results the query starts at,
+    /// encoded exactly like it's requested
+    ///
+    /// To log it, open it in a browser or request it some other way. See
+    /// [dry_run](AO3QueryBuilder::dry_run) for every page [send](AO3QueryBuilder::send) fetches.
+    /// ```rust
+    /// use ao3rs::query::AO3QueryBuilder;
+    ///
+    /// let url = AO3QueryBuilder::new().set_any_field("found family").build_url()?;
+    /// assert!(url.starts_with("https://archiveofourown.org/works/search?work_search[query]=found%20family"));
+    /// # Ok::<(), ao3rs::Error>(())
+    /// ```
+    pub fn build_url(&self) -> Result<String, Error> {
+        let request = reqwest::Client::new()
+            .get(self.create_url(self.page))
+            .build()?;
+        Ok(request.url().to_string())
+    }
+
+    /// The requests [send](AO3QueryBuilder::send) would make, without sending them
+    pub fn dry_run(&self) -> Result<Vec<BuiltRequest>, Error> {
+        let client = reqwest::Client::new();
+        self.pages_needed()
+            .map(|page| {
+                Ok(BuiltRequest::from(
+                    &client.get(self.create_url(page)).build()?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Send query, taking the pages `cache` has from there and adding the ones it doesn't
+    pub async fn send_cached(self, cache: &ResultCache) -> Result<Vec<AO3Work>, Error> {
+        let client = AO3Client::new();
         let mut works = vec![];
-        for page in 1..=page_needed {
-            let url = self.create_url(page);
-            let resp = reqwest::get(url).await?.text().await?;
-            works.append(&mut parse_search(&resp)?);
+        for page in self.pages_needed() {
+            works.extend(client.search_page_cached(&self, page, cache).await?);
         }
         works.truncate(self.limit);
         Ok(works)
     }
+
+    /// Pages [send](AO3QueryBuilder::send) fetches to get `limit` works from the
+    /// [page](AO3QueryBuilder::set_page) it starts at
+    fn pages_needed(&self) -> std::ops::RangeInclusive<usize> {
+        let page_needed = (self.limit as f64 / 20_f64).ceil() as usize;
+        self.page..=self.page + page_needed - 1
+    }
+
+    /// Every work the search finds, page after page from the [page](AO3QueryBuilder::set_page)
+    /// it starts at, each page fetched when the stream gets to it
+    ///
+    /// The search limit doesn't apply, use [take](futures_util::StreamExt::take) for that.
+    /// Ends after the last page, or after the first page that failed.
+    /// ```rust,no_run
+    /// # async fn example() -> Result<(), ao3rs::Error> {
+    /// use ao3rs::query::AO3QueryBuilder;
+    /// use futures_util::StreamExt;
+    ///
+    /// let query = AO3QueryBuilder::new()
+    ///     .push_fandom("Hollow Knight")
+    ///     .set_page_delay(std::time::Duration::from_secs(5));
+    /// let mut works = std::pin::pin!(query.stream());
+    /// while let Some(work) = works.next().await {
+    ///     println!("{}", work?.title);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn stream(&self) -> impl Stream<Item = Result<AO3Work, Error>> + '_ {
+        self.stream_through(AO3Client::new())
+    }
+
+    /// [stream](AO3QueryBuilder::stream) through `client`, see [send_with](AO3QueryBuilder::send_with)
+    pub fn stream_with<'a>(
+        &'a self,
+        client: &'a AO3Client,
+    ) -> impl Stream<Item = Result<AO3Work, Error>> + 'a {
+        self.stream_through(client)
+    }
+
+    fn stream_through<'a>(
+        &'a self,
+        client: impl std::borrow::Borrow<AO3Client> + 'a,
+    ) -> impl Stream<Item = Result<AO3Work, Error>> + 'a {
+        stream::unfold(
+            (client, Some(self.page)),
+            move |(client, page)| async move {
+                let page = page?;
+                if page != self.page {
+                    tokio::time::sleep(self.page_delay).await;
+                }
+                Some(match client.borrow().search_page(self, page).await {
+                    Ok(fetched) => {
+                        let next = (!fetched.is_last()).then_some(page + 1);
+                        (Ok(fetched.items), (client, next))
+                    }
+                    Err(err) => (Err(err), (client, None)),
+                })
+            },
+        )
+        .flat_map(|works| match works {
+            Ok(works) => stream::iter(works.into_iter().map(Ok)).left_stream(),
+            Err(err) => stream::iter([Err(err)]).right_stream(),
+        })
+    }
+
+    /// Send query for the `page`th page of results only, whatever the limit and page set
+    ///
+    /// See [search_page](AO3Client::search_page) for a page with how many pages there are.
+    pub async fn send_page(&self, page: usize) -> Result<Vec<AO3Work>, Error> {
+        Ok(AO3Client::new().search_page(self, page).await?.items)
+    }
+}
+
+/// The names percent-encoded and separated by commas, AO3 doesn't allow commas in tags
+fn encode_names(names: &MultiString) -> String {
+    names
+        .0
+        .iter()
+        .map(|name| encode_query_value(name))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// `a..=b` or `b..=a`, whichever isn't empty
+fn ordered_range(a: usize, b: usize) -> std::ops::RangeInclusive<usize> {
+    a.min(b)..=a.max(b)
+}
+
+/// `&mut self` versions of the setters, named like them with `_mut` at the end
+macro_rules! mut_setters {
+    ($($name:ident => $setter:ident($($arg:ident: $ty:ty),*);)*) => {
+        impl AO3QueryBuilder {
+            $(
+                #[doc = concat!(
+                    "[", stringify!($setter), "](AO3QueryBuilder::", stringify!($setter),
+                    ") on a query that's borrowed instead of taken"
+                )]
+                pub fn $name(&mut self, $($arg: $ty),*) -> &mut Self {
+                    *self = std::mem::take(self).$setter($($arg),*);
+                    self
+                }
+            )*
+        }
+    };
+}
+
+// For building a query step by step, like when a setting is only there sometimes:
+// `if let Some(tag) = tag { query.push_additional_tag_mut(tag); }`
+mut_setters! {
+    set_search_limit_mut => set_search_limit(limit: usize);
+    set_page_mut => set_page(page: usize);
+    set_page_delay_mut => set_page_delay(page_delay: std::time::Duration);
+    set_any_field_mut => set_any_field(any_field: impl Into<String>);
+    set_title_mut => set_title(title: impl Into<String>);
+    set_authors_mut => set_authors(authors: impl IntoIterator<Item = impl Into<String>>);
+    push_author_mut => push_author(author: impl Into<String>);
+    push_exact_author_mut => push_exact_author(author: impl Into<String>);
+    set_date_range_mut => set_date_range(date: DateRange);
+    only_completed_mut => only_completed();
+    ignore_completion_status_mut => ignore_completion_status();
+    only_incomplete_mut => only_incomplete();
+    only_crossover_mut => only_crossover();
+    ignore_crossover_status_mut => ignore_crossover_status();
+    only_non_crossover_mut => only_non_crossover();
+    single_chapter_mut => single_chapter(is_single_chapter: bool);
+    ignore_single_chapter_mut => ignore_single_chapter();
+    set_word_count_mut => set_word_count(word_count: NumericalValueRange);
+    set_fandoms_mut => set_fandoms(fandoms: impl IntoIterator<Item = impl Into<String>>);
+    push_fandom_mut => push_fandom(fandom: impl Into<String>);
+    set_rating_mut => set_rating(rating: Rating);
+    set_archive_warnings_mut => set_archive_warnings(
+        archive_warnings: impl IntoIterator<Item = ArchiveWarning>
+    );
+    add_archive_warning_mut => add_archive_warning(archive_warning: ArchiveWarning);
+    set_categories_mut => set_categories(categories: impl IntoIterator<Item = Category>);
+    push_category_mut => push_category(category: Category);
+    set_characters_mut => set_characters(
+        characters: impl IntoIterator<Item = impl Into<String>>
+    );
+    push_character_mut => push_character(character: impl Into<String>);
+    set_relationships_mut => set_relationships(
+        relationships: impl IntoIterator<Item = impl Into<String>>
+    );
+    push_relationship_mut => push_relationship(relationship: impl Into<String>);
+    set_additional_tags_mut => set_additional_tags(
+        additional_tags: impl IntoIterator<Item = impl Into<String>>
+    );
+    push_additional_tag_mut => push_additional_tag(additional_tag: impl Into<String>);
+    set_excluded_tags_mut => set_excluded_tags(
+        excluded_tags: impl IntoIterator<Item = impl Into<String>>
+    );
+    push_excluded_tag_mut => push_excluded_tag(excluded_tag: impl Into<String>);
+    set_filter_ids_mut => set_filter_ids(ids: impl IntoIterator<Item = impl Into<String>>);
+    push_filter_id_mut => push_filter_id(id: impl Into<String>);
+    set_language_mut => set_language(language: Language);
+    set_hits_mut => set_hits(hits: NumericalValueRange);
+    set_kudos_mut => set_kudos(kudos: NumericalValueRange);
+    set_comments_mut => set_comments(comments: NumericalValueRange);
+    set_bookmarks_mut => set_bookmarks(bookmarks: NumericalValueRange);
+    min_word_count_mut => min_word_count(min: usize);
+    max_word_count_mut => max_word_count(max: usize);
+    word_count_between_mut => word_count_between(min: usize, max: usize);
+    min_hits_mut => min_hits(min: usize);
+    max_hits_mut => max_hits(max: usize);
+    hits_between_mut => hits_between(min: usize, max: usize);
+    min_kudos_mut => min_kudos(min: usize);
+    max_kudos_mut => max_kudos(max: usize);
+    kudos_between_mut => kudos_between(min: usize, max: usize);
+    min_comments_mut => min_comments(min: usize);
+    max_comments_mut => max_comments(max: usize);
+    comments_between_mut => comments_between(min: usize, max: usize);
+    min_bookmarks_mut => min_bookmarks(min: usize);
+    max_bookmarks_mut => max_bookmarks(max: usize);
+    bookmarks_between_mut => bookmarks_between(min: usize, max: usize);
+    set_sort_by_mut => set_sort_by(sort_by: SortBy);
+    set_sort_direction_mut => set_sort_direction(sort_direction: SortDirection);
+    add_raw_param_mut => add_raw_param(key: impl Into<String>, value: impl Into<String>);
+    clear_raw_params_mut => clear_raw_params();
+    clear_authors_mut => clear_authors();
+    clear_fandoms_mut => clear_fandoms();
+    clear_archive_warnings_mut => clear_archive_warnings();
+    clear_categories_mut => clear_categories();
+    clear_characters_mut => clear_characters();
+    clear_relationships_mut => clear_relationships();
+    clear_tags_mut => clear_tags();
+    clear_excluded_tags_mut => clear_excluded_tags();
+    clear_filter_ids_mut => clear_filter_ids();
+    reset_mut => reset();
+    merge_mut => merge(other: AO3QueryBuilder);
+}
+
+/// A field that's different between two queries, see [diff](AO3QueryBuilder::diff)
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct QueryChange {
+    /// Name of the field, like in the query's [Display](std::fmt::Display)
+    pub field: &'static str,
+    pub before: String,
+    pub after: String,
+}
+
+/// Replace `field` with `value`, unless `value` is the default
+fn override_with<T: PartialEq>(field: &mut T, value: T, default: &T) {
+    if value != *default {
+        *field = value;
+    }
 }
 
 impl std::fmt::Display for AO3QueryBuilder {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "Query:")?;
+        if self.page != 1 {
+            writeln!(f, "\tfrom page: {}", self.page)?
+        }
         if self.title.is_included() {
             writeln!(f, "\ttitle: {}", self.title)?
         }
@@ -1017,8 +2364,11 @@ impl std::fmt::Display for AO3QueryBuilder {
         if self.crossover_status.is_included() {
             writeln!(f, "\tcrossover: {}", self.crossover_status)?
         }
-        if self.is_single_chapter.is_included() {
-            writeln!(f, "\tis single chapter: {}", self.is_single_chapter())?;
+        if let Some(is_single_chapter) = self.is_single_chapter {
+            writeln!(f, "\tis single chapter: {}", is_single_chapter)?;
+        }
+        if self.language.is_included() {
+            writeln!(f, "\tlanguage: {}", self.language)?
         }
         if self.word_count.is_included() {
             writeln!(f, "\tword count: {}", self.word_count)?
@@ -1044,6 +2394,12 @@ impl std::fmt::Display for AO3QueryBuilder {
         if self.additional_tags.is_included() {
             writeln!(f, "\tadditional tags: {}", self.additional_tags)?
         }
+        if self.excluded_tags.is_included() {
+            writeln!(f, "\texcluded tags: {}", self.excluded_tags)?
+        }
+        if self.filter_ids.is_included() {
+            writeln!(f, "\ttag ids: {}", self.filter_ids)?
+        }
         if self.hits.is_included() {
             writeln!(f, "\thits: {}", self.hits)?
         }
@@ -1058,6 +2414,9 @@ impl std::fmt::Display for AO3QueryBuilder {
         }
         writeln!(f, "\tSort by: {}", self.sort_by)?;
         writeln!(f, "\tSort direction: {}", self.sort_direction)?;
+        for (key, value) in &self.raw_params {
+            writeln!(f, "\t{key}: {value}")?;
+        }
         std::fmt::Result::Ok(())
     }
 }
@@ -1075,4 +2434,419 @@ mod tests {
         println!("{}", q);
         println!("{:?}", q.send().await.unwrap());
     }
+
+    #[test]
+    fn test_merge() {
+        let base = AO3QueryBuilder::new()
+            .push_fandom("Good Omens")
+            .push_additional_tag("Fluff")
+            .only_completed()
+            .set_sort_by(SortBy::Kudos);
+        let merged = base.clone().merge(
+            AO3QueryBuilder::new()
+                .push_fandom("Hollow Knight")
+                .set_search_limit(40),
+        );
+        assert_eq!(merged.get_fandoms(), "[ Hollow Knight ]");
+        assert_eq!(merged.limit, 40);
+        assert_eq!(merged.additional_tags, base.additional_tags);
+        assert_eq!(merged.completion_status, CompletionStatus::OnlyCompleted);
+        assert_eq!(merged.sort_by, SortBy::Kudos);
+
+        assert_eq!(base.clone().merge(AO3QueryBuilder::new()), base);
+        assert!(!base.clone().clear_tags().additional_tags.is_included());
+        assert_eq!(
+            base.clone().clear_fandoms().get_fandoms(),
+            AO3QueryBuilder::new().get_fandoms()
+        );
+        assert_eq!(base.reset(), AO3QueryBuilder::new());
+    }
+
+    #[test]
+    fn test_diff() {
+        let base = AO3QueryBuilder::new()
+            .push_fandom("Good Omens")
+            .set_rating(Rating::Explicit);
+        let changed = base
+            .clone()
+            .merge(AO3QueryBuilder::new().push_fandom("Hollow Knight"))
+            .set_kudos(NumericalValueRange::MoreThan(10));
+        assert_eq!(
+            base.diff(&changed),
+            vec![
+                QueryChange {
+                    field: "fandoms",
+                    before: "[ Good Omens ]".to_string(),
+                    after: "[ Hollow Knight ]".to_string(),
+                },
+                QueryChange {
+                    field: "kudos",
+                    before: "None".to_string(),
+                    after: "More than 10".to_string(),
+                },
+            ]
+        );
+        assert!(base.diff(&base).is_empty());
+    }
+
+    #[test]
+    fn test_multi_select() {
+        let categories = (Category::FF | Category::MM) + Category::FF;
+        assert_eq!(
+            categories.iter().collect::<Vec<_>>(),
+            [&Category::FF, &Category::MM]
+        );
+        let query = AO3QueryBuilder::new()
+            .set_categories(categories.clone())
+            .push_category(Category::MM)
+            .set_archive_warnings(
+                ArchiveWarning::Underage | ArchiveWarning::Underage | ArchiveWarning::RapeNonCon,
+            )
+            .add_archive_warning(ArchiveWarning::RapeNonCon);
+        assert_eq!(query.categories.len(), 2);
+        assert_eq!(query.archive_warnings.to_query_value(), ["20", "19"]);
+
+        // the same values in another order
+        let state = std::collections::hash_map::RandomState::new();
+        let hash = |select: &MultiSelect<Category>| {
+            use std::hash::BuildHasher;
+            state.hash_one(select)
+        };
+        let reversed = Category::MM | Category::FF;
+        assert_eq!(categories, reversed);
+        assert_eq!(hash(&categories), hash(&reversed));
+        assert_ne!(categories, Category::MM | Category::Gen);
+        assert_ne!(categories, MultiSelect::from(Category::MM));
+    }
+
+    #[test]
+    fn test_encoded_values() {
+        let query = AO3QueryBuilder::new()
+            .set_any_field("C++ & C#")
+            .push_fandom("Kill la Kill & Friends")
+            .push_excluded_tag("#1 Fan")
+            .add_raw_param("work_search[words_from]", "1+1&page=2");
+        let path = query.create_path(1);
+        assert!(path.contains("work_search[query]=C%2B%2B%20%26%20C%23&"));
+        assert!(path.contains("work_search[fandom_names]=Kill%20la%20Kill%20%26%20Friends&"));
+        assert!(path.contains("work_search[excluded_tag_names]=%231%20Fan&"));
+        assert!(path.ends_with("&work_search[words_from]=1%2B1%26page%3D2"));
+        // read back as they were
+        let read_back = AO3QueryBuilder::from_url(&query.create_url(1));
+        assert!(matches!(
+            read_back,
+            Err(Error::UnrecognizedParams(params)) if params == ["work_search[words_from]=1+1&page=2"]
+        ));
+        let query = query.clear_raw_params();
+        assert_eq!(
+            AO3QueryBuilder::from_url(&query.create_url(1)).unwrap(),
+            query
+        );
+    }
+
+    #[test]
+    fn test_excluded_tags() {
+        let query = AO3QueryBuilder::new()
+            .set_excluded_tags(["Major Character Death"])
+            .push_excluded_tag("Unhappy Ending");
+        assert!(query.create_path(1).contains(
+            "work_search[excluded_tag_names]=Major%20Character%20Death,Unhappy%20Ending"
+        ));
+        assert!(!query
+            .clear_excluded_tags()
+            .create_path(1)
+            .contains("excluded_tag_names"));
+    }
+
+    #[test]
+    fn test_relationships_and_comments() {
+        let path = AO3QueryBuilder::new()
+            .push_relationship("Aziraphale/Crowley")
+            .min_comments(5)
+            .create_path(1);
+        assert!(path.contains("work_search[relationship_names]=Aziraphale%2FCrowley&"));
+        assert!(path.contains("work_search[comments_count]=%3E%204&"));
+    }
+
+    #[test]
+    fn test_language() {
+        let query = AO3QueryBuilder::new().set_language(Language::English);
+        assert!(query
+            .create_path(1)
+            .contains("work_search[language_id]=en&"));
+        let query = query.set_language(Language::Other("tlh".to_string()));
+        assert!(query
+            .create_path(1)
+            .contains("work_search[language_id]=tlh&"));
+        assert!(!AO3QueryBuilder::new()
+            .create_path(1)
+            .contains("language_id"));
+    }
+
+    #[test]
+    fn test_mut_setters() {
+        let fandom = Some("Good Omens");
+        let mut query = AO3QueryBuilder::new();
+        query.set_any_field_mut("horror").only_completed_mut();
+        if let Some(fandom) = fandom {
+            query.push_fandom_mut(fandom);
+        }
+        assert_eq!(
+            query,
+            AO3QueryBuilder::new()
+                .set_any_field("horror")
+                .only_completed()
+                .push_fandom("Good Omens")
+        );
+        query.reset_mut();
+        assert_eq!(query, AO3QueryBuilder::new());
+    }
+
+    #[test]
+    fn test_hash() {
+        let horror = AO3QueryBuilder::new()
+            .set_any_field("horror")
+            .set_date_range(DateRange::since(chrono::NaiveDate::MIN))
+            .set_rating(Rating::Mature);
+        let mut cached = std::collections::HashMap::new();
+        cached.insert(horror.clone(), 1);
+        cached.insert(
+            horror
+                .clone()
+                .set_page_delay(std::time::Duration::from_secs(1)),
+            2,
+        );
+        cached.insert(horror.clone(), 3);
+        assert_eq!(cached.len(), 2);
+        assert_eq!(cached[&horror], 3);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let query = AO3QueryBuilder::new()
+            .set_date_range(DateRange::LessThan(2, Period::Weeks))
+            .set_kudos(NumericalValueRange::MoreThan(100))
+            .set_rating(Rating::General)
+            .set_categories(Category::FF | Category::Gen)
+            .push_fandom("Good Omens")
+            .set_language(Language::Other("tlh".to_string()));
+        let json = serde_json::to_string(&query).unwrap();
+        assert_eq!(
+            serde_json::from_str::<AO3QueryBuilder>(&json).unwrap(),
+            query
+        );
+        // presets written by hand don't need every field
+        let preset: AO3QueryBuilder =
+            serde_json::from_str(r#"{ "any_field": "horror", "sort_by": "Kudos" }"#).unwrap();
+        assert_eq!(
+            preset,
+            AO3QueryBuilder::new()
+                .set_any_field("horror")
+                .set_sort_by(SortBy::Kudos)
+        );
+        let twice: AO3QueryBuilder =
+            serde_json::from_str(r#"{ "categories": ["FF", "MM", "FF"] }"#).unwrap();
+        assert_eq!(
+            twice.categories.iter().collect::<Vec<_>>(),
+            [&Category::FF, &Category::MM]
+        );
+        assert!(serde_json::from_str::<AO3QueryBuilder>(r#"{ "page": 0 }"#).is_err());
+    }
+
+    #[test]
+    fn test_numerical_range_from() {
+        assert_eq!(
+            NumericalValueRange::from(100..500),
+            NumericalValueRange::Between(100, 499)
+        );
+        assert_eq!(
+            NumericalValueRange::from(100..=500),
+            NumericalValueRange::Between(100, 500)
+        );
+        assert_eq!(
+            NumericalValueRange::from(1000..),
+            NumericalValueRange::MoreThan(999)
+        );
+        assert_eq!(NumericalValueRange::from(0..), NumericalValueRange::None);
+        assert_eq!(
+            NumericalValueRange::from(..=10),
+            NumericalValueRange::LessThan(11)
+        );
+        assert_eq!(NumericalValueRange::from(..), NumericalValueRange::None);
+        assert_eq!(
+            NumericalValueRange::from(7),
+            NumericalValueRange::Exactly(7)
+        );
+        // empty ranges have no count in them
+        assert_eq!(
+            NumericalValueRange::from(5..5),
+            NumericalValueRange::LessThan(0)
+        );
+        assert_eq!(
+            NumericalValueRange::from(0..0),
+            NumericalValueRange::LessThan(0)
+        );
+        #[allow(clippy::reversed_empty_ranges)]
+        let reversed = NumericalValueRange::from(5..=3);
+        assert_eq!(reversed, NumericalValueRange::LessThan(0));
+        assert_eq!(
+            NumericalValueRange::from(5..6),
+            NumericalValueRange::Exactly(5)
+        );
+    }
+
+    #[test]
+    fn test_absolute_dates() {
+        let day = |d| chrono::NaiveDate::from_ymd_opt(2023, 3, d).unwrap();
+        let today = day(20);
+        assert_eq!(
+            DateRange::since(day(10)).relative_to(today),
+            DateRange::LessThan(11, Period::Days)
+        );
+        assert_eq!(
+            DateRange::before(day(10)).relative_to(today),
+            DateRange::MoreThan(11, Period::Days)
+        );
+        assert_eq!(
+            DateRange::between_dates(day(15), day(5)).relative_to(today),
+            DateRange::Between(5, 15, Period::Days)
+        );
+        let weeks = DateRange::Exactly(2, Period::Weeks);
+        assert_eq!(weeks.relative_to(today), weeks);
+
+        let yesterday = chrono::Utc::now().date_naive() - chrono::Duration::days(1);
+        let query = AO3QueryBuilder::new().set_date_range(DateRange::since(yesterday));
+        assert!(query
+            .create_path(1)
+            .contains("work_search[revised_at]=%3C%202%20days%20ago"));
+        let query = AO3QueryBuilder::new().set_date_range(DateRange::before(yesterday));
+        assert!(query
+            .create_path(1)
+            .contains("work_search[revised_at]=%3E%202%20days%20ago"));
+    }
+
+    #[test]
+    fn test_range_helpers() {
+        let query = AO3QueryBuilder::new()
+            .word_count_between(1000, 5000)
+            .min_kudos(100)
+            .max_hits(10);
+        assert_eq!(query.word_count, NumericalValueRange::Between(1000, 5000));
+        assert_eq!(query.kudos, NumericalValueRange::MoreThan(99));
+        assert_eq!(query.hits, NumericalValueRange::LessThan(11));
+        assert_eq!(
+            AO3QueryBuilder::new().min_bookmarks(0).bookmarks,
+            NumericalValueRange::None
+        );
+        // bounds the wrong way around
+        assert_eq!(
+            AO3QueryBuilder::new()
+                .word_count_between(5000, 1000)
+                .word_count,
+            NumericalValueRange::Between(1000, 5000)
+        );
+
+        let mut query = AO3QueryBuilder::new();
+        query.min_kudos_mut(100).hits_between_mut(20, 10);
+        assert_eq!(query.kudos, NumericalValueRange::MoreThan(99));
+        assert_eq!(query.hits, NumericalValueRange::Between(10, 20));
+    }
+
+    #[test]
+    fn test_single_chapter() {
+        let path = |query: AO3QueryBuilder| query.create_path(1);
+        assert!(!path(AO3QueryBuilder::new()).contains("single_chapter"));
+        assert!(path(AO3QueryBuilder::new().single_chapter(true))
+            .contains("work_search[single_chapter]=1"));
+        assert!(path(AO3QueryBuilder::new().single_chapter(false))
+            .contains("work_search[single_chapter]=0"));
+        assert!(!path(
+            AO3QueryBuilder::new()
+                .single_chapter(true)
+                .ignore_single_chapter()
+        )
+        .contains("single_chapter"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[tokio::test]
+    async fn test_resolve_tag_ids() {
+        let feed = |id| format!(r#"<a href="/tags/{id}/feed.atom">Subscribe</a>"#);
+        let mock = crate::testing::MockAo3::start()
+            .await
+            .unwrap()
+            .page(
+                "/autocomplete/fandom?term=hollow%20knight",
+                r#"[{"id":"Hollow Knight (Video Game)","name":"Hollow Knight (Video Game)"}]"#,
+            )
+            .page("/autocomplete/character?term=Hornet", "[]")
+            .page("/tags/Hollow%20Knight%20(Video%20Game)/works", &feed(123))
+            .page("/tags/Hornet/works", &feed(456));
+        let query = AO3QueryBuilder::new()
+            .push_fandom("hollow knight")
+            .push_character("Hornet")
+            .resolve_tag_ids(&mock.client())
+            .await
+            .unwrap();
+        assert_eq!(query, AO3QueryBuilder::new().set_filter_ids(["123", "456"]));
+        assert!(query
+            .create_path(1)
+            .contains("work_search[filter_ids][]=123&work_search[filter_ids][]=456"));
+    }
+
+    #[test]
+    fn test_creators() {
+        let query = AO3QueryBuilder::new()
+            .push_author("Starchild719")
+            .push_exact_author("astolat");
+        assert!(query
+            .create_path(1)
+            .contains("work_search[creators]=Starchild719%20OR%20%22astolat%22&"));
+        let name = String::from("astolat");
+        let mut owned = AO3QueryBuilder::new().push_author("Starchild719");
+        owned.push_exact_author_mut(name);
+        assert_eq!(owned, query);
+    }
+
+    #[tokio::test]
+    async fn test_send_with() {
+        let query = AO3QueryBuilder::new()
+            .set_any_field("horror")
+            .set_search_limit(25);
+        let search = include_str!("parse_test/search.html");
+        let mock = crate::testing::MockAo3::start()
+            .await
+            .unwrap()
+            .page(&query.create_path(1), search)
+            .page(&query.create_path(2), search);
+        let client = mock.client();
+        assert_eq!(query.clone().send_with(&client).await.unwrap().len(), 25);
+        let results = query.clone().search_with(&client).await.unwrap();
+        assert_eq!((results.page, results.total), (2, Some(10_066_024)));
+        let streamed: Vec<_> = query.stream_with(&client).take(30).collect().await;
+        assert_eq!(streamed.len(), 30);
+        assert_eq!(mock.requests().len(), 6);
+    }
+
+    #[test]
+    fn test_dry_run() {
+        let requests = AO3QueryBuilder::new()
+            .set_any_field("horror")
+            .set_search_limit(25)
+            .dry_run()
+            .unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests.iter().all(|r| r.method == "GET"));
+        assert!(requests[0].url.contains("horror"));
+        assert!(requests[1].url.contains("page=2"));
+
+        let requests = AO3QueryBuilder::new()
+            .set_any_field("horror")
+            .set_page(3)
+            .dry_run()
+            .unwrap();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].url.contains("page=3"));
+    }
 }
@@ -0,0 +1,94 @@
+use crate::client::check_status;
+use crate::parse::{check_unavailable, parse_page_count};
+use crate::search::Search;
+
+use super::*;
+
+impl AO3QueryBuilder {
+    /// [send](AO3QueryBuilder::send) without async, for code that doesn't run in a tokio runtime
+    ///
+    /// Pages are fetched with [reqwest::blocking], one after the other. Like the blocking
+    /// client of reqwest it panics when called from async code, use [send](AO3QueryBuilder::send) there.
+    /// ```rust,no_run
+    /// use ao3rs::query::AO3QueryBuilder;
+    ///
+    /// for work in AO3QueryBuilder::new().set_any_field("horror").send_blocking()? {
+    ///     println!("{} by {}", work.title, work.authors.join(", "));
+    /// }
+    /// # Ok::<(), ao3rs::Error>(())
+    /// ```
+    pub fn send_blocking(self) -> Result<Vec<AO3Work>, Error> {
+        Ok(self.search_blocking()?.works)
+    }
+
+    /// [search](AO3QueryBuilder::search) without async, see [send_blocking](AO3QueryBuilder::send_blocking)
+    pub fn search_blocking(self) -> Result<SearchResults, Error> {
+        self.fetch_blocking(&reqwest::blocking::Client::new(), BASE_AO3_URL)
+    }
+
+    fn fetch_blocking(
+        &self,
+        client: &reqwest::blocking::Client,
+        base_url: &str,
+    ) -> Result<SearchResults, Error> {
+        let mut results = SearchResults {
+            page: self.page,
+            url: self.create_url(self.page),
+            ..Default::default()
+        };
+        for page in self.pages_needed() {
+            let url = format!("{base_url}{}", self.create_path(page));
+            let html = check_response(client.get(&url).send()?)?.text()?;
+            let parsed = self
+                .parse_page(&html)
+                .and_then(|works| Ok((works, parse_page_count(&html)?, self.parse_total(&html)?)));
+            let (works, page_count, total) = parsed.map_err(|err| err.in_page(&url, &html))?;
+            results.works.extend(works);
+            results.page = page;
+            results.page_count = page_count;
+            results.total = total;
+        }
+        results.works.truncate(self.limit);
+        Ok(results)
+    }
+}
+
+/// [check_response](crate::client::check_response) for the blocking client
+fn check_response(resp: reqwest::blocking::Response) -> Result<reqwest::blocking::Response, Error> {
+    match check_status(&resp)? {
+        Some(err) => {
+            check_unavailable(&resp.text().unwrap_or_default())?;
+            Err(err.into())
+        }
+        None => Ok(resp),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_send_blocking() {
+        let query = AO3QueryBuilder::new()
+            .set_any_field("horror")
+            .set_search_limit(25);
+        let search = include_str!("../parse_test/search.html");
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let mock = runtime.block_on(async {
+            crate::testing::MockAo3::start()
+                .await
+                .unwrap()
+                .page(&query.create_path(1), search)
+                .page(&query.create_path(2), search)
+        });
+        let client = reqwest::blocking::Client::new();
+        let results = query.fetch_blocking(&client, &mock.url()).unwrap();
+        assert_eq!(results.len(), 25);
+        assert_eq!((results.page, results.total), (2, Some(10_066_024)));
+        assert_eq!(mock.requests().len(), 2);
+
+        let missing = query.set_page(3).fetch_blocking(&client, &mock.url());
+        assert!(matches!(missing, Err(Error::NotFound)));
+    }
+}
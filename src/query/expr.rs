@@ -0,0 +1,189 @@
+use std::fmt::Display;
+
+/// A search for the [any field](super::AO3QueryBuilder::set_any_field) box, written out in
+/// the syntax AO3 reads there
+///
+/// Words and phrases are escaped and quoted as needed, groups get parentheses when they
+/// need them. Everything joined with [and](QueryExpr::and) has to match, which AO3 does
+/// for words separated by spaces too.
+/// ```rust
+/// use ao3rs::query::{AO3QueryBuilder, QueryExpr};
+///
+/// let expr = QueryExpr::phrase("time travel").and(!QueryExpr::term("angst"));
+/// assert_eq!(expr.to_string(), r#""time travel" -angst"#);
+///
+/// let expr = QueryExpr::field("title", QueryExpr::wildcard("salvat*").or("redemption"));
+/// assert_eq!(expr.to_string(), "title:(salvat* OR redemption)");
+///
+/// let query = AO3QueryBuilder::new().set_any_field(expr);
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum QueryExpr {
+    /// A single word, characters with a meaning in the syntax are escaped
+    Term(String),
+
+    /// A word where `*` stands for any characters and `?` for any one character
+    Wildcard(String),
+
+    /// Words that have to come in this order, quoted
+    Phrase(String),
+
+    /// Only look in one field of the works, like `title` or `summary`
+    Field(String, Box<QueryExpr>),
+
+    /// Every one has to match
+    And(Vec<QueryExpr>),
+
+    /// Any one has to match
+    Or(Vec<QueryExpr>),
+
+    /// Has to not match
+    Not(Box<QueryExpr>),
+}
+
+impl QueryExpr {
+    pub fn term(term: impl Into<String>) -> Self {
+        QueryExpr::Term(term.into())
+    }
+
+    pub fn wildcard(pattern: impl Into<String>) -> Self {
+        QueryExpr::Wildcard(pattern.into())
+    }
+
+    pub fn phrase(phrase: impl Into<String>) -> Self {
+        QueryExpr::Phrase(phrase.into())
+    }
+
+    pub fn field(field: impl Into<String>, expr: impl Into<QueryExpr>) -> Self {
+        QueryExpr::Field(field.into(), Box::new(expr.into()))
+    }
+
+    /// Match both this and `other`
+    pub fn and(self, other: impl Into<QueryExpr>) -> Self {
+        match (self, other.into()) {
+            (QueryExpr::And(mut all), QueryExpr::And(more)) => {
+                all.extend(more);
+                QueryExpr::And(all)
+            }
+            (QueryExpr::And(mut all), other) => {
+                all.push(other);
+                QueryExpr::And(all)
+            }
+            (expr, other) => QueryExpr::And(vec![expr, other]),
+        }
+    }
+
+    /// Match either this or `other`
+    pub fn or(self, other: impl Into<QueryExpr>) -> Self {
+        match (self, other.into()) {
+            (QueryExpr::Or(mut any), QueryExpr::Or(more)) => {
+                any.extend(more);
+                QueryExpr::Or(any)
+            }
+            (QueryExpr::Or(mut any), other) => {
+                any.push(other);
+                QueryExpr::Or(any)
+            }
+            (expr, other) => QueryExpr::Or(vec![expr, other]),
+        }
+    }
+
+    /// Written out in parentheses when it's more than one thing
+    fn grouped(&self) -> String {
+        match self {
+            QueryExpr::And(exprs) | QueryExpr::Or(exprs) if exprs.len() > 1 => format!("({self})"),
+            _ => self.to_string(),
+        }
+    }
+}
+
+/// Escape the characters that mean something in the syntax, except the ones in `keep`
+fn escape(text: &str, keep: &[char]) -> String {
+    // a lone operator would be read as one, quoting it makes it a word
+    if ["AND", "OR", "NOT"].contains(&text) {
+        return format!("\"{text}\"");
+    }
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if !keep.contains(&c) && (c.is_whitespace() || "+-=&|><!(){}[]^\"~*?:\\/".contains(c)) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+impl Display for QueryExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueryExpr::Term(term) => write!(f, "{}", escape(term, &[])),
+            QueryExpr::Wildcard(pattern) => write!(f, "{}", escape(pattern, &['*', '?'])),
+            QueryExpr::Phrase(phrase) => {
+                write!(
+                    f,
+                    "\"{}\"",
+                    phrase.replace('\\', "\\\\").replace('"', "\\\"")
+                )
+            }
+            QueryExpr::Field(field, expr) => write!(f, "{field}:{}", expr.grouped()),
+            QueryExpr::And(exprs) => {
+                let exprs: Vec<_> = exprs.iter().map(QueryExpr::grouped).collect();
+                write!(f, "{}", exprs.join(" "))
+            }
+            QueryExpr::Or(exprs) => {
+                let exprs: Vec<_> = exprs.iter().map(QueryExpr::grouped).collect();
+                write!(f, "{}", exprs.join(" OR "))
+            }
+            QueryExpr::Not(expr) => write!(f, "-{}", expr.grouped()),
+        }
+    }
+}
+
+impl std::ops::Not for QueryExpr {
+    type Output = QueryExpr;
+
+    fn not(self) -> QueryExpr {
+        match self {
+            QueryExpr::Not(expr) => *expr,
+            expr => QueryExpr::Not(Box::new(expr)),
+        }
+    }
+}
+
+impl From<&str> for QueryExpr {
+    fn from(term: &str) -> Self {
+        QueryExpr::term(term)
+    }
+}
+
+impl From<QueryExpr> for String {
+    fn from(expr: QueryExpr) -> Self {
+        expr.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_expr() {
+        let expr = QueryExpr::phrase("time travel")
+            .and(!QueryExpr::term("angst"))
+            .and(QueryExpr::term("fluff").or("humor"));
+        assert_eq!(expr.to_string(), r#""time travel" -angst (fluff OR humor)"#);
+
+        let expr = QueryExpr::field("creators", "a/b").or(!QueryExpr::term("x").and("y"));
+        assert_eq!(expr.to_string(), r"creators:a\/b OR -(x y)");
+
+        assert_eq!(QueryExpr::term("AND").to_string(), r#""AND""#);
+        assert_eq!(QueryExpr::term("c++ and").to_string(), r"c\+\+\ and");
+        assert_eq!(QueryExpr::wildcard("c?t*:").to_string(), r"c?t*\:");
+        assert_eq!(
+            QueryExpr::phrase(r#"say "hi""#).to_string(),
+            r#""say \"hi\"""#
+        );
+        assert_eq!(!!QueryExpr::term("angst"), QueryExpr::term("angst"));
+    }
+}
@@ -0,0 +1,374 @@
+use super::*;
+
+impl AO3QueryBuilder {
+    /// Read back a work search url, like one copied from the browser
+    ///
+    /// Every parameter of the search form the builder has a setter for is read, empty ones
+    /// are skipped like AO3 does. Parameters it doesn't know, or with values it can't read,
+    /// make it fail with [UnrecognizedParams](Error::UnrecognizedParams) listing all of them,
+    /// they can be sent anyway with [add_raw_param](AO3QueryBuilder::add_raw_param).
+    /// ```rust
+    /// use ao3rs::query::{AO3QueryBuilder, Language};
+    ///
+    /// let query = AO3QueryBuilder::from_url(
+    ///     "https://archiveofourown.org/works/search?work_search[query]=horror&work_search[language_id]=en",
+    /// )?;
+    /// assert_eq!(query, AO3QueryBuilder::new().set_any_field("horror").set_language(Language::English));
+    /// # Ok::<(), ao3rs::Error>(())
+    /// ```
+    pub fn from_url(url: &str) -> Result<Self, Error> {
+        let not_search = || Error::InvalidInput(format!("Not the url of a work search: {url}"));
+        let parsed = if url.contains("://") {
+            reqwest::Url::parse(url)
+        } else {
+            reqwest::Url::parse(&format!("https://{url}"))
+        }
+        .map_err(|_| not_search())?;
+        let is_ao3 = parsed.host_str().is_some_and(|host| {
+            host == "archiveofourown.org" || host.ends_with(".archiveofourown.org")
+        });
+        if !is_ao3 || parsed.path().trim_end_matches('/') != "/works/search" {
+            return Err(not_search());
+        }
+
+        let mut query = AO3QueryBuilder::new();
+        let mut unrecognized = vec![];
+        for (key, value) in parsed.query_pairs() {
+            let value = value.trim();
+            if !query.read_param(&key, value) {
+                unrecognized.push(format!("{key}={value}"));
+            }
+        }
+        if !unrecognized.is_empty() {
+            return Err(Error::UnrecognizedParams(unrecognized));
+        }
+        Ok(query)
+    }
+
+    /// Set what the parameter `key` says, false when the parameter isn't known or its value can't be read
+    fn read_param(&mut self, key: &str, value: &str) -> bool {
+        let Some(field) = key
+            .strip_prefix("work_search[")
+            .and_then(|key| key.strip_suffix("][]").or(key.strip_suffix(']')))
+        else {
+            return match key {
+                "page" => value.parse().map(|page| self.set_page_mut(page)).is_ok(),
+                // the search button and the form's encoding
+                "commit" | "utf8" => true,
+                _ => value.is_empty(),
+            };
+        };
+        let known = [
+            "query",
+            "title",
+            "creators",
+            "authors",
+            "revised_at",
+            "complete",
+            "crossover",
+            "single_chapter",
+            "word_count",
+            "language_id",
+            "fandom_names",
+            "rating_ids",
+            "archive_warning_ids",
+            "category_ids",
+            "character_names",
+            "relationship_names",
+            "relationship_name",
+            "freeform_names",
+            "excluded_tag_names",
+            "filter_ids",
+            "hits",
+            "kudos_count",
+            "comments_count",
+            "commets_count",
+            "bookmarks_count",
+            "sort_column",
+            "sort_direction",
+        ];
+        if value.is_empty() {
+            return known.contains(&field);
+        }
+        match field {
+            "query" => self.any_field = value.to_string(),
+            "title" => self.title = value.to_string(),
+            "creators" => {
+                let names = value.split(" OR ").map(|name| name.trim().to_string());
+                self.authors = MultiString(names.filter(|name| !name.is_empty()).collect())
+            }
+            "authors" => self.authors = split_names(value),
+            "revised_at" => return read_into(&mut self.date, parse_date_range(value)),
+            "complete" => {
+                return read_into(
+                    &mut self.completion_status,
+                    find_value(
+                        [
+                            CompletionStatus::OnlyCompleted,
+                            CompletionStatus::OnlyIncomplete,
+                        ],
+                        value,
+                    ),
+                )
+            }
+            "crossover" => {
+                return read_into(
+                    &mut self.crossover_status,
+                    find_value(
+                        [
+                            CrossoverStatus::OnlyCrossover,
+                            CrossoverStatus::OnlyNonCrossover,
+                        ],
+                        value,
+                    ),
+                )
+            }
+            "single_chapter" => {
+                return read_into(
+                    &mut self.is_single_chapter,
+                    find_value([true, false], value).map(Some),
+                )
+            }
+            "word_count" => return read_into(&mut self.word_count, parse_numerical_range(value)),
+            "language_id" => self.language = language_from_code(value),
+            "fandom_names" => self.fandoms = split_names(value),
+            "rating_ids" => {
+                return read_into(
+                    &mut self.rating,
+                    find_value(
+                        [
+                            Rating::NotRated,
+                            Rating::General,
+                            Rating::TeenAndUp,
+                            Rating::Mature,
+                            Rating::Explicit,
+                        ],
+                        value,
+                    ),
+                )
+            }
+            "archive_warning_ids" => {
+                let warning = find_value(
+                    [
+                        ArchiveWarning::CreatureChoseNotToUseArchiveWarnings,
+                        ArchiveWarning::GraphicDepictionOfViolence,
+                        ArchiveWarning::MajorCharacterDeath,
+                        ArchiveWarning::NoArchiveWarningsApply,
+                        ArchiveWarning::RapeNonCon,
+                        ArchiveWarning::Underage,
+                    ],
+                    value,
+                );
+                return warning.map(|w| self.archive_warnings.insert(w)).is_some();
+            }
+            "category_ids" => {
+                let category = find_value(
+                    [
+                        Category::FF,
+                        Category::FM,
+                        Category::Gen,
+                        Category::MM,
+                        Category::Multi,
+                        Category::Other,
+                    ],
+                    value,
+                );
+                return category.map(|c| self.categories.insert(c)).is_some();
+            }
+            "character_names" => self.characters = split_names(value),
+            "relationship_names" | "relationship_name" => self.relationships = split_names(value),
+            "freeform_names" => self.additional_tags = split_names(value),
+            "excluded_tag_names" => self.excluded_tags = split_names(value),
+            "filter_ids" => self.filter_ids.0.push(value.to_string()),
+            "hits" => return read_into(&mut self.hits, parse_numerical_range(value)),
+            "kudos_count" => return read_into(&mut self.kudos, parse_numerical_range(value)),
+            "comments_count" | "commets_count" => {
+                return read_into(&mut self.comments, parse_numerical_range(value))
+            }
+            "bookmarks_count" => {
+                return read_into(&mut self.bookmarks, parse_numerical_range(value))
+            }
+            "sort_column" => {
+                return read_into(
+                    &mut self.sort_by,
+                    find_value(
+                        [
+                            SortBy::BestMatch,
+                            SortBy::Author,
+                            SortBy::Title,
+                            SortBy::DatePosted,
+                            SortBy::DateUpdated,
+                            SortBy::WordCount,
+                            SortBy::Hits,
+                            SortBy::Kudos,
+                            SortBy::Comments,
+                            SortBy::Bookmarks,
+                        ],
+                        value,
+                    ),
+                )
+            }
+            "sort_direction" => {
+                return read_into(
+                    &mut self.sort_direction,
+                    find_value([SortDirection::Descending, SortDirection::Ascending], value),
+                )
+            }
+            _ => return false,
+        }
+        true
+    }
+}
+
+fn read_into<T>(field: &mut T, value: Option<T>) -> bool {
+    value.map(|value| *field = value).is_some()
+}
+
+/// The one of `options` with `value` as its query value
+fn find_value<T: QueryValue<Output = String>>(
+    options: impl IntoIterator<Item = T>,
+    value: &str,
+) -> Option<T> {
+    options
+        .into_iter()
+        .find(|option| option.to_query_value() == value)
+}
+
+fn split_names(value: &str) -> MultiString {
+    MultiString(
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect(),
+    )
+}
+
+fn language_from_code(code: &str) -> Language {
+    find_value(
+        [
+            Language::English,
+            Language::Spanish,
+            Language::French,
+            Language::German,
+            Language::Italian,
+            Language::Portuguese,
+            Language::Russian,
+            Language::Polish,
+            Language::Dutch,
+            Language::Chinese,
+            Language::Japanese,
+            Language::Korean,
+            Language::Vietnamese,
+            Language::Indonesian,
+        ],
+        code,
+    )
+    .unwrap_or_else(|| Language::Other(code.to_string()))
+}
+
+/// Read what [NumericalValueRange::to_query_value] writes
+fn parse_numerical_range(value: &str) -> Option<NumericalValueRange> {
+    if let Some(num) = value.strip_prefix('>') {
+        return Some(NumericalValueRange::MoreThan(num.trim().parse().ok()?));
+    }
+    if let Some(num) = value.strip_prefix('<') {
+        return Some(NumericalValueRange::LessThan(num.trim().parse().ok()?));
+    }
+    if let Some((from, to)) = value.split_once('-') {
+        return Some(NumericalValueRange::Between(
+            from.trim().parse().ok()?,
+            to.trim().parse().ok()?,
+        ));
+    }
+    Some(NumericalValueRange::Exactly(value.parse().ok()?))
+}
+
+/// Read what [DateRange::to_query_value] writes
+fn parse_date_range(value: &str) -> Option<DateRange> {
+    let (range, period) = value
+        .strip_suffix("ago")
+        .unwrap_or(value)
+        .trim()
+        .rsplit_once(' ')?;
+    let period = match period.trim_end_matches('s') {
+        "year" => Period::Years,
+        "week" => Period::Weeks,
+        "month" => Period::Months,
+        "day" => Period::Days,
+        "hour" => Period::Hours,
+        _ => return None,
+    };
+    Some(match parse_numerical_range(range.trim())? {
+        NumericalValueRange::Exactly(time) => DateRange::Exactly(time, period),
+        NumericalValueRange::MoreThan(time) => DateRange::MoreThan(time, period),
+        NumericalValueRange::LessThan(time) => DateRange::LessThan(time, period),
+        NumericalValueRange::Between(from, to) => DateRange::Between(from, to, period),
+        NumericalValueRange::None => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_url() {
+        let query = AO3QueryBuilder::new()
+            .set_any_field("horror")
+            .set_title("Salvation")
+            .set_authors(["a", "b"])
+            .push_exact_author("c d")
+            .set_date_range(DateRange::Between(1, 2, Period::Weeks))
+            .only_completed()
+            .only_non_crossover()
+            .single_chapter(true)
+            .set_word_count(NumericalValueRange::MoreThan(1000))
+            .push_fandom("Good Omens")
+            .set_rating(Rating::Mature)
+            .set_archive_warnings([ArchiveWarning::NoArchiveWarningsApply])
+            .set_categories(Category::FF | Category::Gen)
+            .push_character("Aziraphale")
+            .push_relationship("Aziraphale/Crowley")
+            .push_additional_tag("Fluff")
+            .push_excluded_tag("Angst")
+            .set_filter_ids(["123", "456"])
+            .set_language(Language::English)
+            .set_kudos(NumericalValueRange::Between(10, 20))
+            .set_comments(NumericalValueRange::LessThan(5))
+            .set_sort_by(SortBy::Kudos)
+            .set_sort_direction(SortDirection::Ascending)
+            .set_page(2);
+        assert_eq!(
+            AO3QueryBuilder::from_url(&query.create_url(2)).unwrap(),
+            query
+        );
+
+        let browser = "archiveofourown.org/works/search?commit=Search&work_search%5Bquery%5D=horror\
+            &work_search%5Btitle%5D=&work_search%5Bcomments_count%5D=%3E+5&work_search%5Blanguage_id%5D=tlh";
+        assert_eq!(
+            AO3QueryBuilder::from_url(browser).unwrap(),
+            AO3QueryBuilder::new()
+                .set_any_field("horror")
+                .set_comments(NumericalValueRange::MoreThan(5))
+                .set_language(Language::Other("tlh".to_string()))
+        );
+
+        let unknown = AO3QueryBuilder::from_url(
+            "https://archiveofourown.org/works/search?work_search[hits]=lots&work_search[mood]=cozy",
+        );
+        assert!(matches!(
+            unknown,
+            Err(Error::UnrecognizedParams(params))
+                if params == ["work_search[hits]=lots", "work_search[mood]=cozy"]
+        ));
+        // there is no page before the first
+        let first = AO3QueryBuilder::from_url("archiveofourown.org/works/search?page=0").unwrap();
+        assert_eq!(first.get_page(), 1);
+        assert!(matches!(
+            AO3QueryBuilder::from_url("https://archiveofourown.org/works/45221314"),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+}
@@ -0,0 +1,345 @@
+use chrono::NaiveDate;
+
+use crate::client::tag_path;
+use crate::search::Search;
+
+use super::*;
+
+/// Sort options of a tag's works, the search's [SortBy] without best match
+#[derive(Debug, Default, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TagWorksSortBy {
+    Author,
+    Title,
+    DatePosted,
+    #[default]
+    DateUpdated,
+    WordCount,
+    Hits,
+    Kudos,
+    Comments,
+    Bookmarks,
+}
+
+impl QueryValue for TagWorksSortBy {
+    type Output = String;
+
+    fn to_query_value(&self) -> Self::Output {
+        match self {
+            TagWorksSortBy::Author => String::from("authors_to_sort_on"),
+            TagWorksSortBy::Title => String::from("title_to_sort_on"),
+            TagWorksSortBy::DatePosted => String::from("created_at"),
+            TagWorksSortBy::DateUpdated => String::from("revised_at"),
+            TagWorksSortBy::WordCount => String::from("word_count"),
+            TagWorksSortBy::Hits => String::from("hits"),
+            TagWorksSortBy::Kudos => String::from("kudos_count"),
+            TagWorksSortBy::Comments => String::from("comments_count"),
+            TagWorksSortBy::Bookmarks => String::from("bookmarks_count"),
+        }
+    }
+
+    fn is_included(&self) -> bool {
+        true
+    }
+}
+
+impl std::fmt::Display for TagWorksSortBy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TagWorksSortBy::Author => write!(f, "Creator"),
+            TagWorksSortBy::Title => write!(f, "Title"),
+            TagWorksSortBy::DatePosted => write!(f, "Date Posted"),
+            TagWorksSortBy::DateUpdated => write!(f, "Date Updated"),
+            TagWorksSortBy::WordCount => write!(f, "Word Count"),
+            TagWorksSortBy::Hits => write!(f, "Hits"),
+            TagWorksSortBy::Kudos => write!(f, "Kudos"),
+            TagWorksSortBy::Comments => write!(f, "Comments"),
+            TagWorksSortBy::Bookmarks => write!(f, "Bookmarks"),
+        }
+    }
+}
+
+/// Filter the works of a tag, like the sidebar of `/tags/<tag>/works`
+///
+/// Unlike [AO3QueryBuilder] this browses the works of one tag, synonyms included.
+/// Other tags are included or excluded by their id, see
+/// [fetch_tag_id](crate::client::AO3Client::fetch_tag_id).
+/// ```rust,no_run
+/// # async fn example() -> Result<(), ao3rs::Error> {
+/// use ao3rs::models::TagKind;
+/// use ao3rs::query::{TagWorksFilterBuilder, TagWorksSortBy};
+///
+/// let works = TagWorksFilterBuilder::new("Good Omens (TV)")
+///     .include(TagKind::Freeform, "110")
+///     .exclude(TagKind::Freeform, "163")
+///     .set_words_from(10_000)
+///     .only_completed()
+///     .set_sort_by(TagWorksSortBy::Kudos)
+///     .send()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TagWorksFilterBuilder {
+    tag: String,
+
+    /// Query Limit
+    limit: usize,
+
+    /// Ids of the tags works must have, by the kind of filter they're in
+    included: Vec<(TagKind, String)>,
+
+    /// Ids of the tags works must not have
+    excluded: Vec<(TagKind, String)>,
+
+    words_from: Option<usize>,
+    words_to: Option<usize>,
+
+    /// Range of the dates works were last updated on
+    date_from: Option<NaiveDate>,
+    date_to: Option<NaiveDate>,
+
+    completion_status: CompletionStatus,
+    crossover_status: CrossoverStatus,
+    sort_by: TagWorksSortBy,
+}
+
+impl TagWorksFilterBuilder {
+    pub fn new(tag: impl Into<String>) -> Self {
+        TagWorksFilterBuilder {
+            tag: tag.into(),
+            limit: 20,
+            included: vec![],
+            excluded: vec![],
+            words_from: None,
+            words_to: None,
+            date_from: None,
+            date_to: None,
+            completion_status: Default::default(),
+            crossover_status: Default::default(),
+            sort_by: Default::default(),
+        }
+    }
+
+    pub fn get_tag(&self) -> &str {
+        &self.tag
+    }
+
+    pub fn set_search_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    pub fn get_search_limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Only works that also have the tag with id `id`, `kind` is the filter it's in on the sidebar
+    pub fn include(mut self, kind: TagKind, id: impl Into<String>) -> Self {
+        self.included.push((kind, id.into()));
+        self
+    }
+
+    /// Leave out works that have the tag with id `id`
+    pub fn exclude(mut self, kind: TagKind, id: impl Into<String>) -> Self {
+        self.excluded.push((kind, id.into()));
+        self
+    }
+
+    pub fn get_included(&self) -> &[(TagKind, String)] {
+        &self.included
+    }
+
+    pub fn get_excluded(&self) -> &[(TagKind, String)] {
+        &self.excluded
+    }
+
+    /// Only works with at least `words` words
+    pub fn set_words_from(mut self, words: usize) -> Self {
+        self.words_from = Some(words);
+        self
+    }
+
+    /// Only works with at most `words` words
+    pub fn set_words_to(mut self, words: usize) -> Self {
+        self.words_to = Some(words);
+        self
+    }
+
+    /// Only works updated on `date` or after
+    pub fn set_date_from(mut self, date: NaiveDate) -> Self {
+        self.date_from = Some(date);
+        self
+    }
+
+    /// Only works updated on `date` or before
+    pub fn set_date_to(mut self, date: NaiveDate) -> Self {
+        self.date_to = Some(date);
+        self
+    }
+
+    pub fn only_completed(mut self) -> Self {
+        self.completion_status = CompletionStatus::OnlyCompleted;
+        self
+    }
+
+    pub fn only_incomplete(mut self) -> Self {
+        self.completion_status = CompletionStatus::OnlyIncomplete;
+        self
+    }
+
+    pub fn ignore_completion(mut self) -> Self {
+        self.completion_status = CompletionStatus::Ignore;
+        self
+    }
+
+    pub fn get_completion_status(&self) -> &CompletionStatus {
+        &self.completion_status
+    }
+
+    pub fn only_crossover(mut self) -> Self {
+        self.crossover_status = CrossoverStatus::OnlyCrossover;
+        self
+    }
+
+    pub fn only_non_crossover(mut self) -> Self {
+        self.crossover_status = CrossoverStatus::OnlyNonCrossover;
+        self
+    }
+
+    pub fn ignore_crossover(mut self) -> Self {
+        self.crossover_status = CrossoverStatus::Ignore;
+        self
+    }
+
+    /// Sort the works by `sort_by`, they're sorted by when they were updated by default
+    pub fn set_sort_by(mut self, sort_by: TagWorksSortBy) -> Self {
+        self.sort_by = sort_by;
+        self
+    }
+
+    pub fn get_sort_by(&self) -> &TagWorksSortBy {
+        &self.sort_by
+    }
+
+    /// Path of the `page`th page of the works, relative to the root of AO3
+    pub(crate) fn create_path(&self, page: usize) -> String {
+        let mut q = format!(
+            "/tags/{}/works?work_search[sort_column]={}",
+            tag_path(&self.tag),
+            self.sort_by.to_query_value()
+        );
+        for (kind, id) in &self.included {
+            q.push_str(&format!("&include_work_search[{}_ids][]={id}", kind.name()));
+        }
+        for (kind, id) in &self.excluded {
+            q.push_str(&format!("&exclude_work_search[{}_ids][]={id}", kind.name()));
+        }
+        if self.crossover_status.is_included() {
+            q.push_str(&format!(
+                "&work_search[crossover]={}",
+                self.crossover_status.to_query_value()
+            ));
+        }
+        if self.completion_status.is_included() {
+            q.push_str(&format!(
+                "&work_search[complete]={}",
+                self.completion_status.to_query_value()
+            ));
+        }
+        if let Some(words) = self.words_from {
+            q.push_str(&format!("&work_search[words_from]={words}"));
+        }
+        if let Some(words) = self.words_to {
+            q.push_str(&format!("&work_search[words_to]={words}"));
+        }
+        if let Some(date) = self.date_from {
+            q.push_str(&format!(
+                "&work_search[date_from]={}",
+                date.format("%Y-%m-%d")
+            ));
+        }
+        if let Some(date) = self.date_to {
+            q.push_str(&format!(
+                "&work_search[date_to]={}",
+                date.format("%Y-%m-%d")
+            ));
+        }
+        if page != 1 {
+            q.push_str(&format!("&page={page}"));
+        }
+        q.push_str("&view_adult=true");
+        q
+    }
+
+    /// The url of the `page`th page of the works
+    pub fn create_url(&self, page: usize) -> String {
+        format!("{BASE_AO3_URL}{}", self.create_path(page))
+    }
+
+    /// Fetch the works, up to the [search limit](TagWorksFilterBuilder::set_search_limit)
+    pub async fn send(self) -> Result<Vec<AO3Work>, Error> {
+        self.send_with(&AO3Client::new()).await
+    }
+
+    /// [send](TagWorksFilterBuilder::send) through `client`, see [AO3QueryBuilder::send_with]
+    pub async fn send_with(self, client: &AO3Client) -> Result<Vec<AO3Work>, Error> {
+        client.search_all(&self, self.limit).await
+    }
+}
+
+impl Search for TagWorksFilterBuilder {
+    type Item = AO3Work;
+
+    fn path(&self, page: usize) -> String {
+        self.create_path(page)
+    }
+
+    fn parse_page(&self, html: &str) -> Result<Vec<AO3Work>, Error> {
+        crate::parse::parse_search(html)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_path() {
+        let filter = TagWorksFilterBuilder::new("Aziraphale/Crowley (Good Omens)")
+            .include(TagKind::Freeform, "110")
+            .exclude(TagKind::Character, "7")
+            .set_words_from(1000)
+            .set_date_to(NaiveDate::from_ymd_opt(2024, 2, 1).unwrap())
+            .only_completed()
+            .only_non_crossover()
+            .set_sort_by(TagWorksSortBy::Kudos);
+        assert_eq!(
+            filter.create_path(2),
+            "/tags/Aziraphale*s*Crowley (Good Omens)/works?work_search[sort_column]=kudos_count\
+            &include_work_search[freeform_ids][]=110&exclude_work_search[character_ids][]=7\
+            &work_search[crossover]=F&work_search[complete]=T&work_search[words_from]=1000\
+            &work_search[date_to]=2024-02-01&page=2&view_adult=true"
+        );
+        assert_eq!(
+            TagWorksFilterBuilder::new("Horror").create_path(1),
+            "/tags/Horror/works?work_search[sort_column]=revised_at&view_adult=true"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_with() {
+        let filter = TagWorksFilterBuilder::new("Horror").set_search_limit(25);
+        let search = include_str!("../parse_test/search.html");
+        let mock = crate::testing::MockAo3::start()
+            .await
+            .unwrap()
+            .page(&filter.create_path(1), search)
+            .page(&filter.create_path(2), search);
+        let works = filter.send_with(&mock.client()).await.unwrap();
+        assert_eq!(works.len(), 25);
+        assert_eq!(mock.requests().len(), 2);
+    }
+}
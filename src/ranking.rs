@@ -0,0 +1,154 @@
+use crate::models::AO3Work;
+
+/// A way of telling how good a work is likely to be, higher is better
+///
+/// Any `Fn(&AO3Work) -> f64` is a score too.
+pub trait Score {
+    fn score(&self, work: &AO3Work) -> f64;
+}
+
+impl<F: Fn(&AO3Work) -> f64> Score for F {
+    fn score(&self, work: &AO3Work) -> f64 {
+        self(work)
+    }
+}
+
+/// Kudos per hit, how many readers liked the work enough to leave kudos
+///
+/// Works with fewer than `min_hits` hits score 0, a handful of hits says little.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KudosPerHit {
+    pub min_hits: usize,
+}
+
+impl Default for KudosPerHit {
+    fn default() -> Self {
+        KudosPerHit { min_hits: 100 }
+    }
+}
+
+impl Score for KudosPerHit {
+    fn score(&self, work: &AO3Work) -> f64 {
+        if work.hits < self.min_hits.max(1) {
+            return 0.0;
+        }
+        work.kudos as f64 / work.hits as f64
+    }
+}
+
+/// Kudos per 1000 words, so long works don't win on length alone
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct KudosPerThousandWords;
+
+impl Score for KudosPerThousandWords {
+    fn score(&self, work: &AO3Work) -> f64 {
+        work.kudos as f64 * 1000.0 / work.word_count.max(1) as f64
+    }
+}
+
+/// Another score, halved for every `half_life` since the work was last updated
+/// ```rust
+/// use ao3rs::ranking::{rank, KudosPerHit, RecencyWeighted};
+/// # let works: Vec<ao3rs::models::AO3Work> = vec![];
+///
+/// // a month old work counts half as much as one updated today
+/// let score = RecencyWeighted::new(KudosPerHit::default(), chrono::Duration::days(30));
+/// let works = rank(works, &score);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecencyWeighted<S> {
+    score: S,
+    half_life: chrono::Duration,
+    today: chrono::NaiveDate,
+}
+
+impl<S: Score> RecencyWeighted<S> {
+    pub fn new(score: S, half_life: chrono::Duration) -> Self {
+        RecencyWeighted {
+            score,
+            half_life,
+            today: chrono::Utc::now().date_naive(),
+        }
+    }
+
+    /// Day the age of works is counted from, today by default
+    pub fn set_today(mut self, today: chrono::NaiveDate) -> Self {
+        self.today = today;
+        self
+    }
+}
+
+impl<S: Score> Score for RecencyWeighted<S> {
+    fn score(&self, work: &AO3Work) -> f64 {
+        let age = (self.today - work.date).num_seconds().max(0) as f64;
+        let half_life = self.half_life.num_seconds().max(1) as f64;
+        self.score.score(work) * 0.5_f64.powf(age / half_life)
+    }
+}
+
+/// Sort `works` by `score`, best first, works that score the same keep their order
+pub fn rank<S: Score + ?Sized>(works: Vec<AO3Work>, score: &S) -> Vec<AO3Work> {
+    scored(works, score).into_iter().map(|(_, w)| w).collect()
+}
+
+/// [rank], keeping the score of each work
+pub fn scored<S: Score + ?Sized>(works: Vec<AO3Work>, score: &S) -> Vec<(f64, AO3Work)> {
+    let mut scored: Vec<(f64, AO3Work)> = works.into_iter().map(|w| (score.score(&w), w)).collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    scored
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::WorkBuilder;
+
+    fn ids(works: Vec<AO3Work>) -> Vec<String> {
+        works.into_iter().map(|w| w.id).collect()
+    }
+
+    #[test]
+    fn test_rank() {
+        let works = vec![
+            WorkBuilder::new("1")
+                .set_kudos(10)
+                .set_hits(1000)
+                .set_word_count(100_000)
+                .set_date(2024, 1, 1)
+                .build(),
+            WorkBuilder::new("2")
+                .set_kudos(50)
+                .set_hits(500)
+                .set_word_count(5_000)
+                .set_date(2024, 1, 1)
+                .build(),
+            WorkBuilder::new("3")
+                .set_kudos(9)
+                .set_hits(10)
+                .set_word_count(1_000)
+                .set_date(2024, 1, 31)
+                .build(),
+        ];
+        assert_eq!(
+            ids(rank(works.clone(), &KudosPerHit::default())),
+            ["2", "1", "3"]
+        );
+        assert_eq!(
+            ids(rank(works.clone(), &KudosPerThousandWords)),
+            ["2", "3", "1"]
+        );
+        assert_eq!(
+            ids(rank(works.clone(), &|w: &AO3Work| w.hits as f64)),
+            ["1", "2", "3"]
+        );
+
+        let today = chrono::NaiveDate::from_ymd_opt(2024, 1, 31).unwrap();
+        let recent = RecencyWeighted::new(KudosPerThousandWords, chrono::Duration::days(10))
+            .set_today(today);
+        let scored = scored(works, &recent);
+        assert_eq!(scored[0].1.id, "3");
+        assert_eq!(scored[0].0, 9.0);
+        // 30 days are three half lives
+        assert_eq!(scored[1].0, 10.0 / 8.0);
+    }
+}
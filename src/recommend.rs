@@ -0,0 +1,163 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::filter::normalize_tag;
+use crate::models::AO3Work;
+use crate::ranking::{scored, Score};
+
+/// How much a shared tag of each kind counts towards two works being alike
+///
+/// Sharing a relationship says more than sharing a fandom, which most candidates do anyway.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TagWeights {
+    pub fandom: f64,
+    pub relationship: f64,
+    pub character: f64,
+    pub freeform: f64,
+}
+
+impl Default for TagWeights {
+    fn default() -> Self {
+        TagWeights {
+            fandom: 1.0,
+            relationship: 3.0,
+            character: 2.0,
+            freeform: 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Kind {
+    Fandom,
+    Relationship,
+    Character,
+    Freeform,
+}
+
+/// The tags of a work with their kind, normalized and once each
+fn tags_of(work: &AO3Work) -> HashSet<(Kind, String)> {
+    let kinds = [
+        (Kind::Fandom, &work.fandoms),
+        (Kind::Relationship, &work.relationships),
+        (Kind::Character, &work.characters),
+        (Kind::Freeform, &work.tags),
+    ];
+    kinds
+        .into_iter()
+        .flat_map(|(kind, tags)| tags.iter().map(move |tag| (kind, normalize_tag(tag))))
+        .collect()
+}
+
+/// Scores works by the tags they share with a seed work, or with a whole library
+///
+/// Each shared tag adds the weight of its kind, times the share of the seed works that have it
+/// when there are several. The sum is divided by the square root of the work's tag count,
+/// so works aren't ahead just for having a hundred tags.
+/// Synonyms only count as the same tag once [canonicalized](crate::tags::TagResolver::canonicalize).
+/// ```rust
+/// use ao3rs::recommend::SimilarTo;
+/// use ao3rs::ranking::rank;
+/// # let (seed, candidates): (ao3rs::models::AO3Work, Vec<ao3rs::models::AO3Work>) = Default::default();
+///
+/// let more_like_this = rank(candidates, &SimilarTo::work(&seed));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimilarTo {
+    /// Share of the seed works with each tag
+    tags: HashMap<(Kind, String), f64>,
+    weights: TagWeights,
+}
+
+impl SimilarTo {
+    pub fn work(seed: &AO3Work) -> Self {
+        Self::works(std::slice::from_ref(seed))
+    }
+
+    /// Alike to the works as a whole, e.g. the works of a library or a user's bookmarks
+    pub fn works(seeds: &[AO3Work]) -> Self {
+        let mut tags: HashMap<(Kind, String), f64> = HashMap::new();
+        for tag in seeds.iter().flat_map(tags_of) {
+            *tags.entry(tag).or_default() += 1.0 / seeds.len() as f64;
+        }
+        SimilarTo {
+            tags,
+            weights: TagWeights::default(),
+        }
+    }
+
+    pub fn set_weights(mut self, weights: TagWeights) -> Self {
+        self.weights = weights;
+        self
+    }
+
+    fn weight(&self, kind: Kind) -> f64 {
+        match kind {
+            Kind::Fandom => self.weights.fandom,
+            Kind::Relationship => self.weights.relationship,
+            Kind::Character => self.weights.character,
+            Kind::Freeform => self.weights.freeform,
+        }
+    }
+}
+
+impl Score for SimilarTo {
+    fn score(&self, work: &AO3Work) -> f64 {
+        let tags = tags_of(work);
+        if tags.is_empty() {
+            return 0.0;
+        }
+        let shared: f64 = tags
+            .iter()
+            .filter_map(|tag| Some(self.tags.get(tag)? * self.weight(tag.0)))
+            .sum();
+        shared / (tags.len() as f64).sqrt()
+    }
+}
+
+/// The `count` candidates most like the seed works, most alike first
+///
+/// The seed works themselves and candidates with nothing in common with them are left out.
+pub fn recommend(seeds: &[AO3Work], candidates: Vec<AO3Work>, count: usize) -> Vec<AO3Work> {
+    let seen: HashSet<&str> = seeds.iter().map(|w| w.id.as_str()).collect();
+    let candidates = candidates
+        .into_iter()
+        .filter(|w| !seen.contains(w.id.as_str()))
+        .collect();
+    scored(candidates, &SimilarTo::works(seeds))
+        .into_iter()
+        .take_while(|(score, _)| *score > 0.0)
+        .take(count)
+        .map(|(_, work)| work)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recommend() {
+        let works = crate::parse::parse_search(include_str!("parse_test/search.html")).unwrap();
+        let seed = works
+            .iter()
+            .find(|w| {
+                w.relationships
+                    .contains(&"Will Graham/Hannibal Lecter".to_string())
+            })
+            .unwrap()
+            .clone();
+        let mut alike = AO3Work::default();
+        alike.id = "1".to_string();
+        alike.fandoms = seed.fandoms.clone();
+        alike.relationships = vec!["will graham/hannibal lecter".to_string()];
+        let mut candidates = works;
+        candidates.push(alike);
+
+        let recommended = recommend(std::slice::from_ref(&seed), candidates, 3);
+        assert_eq!(recommended[0].id, "1");
+        assert!(recommended.iter().all(|w| w.id != seed.id));
+
+        let none = recommend(&[seed], vec![AO3Work::default()], 3);
+        assert!(none.is_empty());
+    }
+}
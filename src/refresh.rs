@@ -0,0 +1,161 @@
+use crate::client::AO3Client;
+use crate::error::Error;
+use crate::models::AO3Work;
+
+/// A field of [AO3Work] that can change when the work is updated
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WorkField {
+    Title,
+    Authors,
+    Date,
+    IsComplete,
+    IsCrossover,
+    WordCount,
+    ChapterCount,
+    Fandoms,
+    Relationships,
+    Characters,
+    Tags,
+    Warnings,
+    Summary,
+    Rating,
+    Kudos,
+    Hits,
+    Bookmarks,
+    Comments,
+}
+
+/// What a [refresh](AO3Work::refresh) changed
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WorkChanges {
+    /// The work as it was before the refresh
+    pub previous: AO3Work,
+
+    /// Fields that are different now, in the order of [WorkField]
+    pub fields: Vec<WorkField>,
+}
+
+impl WorkChanges {
+    /// Whether nothing changed at all
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    pub fn contains(&self, field: WorkField) -> bool {
+        self.fields.contains(&field)
+    }
+
+    /// Whether the author updated the work, not just readers leaving kudos, comments, ...
+    pub fn is_update(&self) -> bool {
+        self.fields.iter().any(|field| {
+            !matches!(
+                field,
+                WorkField::Kudos | WorkField::Hits | WorkField::Bookmarks | WorkField::Comments
+            )
+        })
+    }
+}
+
+impl AO3Work {
+    /// Fetch the work's page again and update it in place
+    ///
+    /// Handy for following a WIP without keeping the old and new copies around:
+    /// ```rust,no_run
+    /// # async fn example(mut work: ao3rs::models::AO3Work) -> Result<(), ao3rs::Error> {
+    /// use ao3rs::client::AO3Client;
+    ///
+    /// let changes = work.refresh(&AO3Client::new()).await?;
+    /// if work.chapter_count > changes.previous.chapter_count {
+    ///     println!("{} has a new chapter", work.title);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    /// The work is left as it was when fetching fails.
+    pub async fn refresh(&mut self, client: &AO3Client) -> Result<WorkChanges, Error> {
+        let current = client.fetch_work(&self.id).await?;
+        let fields = current.changed_fields(self);
+        let previous = std::mem::replace(self, current);
+        Ok(WorkChanges { previous, fields })
+    }
+
+    /// The fields that are different in `other`
+    ///
+    /// The id and url aren't compared, they're the same for any copy of a work.
+    pub fn changed_fields(&self, other: &AO3Work) -> Vec<WorkField> {
+        let fields = [
+            (WorkField::Title, self.title != other.title),
+            (WorkField::Authors, self.authors != other.authors),
+            (WorkField::Date, self.date != other.date),
+            (WorkField::IsComplete, self.is_complete != other.is_complete),
+            (
+                WorkField::IsCrossover,
+                self.is_crossover != other.is_crossover,
+            ),
+            (WorkField::WordCount, self.word_count != other.word_count),
+            (
+                WorkField::ChapterCount,
+                self.chapter_count != other.chapter_count,
+            ),
+            (WorkField::Fandoms, self.fandoms != other.fandoms),
+            (
+                WorkField::Relationships,
+                self.relationships != other.relationships,
+            ),
+            (WorkField::Characters, self.characters != other.characters),
+            (WorkField::Tags, self.tags != other.tags),
+            (WorkField::Warnings, self.warnings != other.warnings),
+            (WorkField::Summary, self.summary != other.summary),
+            (WorkField::Rating, self.get_rating() != other.get_rating()),
+            (WorkField::Kudos, self.kudos != other.kudos),
+            (WorkField::Hits, self.hits != other.hits),
+            (WorkField::Bookmarks, self.bookmarks != other.bookmarks),
+            (WorkField::Comments, self.comments != other.comments),
+        ];
+        fields
+            .into_iter()
+            .filter(|(_, changed)| *changed)
+            .map(|(field, _)| field)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockAo3;
+
+    #[tokio::test]
+    async fn test_refresh() {
+        let html = include_str!("parse_test/work.html");
+        let mock = MockAo3::start()
+            .await
+            .unwrap()
+            .page("/works/45221314?view_adult=true", html);
+        let client = mock.client();
+        let mut work = crate::parse::parse_work(html).unwrap();
+        let fresh = work.clone();
+        work.chapter_count -= 1;
+        work.hits = 0;
+
+        let changes = work.refresh(&client).await.unwrap();
+        assert_eq!(work, fresh);
+        assert_eq!(
+            changes.fields,
+            vec![WorkField::ChapterCount, WorkField::Hits]
+        );
+        assert!(changes.is_update());
+        assert_eq!(changes.previous.chapter_count, fresh.chapter_count - 1);
+
+        let changes = work.refresh(&client).await.unwrap();
+        assert!(changes.is_empty());
+        assert!(!changes.is_update());
+
+        let mut missing = AO3Work::default();
+        missing.id = "1".to_string();
+        assert!(missing.refresh(&client).await.is_err());
+        assert_eq!(missing.id, "1");
+    }
+}
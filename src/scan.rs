@@ -0,0 +1,249 @@
+use std::path::Path;
+
+use regex::{Regex, RegexBuilder};
+
+use crate::client::AO3Client;
+use crate::convert::html_to_plain_text;
+use crate::error::Error;
+use crate::models::{AO3Chapter, AO3Work};
+
+/// Flags works whose tags, summary or text match lists of keywords or patterns
+///
+/// For content AO3's archive warnings don't cover: each rule has the name of a flag
+/// and what raises it. Keywords match whole words, ignoring case. Patterns are
+/// [regular expressions](https://docs.rs/regex/latest/regex/#syntax), also ignoring case.
+/// ```rust,no_run
+/// # async fn example(works: Vec<ao3rs::models::AO3Work>) -> Result<(), Box<dyn std::error::Error>> {
+/// use ao3rs::client::AO3Client;
+/// use ao3rs::scan::ContentScanner;
+///
+/// let scanner = ContentScanner::new()
+///     .keyword("spiders", "spider")
+///     .keyword("spiders", "arachnid")
+///     .pattern("needles", r"needles?|syringes?")?;
+/// for work in &works {
+///     let flagged = scanner.scan_with_text(&AO3Client::new(), work).await?;
+///     println!("{}: {:?}", work.title, flagged.flag_names());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct ContentScanner {
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    flag: String,
+    regex: Regex,
+}
+
+/// Where a flag was raised
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Place {
+    /// A fandom, relationship, character, warning or additional tag
+    Tag,
+    Summary,
+    /// The text of a chapter, by index
+    Chapter(usize),
+}
+
+/// A rule that matched
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ContentFlag {
+    pub flag: String,
+    pub place: Place,
+
+    /// The text that matched
+    pub found: String,
+}
+
+/// A work with the flags it raised
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FlaggedWork {
+    pub work: AO3Work,
+    pub flags: Vec<ContentFlag>,
+}
+
+impl FlaggedWork {
+    /// The names of the flags raised, once each, in the order of the rules
+    pub fn flag_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = vec![];
+        for flag in &self.flags {
+            if !names.contains(&flag.flag.as_str()) {
+                names.push(&flag.flag);
+            }
+        }
+        names
+    }
+
+    pub fn is_flagged(&self) -> bool {
+        !self.flags.is_empty()
+    }
+}
+
+impl ContentScanner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rules from the file at `path`, see [parse](ContentScanner::parse)
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Rules from lines `<flag>\t<keyword>`, or `<flag>\t/<pattern>/` for a pattern
+    ///
+    /// Empty lines and lines starting with `#` are skipped.
+    pub fn parse(rules: &str) -> Result<Self, Error> {
+        let mut scanner = Self::new();
+        for line in rules.lines() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((flag, rule)) = line.split_once('\t') else {
+                return Err(Error::InvalidInput(format!(
+                    "Expected a flag and a keyword separated by a tab: {line}"
+                )));
+            };
+            scanner = match rule.strip_prefix('/').and_then(|r| r.strip_suffix('/')) {
+                Some(pattern) => scanner.pattern(flag, pattern)?,
+                None => scanner.keyword(flag, rule),
+            };
+        }
+        Ok(scanner)
+    }
+
+    /// Raise `flag` when `keyword` is there as a whole word (or words)
+    pub fn keyword(mut self, flag: &str, keyword: &str) -> Self {
+        let regex = RegexBuilder::new(&format!(r"\b{}\b", regex::escape(keyword.trim())))
+            .case_insensitive(true)
+            .build()
+            .expect("an escaped keyword is a valid pattern");
+        self.rules.push(Rule {
+            flag: flag.to_string(),
+            regex,
+        });
+        self
+    }
+
+    /// Raise `flag` when `pattern` matches
+    pub fn pattern(mut self, flag: &str, pattern: &str) -> Result<Self, Error> {
+        let regex = RegexBuilder::new(pattern)
+            .case_insensitive(true)
+            .build()
+            .map_err(|err| Error::InvalidInput(format!("Invalid pattern for {flag}: {err}")))?;
+        self.rules.push(Rule {
+            flag: flag.to_string(),
+            regex,
+        });
+        Ok(self)
+    }
+
+    /// Every rule that matches `text`, at most once per rule
+    fn scan_text(&self, text: &str, place: Place) -> Vec<ContentFlag> {
+        self.rules
+            .iter()
+            .filter_map(|rule| {
+                let found = rule.regex.find(text)?;
+                Some(ContentFlag {
+                    flag: rule.flag.clone(),
+                    place,
+                    found: found.as_str().to_string(),
+                })
+            })
+            .collect()
+    }
+
+    /// Scan the tags and summary of a work, that's all a search result has
+    pub fn scan_work(&self, work: &AO3Work) -> Vec<ContentFlag> {
+        let tags = work
+            .fandoms
+            .iter()
+            .chain(&work.relationships)
+            .chain(&work.characters)
+            .chain(&work.warnings)
+            .chain(&work.tags);
+        let mut flags: Vec<ContentFlag> = vec![];
+        for tag in tags {
+            for flag in self.scan_text(tag, Place::Tag) {
+                if !flags.iter().any(|f| f.flag == flag.flag) {
+                    flags.push(flag);
+                }
+            }
+        }
+        flags.extend(self.scan_text(&html_to_plain_text(&work.summary), Place::Summary));
+        flags
+    }
+
+    /// Scan the text of chapters, see [fetch_chapters](AO3Client::fetch_chapters)
+    pub fn scan_chapters(&self, chapters: &[AO3Chapter]) -> Vec<ContentFlag> {
+        chapters
+            .iter()
+            .enumerate()
+            .flat_map(|(i, chapter)| {
+                self.scan_text(&html_to_plain_text(&chapter.text), Place::Chapter(i))
+            })
+            .collect()
+    }
+
+    /// [scan_work](ContentScanner::scan_work) a work, without fetching anything
+    pub fn annotate(&self, work: AO3Work) -> FlaggedWork {
+        let flags = self.scan_work(&work);
+        FlaggedWork { work, flags }
+    }
+
+    /// Scan the tags and summary of a work and the text of its chapters, which are fetched
+    ///
+    /// That's a request per work, opt in to it for the works that are about to be read.
+    pub async fn scan_with_text(
+        &self,
+        client: &AO3Client,
+        work: &AO3Work,
+    ) -> Result<FlaggedWork, Error> {
+        let chapters = client.fetch_chapters(&work.id).await?;
+        let mut flagged = self.annotate(work.clone());
+        flagged.flags.extend(self.scan_chapters(&chapters));
+        Ok(flagged)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_scanner() {
+        let scanner = ContentScanner::parse(
+            "# my list\nspiders\tspider\nspiders\tArachnophobia\nneedles\t/needles?|syringes?/\n",
+        )
+        .unwrap();
+        let mut work = AO3Work::default();
+        work.tags = vec!["Arachnophobia".to_string(), "Spider-Man".to_string()];
+        work.summary = "<p>There is a Syringe on the table.</p>".to_string();
+        let flagged = scanner.annotate(work);
+        assert_eq!(flagged.flag_names(), vec!["spiders", "needles"]);
+        assert_eq!(flagged.flags[0].found, "Arachnophobia");
+        assert_eq!(flagged.flags[1].place, Place::Summary);
+        assert_eq!(flagged.flags[1].found, "Syringe");
+
+        let chapters = vec![
+            AO3Chapter::default(),
+            AO3Chapter {
+                text: "<p>Spiders everywhere, not a single spider left out.</p>".to_string(),
+                ..Default::default()
+            },
+        ];
+        let flags = scanner.scan_chapters(&chapters);
+        assert_eq!(flags.len(), 1);
+        assert_eq!(flags[0].place, Place::Chapter(1));
+        assert_eq!(flags[0].found, "spider");
+
+        assert!(ContentScanner::parse("no tab here").is_err());
+        assert!(ContentScanner::new().pattern("broken", "(").is_err());
+    }
+}
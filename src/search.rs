@@ -0,0 +1,202 @@
+use futures_util::stream::{self, Stream};
+
+use crate::client::AO3Client;
+use crate::error::Error;
+use crate::form::BASE_AO3_URL;
+use crate::models::{AO3Work, SearchResults};
+use crate::parse::{check_search_unavailable, parse_page_count, parse_results_count, parse_search};
+use crate::query::AO3QueryBuilder;
+
+/// Something AO3 answers with pages of results, like a work search
+///
+/// Paging through the results is written once against this trait, see
+/// [search_page](AO3Client::search_page), [search_pages](AO3Client::search_pages)
+/// and [search_all](AO3Client::search_all).
+pub trait Search {
+    type Item;
+
+    /// Path of a page of the results, relative to the root of AO3, pages start at 1
+    fn path(&self, page: usize) -> String;
+
+    /// The results on a page
+    fn parse_page(&self, html: &str) -> Result<Vec<Self::Item>, Error>;
+
+    /// How many results there are in all, when the page says
+    fn parse_total(&self, html: &str) -> Result<Option<usize>, Error> {
+        parse_results_count(html)
+    }
+}
+
+impl Search for AO3QueryBuilder {
+    type Item = AO3Work;
+
+    fn path(&self, page: usize) -> String {
+        self.create_path(page)
+    }
+
+    fn parse_page(&self, html: &str) -> Result<Vec<AO3Work>, Error> {
+        check_search_unavailable(html)?;
+        parse_search(html)
+    }
+}
+
+/// A page of results of a [Search]
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct SearchPage<T> {
+    pub items: Vec<T>,
+    pub page: usize,
+
+    /// How many pages of results there are
+    pub page_count: usize,
+
+    /// How many results there are in all, when AO3 says
+    pub total: Option<usize>,
+
+    /// Html of the page, when the client [keeps it](AO3Client::set_keep_raw)
+    pub raw_html: Option<String>,
+}
+
+impl<T> SearchPage<T> {
+    pub fn is_last(&self) -> bool {
+        self.page >= self.page_count || self.items.is_empty()
+    }
+}
+
+impl AO3Client {
+    /// Fetch a page of the results of `search`
+    pub async fn search_page<S: Search>(
+        &self,
+        search: &S,
+        page: usize,
+    ) -> Result<SearchPage<S::Item>, Error> {
+        self.get_parsed(&search.path(page), |html| {
+            Ok(SearchPage {
+                items: search.parse_page(html)?,
+                page,
+                page_count: parse_page_count(html)?,
+                total: search.parse_total(html)?,
+                raw_html: self.raw(html),
+            })
+        })
+        .await
+    }
+
+    /// The pages of the results of `search`, each fetched when the stream gets to it
+    ///
+    /// Ends after the last page, or after the first page that failed.
+    pub fn search_pages<'a, S: Search>(
+        &'a self,
+        search: &'a S,
+    ) -> impl Stream<Item = Result<SearchPage<S::Item>, Error>> + 'a {
+        stream::unfold(Some(1), move |page| async move {
+            let result = self.search_page(search, page?).await;
+            let next = match &result {
+                Ok(fetched) if !fetched.is_last() => Some(fetched.page + 1),
+                _ => None,
+            };
+            Some((result, next))
+        })
+    }
+
+    /// The first `limit` results of `search`, fetching as many pages as that takes
+    pub async fn search_all<S: Search>(
+        &self,
+        search: &S,
+        limit: usize,
+    ) -> Result<Vec<S::Item>, Error> {
+        Ok(self.search_up_to(search, limit).await?.0.items)
+    }
+
+    /// [search_all](AO3Client::search_all) for works, with how many were found and the
+    /// pages of results when they're [kept](AO3Client::set_keep_raw)
+    pub async fn search_results<S: Search<Item = AO3Work>>(
+        &self,
+        search: &S,
+        limit: usize,
+    ) -> Result<SearchResults, Error> {
+        let (fetched, raw_pages) = self.search_up_to(search, limit).await?;
+        Ok(SearchResults {
+            works: fetched.items,
+            total: fetched.total,
+            page: fetched.page,
+            page_count: fetched.page_count,
+            url: format!("{BASE_AO3_URL}{}", search.path(1)),
+            raw_pages,
+        })
+    }
+
+    /// The first `limit` results as one page, numbered like the last page they're from,
+    /// and the html of the pages if it's kept
+    async fn search_up_to<S: Search>(
+        &self,
+        search: &S,
+        limit: usize,
+    ) -> Result<(SearchPage<S::Item>, Vec<String>), Error> {
+        let mut all = SearchPage {
+            items: vec![],
+            page: 0,
+            page_count: 0,
+            total: None,
+            raw_html: None,
+        };
+        let mut raw_pages = vec![];
+        while all.items.len() < limit {
+            let fetched = self.search_page(search, all.page + 1).await?;
+            let is_last = fetched.is_last();
+            all.items.extend(fetched.items);
+            all.page = fetched.page;
+            all.page_count = fetched.page_count;
+            all.total = fetched.total;
+            raw_pages.extend(fetched.raw_html);
+            if is_last {
+                break;
+            }
+        }
+        all.items.truncate(limit);
+        Ok((all, raw_pages))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockAo3;
+    use futures_util::StreamExt;
+
+    #[tokio::test]
+    async fn test_search() {
+        let query = AO3QueryBuilder::new().set_any_field("horror");
+        let search = include_str!("parse_test/search.html");
+        let last = search.replace("class=\"pagination actions\"", "class=\"actions\"");
+        let mock = MockAo3::start()
+            .await
+            .unwrap()
+            .page(&query.path(1), search)
+            .page(&query.path(2), &last);
+        let client = mock.client();
+
+        let first = client.search_page(&query, 1).await.unwrap();
+        assert_eq!(first.items.len(), 20);
+        assert_eq!(first.total, Some(10_066_024));
+        assert!(!first.is_last());
+
+        let pages: Vec<_> = client.search_pages(&query).collect().await;
+        assert_eq!(pages.len(), 2);
+        assert!(pages[1].as_ref().unwrap().is_last());
+
+        assert_eq!(client.search_all(&query, 25).await.unwrap().len(), 25);
+        assert_eq!(client.search_all(&query, 100).await.unwrap().len(), 40);
+
+        let kept = client
+            .set_keep_raw(true)
+            .search_results(&query, 21)
+            .await
+            .unwrap();
+        assert_eq!(kept.len(), 21);
+        assert_eq!(kept.page, 2);
+        assert_eq!(kept.total, Some(10_066_024));
+        assert!(kept.is_last_page());
+        assert!(kept.url.ends_with(&query.path(1)));
+        assert_eq!(kept.raw_pages, vec![search.to_string(), last]);
+    }
+}
@@ -0,0 +1,134 @@
+use crate::client::AO3Client;
+use crate::error::{Error, ParsingError};
+use crate::models::DownloadFormat;
+use crate::parse::{
+    check_search_unavailable, parse_chapter_ids, parse_chapters, parse_download_path,
+    parse_page_count, parse_search_with, parse_work_with, ParseMode,
+};
+
+/// A work that has been on AO3 unchanged for a long time, with several chapters
+pub const SELFTEST_WORK_ID: &str = "45221314";
+
+/// A search that always finds a handful of works
+const SELFTEST_SEARCH: &str =
+    "/works/search?work_search[query]=Salvation&work_search[sort_column]=kudos_count";
+
+/// Fetch a known work and a small search and run every parser on them
+///
+/// Meant for deployed bots to notice AO3 changing its layout before they store empty data.
+/// Three pages are fetched, respecting the client's rate limit.
+/// ```rust,no_run
+/// # async fn example() {
+/// use ao3rs::client::AO3Client;
+///
+/// let report = ao3rs::selftest(&AO3Client::new()).await;
+/// for failure in &report.failures {
+///     eprintln!("{}: {}", failure.parser, failure.error);
+/// }
+/// # }
+/// ```
+pub async fn selftest(client: &AO3Client) -> SelfTestReport {
+    let mut report = SelfTestReport::default();
+    let work_path = format!("/works/{SELFTEST_WORK_ID}?view_full_work=true&view_adult=true");
+    if let Some(html) = report.check("work page", client.get_html(&work_path).await) {
+        report.check_work_page(&html);
+    }
+    let navigate_path = format!("/works/{SELFTEST_WORK_ID}/navigate");
+    if let Some(html) = report.check("chapter index", client.get_html(&navigate_path).await) {
+        report.check_chapter_index(&html);
+    }
+    if let Some(html) = report.check("search", client.get_html(SELFTEST_SEARCH).await) {
+        report.check_search(&html);
+    }
+    report
+}
+
+/// What [selftest] found, nothing failed when there are no failures
+#[derive(Debug, Default)]
+pub struct SelfTestReport {
+    pub failures: Vec<SelfTestFailure>,
+}
+
+/// A parser that failed, or a page that couldn't be fetched
+#[derive(Debug)]
+pub struct SelfTestFailure {
+    /// The parser (or page) that failed, e.g. "work" or "chapters"
+    pub parser: &'static str,
+    pub error: Error,
+}
+
+impl SelfTestReport {
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    fn check<T>(&mut self, parser: &'static str, result: Result<T, Error>) -> Option<T> {
+        result
+            .map_err(|error| self.failures.push(SelfTestFailure { parser, error }))
+            .ok()
+    }
+
+    fn check_missing(&mut self, parser: &'static str, missing: Vec<ParsingError>) {
+        self.failures
+            .extend(missing.into_iter().map(|source| SelfTestFailure {
+                parser,
+                error: source.into(),
+            }));
+    }
+
+    /// An empty list is as much a failure as an error, it's what a layout change usually looks like
+    fn check_not_empty<T>(&mut self, parser: &'static str, items: &[T], what: &'static str) {
+        if items.is_empty() {
+            self.check::<()>(parser, Err(ParsingError::CouldNotFind(what).into()));
+        }
+    }
+
+    fn check_work_page(&mut self, html: &str) {
+        if let Some(parsed) = self.check("work", parse_work_with(html, ParseMode::Lenient)) {
+            self.check_missing("work", parsed.missing);
+        }
+        if let Some(chapters) = self.check("chapters", parse_chapters(html)) {
+            self.check_not_empty("chapters", &chapters, "the chapters of the work.");
+        }
+        self.check("download", parse_download_path(html, &DownloadFormat::Epub));
+    }
+
+    fn check_chapter_index(&mut self, html: &str) {
+        if let Some(ids) = self.check("chapter ids", parse_chapter_ids(html)) {
+            self.check_not_empty("chapter ids", &ids, "the ids of the chapters.");
+        }
+    }
+
+    fn check_search(&mut self, html: &str) {
+        if self
+            .check("search", check_search_unavailable(html))
+            .is_none()
+        {
+            return;
+        }
+        if let Some(parsed) = self.check("search", parse_search_with(html, ParseMode::Lenient)) {
+            self.check_not_empty("search", &parsed.value, "the list of works.");
+            self.check_missing("search", parsed.missing);
+        }
+        self.check("page count", parse_page_count(html));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selftest_report() {
+        let mut report = SelfTestReport::default();
+        report.check_work_page(include_str!("parse_test/work.html"));
+        report.check_chapter_index(include_str!("parse_test/navigate.html"));
+        report.check_search(include_str!("parse_test/search.html"));
+        assert!(report.is_ok(), "{:?}", report.failures);
+
+        // a page from before a layout change everything relies on
+        report.check_work_page("<html><body><p>Moved</p></body></html>");
+        let parsers: Vec<_> = report.failures.iter().map(|f| f.parser).collect();
+        assert_eq!(parsers, vec!["work", "chapters", "download"]);
+    }
+}
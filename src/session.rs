@@ -0,0 +1,365 @@
+use crate::error::Error;
+use crate::form::FormSession;
+use crate::models::{
+    AO3Assignment, AO3AssignmentRequest, AO3Bookmark, AO3Claim, AO3Draft, AO3ImportReport,
+    AO3Prompt, AO3Subscription, AO3Work,
+};
+use crate::parse::{
+    parse_assignment_requests, parse_assignments, parse_claims, parse_drafts, parse_import_report,
+    parse_prompts, parse_search, parse_subscriptions, parse_work_bookmarks, parse_work_preview,
+};
+
+mod actions;
+#[cfg(feature = "serde")]
+mod backup;
+mod bookmarks;
+mod posting;
+
+#[cfg(feature = "serde")]
+pub use backup::BackupReport;
+pub use bookmarks::{BookmarkFileError, BookmarkImportReport, NewBookmark};
+pub use posting::{NewWork, ParentWork};
+
+#[derive(Debug)]
+pub enum SessionError {
+    /// AO3 did not accept the username and password
+    LoginFailed(String),
+
+    /// AO3 rejected a form, with the errors it gave
+    Rejected(Vec<String>),
+}
+
+impl std::fmt::Display for SessionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SessionError::LoginFailed(username) => {
+                write!(f, "Could not log in as: {}", username)
+            }
+            SessionError::Rejected(errors) => {
+                write!(f, "AO3 rejected the form: {}", errors.join(", "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for SessionError {}
+
+/// Import works into AO3 from an existing url
+///
+/// Mirrors the "Import From An Existing URL" form at `/works/import`
+/// ```rust
+/// use ao3rs::session::WorkImport;
+///
+/// let import = WorkImport::new()
+///     .push_url("http://example.com/story/1")
+///     .push_url("http://example.com/story/2")
+///     .restricted(true);
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct WorkImport {
+    /// Urls to import, one work per url (or one chapter per url)
+    urls: Vec<String>,
+
+    /// Import the urls as chapters of a single work instead of separate works
+    as_chapters: bool,
+
+    /// Only show the imported works to logged in users
+    restricted: bool,
+
+    /// Post the works right away instead of saving them as drafts
+    post_without_preview: bool,
+
+    /// Character encoding of the original pages, AO3 guesses when empty
+    encoding: String,
+
+    /// Fandoms to use when the original page doesn't say
+    fandoms: Vec<String>,
+}
+
+impl WorkImport {
+    pub fn new() -> Self {
+        WorkImport {
+            ..Default::default()
+        }
+    }
+
+    pub fn set_urls<S: Into<String>>(mut self, urls: impl IntoIterator<Item = S>) -> Self {
+        self.urls = urls.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn push_url(mut self, url: impl Into<String>) -> Self {
+        self.urls.push(url.into());
+        self
+    }
+
+    pub fn as_chapters(mut self, as_chapters: bool) -> Self {
+        self.as_chapters = as_chapters;
+        self
+    }
+
+    pub fn restricted(mut self, restricted: bool) -> Self {
+        self.restricted = restricted;
+        self
+    }
+
+    pub fn post_without_preview(mut self, post_without_preview: bool) -> Self {
+        self.post_without_preview = post_without_preview;
+        self
+    }
+
+    pub fn set_encoding(mut self, encoding: impl Into<String>) -> Self {
+        self.encoding = encoding.into();
+        self
+    }
+
+    pub fn set_fandoms<S: Into<String>>(mut self, fandoms: impl IntoIterator<Item = S>) -> Self {
+        self.fandoms = fandoms.into_iter().map(Into::into).collect();
+        self
+    }
+
+    fn to_form(&self) -> Vec<(&'static str, String)> {
+        let mut form = vec![
+            ("urls", self.urls.join("\n")),
+            (
+                "import_multiple",
+                String::from(if self.as_chapters {
+                    "chapters"
+                } else {
+                    "works"
+                }),
+            ),
+            ("encoding", self.encoding.clone()),
+            ("work[fandom_string]", self.fandoms.join(",")),
+            ("commit", String::from("Import")),
+        ];
+        if self.restricted {
+            form.push(("restricted", String::from("1")));
+        }
+        if self.post_without_preview {
+            form.push(("post_without_preview", String::from("1")));
+        }
+        form
+    }
+}
+
+/// A logged in AO3 session
+///
+/// Keeps the cookies AO3 hands out after logging in, so pages that are only
+/// visible to the user (drafts, assignments, ...) can be fetched.
+#[derive(Debug)]
+pub struct AO3Session {
+    form: FormSession,
+    username: String,
+}
+
+impl AO3Session {
+    /// Log in to AO3
+    pub async fn login(username: &str, password: &str) -> Result<Self, Error> {
+        let form = FormSession::new()?;
+        let resp = form
+            .submit(
+                "/users/login",
+                "/users/login",
+                &[
+                    ("user[login]", username),
+                    ("user[password]", password),
+                    ("user[remember_me]", "1"),
+                    ("commit", "Log In"),
+                ],
+            )
+            .await?;
+        // on success AO3 redirects to the user's dashboard, otherwise we end up on the login page again
+        if resp.url.contains("/users/login") {
+            return Err(SessionError::LoginFailed(username.to_string()).into());
+        }
+        Ok(Self {
+            form,
+            username: username.to_string(),
+        })
+    }
+
+    /// Save the html of every page that fails to parse to a file in `dir`,
+    /// see [FormSession::set_snapshot_dir]
+    pub fn set_snapshot_dir(mut self, dir: impl Into<std::path::PathBuf>) -> Self {
+        self.form = self.form.set_snapshot_dir(dir);
+        self
+    }
+
+    pub fn get_username(&self) -> &str {
+        &self.username
+    }
+
+    /// The form session used for this login, for submitting forms the crate doesn't wrap yet
+    pub fn form_session(&self) -> &FormSession {
+        &self.form
+    }
+
+    /// Fetch the drafts of the logged in user, together with when they will be deleted
+    pub async fn fetch_drafts(&self) -> Result<Vec<AO3Draft>, Error> {
+        let resp = self
+            .form
+            .get(&format!("/users/{}/works/drafts", self.username))
+            .await?;
+        self.form.parse(&resp, parse_drafts)
+    }
+
+    /// Fetch a page of the works the logged in user posted
+    pub async fn fetch_own_works(&self, page: usize) -> Result<Vec<AO3Work>, Error> {
+        let resp = self
+            .form
+            .get(&format!("/users/{}/works?page={page}", self.username))
+            .await?;
+        self.form.parse(&resp, parse_search)
+    }
+
+    /// Fetch a page of the logged in user's bookmarks, private ones included
+    pub async fn fetch_bookmarks(&self, page: usize) -> Result<Vec<AO3Bookmark>, Error> {
+        let resp = self
+            .form
+            .get(&format!("/users/{}/bookmarks?page={page}", self.username))
+            .await?;
+        self.form.parse(&resp, parse_work_bookmarks)
+    }
+
+    /// Fetch a page of the works the logged in user marked for later
+    pub async fn fetch_marked_for_later(&self, page: usize) -> Result<Vec<AO3Work>, Error> {
+        let resp = self
+            .form
+            .get(&format!(
+                "/users/{}/readings?show=to-read&page={page}",
+                self.username
+            ))
+            .await?;
+        self.form.parse(&resp, parse_search)
+    }
+
+    /// Fetch a page of the logged in user's reading history, last visited first
+    pub async fn fetch_history(&self, page: usize) -> Result<Vec<AO3Work>, Error> {
+        let resp = self
+            .form
+            .get(&format!("/users/{}/readings?page={page}", self.username))
+            .await?;
+        self.form.parse(&resp, parse_search)
+    }
+
+    /// Fetch a page of the works, series and users the logged in user subscribed to
+    pub async fn fetch_subscriptions(&self, page: usize) -> Result<Vec<AO3Subscription>, Error> {
+        let resp = self
+            .form
+            .get(&format!(
+                "/users/{}/subscriptions?page={page}",
+                self.username
+            ))
+            .await?;
+        self.form.parse(&resp, parse_subscriptions)
+    }
+
+    /// Fetch the preview of a draft, returns the html of its chapters
+    pub async fn fetch_draft_preview(&self, work_id: &str) -> Result<String, Error> {
+        let resp = self.form.get(&format!("/works/{work_id}/preview")).await?;
+        self.form.parse(&resp, parse_work_preview)
+    }
+
+    /// Import works from other sites, failed urls are listed in the report rather than returned as an error
+    pub async fn import_works(&self, import: &WorkImport) -> Result<AO3ImportReport, Error> {
+        let resp = self
+            .form
+            .submit("/works/import", "/works/import", &import.to_form())
+            .await?;
+        let mut report = self
+            .form
+            .parse(&resp, |html| parse_import_report(html, &resp.url))?;
+        // a single failed import doesn't say which url failed
+        if let [url] = import.urls.as_slice() {
+            report
+                .failed
+                .iter_mut()
+                .filter(|(failed_url, _)| failed_url.is_empty())
+                .for_each(|(failed_url, _)| *failed_url = url.clone());
+        }
+        Ok(report)
+    }
+
+    /// Fetch the gift exchange assignments of the logged in user
+    ///
+    /// Post the gift for one of them with [fulfill_assignment](AO3Session::fulfill_assignment),
+    /// it goes to the right collection and recipient.
+    pub async fn fetch_assignments(&self) -> Result<Vec<AO3Assignment>, Error> {
+        let resp = self
+            .form
+            .get(&format!("/users/{}/assignments", self.username))
+            .await?;
+        self.form.parse(&resp, parse_assignments)
+    }
+
+    /// Fetch what the recipient of an assignment asked for
+    pub async fn fetch_assignment_requests(
+        &self,
+        assignment: &AO3Assignment,
+    ) -> Result<Vec<AO3AssignmentRequest>, Error> {
+        let resp = self
+            .form
+            .get(&format!(
+                "/collections/{}/assignments/{}",
+                assignment.collection, assignment.id
+            ))
+            .await?;
+        self.form.parse(&resp, parse_assignment_requests)
+    }
+
+    /// Fetch a page of the prompts in a prompt meme
+    pub async fn fetch_prompts(
+        &self,
+        collection: &str,
+        page: usize,
+    ) -> Result<Vec<AO3Prompt>, Error> {
+        let resp = self
+            .form
+            .get(&format!("/collections/{collection}/requests?page={page}"))
+            .await?;
+        self.form.parse(&resp, parse_prompts)
+    }
+
+    /// Fetch the prompts the logged in user has claimed
+    pub async fn fetch_claims(&self) -> Result<Vec<AO3Claim>, Error> {
+        let resp = self
+            .form
+            .get(&format!("/users/{}/claims", self.username))
+            .await?;
+        self.form.parse(&resp, parse_claims)
+    }
+
+    pub async fn claim_prompt(&self, prompt: &AO3Prompt) -> Result<(), Error> {
+        let resp = self
+            .form
+            .submit(
+                &format!("/collections/{}/requests", prompt.collection),
+                &format!("/collections/{}/claims", prompt.collection),
+                &[
+                    ("challenge_claim[request_prompt_id]", prompt.id.as_str()),
+                    ("challenge_claim[prompt_type]", "request"),
+                ],
+            )
+            .await?;
+        if resp.has_errors() {
+            return Err(SessionError::Rejected(resp.errors).into());
+        }
+        Ok(())
+    }
+
+    pub async fn unclaim_prompt(&self, claim: &AO3Claim) -> Result<(), Error> {
+        let resp = self
+            .form
+            .submit(
+                &format!("/users/{}/claims", self.username),
+                &format!("/collections/{}/claims/{}", claim.collection, claim.id),
+                &[("_method", "delete")],
+            )
+            .await?;
+        if resp.has_errors() {
+            return Err(SessionError::Rejected(resp.errors).into());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,190 @@
+use super::{AO3Session, NewBookmark, SessionError};
+use crate::error::Error;
+use crate::form::BASE_AO3_URL;
+use crate::models::{AO3Series, AO3Work};
+
+impl AO3Session {
+    /// Leave kudos on a work as the logged in user
+    ///
+    /// AO3 rejects kudos on a work the user already left kudos on.
+    pub async fn leave_kudos(&self, work_id: &str) -> Result<(), Error> {
+        let resp = self
+            .form
+            .submit(
+                &format!("/works/{work_id}"),
+                "/kudos",
+                &[
+                    ("kudo[commentable_id]", work_id),
+                    ("kudo[commentable_type]", "Work"),
+                ],
+            )
+            .await?;
+        if resp.has_errors() {
+            return Err(SessionError::Rejected(resp.errors).into());
+        }
+        Ok(())
+    }
+
+    /// Subscribe to a work, to be emailed when a chapter is posted
+    pub async fn subscribe_to_work(&self, work_id: &str) -> Result<(), Error> {
+        self.subscribe(&format!("/works/{work_id}"), work_id, "Work")
+            .await
+    }
+
+    /// Subscribe to a series, to be emailed when a work is added to it
+    pub async fn subscribe_to_series(&self, series_id: &str) -> Result<(), Error> {
+        self.subscribe(&format!("/series/{series_id}"), series_id, "Series")
+            .await
+    }
+
+    async fn subscribe(&self, form_page: &str, id: &str, kind: &str) -> Result<(), Error> {
+        let resp = self
+            .form
+            .submit(
+                form_page,
+                &format!("/users/{}/subscriptions", self.username),
+                &[
+                    ("subscription[subscribable_id]", id),
+                    ("subscription[subscribable_type]", kind),
+                ],
+            )
+            .await?;
+        if resp.has_errors() {
+            return Err(SessionError::Rejected(resp.errors).into());
+        }
+        Ok(())
+    }
+}
+
+impl AO3Work {
+    /// See [leave_kudos](AO3Session::leave_kudos)
+    /// ```rust,no_run
+    /// # async fn example(session: &ao3rs::session::AO3Session) -> Result<(), ao3rs::Error> {
+    /// use ao3rs::client::AO3Client;
+    /// use ao3rs::session::NewBookmark;
+    ///
+    /// let work = AO3Client::new().fetch_work("45221314").await?;
+    /// work.leave_kudos(session).await?;
+    /// work.bookmark(session, NewBookmark { is_rec: true, ..Default::default() }).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn leave_kudos(&self, session: &AO3Session) -> Result<(), Error> {
+        session.leave_kudos(&self.id).await
+    }
+
+    /// Bookmark the work with the tags, notes, ... of `bookmark`, its url is the work's
+    pub async fn bookmark(&self, session: &AO3Session, bookmark: NewBookmark) -> Result<(), Error> {
+        let bookmark = NewBookmark {
+            url: format!("{BASE_AO3_URL}/works/{}", self.id),
+            ..bookmark
+        };
+        session.create_bookmark(&bookmark).await
+    }
+
+    /// See [subscribe_to_work](AO3Session::subscribe_to_work)
+    pub async fn subscribe(&self, session: &AO3Session) -> Result<(), Error> {
+        session.subscribe_to_work(&self.id).await
+    }
+}
+
+impl AO3Series {
+    /// See [subscribe_to_series](AO3Session::subscribe_to_series)
+    pub async fn subscribe(&self, session: &AO3Session) -> Result<(), Error> {
+        session.subscribe_to_series(&self.id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::BuiltRequest;
+    use crate::testing::MockAo3;
+
+    fn session(mock: &MockAo3) -> AO3Session {
+        AO3Session {
+            form: mock.form_session().unwrap().set_dry_run(true),
+            username: String::from("reader"),
+        }
+    }
+
+    /// The request a dry run would have sent
+    fn dry_run(result: Result<(), Error>) -> BuiltRequest {
+        match result {
+            Err(Error::DryRun(request)) => *request,
+            other => panic!("not a dry run: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_leave_kudos() {
+        let mock = MockAo3::start().await.unwrap();
+        let session = session(&mock);
+        let request = dry_run(session.leave_kudos("45221314").await);
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.url, format!("{}/kudos", mock.url()));
+        assert_eq!(
+            request.body.as_deref(),
+            Some(
+                "authenticity_token=DRY_RUN&kudo%5Bcommentable_id%5D=45221314\
+                &kudo%5Bcommentable_type%5D=Work"
+            )
+        );
+
+        let mut work = AO3Work::default();
+        work.id = String::from("45221314");
+        assert_eq!(dry_run(work.leave_kudos(&session).await), request);
+        assert!(mock.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_subscribe() {
+        let mock = MockAo3::start().await.unwrap();
+        let session = session(&mock);
+        let subscriptions = format!("{}/users/reader/subscriptions", mock.url());
+
+        let request = dry_run(session.subscribe_to_work("45221314").await);
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.url, subscriptions);
+        assert_eq!(
+            request.body.as_deref(),
+            Some(
+                "authenticity_token=DRY_RUN&subscription%5Bsubscribable_id%5D=45221314\
+                &subscription%5Bsubscribable_type%5D=Work"
+            )
+        );
+        let mut work = AO3Work::default();
+        work.id = String::from("45221314");
+        assert_eq!(dry_run(work.subscribe(&session).await), request);
+
+        let request = dry_run(session.subscribe_to_series("2712415").await);
+        assert_eq!(request.url, subscriptions);
+        assert_eq!(
+            request.body.as_deref(),
+            Some(
+                "authenticity_token=DRY_RUN&subscription%5Bsubscribable_id%5D=2712415\
+                &subscription%5Bsubscribable_type%5D=Series"
+            )
+        );
+        let series = AO3Series {
+            id: String::from("2712415"),
+            ..Default::default()
+        };
+        assert_eq!(dry_run(series.subscribe(&session).await), request);
+        assert!(mock.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_bookmark_work() {
+        let mock = MockAo3::start().await.unwrap();
+        let mut work = AO3Work::default();
+        work.id = String::from("45221314");
+        // the form page is fetched first, for the pseud to bookmark as
+        let request = dry_run(work.bookmark(&session(&mock), NewBookmark::default()).await);
+        assert_eq!(request.method, "GET");
+        assert_eq!(
+            request.url,
+            format!("{}/works/45221314/bookmarks/new", mock.url())
+        );
+    }
+}
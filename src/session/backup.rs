@@ -0,0 +1,159 @@
+use std::path::Path;
+use std::time::Duration;
+
+use super::AO3Session;
+use crate::checkpoint::Checkpoint;
+use crate::error::Error;
+use crate::export::work_to_html;
+use crate::parse::{
+    parse_chapters, parse_page_count, parse_search, parse_subscriptions, parse_work_bookmarks,
+};
+
+/// Time to wait between fetching works, a backup makes a lot of requests
+const REQUEST_DELAY: Duration = Duration::from_secs(3);
+
+/// What [backup_account](AO3Session::backup_account) wrote
+///
+/// When a backup resumes, only what was written by the last run is counted.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct BackupReport {
+    pub works: usize,
+    pub drafts: usize,
+    pub bookmarks: usize,
+    pub marked_for_later: usize,
+    pub subscriptions: usize,
+    pub history: usize,
+}
+
+impl AO3Session {
+    /// Back up everything of the logged in user into the directory `path`
+    ///
+    /// ```text
+    /// works.json             metadata of the posted works
+    /// works/<id>.html        each work with all its chapters
+    /// drafts.json
+    /// drafts/<id>.html       the preview of each draft
+    /// bookmarks.json         bookmarks with their tags and notes
+    /// marked_for_later.json
+    /// subscriptions.json
+    /// history.json
+    /// ```
+    /// Progress is kept in a [checkpoint](Checkpoint) in the directory, running the backup
+    /// again after it was interrupted skips what was already written.
+    pub async fn backup_account(&self, path: impl AsRef<Path>) -> Result<BackupReport, Error> {
+        let dir = path.as_ref();
+        std::fs::create_dir_all(dir.join("works"))?;
+        std::fs::create_dir_all(dir.join("drafts"))?;
+        let mut checkpoint = Checkpoint::load(
+            dir.join(".checkpoint"),
+            &format!("backup_account {}", self.username),
+        )?;
+        let mut report = BackupReport::default();
+        let user = format!("/users/{}", self.username);
+
+        let works = self
+            .fetch_every_page(&format!("{user}/works"), parse_search)
+            .await?;
+        write_json(dir, "works.json", &works)?;
+        for work in &works {
+            let item = format!("work {}", work.id);
+            if checkpoint.is_done(&item) {
+                continue;
+            }
+            tokio::time::sleep(REQUEST_DELAY).await;
+            let resp = self
+                .form
+                .get(&format!(
+                    "/works/{}?view_full_work=true&view_adult=true",
+                    work.id
+                ))
+                .await?;
+            let chapters = self.form.parse(&resp, parse_chapters)?;
+            std::fs::write(
+                dir.join("works").join(format!("{}.html", work.id)),
+                work_to_html(work, &chapters),
+            )?;
+            checkpoint.mark_done(&item)?;
+            report.works += 1;
+        }
+
+        let drafts = self.fetch_drafts().await?;
+        write_json(dir, "drafts.json", &drafts)?;
+        for draft in &drafts {
+            let item = format!("draft {}", draft.work.id);
+            if checkpoint.is_done(&item) {
+                continue;
+            }
+            tokio::time::sleep(REQUEST_DELAY).await;
+            let preview = self.fetch_draft_preview(&draft.work.id).await?;
+            std::fs::write(
+                dir.join("drafts").join(format!("{}.html", draft.work.id)),
+                preview,
+            )?;
+            checkpoint.mark_done(&item)?;
+            report.drafts += 1;
+        }
+
+        if !checkpoint.is_done("bookmarks") {
+            let bookmarks = self
+                .fetch_every_page(&format!("{user}/bookmarks"), parse_work_bookmarks)
+                .await?;
+            write_json(dir, "bookmarks.json", &bookmarks)?;
+            checkpoint.mark_done("bookmarks")?;
+            report.bookmarks = bookmarks.len();
+        }
+        if !checkpoint.is_done("marked_for_later") {
+            let marked = self
+                .fetch_every_page(&format!("{user}/readings?show=to-read"), parse_search)
+                .await?;
+            write_json(dir, "marked_for_later.json", &marked)?;
+            checkpoint.mark_done("marked_for_later")?;
+            report.marked_for_later = marked.len();
+        }
+        if !checkpoint.is_done("subscriptions") {
+            let subscriptions = self
+                .fetch_every_page(&format!("{user}/subscriptions"), parse_subscriptions)
+                .await?;
+            write_json(dir, "subscriptions.json", &subscriptions)?;
+            checkpoint.mark_done("subscriptions")?;
+            report.subscriptions = subscriptions.len();
+        }
+        if !checkpoint.is_done("history") {
+            let history = self
+                .fetch_every_page(&format!("{user}/readings"), parse_search)
+                .await?;
+            write_json(dir, "history.json", &history)?;
+            checkpoint.mark_done("history")?;
+            report.history = history.len();
+        }
+
+        checkpoint.finish()?;
+        Ok(report)
+    }
+
+    /// Fetch every page of a paginated list, `path` is the url of the list without a page
+    async fn fetch_every_page<T, P>(&self, path: &str, parse: P) -> Result<Vec<T>, Error>
+    where
+        P: Fn(&str) -> Result<Vec<T>, Error>,
+    {
+        let separator = if path.contains('?') { '&' } else { '?' };
+        let mut items = vec![];
+        let mut page = 1;
+        loop {
+            let resp = self
+                .form
+                .get(&format!("{path}{separator}page={page}"))
+                .await?;
+            items.append(&mut self.form.parse(&resp, &parse)?);
+            if page >= self.form.parse(&resp, parse_page_count)? {
+                return Ok(items);
+            }
+            page += 1;
+        }
+    }
+}
+
+fn write_json(dir: &Path, name: &str, value: &impl serde::Serialize) -> Result<(), Error> {
+    std::fs::write(dir.join(name), serde_json::to_string_pretty(value)?)?;
+    Ok(())
+}
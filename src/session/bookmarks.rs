@@ -0,0 +1,442 @@
+use std::time::Duration;
+
+use super::AO3Session;
+use crate::error::Error;
+use crate::parse::parse_bookmark_pseud_id;
+
+/// Most times a row is retried while AO3 says there were too many requests
+const MAX_RETRIES: usize = 5;
+
+const MAX_BACKOFF: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug)]
+pub enum BookmarkFileError {
+    /// The csv file has no column with this name
+    MissingColumn(&'static str),
+}
+
+impl std::fmt::Display for BookmarkFileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BookmarkFileError::MissingColumn(column) => {
+                write!(f, "The bookmarks file has no {column} column")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BookmarkFileError {}
+
+/// A bookmark to create, e.g. a row of a rec list kept somewhere else
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct NewBookmark {
+    /// Url of the work on AO3
+    pub url: String,
+
+    /// Title the list gives the work, AO3 doesn't need it
+    pub title: String,
+    pub tags: Vec<String>,
+
+    /// Notes, may contain the html AO3 allows
+    pub notes: String,
+    pub is_rec: bool,
+}
+
+impl NewBookmark {
+    /// Id of the work the url goes to
+    pub fn work_id(&self) -> Option<&str> {
+        let (_, rest) = self.url.split_once("/works/")?;
+        let end = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        (end > 0).then(|| &rest[..end])
+    }
+
+    /// Read bookmarks from csv with a header row
+    ///
+    /// Needs a `url` column, `title`, `tags` (comma separated), `notes` and `rec`
+    /// (`1`, `yes`, `true` or `x`) are optional. Pinboard's columns work too:
+    /// `href` for the url, `description` for the title, `extended` for the notes
+    /// and `tags` separated by spaces.
+    /// ```rust
+    /// use ao3rs::session::NewBookmark;
+    ///
+    /// let csv = "url,tags,rec\nhttps://archiveofourown.org/works/45221314,\"horror, short\",yes\n";
+    /// let bookmarks = NewBookmark::from_csv(csv).unwrap();
+    /// assert_eq!(bookmarks[0].tags, vec!["horror", "short"]);
+    /// ```
+    pub fn from_csv(csv: &str) -> Result<Vec<Self>, BookmarkFileError> {
+        let mut rows = parse_csv(csv).into_iter();
+        let header: Vec<String> = rows
+            .next()
+            .unwrap_or_default()
+            .iter()
+            .map(|name| name.trim().to_lowercase())
+            .collect();
+        let column = |names: &[&str]| header.iter().position(|h| names.contains(&h.as_str()));
+        let pinboard = column(&["href"]).is_some();
+        let url =
+            column(&["url", "href", "link"]).ok_or(BookmarkFileError::MissingColumn("url"))?;
+        let title = column(&["title", "description"]);
+        let tags = column(&["tags"]);
+        let notes = column(&["notes", "extended"]);
+        let rec = column(&["rec", "is_rec"]);
+        let field = |row: &[String], column: Option<usize>| -> String {
+            column
+                .and_then(|i| row.get(i))
+                .map(|value| value.trim().to_string())
+                .unwrap_or_default()
+        };
+        Ok(rows
+            .filter(|row| row.iter().any(|value| !value.trim().is_empty()))
+            .map(|row| NewBookmark {
+                url: field(&row, Some(url)),
+                title: field(&row, title),
+                tags: if pinboard {
+                    split_words(&field(&row, tags))
+                } else {
+                    split_tags(&field(&row, tags))
+                },
+                notes: field(&row, notes),
+                is_rec: matches!(
+                    field(&row, rec).to_lowercase().as_str(),
+                    "1" | "yes" | "y" | "true" | "x"
+                ),
+            })
+            .collect())
+    }
+
+    /// Read bookmarks from a json array of objects with the same fields as [from_csv](NewBookmark::from_csv)
+    ///
+    /// Tags can be a list or a single string, which is split on commas, or on spaces if it has none.
+    /// Pinboard's json export can be read as it is, its tags are always split on spaces.
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<Vec<Self>, serde_json::Error> {
+        #[derive(serde::Deserialize)]
+        #[serde(untagged)]
+        enum Tags {
+            List(Vec<String>),
+            Text(String),
+        }
+
+        #[derive(serde::Deserialize)]
+        struct Row {
+            #[serde(alias = "link")]
+            url: Option<String>,
+
+            /// Pinboard's url, rows with it are from Pinboard
+            href: Option<String>,
+            #[serde(alias = "description")]
+            title: Option<String>,
+            tags: Option<Tags>,
+            #[serde(alias = "extended")]
+            notes: Option<String>,
+            #[serde(alias = "is_rec")]
+            rec: Option<bool>,
+        }
+
+        let rows: Vec<Row> = serde_json::from_str(json)?;
+        rows.into_iter()
+            .map(|row| {
+                let pinboard = row.href.is_some();
+                Ok(NewBookmark {
+                    url: row
+                        .url
+                        .or(row.href)
+                        .ok_or_else(|| serde::de::Error::missing_field("url"))?,
+                    title: row.title.unwrap_or_default(),
+                    tags: match row.tags {
+                        Some(Tags::List(tags)) => tags,
+                        Some(Tags::Text(tags)) if pinboard || !tags.contains(',') => {
+                            split_words(&tags)
+                        }
+                        Some(Tags::Text(tags)) => split_tags(&tags),
+                        None => vec![],
+                    },
+                    notes: row.notes.unwrap_or_default(),
+                    is_rec: row.rec.unwrap_or_default(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Outcome of [import_bookmarks](AO3Session::import_bookmarks), rows are counted from 0
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct BookmarkImportReport {
+    /// Rows that were bookmarked
+    pub created: Vec<usize>,
+
+    /// Rows that could not be bookmarked, with the reason
+    pub failed: Vec<(usize, String)>,
+}
+
+impl AO3Session {
+    /// Bookmark a work as the logged in user, with their default pseud
+    pub async fn create_bookmark(&self, bookmark: &NewBookmark) -> Result<(), Error> {
+        let work_id = bookmark.work_id().ok_or_else(|| {
+            Error::InvalidInput(format!("Not the url of a work: {}", bookmark.url))
+        })?;
+        let form_page = format!("/works/{work_id}/bookmarks/new");
+        let page = self.form.get(&form_page).await?;
+        let page = page_or_rate_limited(page)?;
+        let resp = self
+            .form
+            .submit(
+                &form_page,
+                &format!("/works/{work_id}/bookmarks"),
+                &[
+                    (
+                        "bookmark[pseud_id]",
+                        self.form.parse(&page, parse_bookmark_pseud_id)?,
+                    ),
+                    ("bookmark[bookmarker_notes]", bookmark.notes.clone()),
+                    ("bookmark[tag_string]", bookmark.tags.join(",")),
+                    (
+                        "bookmark[rec]",
+                        String::from(if bookmark.is_rec { "1" } else { "0" }),
+                    ),
+                    ("bookmark[private]", String::from("0")),
+                    ("commit", String::from("Create")),
+                ],
+            )
+            .await?;
+        let resp = page_or_rate_limited(resp)?;
+        if resp.has_errors() {
+            return Err(super::SessionError::Rejected(resp.errors).into());
+        }
+        Ok(())
+    }
+
+    /// Bookmark every work in `bookmarks`, waiting `delay` between works
+    ///
+    /// A row that fails doesn't stop the import, it's listed in the report.
+    /// When AO3 says there were too many requests the wait is doubled and the row tried again.
+    pub async fn import_bookmarks(
+        &self,
+        bookmarks: &[NewBookmark],
+        delay: Duration,
+    ) -> BookmarkImportReport {
+        let mut report = BookmarkImportReport::default();
+        let mut backoff = delay;
+        for (row, bookmark) in bookmarks.iter().enumerate() {
+            let mut retries = 0;
+            loop {
+                if row > 0 || retries > 0 {
+                    tokio::time::sleep(backoff).await;
+                }
+                match self.create_bookmark(bookmark).await {
+                    Ok(()) => {
+                        report.created.push(row);
+                        backoff = delay;
+                    }
+                    Err(Error::RateLimited { .. }) if retries < MAX_RETRIES => {
+                        retries += 1;
+                        backoff = (backoff * 2).min(MAX_BACKOFF).max(delay);
+                        continue;
+                    }
+                    Err(err) => report.failed.push((row, err.to_string())),
+                }
+                break;
+            }
+        }
+        report
+    }
+}
+
+fn page_or_rate_limited(
+    resp: crate::form::FormResponse,
+) -> Result<crate::form::FormResponse, Error> {
+    if resp.status == 429 {
+        Err(Error::RateLimited { retry_after: None })
+    } else {
+        Ok(resp)
+    }
+}
+
+fn split_tags(tags: &str) -> Vec<String> {
+    tags.split(',')
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Split tags separated by spaces, like Pinboard's
+fn split_words(tags: &str) -> Vec<String> {
+    tags.split_whitespace().map(str::to_string).collect()
+}
+
+/// Split csv into rows of fields, quoted fields can contain commas, quotes ("") and newlines
+fn parse_csv(csv: &str) -> Vec<Vec<String>> {
+    let mut rows = vec![];
+    let mut row = vec![];
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = csv.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => row.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes => {}
+            '\n' if !in_quotes => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockAo3;
+
+    #[test]
+    fn test_from_csv() {
+        let csv = "URL,Tags,Notes,Rec\r\n\
+            https://archiveofourown.org/works/45221314,\"horror, short and sweet\",\"Read it at night,\nregretted \"\"nothing\"\"\",yes\r\n\
+            https://archiveofourown.org/works/45399999/chapters/1,,,\r\n\
+            \r\n";
+        let bookmarks = NewBookmark::from_csv(csv).unwrap();
+        assert_eq!(bookmarks.len(), 2);
+        assert_eq!(bookmarks[0].work_id(), Some("45221314"));
+        assert_eq!(bookmarks[0].tags, vec!["horror", "short and sweet"]);
+        assert_eq!(
+            bookmarks[0].notes,
+            "Read it at night,\nregretted \"nothing\""
+        );
+        assert!(bookmarks[0].is_rec);
+        assert_eq!(bookmarks[1].work_id(), Some("45399999"));
+        assert!(bookmarks[1].tags.is_empty());
+        assert!(!bookmarks[1].is_rec);
+        assert!(matches!(
+            NewBookmark::from_csv("title\nSalvation\n"),
+            Err(BookmarkFileError::MissingColumn("url"))
+        ));
+    }
+
+    #[test]
+    fn test_from_pinboard_csv() {
+        let csv = "href,description,extended,tags\n\
+            https://archiveofourown.org/works/45221314,\"Salvation - Starchild719 - Original Work [Archive of Our Own]\",\"Read it at night, regretted nothing\",horror short_and_sweet\n";
+        let bookmarks = NewBookmark::from_csv(csv).unwrap();
+        assert_eq!(
+            bookmarks[0].title,
+            "Salvation - Starchild719 - Original Work [Archive of Our Own]"
+        );
+        assert_eq!(bookmarks[0].notes, "Read it at night, regretted nothing");
+        assert_eq!(bookmarks[0].tags, vec!["horror", "short_and_sweet"]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_from_pinboard_json() {
+        // as exported from pinboard.in/settings/backup
+        let json = r#"[{"href":"https:\/\/archiveofourown.org\/works\/45221314","description":"Salvation - Starchild719 - Original Work [Archive of Our Own]","extended":"Read it at night, regretted nothing","meta":"5e2b1c0a8d1f4b7e9c3a6d2f8b0e4c1a","hash":"9f1d3c7b2a6e8f0d4b1c5a9e7d3f2b6c","time":"2023-03-01T20:14:05Z","shared":"no","toread":"yes","tags":"horror short_and_sweet"},
+{"href":"https:\/\/archiveofourown.org\/works\/45399999","description":"Embers","extended":"","meta":"0a1b2c3d4e5f60718293a4b5c6d7e8f9","hash":"1a2b3c4d5e6f708192a3b4c5d6e7f809","time":"2023-02-11T08:00:00Z","shared":"yes","toread":"no","tags":""}]"#;
+        let bookmarks = NewBookmark::from_json(json).unwrap();
+        assert_eq!(bookmarks.len(), 2);
+        assert_eq!(bookmarks[0].work_id(), Some("45221314"));
+        assert_eq!(
+            bookmarks[0].title,
+            "Salvation - Starchild719 - Original Work [Archive of Our Own]"
+        );
+        assert_eq!(bookmarks[0].notes, "Read it at night, regretted nothing");
+        assert_eq!(bookmarks[0].tags, vec!["horror", "short_and_sweet"]);
+        assert_eq!(bookmarks[1].title, "Embers");
+        assert!(bookmarks[1].tags.is_empty());
+        assert!(NewBookmark::from_json(r#"[{"title": "Salvation"}]"#).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_from_json() {
+        let json = r#"[
+            {"href": "https://archiveofourown.org/works/45221314", "extended": "Loved it", "tags": "horror wip"},
+            {"url": "https://archiveofourown.org/works/45399999", "tags": ["short and sweet"], "rec": true}
+        ]"#;
+        let bookmarks = NewBookmark::from_json(json).unwrap();
+        assert_eq!(bookmarks[0].tags, vec!["horror", "wip"]);
+        assert_eq!(bookmarks[0].notes, "Loved it");
+        assert_eq!(bookmarks[1].tags, vec!["short and sweet"]);
+        assert!(bookmarks[1].is_rec);
+        assert_eq!(
+            NewBookmark {
+                url: String::from("https://example.com"),
+                ..Default::default()
+            }
+            .work_id(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_import_bookmarks() {
+        let form = r#"<form>
+            <input type="hidden" name="authenticity_token" value="token" />
+            <input type="hidden" name="bookmark[pseud_id]" value="1234" />
+        </form>"#;
+        let mock = MockAo3::start()
+            .await
+            .unwrap()
+            .page("/works/1/bookmarks/new", form)
+            .page(
+                "/works/1/bookmarks",
+                "<p>Bookmark was successfully created.</p>",
+            )
+            .respond_once("/works/2/bookmarks/new", 429, "Retry later")
+            .page("/works/2/bookmarks/new", form)
+            .page(
+                "/works/2/bookmarks",
+                "<p>Bookmark was successfully created.</p>",
+            )
+            .page("/works/3/bookmarks/new", form)
+            .page(
+                "/works/3/bookmarks",
+                r#"<div id="error" class="error"><ul><li>Tags are too long</li></ul></div>"#,
+            );
+        let session = AO3Session {
+            form: mock.form_session().unwrap(),
+            username: String::from("reader"),
+        };
+        let bookmarks: Vec<_> = (1..=3)
+            .map(|id| NewBookmark {
+                url: format!("https://archiveofourown.org/works/{id}"),
+                ..Default::default()
+            })
+            .collect();
+        let start = tokio::time::Instant::now();
+        let report = session
+            .import_bookmarks(&bookmarks, Duration::from_millis(10))
+            .await;
+        assert_eq!(report.created, vec![0, 1]);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].0, 2);
+        assert!(report.failed[0].1.contains("Tags are too long"));
+        // 10ms before the second row, 20ms before retrying it and 10ms before the third
+        assert!(start.elapsed() >= Duration::from_millis(40));
+        assert_eq!(
+            mock.requests(),
+            vec![
+                "/works/1/bookmarks/new",
+                "/works/1/bookmarks",
+                "/works/2/bookmarks/new",
+                "/works/2/bookmarks/new",
+                "/works/2/bookmarks",
+                "/works/3/bookmarks/new",
+                "/works/3/bookmarks",
+            ]
+        );
+    }
+}
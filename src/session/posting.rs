@@ -0,0 +1,465 @@
+use super::{AO3Session, SessionError};
+use crate::error::Error;
+use crate::models::{AO3Assignment, Rating};
+use crate::parse::{parse_work, parse_work_language_id, parse_work_pseud_id};
+use crate::query::{ArchiveWarning, Language};
+
+/// A work this one is a translation of or was inspired by, AO3 links the two
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct ParentWork {
+    /// Url of the work, on AO3 or somewhere else
+    pub url: String,
+
+    /// Title and creator, only needed for works that aren't on AO3
+    pub title: String,
+    pub author: String,
+
+    /// Language of the work, [Any](Language::Any) leaves it out
+    pub language: Language,
+    pub translation: bool,
+}
+
+/// A work to post, mirrors the form at `/works/new`
+///
+/// Only the first chapter is posted, the work is saved as a draft unless it's [posted right away](NewWork::post_without_preview).
+/// ```rust
+/// use ao3rs::models::Rating;
+/// use ao3rs::query::{ArchiveWarning, Language};
+/// use ao3rs::session::NewWork;
+///
+/// let work = NewWork::new("Salvación", "<p>...</p>")
+///     .push_fandom("Original Work")
+///     .set_rating(Rating::TeenAndUp)
+///     .push_archive_warning(ArchiveWarning::NoArchiveWarningsApply)
+///     .set_language(Language::Spanish)
+///     .translation_of("https://archiveofourown.org/works/45221314");
+/// ```
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct NewWork {
+    title: String,
+
+    /// Html of the first chapter
+    content: String,
+    fandoms: Vec<String>,
+    rating: Rating,
+    archive_warnings: Vec<ArchiveWarning>,
+    relationships: Vec<String>,
+    characters: Vec<String>,
+    additional_tags: Vec<String>,
+    summary: String,
+    notes: String,
+
+    /// Language the work is written in, English when it's [Any](Language::Any)
+    language: Language,
+    collections: Vec<String>,
+
+    /// Users the work is a gift for
+    recipients: Vec<String>,
+    parents: Vec<ParentWork>,
+    post_without_preview: bool,
+}
+
+impl NewWork {
+    pub fn new(title: impl Into<String>, content: impl Into<String>) -> Self {
+        NewWork {
+            title: title.into(),
+            content: content.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn push_fandom(mut self, fandom: impl Into<String>) -> Self {
+        self.fandoms.push(fandom.into());
+        self
+    }
+
+    pub fn set_rating(mut self, rating: Rating) -> Self {
+        self.rating = rating;
+        self
+    }
+
+    pub fn push_archive_warning(mut self, warning: ArchiveWarning) -> Self {
+        self.archive_warnings.push(warning);
+        self
+    }
+
+    pub fn push_relationship(mut self, relationship: impl Into<String>) -> Self {
+        self.relationships.push(relationship.into());
+        self
+    }
+
+    pub fn push_character(mut self, character: impl Into<String>) -> Self {
+        self.characters.push(character.into());
+        self
+    }
+
+    pub fn push_additional_tag(mut self, tag: impl Into<String>) -> Self {
+        self.additional_tags.push(tag.into());
+        self
+    }
+
+    pub fn set_summary(mut self, summary: impl Into<String>) -> Self {
+        self.summary = summary.into();
+        self
+    }
+
+    pub fn set_notes(mut self, notes: impl Into<String>) -> Self {
+        self.notes = notes.into();
+        self
+    }
+
+    pub fn set_language(mut self, language: Language) -> Self {
+        self.language = language;
+        self
+    }
+
+    /// Post the work to the collection named `collection`, as used in urls
+    pub fn push_collection(mut self, collection: impl Into<String>) -> Self {
+        self.collections.push(collection.into());
+        self
+    }
+
+    /// Gift the work to the user `recipient`
+    pub fn push_recipient(mut self, recipient: impl Into<String>) -> Self {
+        self.recipients.push(recipient.into());
+        self
+    }
+
+    /// Link the work to `parent`, see [translation_of](NewWork::translation_of) and [inspired_by](NewWork::inspired_by)
+    pub fn push_parent(mut self, parent: ParentWork) -> Self {
+        self.parents.push(parent);
+        self
+    }
+
+    /// Mark the work as a translation of the work at `url`
+    pub fn translation_of(self, url: impl Into<String>) -> Self {
+        self.push_parent(ParentWork {
+            url: url.into(),
+            translation: true,
+            ..Default::default()
+        })
+    }
+
+    /// Mark the work as inspired by the work at `url`
+    pub fn inspired_by(self, url: impl Into<String>) -> Self {
+        self.push_parent(ParentWork {
+            url: url.into(),
+            ..Default::default()
+        })
+    }
+
+    /// Post the work to the collection of `assignment` as a gift for its recipient, see
+    /// [fulfill_assignment](AO3Session::fulfill_assignment)
+    pub fn for_assignment(mut self, assignment: &AO3Assignment) -> Self {
+        if !self.collections.contains(&assignment.collection) {
+            self.collections.push(assignment.collection.clone());
+        }
+        if let Some(recipient) = &assignment.recipient {
+            if !self.recipients.contains(recipient) {
+                self.recipients.push(recipient.clone());
+            }
+        }
+        self
+    }
+
+    pub fn get_collections(&self) -> &[String] {
+        &self.collections
+    }
+
+    pub fn get_recipients(&self) -> &[String] {
+        &self.recipients
+    }
+
+    pub fn get_parents(&self) -> &[ParentWork] {
+        &self.parents
+    }
+
+    pub fn post_without_preview(mut self, post_without_preview: bool) -> Self {
+        self.post_without_preview = post_without_preview;
+        self
+    }
+
+    /// Fields of the form, `form_page` is the html of the form for the ids AO3 uses in it
+    pub(crate) fn to_form(&self, form_page: &str) -> Result<Vec<(String, String)>, Error> {
+        let language_id = |language: &Language| match language {
+            Language::Any => parse_work_language_id(form_page, "en"),
+            language => parse_work_language_id(form_page, &language.code()),
+        };
+        let mut form = vec![
+            (
+                "work[author_attributes][ids][]".to_string(),
+                parse_work_pseud_id(form_page)?,
+            ),
+            ("work[title]".to_string(), self.title.clone()),
+            ("work[fandom_string]".to_string(), self.fandoms.join(",")),
+            (
+                "work[rating_string]".to_string(),
+                match self.rating {
+                    Rating::None => Rating::NotRated.name(),
+                    rating => rating.name(),
+                }
+                .to_string(),
+            ),
+            (
+                "work[relationship_string]".to_string(),
+                self.relationships.join(","),
+            ),
+            (
+                "work[character_string]".to_string(),
+                self.characters.join(","),
+            ),
+            (
+                "work[freeform_string]".to_string(),
+                self.additional_tags.join(","),
+            ),
+            ("work[summary]".to_string(), self.summary.clone()),
+            ("work[notes]".to_string(), self.notes.clone()),
+            (
+                "work[language_id]".to_string(),
+                language_id(&self.language)?,
+            ),
+            (
+                "work[collection_names]".to_string(),
+                self.collections.join(","),
+            ),
+            ("work[recipients]".to_string(), self.recipients.join(",")),
+            (
+                "work[chapter_attributes][content]".to_string(),
+                self.content.clone(),
+            ),
+        ];
+        for warning in &self.archive_warnings {
+            form.push((
+                "work[archive_warning_strings][]".to_string(),
+                warning_name(warning).to_string(),
+            ));
+        }
+        for (i, parent) in self.parents.iter().enumerate() {
+            let field =
+                |name: &str| format!("work[parent_work_relationships_attributes][{i}][{name}]");
+            form.push((field("url"), parent.url.clone()));
+            form.push((field("title"), parent.title.clone()));
+            form.push((field("author"), parent.author.clone()));
+            if parent.language != Language::Any {
+                form.push((field("language_id"), language_id(&parent.language)?));
+            }
+            if parent.translation {
+                form.push((field("translation"), String::from("1")));
+            }
+        }
+        form.push(if self.post_without_preview {
+            (String::from("post_button"), String::from("Post"))
+        } else {
+            (String::from("preview_button"), String::from("Preview"))
+        });
+        Ok(form)
+    }
+}
+
+/// Name of the warning in the form, which isn't the one searches show
+fn warning_name(warning: &ArchiveWarning) -> &'static str {
+    match warning {
+        ArchiveWarning::CreatureChoseNotToUseArchiveWarnings => {
+            "Creator Chose Not To Use Archive Warnings"
+        }
+        ArchiveWarning::GraphicDepictionOfViolence => "Graphic Depictions Of Violence",
+        ArchiveWarning::MajorCharacterDeath => "Major Character Death",
+        ArchiveWarning::NoArchiveWarningsApply => "No Archive Warnings Apply",
+        ArchiveWarning::RapeNonCon => "Rape/Non-Con",
+        ArchiveWarning::Underage => "Underage Sex",
+    }
+}
+
+impl AO3Session {
+    /// Post a work as the logged in user, with their default pseud, returns the id AO3 gave it
+    pub async fn post_work(&self, work: &NewWork) -> Result<String, Error> {
+        self.post_work_from("/works/new", work, &[]).await
+    }
+
+    /// Post `work` as the gift for `assignment`, to the exchange's collection and for its recipient
+    ///
+    /// Returns the id AO3 gave the work, like [post_work](AO3Session::post_work).
+    pub async fn fulfill_assignment(
+        &self,
+        assignment: &AO3Assignment,
+        work: NewWork,
+    ) -> Result<String, Error> {
+        let work = work.for_assignment(assignment);
+        let assignment_id = [(
+            String::from("work[challenge_assignment_ids][]"),
+            assignment.id.clone(),
+        )];
+        self.post_work_from(&assignment.fulfill_path(), &work, &assignment_id)
+            .await
+    }
+
+    /// Post `work` through the form at `form_page`, with `extra` fields the form adds
+    pub(crate) async fn post_work_from(
+        &self,
+        form_page: &str,
+        work: &NewWork,
+        extra: &[(String, String)],
+    ) -> Result<String, Error> {
+        let page = self.form.get(form_page).await?;
+        let mut fields = self.form.parse(&page, |html| work.to_form(html))?;
+        fields.extend_from_slice(extra);
+        let resp = self.form.submit(form_page, "/works", &fields).await?;
+        if resp.has_errors() {
+            return Err(SessionError::Rejected(resp.errors).into());
+        }
+        // AO3 sends us on to the work, or to its preview for a draft
+        let posted = reqwest::Url::parse(&resp.url).ok().and_then(|url| {
+            let id = url.path().strip_prefix("/works/")?.split('/').next()?;
+            (!id.is_empty() && id.chars().all(|c| c.is_ascii_digit())).then(|| id.to_string())
+        });
+        match posted {
+            Some(id) => Ok(id),
+            None => Ok(self.form.parse(&resp, parse_work)?.id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockAo3;
+
+    fn session(mock: &MockAo3) -> AO3Session {
+        AO3Session {
+            form: mock.form_session().unwrap(),
+            username: String::from("reader"),
+        }
+    }
+
+    #[test]
+    fn test_to_form() {
+        let form_page = include_str!("../parse_test/new_work.html");
+        let form = NewWork::new("Salvación", "<p>Hola</p>")
+            .push_fandom("Original Work")
+            .push_archive_warning(ArchiveWarning::NoArchiveWarningsApply)
+            .set_language(Language::Spanish)
+            .translation_of("https://archiveofourown.org/works/45221314")
+            .push_parent(ParentWork {
+                url: String::from("https://example.com/story"),
+                title: String::from("Inspiration"),
+                author: String::from("someone"),
+                language: Language::German,
+                translation: false,
+            })
+            .to_form(form_page)
+            .unwrap();
+        let value_of = |name: &str| {
+            form.iter()
+                .find(|(field, _)| field == name)
+                .map(|(_, value)| value.as_str())
+        };
+        assert_eq!(value_of("work[author_attributes][ids][]"), Some("5678"));
+        assert_eq!(value_of("work[language_id]"), Some("2"));
+        assert_eq!(value_of("work[rating_string]"), Some("Not Rated"));
+        assert_eq!(
+            value_of("work[archive_warning_strings][]"),
+            Some("No Archive Warnings Apply")
+        );
+        assert_eq!(
+            value_of("work[parent_work_relationships_attributes][0][url]"),
+            Some("https://archiveofourown.org/works/45221314")
+        );
+        assert_eq!(
+            value_of("work[parent_work_relationships_attributes][0][translation]"),
+            Some("1")
+        );
+        assert_eq!(
+            value_of("work[parent_work_relationships_attributes][1][language_id]"),
+            Some("3")
+        );
+        assert_eq!(
+            value_of("work[parent_work_relationships_attributes][1][translation]"),
+            None
+        );
+        assert_eq!(value_of("preview_button"), Some("Preview"));
+    }
+
+    fn assignment() -> AO3Assignment {
+        AO3Assignment {
+            id: String::from("1101"),
+            collection: String::from("yuletide2023"),
+            collection_title: String::from("Yuletide 2023"),
+            recipient: Some(String::from("recipient_one")),
+        }
+    }
+
+    #[test]
+    fn test_for_assignment() {
+        let work = NewWork::new("Salvation", "<p>...</p>")
+            .push_collection("yuletide2023")
+            .for_assignment(&assignment());
+        assert_eq!(work.get_collections(), ["yuletide2023"]);
+        assert_eq!(work.get_recipients(), ["recipient_one"]);
+
+        let pinch_hit = AO3Assignment {
+            recipient: None,
+            ..assignment()
+        };
+        let work = NewWork::new("Salvation", "<p>...</p>").for_assignment(&pinch_hit);
+        assert_eq!(work.get_collections(), ["yuletide2023"]);
+        assert!(work.get_recipients().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fulfill_assignment() {
+        let mock = MockAo3::start()
+            .await
+            .unwrap()
+            .page(
+                "/collections/yuletide2023/works/new?assignment_id=1101",
+                include_str!("../parse_test/new_work.html"),
+            )
+            .page("/works", include_str!("../parse_test/work.html"));
+        let id = session(&mock)
+            .fulfill_assignment(
+                &assignment(),
+                NewWork::new("Salvation", "<p>...</p>").push_fandom("Original Work"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(id, "45221314");
+        assert_eq!(
+            mock.requests(),
+            vec![
+                "/collections/yuletide2023/works/new?assignment_id=1101",
+                "/works"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_post_work() {
+        let mock = MockAo3::start()
+            .await
+            .unwrap()
+            .page("/works/new", include_str!("../parse_test/new_work.html"))
+            .page("/works", include_str!("../parse_test/work.html"));
+        let id = session(&mock)
+            .post_work(&NewWork::new("Salvation", "<p>...</p>").push_fandom("Original Work"))
+            .await
+            .unwrap();
+        assert_eq!(id, "45221314");
+        assert_eq!(mock.requests(), vec!["/works/new", "/works"]);
+
+        let rejected = MockAo3::start()
+            .await
+            .unwrap()
+            .page("/works/new", include_str!("../parse_test/new_work.html"))
+            .page(
+                "/works",
+                r#"<div id="error" class="error"><ul><li>Fandom can't be blank</li></ul></div>"#,
+            );
+        let result = session(&rejected)
+            .post_work(&NewWork::new("Salvation", "<p>...</p>"))
+            .await;
+        assert!(matches!(
+            result,
+            Err(Error::Session(SessionError::Rejected(errors))) if errors == ["Fandom can't be blank"]
+        ));
+    }
+}
@@ -0,0 +1,259 @@
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::client::AO3Client;
+use crate::error::Error;
+use crate::filter::normalize_tag;
+use crate::models::{AO3Tag, AO3Work};
+
+/// Maps tags to their canonical tag, so synonyms count as one tag the way they do in AO3's filters
+///
+/// Tags are resolved by fetching their page once. What's learned is kept in memory,
+/// and with [open](TagResolver::open) in a file of lines `<tag>\t<canonical>`,
+/// so works can be canonicalized offline later on. A canonical tag's page lists its synonyms,
+/// they are all learned from it.
+/// ```rust,no_run
+/// # async fn example(works: Vec<ao3rs::models::AO3Work>) -> Result<(), Box<dyn std::error::Error>> {
+/// use ao3rs::client::AO3Client;
+/// use ao3rs::tags::TagResolver;
+///
+/// let resolver = TagResolver::open("tags.tsv")?;
+/// for (tag, err) in resolver.resolve_works(&AO3Client::new(), &works).await {
+///     eprintln!("Could not resolve {tag}: {err}");
+/// }
+/// let works = resolver.canonicalize_works(works);
+/// let frequency = ao3rs::analysis::tag_frequency(&works);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct TagResolver {
+    /// Canonical name of each tag, by [normalized](normalize_tag) name
+    canonical: Mutex<HashMap<String, String>>,
+    path: Option<PathBuf>,
+}
+
+impl TagResolver {
+    /// A resolver that only keeps what it learns in memory
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A resolver kept in the file at `path`, with what the file already has
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref().to_path_buf();
+        let canonical = match std::fs::read_to_string(&path) {
+            // a later line for the same tag wins, like the appended lines are meant to
+            Ok(contents) => contents
+                .lines()
+                .filter_map(|line| line.split_once('\t'))
+                .map(|(tag, canonical)| (normalize_tag(tag), canonical.to_string()))
+                .collect(),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(err) => return Err(err.into()),
+        };
+        Ok(TagResolver {
+            canonical: Mutex::new(canonical),
+            path: Some(path),
+        })
+    }
+
+    /// The canonical tag of `tag`, if it's known without fetching anything
+    ///
+    /// A canonical tag is its own canonical tag, so is a tag that hasn't been wrangled yet.
+    pub fn canonical(&self, tag: &str) -> Option<String> {
+        self.canonical
+            .lock()
+            .unwrap()
+            .get(&normalize_tag(tag))
+            .cloned()
+    }
+
+    /// Learn what a tag's page says, see [fetch_tag](AO3Client::fetch_tag)
+    pub fn insert(&self, tag: &AO3Tag) -> Result<(), Error> {
+        let canonical = tag.canonical.as_deref().unwrap_or(&tag.name);
+        let mut learned = vec![(tag.name.as_str(), canonical)];
+        if tag.is_canonical {
+            learned.extend(tag.synonyms.iter().map(|s| (s.as_str(), tag.name.as_str())));
+        }
+        let mut known = self.canonical.lock().unwrap();
+        let new: Vec<_> = learned
+            .into_iter()
+            .filter(|(name, canonical)| {
+                known.get(&normalize_tag(name)).map(String::as_str) != Some(canonical)
+            })
+            .collect();
+        if let Some(path) = &self.path {
+            if !new.is_empty() {
+                let mut file = std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)?;
+                for (name, canonical) in &new {
+                    writeln!(file, "{name}\t{canonical}")?;
+                }
+            }
+        }
+        for (name, canonical) in new {
+            known.insert(normalize_tag(name), canonical.to_string());
+        }
+        Ok(())
+    }
+
+    /// The canonical tag of `tag`, fetching its page unless it's already known
+    pub async fn resolve(&self, client: &AO3Client, tag: &str) -> Result<String, Error> {
+        if let Some(canonical) = self.canonical(tag) {
+            return Ok(canonical);
+        }
+        let page = client.fetch_tag(tag).await?;
+        self.insert(&page)?;
+        Ok(page.canonical.unwrap_or(page.name))
+    }
+
+    /// Resolve every fandom, relationship, character and additional tag of `works`
+    /// that isn't known yet
+    ///
+    /// That's a request per unknown tag, which adds up on a first run. A tag that
+    /// can't be resolved doesn't stop the others, it's returned with why, once.
+    pub async fn resolve_works(
+        &self,
+        client: &AO3Client,
+        works: &[AO3Work],
+    ) -> Vec<(String, Error)> {
+        let tags = works.iter().flat_map(|w| {
+            w.fandoms
+                .iter()
+                .chain(&w.relationships)
+                .chain(&w.characters)
+                .chain(&w.tags)
+        });
+        let mut tried = HashSet::new();
+        let mut failed = vec![];
+        for tag in tags {
+            if !tried.insert(normalize_tag(tag)) {
+                continue;
+            }
+            if let Err(err) = self.resolve(client, tag).await {
+                failed.push((tag.clone(), err));
+            }
+        }
+        failed
+    }
+
+    /// The canonical tag of `tag` when it's known, `tag` itself otherwise
+    pub fn canonicalize_tag(&self, tag: &str) -> String {
+        self.canonical(tag).unwrap_or_else(|| tag.to_string())
+    }
+
+    /// Replace the fandoms, relationships, characters and additional tags of `work`
+    /// with their canonical tags
+    ///
+    /// Only known tags are replaced, nothing is fetched. A tag that turns out
+    /// to be there twice is kept once, where it was first.
+    pub fn canonicalize(&self, work: &mut AO3Work) {
+        let kinds = [
+            &mut work.fandoms,
+            &mut work.relationships,
+            &mut work.characters,
+            &mut work.tags,
+        ];
+        for tags in kinds {
+            let mut canonical: Vec<String> = vec![];
+            for tag in tags.drain(..) {
+                let tag = self.canonicalize_tag(&tag);
+                if !canonical.contains(&tag) {
+                    canonical.push(tag);
+                }
+            }
+            *tags = canonical;
+        }
+    }
+
+    /// [canonicalize](TagResolver::canonicalize) every work
+    pub fn canonicalize_works(&self, mut works: Vec<AO3Work>) -> Vec<AO3Work> {
+        works.iter_mut().for_each(|work| self.canonicalize(work));
+        works
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_tag;
+    use crate::testing::MockAo3;
+
+    #[tokio::test]
+    async fn test_tag_resolver() {
+        let synonym = include_str!("parse_test/tag_synonym.html");
+        let canonical = include_str!("parse_test/tag_canonical.html");
+        let mock = MockAo3::start()
+            .await
+            .unwrap()
+            .page("/tags/Hermione%20Granger*s*Draco%20Malfoy", synonym);
+        let client = mock.client();
+
+        let path = std::env::temp_dir().join(format!("ao3rs_tags_{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let resolver = TagResolver::open(&path).unwrap();
+        assert_eq!(
+            resolver
+                .resolve(&client, "Hermione Granger/Draco Malfoy")
+                .await
+                .unwrap(),
+            "Draco Malfoy/Hermione Granger"
+        );
+        // known now, without asking again
+        resolver
+            .resolve(&client, "hermione granger/draco malfoy")
+            .await
+            .unwrap();
+        assert_eq!(mock.requests().len(), 1);
+
+        resolver.insert(&parse_tag(canonical).unwrap()).unwrap();
+        let mut work = AO3Work::default();
+        work.relationships = vec![
+            "Dramione".to_string(),
+            "Draco Malfoy/Hermione Granger".to_string(),
+        ];
+        work.tags = vec!["Fluff".to_string()];
+        let reopened = TagResolver::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        reopened.canonicalize(&mut work);
+        assert_eq!(work.relationships, vec!["Draco Malfoy/Hermione Granger"]);
+        assert_eq!(work.tags, vec!["Fluff"]);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_works() {
+        let mock = MockAo3::start().await.unwrap().page(
+            "/tags/Hermione%20Granger*s*Draco%20Malfoy",
+            include_str!("parse_test/tag_synonym.html"),
+        );
+        let mut work = AO3Work::default();
+        work.relationships = vec!["Hermione Granger/Draco Malfoy".to_string()];
+        // no page for it, so it fails
+        work.characters = vec!["Hermione Granger".to_string()];
+        let works = vec![work.clone(), work];
+
+        let resolver = TagResolver::new();
+        let failed = resolver.resolve_works(&mock.client(), &works).await;
+        let failed: Vec<_> = failed.iter().map(|(tag, _)| tag.as_str()).collect();
+        assert_eq!(failed, vec!["Hermione Granger"]);
+        assert_eq!(
+            resolver
+                .canonical("Hermione Granger/Draco Malfoy")
+                .as_deref(),
+            Some("Draco Malfoy/Hermione Granger")
+        );
+        assert_eq!(mock.requests().len(), 2);
+
+        let works = resolver.canonicalize_works(works);
+        assert_eq!(
+            works[0].relationships,
+            vec!["Draco Malfoy/Hermione Granger"]
+        );
+        assert_eq!(works[0].characters, vec!["Hermione Granger"]);
+    }
+}
@@ -0,0 +1,258 @@
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::client::AO3Client;
+use crate::form::FormSession;
+use crate::offline::relative_links;
+
+mod fixtures;
+
+pub use fixtures::{fixture, fixtures, fixtures_of, Fixture, FixtureKind};
+
+/// A page the mock server answers with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+/// How a path is answered, the responses given once come first
+#[derive(Debug, Default)]
+struct Route {
+    once: VecDeque<MockResponse>,
+    always: Option<MockResponse>,
+}
+
+impl Route {
+    fn next(&mut self) -> Option<MockResponse> {
+        self.once.pop_front().or_else(|| self.always.clone())
+    }
+}
+
+type Routes = Arc<Mutex<HashMap<String, Route>>>;
+
+/// A local server that answers like AO3 with pages given to it, for testing code that uses the crate
+///
+/// Requests are matched on their path and query first, then on their path alone.
+/// Anything else gets a 404 Not Found. The server stops when it's dropped.
+/// ```rust
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// use ao3rs::testing::MockAo3;
+///
+/// let mock = MockAo3::start().await?.with_recorded_pages();
+/// let work = mock.client().fetch_work("45221314").await?;
+/// assert_eq!(work.title, "Salvation");
+/// assert_eq!(mock.requests(), vec!["/works/45221314?view_adult=true"]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct MockAo3 {
+    addr: SocketAddr,
+    routes: Routes,
+    requests: Arc<Mutex<Vec<String>>>,
+    server: tokio::task::JoinHandle<()>,
+}
+
+impl MockAo3 {
+    /// Start the server on a free port of localhost, it has no pages yet
+    pub async fn start() -> std::io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let routes = Routes::default();
+        let requests = Arc::new(Mutex::new(vec![]));
+        let server = tokio::spawn(serve(listener, routes.clone(), requests.clone()));
+        Ok(MockAo3 {
+            addr,
+            routes,
+            requests,
+            server,
+        })
+    }
+
+    /// Answer requests for `path` with `body` and a 200 OK
+    ///
+    /// `path` is relative to the root, with or without a query.
+    pub fn page(self, path: &str, body: &str) -> Self {
+        self.respond(path, 200, body)
+    }
+
+    /// Answer requests for `path` with `status` and `body`, e.g. to test a 429 Too Many Requests
+    pub fn respond(self, path: &str, status: u16, body: &str) -> Self {
+        self.routes
+            .lock()
+            .unwrap()
+            .entry(path.to_string())
+            .or_default()
+            .always = Some(MockResponse {
+            status,
+            body: body.to_string(),
+        });
+        self
+    }
+
+    /// Answer the next request for `path` with `status` and `body`, the ones after it as before
+    ///
+    /// Given several times the responses are used in order, e.g. to test that a request is retried.
+    pub fn respond_once(self, path: &str, status: u16, body: &str) -> Self {
+        self.routes
+            .lock()
+            .unwrap()
+            .entry(path.to_string())
+            .or_default()
+            .once
+            .push_back(MockResponse {
+                status,
+                body: body.to_string(),
+            });
+        self
+    }
+
+    /// Serve every [fixture](fixtures) at its path, the synthetic ones too
+    pub fn with_recorded_pages(self) -> Self {
+        // some were saved from a browser, which made the links absolute
+        fixtures().fold(self, |mock, fixture| {
+            mock.page(fixture.path, &relative_links(fixture.html))
+        })
+    }
+
+    /// Url of the server, without a trailing slash
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// A client that sends its requests to this server
+    pub fn client(&self) -> AO3Client {
+        AO3Client::new().set_base_url(&self.url())
+    }
+
+    /// A form session that sends its requests to this server
+    pub fn form_session(&self) -> Result<FormSession, reqwest::Error> {
+        Ok(FormSession::new()?.set_base_url(&self.url()))
+    }
+
+    /// Paths (with their query) requested so far, in order
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl Drop for MockAo3 {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+async fn serve(listener: TcpListener, routes: Routes, requests: Arc<Mutex<Vec<String>>>) {
+    while let Ok((stream, _)) = listener.accept().await {
+        let routes = routes.clone();
+        let requests = requests.clone();
+        tokio::spawn(async move {
+            // a client that hangs up halfway isn't the test's concern
+            let _ = answer(stream, &routes, &requests).await;
+        });
+    }
+}
+
+/// Read a single request and answer it, the connection is closed afterwards
+async fn answer(
+    stream: TcpStream,
+    routes: &Routes,
+    requests: &Mutex<Vec<String>>,
+) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await?;
+    let target = request_line
+        .split_whitespace()
+        .nth(1)
+        .unwrap_or("/")
+        .to_string();
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).await? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or_default();
+            }
+        }
+    }
+    // forms are posted with a body, it has to be read before answering
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).await?;
+
+    requests.lock().unwrap().push(target.clone());
+    let path = target.split('?').next().unwrap_or_default();
+    let response = {
+        let mut routes = routes.lock().unwrap();
+        let response = routes.get_mut(&target).and_then(Route::next);
+        response.or_else(|| routes.get_mut(path).and_then(Route::next))
+    }
+    .unwrap_or(MockResponse {
+        status: 404,
+        body: String::new(),
+    });
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        reqwest::StatusCode::from_u16(response.status)
+            .ok()
+            .and_then(|s| s.canonical_reason())
+            .unwrap_or_default(),
+        response.body.len()
+    );
+    let mut stream = reader.into_inner();
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(response.body.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+
+    #[tokio::test]
+    async fn test_mock_ao3() {
+        let mock = MockAo3::start()
+            .await
+            .unwrap()
+            .with_recorded_pages()
+            .respond("/works/1", 429, "Retry later")
+            .respond_once("/works/3", 429, "Retry later");
+        let client = mock.client();
+        assert_eq!(
+            client.fetch_work("45221314").await.unwrap().title,
+            "Salvation"
+        );
+        assert_eq!(client.fetch_series("3001234").await.unwrap().works.len(), 2);
+        assert!(matches!(
+            client.fetch_work("1").await,
+            Err(Error::RateLimited { .. })
+        ));
+        assert!(matches!(client.fetch_work("2").await, Err(Error::NotFound)));
+        assert!(matches!(
+            client.fetch_work("3").await,
+            Err(Error::RateLimited { .. })
+        ));
+        assert!(matches!(client.fetch_work("3").await, Err(Error::NotFound)));
+        assert_eq!(
+            mock.requests(),
+            vec![
+                "/works/45221314?view_adult=true",
+                "/series/3001234",
+                "/works/1?view_adult=true",
+                "/works/2?view_adult=true",
+                "/works/3?view_adult=true",
+                "/works/3?view_adult=true",
+            ]
+        );
+    }
+}
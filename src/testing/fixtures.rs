@@ -0,0 +1,191 @@
+use crate::error::Error;
+use crate::offline;
+
+/// What kind of page a [Fixture] is, which says what parses it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FixtureKind {
+    /// A work page, with every chapter
+    Work,
+    /// A chapter index (`/works/<id>/navigate`)
+    ChapterIndex,
+    /// A work page with its comments shown
+    Comments,
+    /// The public bookmarks of a work
+    Bookmarks,
+    Series,
+    /// A list of work blurbs
+    Search,
+    /// A tag's page (`/tags/<name>`)
+    Tag,
+}
+
+/// A page recorded from AO3, or written in its markup for a case no recorded page has
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fixture {
+    /// Short name of the page, e.g. "work_anonymous"
+    pub name: &'static str,
+    pub kind: FixtureKind,
+    /// Path (and query) the page was fetched from, or would be for a synthetic one
+    pub path: &'static str,
+    pub html: &'static str,
+
+    /// Written by hand rather than recorded, its ids don't exist on AO3
+    pub synthetic: bool,
+}
+
+impl Fixture {
+    /// Run the parser that goes with the page's [kind](Fixture::kind), only whether it succeeded is kept
+    pub fn parse(&self) -> Result<(), Error> {
+        match self.kind {
+            FixtureKind::Work => {
+                offline::parse_work_html(self.html)?;
+                offline::parse_chapters_html(self.html).map(drop)
+            }
+            FixtureKind::ChapterIndex => crate::parse::parse_chapter_ids(self.html).map(drop),
+            FixtureKind::Comments => offline::parse_comments_html(self.html).map(drop),
+            FixtureKind::Bookmarks => offline::parse_bookmarks_html(self.html).map(drop),
+            FixtureKind::Series => offline::parse_series_html(self.html).map(drop),
+            FixtureKind::Search => offline::parse_search_html(self.html).map(drop),
+            FixtureKind::Tag => offline::parse_tag_html(self.html).map(drop),
+        }
+    }
+}
+
+const FIXTURES: &[Fixture] = &[
+    Fixture {
+        name: "work",
+        kind: FixtureKind::Work,
+        path: "/works/45221314",
+        html: include_str!("../parse_test/work.html"),
+        synthetic: false,
+    },
+    Fixture {
+        name: "work_anonymous",
+        kind: FixtureKind::Work,
+        path: "/works/50000001",
+        html: include_str!("../parse_test/work_anonymous.html"),
+        synthetic: true,
+    },
+    Fixture {
+        name: "work_restricted",
+        kind: FixtureKind::Work,
+        path: "/works/50000002",
+        html: include_str!("../parse_test/work_restricted.html"),
+        synthetic: true,
+    },
+    Fixture {
+        name: "work_many_tags",
+        kind: FixtureKind::Work,
+        path: "/works/50000003",
+        html: include_str!("../parse_test/work_many_tags.html"),
+        synthetic: true,
+    },
+    Fixture {
+        name: "navigate",
+        kind: FixtureKind::ChapterIndex,
+        path: "/works/45221314/navigate",
+        html: include_str!("../parse_test/navigate.html"),
+        synthetic: false,
+    },
+    Fixture {
+        name: "comments",
+        kind: FixtureKind::Comments,
+        path: "/works/45221314?show_comments=true&view_full_work=true&page=1",
+        html: include_str!("../parse_test/comments.html"),
+        synthetic: false,
+    },
+    Fixture {
+        name: "work_bookmarks",
+        kind: FixtureKind::Bookmarks,
+        path: "/works/45221314/bookmarks",
+        html: include_str!("../parse_test/work_bookmarks.html"),
+        synthetic: false,
+    },
+    Fixture {
+        name: "series",
+        kind: FixtureKind::Series,
+        path: "/series/3001234",
+        html: include_str!("../parse_test/series.html"),
+        synthetic: false,
+    },
+    Fixture {
+        name: "search",
+        kind: FixtureKind::Search,
+        path: "/works/search",
+        html: include_str!("../parse_test/search.html"),
+        synthetic: false,
+    },
+    Fixture {
+        name: "tag_synonym",
+        kind: FixtureKind::Tag,
+        path: "/tags/Hermione%20Granger*s*Draco%20Malfoy",
+        html: include_str!("../parse_test/tag_synonym.html"),
+        synthetic: false,
+    },
+    Fixture {
+        name: "tag_canonical",
+        kind: FixtureKind::Tag,
+        path: "/tags/Draco%20Malfoy*s*Hermione%20Granger",
+        html: include_str!("../parse_test/tag_canonical.html"),
+        synthetic: false,
+    },
+];
+
+/// Every page: works (anonymous, restricted, with a huge tag list, ...),
+/// search results, a series, comments, bookmarks and tags
+///
+/// The odd works are [synthetic](Fixture::synthetic), the rest were recorded from AO3.
+///
+/// The pages are built into the crate, no network is needed.
+/// ```rust
+/// use ao3rs::testing::fixtures;
+///
+/// for fixture in fixtures() {
+///     assert!(fixture.parse().is_ok(), "{} doesn't parse", fixture.name);
+/// }
+/// ```
+pub fn fixtures() -> impl Iterator<Item = &'static Fixture> {
+    FIXTURES.iter()
+}
+
+/// The pages of one kind
+pub fn fixtures_of(kind: FixtureKind) -> impl Iterator<Item = &'static Fixture> {
+    fixtures().filter(move |fixture| fixture.kind == kind)
+}
+
+/// The page called `name`
+pub fn fixture(name: &str) -> Option<&'static Fixture> {
+    fixtures().find(|fixture| fixture.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixtures() {
+        for fixture in fixtures() {
+            if let Err(err) = fixture.parse() {
+                panic!("{}: {err}", fixture.name);
+            }
+        }
+        assert_eq!(fixtures_of(FixtureKind::Work).count(), 4);
+        assert_eq!(
+            fixtures()
+                .filter(|fixture| fixture.synthetic)
+                .map(|fixture| fixture.name)
+                .collect::<Vec<_>>(),
+            vec!["work_anonymous", "work_restricted", "work_many_tags"]
+        );
+
+        let anonymous = offline::parse_work_html(fixture("work_anonymous").unwrap().html).unwrap();
+        assert!(anonymous.authors.is_empty());
+        assert!(anonymous.summary.is_empty());
+        let restricted =
+            offline::parse_work_html(fixture("work_restricted").unwrap().html).unwrap();
+        assert_eq!(restricted.title, "The Long Road");
+        assert!(!restricted.is_complete);
+        let many_tags = offline::parse_work_html(fixture("work_many_tags").unwrap().html).unwrap();
+        assert_eq!(many_tags.tags.len(), 120);
+    }
+}
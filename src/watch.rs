@@ -0,0 +1,301 @@
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+use crate::client::AO3Client;
+use crate::error::Error;
+use crate::models::AO3Work;
+use crate::query::{AO3QueryBuilder, SortBy};
+use crate::search::Search;
+
+/// A search that remembers what it found last time
+///
+/// Each [refresh](SavedSearch::refresh) only returns the works that are new
+/// or were updated since the previous one, the first one returns everything.
+/// A refresh looks at the first page of results, so the search should have
+/// the latest works first, like [watch_tag](Watcher::watch_tag)'s do.
+/// Any [Search] for works can be saved, a work search by default.
+/// ```rust,no_run
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// use ao3rs::query::AO3QueryBuilder;
+/// use ao3rs::watch::SavedSearch;
+///
+/// let mut search = SavedSearch::new("horror", AO3QueryBuilder::new().push_additional_tag("Horror"));
+/// search.refresh().await?;
+/// // later on
+/// for work in search.refresh().await? {
+///     println!("new or updated: {}", work.title);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SavedSearch<S = AO3QueryBuilder> {
+    pub name: String,
+    query: S,
+    /// Ids of the works seen so far, with the date they had then
+    seen: HashMap<String, chrono::NaiveDate>,
+}
+
+impl<S: Search<Item = AO3Work>> SavedSearch<S> {
+    pub fn new(name: &str, query: S) -> Self {
+        SavedSearch {
+            name: name.to_string(),
+            query,
+            seen: HashMap::new(),
+        }
+    }
+
+    /// Restore a saved search, with the works it had seen
+    pub fn with_seen(mut self, seen: HashMap<String, chrono::NaiveDate>) -> Self {
+        self.seen = seen;
+        self
+    }
+
+    pub fn get_query(&self) -> &S {
+        &self.query
+    }
+
+    /// Ids of the works seen so far, with the date they had then
+    pub fn get_seen(&self) -> &HashMap<String, chrono::NaiveDate> {
+        &self.seen
+    }
+
+    /// Run the search again, returns the works that are new or updated since the last run
+    pub async fn refresh(&mut self) -> Result<Vec<AO3Work>, Error> {
+        self.refresh_with(&AO3Client::new()).await
+    }
+
+    /// [refresh](SavedSearch::refresh) through `client`
+    pub async fn refresh_with(&mut self, client: &AO3Client) -> Result<Vec<AO3Work>, Error> {
+        let works = client.search_page(&self.query, 1).await?.items;
+        Ok(self.diff(works))
+    }
+
+    /// Keep the works that are new or updated, and remember all of them as seen
+    pub fn diff(&mut self, works: Vec<AO3Work>) -> Vec<AO3Work> {
+        works
+            .into_iter()
+            .filter(|work| {
+                let previous = self.seen.insert(work.id.clone(), work.date);
+                previous != Some(work.date)
+            })
+            .collect()
+    }
+}
+
+/// Something a [Watcher](Watcher) noticed
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum WatchEvent {
+    /// Works that are new or updated in a saved search
+    NewWorks { search: String, works: Vec<AO3Work> },
+
+    /// Running a saved search failed, it will be tried again next round
+    Error { search: String, message: String },
+}
+
+/// Runs saved searches over and over and tells about the works they find
+///
+/// The searches all go through one [AO3Client], so they share its rate limit.
+/// Searches are run one after the other with a delay between them, so AO3 isn't hammered.
+/// When AO3 answers with "429 Too Many Requests" the watcher backs off,
+/// doubling the delay each time until a search goes through again.
+/// ```rust,no_run
+/// # async fn example() {
+/// use std::time::Duration;
+/// use ao3rs::watch::{WatchEvent, Watcher};
+///
+/// let mut events = Watcher::new(Duration::from_secs(30 * 60))
+///     .watch_tag("Horror")
+///     .spawn();
+/// while let Some(event) = events.recv().await {
+///     if let WatchEvent::NewWorks { search, works } = event {
+///         println!("{} new works in {search}", works.len());
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Watcher<S = AO3QueryBuilder> {
+    searches: Vec<SavedSearch<S>>,
+
+    client: AO3Client,
+
+    /// Time between two rounds of searches
+    interval: Duration,
+
+    /// Up to how much time is randomly added to the interval, so watchers don't all go off at once
+    jitter: Duration,
+
+    /// Time between two searches of the same round
+    request_delay: Duration,
+}
+
+/// Longest the watcher waits after being rate limited
+const MAX_BACKOFF: Duration = Duration::from_secs(30 * 60);
+
+impl<S: Search<Item = AO3Work>> Watcher<S> {
+    pub fn new(interval: Duration) -> Self {
+        Watcher {
+            searches: vec![],
+            client: AO3Client::new(),
+            interval,
+            jitter: interval / 10,
+            request_delay: Duration::from_secs(5),
+        }
+    }
+
+    pub fn set_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    pub fn set_request_delay(mut self, request_delay: Duration) -> Self {
+        self.request_delay = request_delay;
+        self
+    }
+
+    /// Run the searches through `client` instead of a client of the watcher's own
+    pub fn set_client(mut self, client: AO3Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    pub fn watch(mut self, search: SavedSearch<S>) -> Self {
+        self.searches.push(search);
+        self
+    }
+
+    /// Run the watcher in the background, events arrive through the returned channel
+    ///
+    /// The watcher stops once the receiver is dropped.
+    pub fn spawn(self) -> tokio::sync::mpsc::UnboundedReceiver<WatchEvent>
+    where
+        S: Send + Sync + 'static,
+    {
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(self.run_while(move |event| sender.send(event).is_ok()));
+        receiver
+    }
+
+    /// Run the watcher forever, calling `on_event` for everything it notices
+    pub async fn run(self, mut on_event: impl FnMut(WatchEvent)) {
+        self.run_while(|event| {
+            on_event(event);
+            true
+        })
+        .await
+    }
+
+    /// Run until `deliver` returns false
+    async fn run_while(mut self, mut deliver: impl FnMut(WatchEvent) -> bool) {
+        let mut backoff = self.request_delay;
+        loop {
+            for search in self.searches.iter_mut() {
+                let event = match search.refresh_with(&self.client).await {
+                    Ok(works) if works.is_empty() => None,
+                    Ok(works) => Some(WatchEvent::NewWorks {
+                        search: search.name.clone(),
+                        works,
+                    }),
+                    Err(err) => {
+                        if matches!(err, Error::RateLimited { .. }) {
+                            backoff = (backoff * 2).min(MAX_BACKOFF);
+                        }
+                        Some(WatchEvent::Error {
+                            search: search.name.clone(),
+                            message: err.to_string(),
+                        })
+                    }
+                };
+                if !matches!(event, Some(WatchEvent::Error { .. })) {
+                    backoff = self.request_delay;
+                }
+                if let Some(event) = event {
+                    if !deliver(event) {
+                        return;
+                    }
+                }
+                tokio::time::sleep(backoff.max(self.request_delay)).await;
+            }
+            tokio::time::sleep(self.interval + jitter(self.jitter)).await;
+        }
+    }
+}
+
+impl Watcher {
+    /// Watch for works newly posted or updated in an additional tag
+    pub fn watch_tag(self, tag: &str) -> Self {
+        let query = AO3QueryBuilder::new()
+            .push_additional_tag(tag)
+            .set_sort_by(SortBy::DateUpdated);
+        self.watch(SavedSearch::new(tag, query))
+    }
+}
+
+/// Random duration up to `max`
+fn jitter(max: Duration) -> Duration {
+    // std seeds every RandomState randomly, that's plenty for spreading requests out
+    let random = std::collections::hash_map::RandomState::new()
+        .build_hasher()
+        .finish();
+    max.mul_f64(random as f64 / u64::MAX as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_saved_search_diff() {
+        let work = |id: &str, day| {
+            let mut work = AO3Work::default();
+            work.id = id.to_string();
+            work.date = chrono::NaiveDate::from_ymd_opt(2023, 2, day).unwrap();
+            work
+        };
+        let mut search = SavedSearch::new("test", AO3QueryBuilder::new());
+        assert_eq!(search.diff(vec![work("1", 1), work("2", 1)]).len(), 2);
+        assert!(search.diff(vec![work("1", 1), work("2", 1)]).is_empty());
+        let changed = search.diff(vec![work("1", 1), work("2", 5), work("3", 5)]);
+        assert_eq!(changed, vec![work("2", 5), work("3", 5)]);
+        assert_eq!(search.get_seen().len(), 3);
+    }
+
+    /// A search of the works of a user, to watch something other than a work search
+    #[derive(Debug, Clone)]
+    struct UserWorks(&'static str);
+
+    impl Search for UserWorks {
+        type Item = AO3Work;
+
+        fn path(&self, page: usize) -> String {
+            format!("/users/{}/works?page={page}", self.0)
+        }
+
+        fn parse_page(&self, html: &str) -> Result<Vec<AO3Work>, Error> {
+            crate::parse::parse_search(html)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_refresh() {
+        let mock = crate::testing::MockAo3::start().await.unwrap().page(
+            "/users/Starchild719/works?page=1",
+            include_str!("parse_test/search.html"),
+        );
+        let client = mock.client();
+        let mut search = SavedSearch::new("Starchild719", UserWorks("Starchild719"));
+        assert_eq!(search.refresh_with(&client).await.unwrap().len(), 20);
+        assert!(search.refresh_with(&client).await.unwrap().is_empty());
+        assert_eq!(mock.requests().len(), 2);
+    }
+
+    #[test]
+    fn test_jitter() {
+        let max = Duration::from_secs(60);
+        assert!((0..100).map(|_| jitter(max)).all(|j| j <= max));
+        assert_eq!(jitter(Duration::ZERO), Duration::ZERO);
+    }
+}